@@ -1,6 +1,8 @@
-use clap::{command, Parser, Subcommand};
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use mgit::core::repos::ConfigFormat;
 
 use crate::commands::*;
+use crate::utils::progress::ProgressStyleKind;
 
 #[derive(Parser)]
 #[command(
@@ -15,6 +17,40 @@ use crate::commands::*;
 pub(crate) struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Log every git command with its directory, exit code and duration
+    #[arg(long, global = true, action = ArgAction::SetTrue)]
+    pub trace_git: bool,
+
+    /// Look of the progress bars
+    #[arg(long, global = true, value_enum, default_value_t = ProgressStyleKind::Default)]
+    pub progress_style: ProgressStyleKind,
+
+    /// Format of the config file, `--config -` reads it from stdin
+    #[arg(long, global = true, value_enum, default_value_t = ConfigFormatArg::Auto)]
+    pub config_format: ConfigFormatArg,
+}
+
+/// how config files are parsed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ConfigFormatArg {
+    /// Detect by file extension, then by content
+    #[default]
+    Auto,
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl From<ConfigFormatArg> for ConfigFormat {
+    fn from(value: ConfigFormatArg) -> Self {
+        match value {
+            ConfigFormatArg::Auto => ConfigFormat::Auto,
+            ConfigFormatArg::Toml => ConfigFormat::Toml,
+            ConfigFormatArg::Yaml => ConfigFormat::Yaml,
+            ConfigFormatArg::Json => ConfigFormat::Json,
+        }
+    }
 }
 
 #[derive(Subcommand, Debug)]
@@ -25,11 +61,39 @@ pub(crate) enum Commands {
     /// Fetch git repos
     Fetch(FetchCommand),
 
+    /// Pull upstream branches into current branches of git repos
+    Pull(PullCommand),
+
+    /// Push current branches of git repos to their upstreams
+    Push(PushCommand),
+
     /// Snapshot git repos
     Snapshot(SnapshotCommand),
 
     /// Sync git repos
-    Sync(SyncCommand),
+    Sync(Box<SyncCommand>),
+
+    /// Clone missing git repos, leaving existing ones untouched
+    Clone(CloneCommand),
+
+    /// Show git repos status against remotes
+    Status(StatusCommand),
+
+    /// Show a combined diff stat of git repos
+    Diff(DiffCommand),
+
+    /// Checkout git repos to their manifest branches without fetching
+    Repair(RepairCommand),
+
+    /// Checkout git repos to the same branch or tag, whatever the manifest pins
+    Checkout(CheckoutCommand),
+
+    /// Check remote urls of git repos against the manifest
+    #[command(name = "verify-remote")]
+    VerifyRemote(VerifyRemoteCommand),
+
+    /// Check the config file for consistency problems
+    Validate(ValidateCommand),
 
     /// Clean unused git repos
     Clean(CleanCommand),
@@ -38,9 +102,34 @@ pub(crate) enum Commands {
     #[command(name = "ls-files")]
     ListFiles(ListFilesCommand),
 
+    /// Show what the config file describes
+    Info(InfoCommand),
+
+    /// Write the config file in another format
+    Convert(ConvertCommand),
+
+    /// List repos of the config file
+    #[command(name = "list-repos")]
+    ListRepos(ListReposCommand),
+
+    /// Add a repo to the config file
+    Add(AddCommand),
+
+    /// Remove repos from the config file, and their directories with --delete
+    Remove(RemoveCommand),
+
     /// Track remote branch
     Track(TrackCommand),
 
+    /// Print or open the web page of repos
+    Open(OpenCommand),
+
+    /// Run a shell command in every repo
+    Foreach(ForeachCommand),
+
+    /// Manage git hooks of repos
+    Hooks(HooksCommand),
+
     /// Log git repos
     #[command(name = "log-repos")]
     LogRepos(LogReposCommand),
@@ -56,4 +145,7 @@ pub(crate) enum Commands {
     /// New tag
     #[command(name = "new-tag")]
     NewTag(NewTagCommand),
+
+    /// Generate shell completions
+    Completions(CompletionsCommand),
 }
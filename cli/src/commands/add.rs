@@ -0,0 +1,70 @@
+use clap::{ArgAction, ArgGroup, Args};
+use std::path::PathBuf;
+
+use mgit::core::git::RemoteRef;
+use mgit::ops::{self, AddOptions};
+use mgit::utils::error::MgitResult;
+
+use crate::utils::progress::MultiProgress;
+use crate::CliCommad;
+
+/// Add a repo to the config file
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default, Args)]
+#[command(group(ArgGroup::new("remote_ref").required(true).args(["branch", "tag", "commit"])))]
+pub(crate) struct AddCommand {
+    /// The work directory
+    pub path: Option<PathBuf>,
+
+    /// Use specified config file
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Local path of the repo in the work directory
+    #[arg(long, value_name = "LOCAL")]
+    pub local: String,
+
+    /// Remote url of the repo
+    #[arg(long, value_name = "URL")]
+    pub remote: String,
+
+    /// Branch to sync the repo to
+    #[arg(long)]
+    pub branch: Option<String>,
+
+    /// Tag to sync the repo to
+    #[arg(long)]
+    pub tag: Option<String>,
+
+    /// Commit to sync the repo to
+    #[arg(long)]
+    pub commit: Option<String>,
+
+    /// Clone the repo right after adding it
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub sync: bool,
+}
+
+impl CliCommad for AddCommand {
+    fn exec(self) -> MgitResult {
+        ops::add_repo(self.into(), MultiProgress::default())
+    }
+}
+
+impl From<AddCommand> for AddOptions {
+    fn from(value: AddCommand) -> Self {
+        let remote_ref = match (value.branch, value.tag, value.commit) {
+            (_, _, Some(commit)) => RemoteRef::Commit(commit),
+            (_, Some(tag), _) => RemoteRef::Tag(tag),
+            (branch, _, _) => RemoteRef::Branch(branch.unwrap_or_default()),
+        };
+        let mut options = AddOptions::new(
+            value.path,
+            value.config,
+            value.local,
+            value.remote,
+            remote_ref,
+        );
+        options.sync = value.sync;
+        options
+    }
+}
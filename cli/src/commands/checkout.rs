@@ -0,0 +1,46 @@
+use clap::{ArgAction, Args};
+use std::path::PathBuf;
+
+use mgit::ops::{self, CheckoutOptions};
+use mgit::utils::error::MgitResult;
+
+use crate::CliCommad;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default, Args)]
+pub(crate) struct CheckoutCommand {
+    /// Branch or tag to check out in every repo
+    pub name: String,
+
+    /// The work directory
+    pub path: Option<PathBuf>,
+
+    /// Use specified config file
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Create the branch from HEAD in repos which have neither the branch nor the tag
+    #[arg(long, action = ArgAction::SetTrue)]
+    create: bool,
+
+    /// Ignore specified repositories for checkout
+    #[arg(long)]
+    ignore: Option<Vec<String>>,
+}
+
+impl CliCommad for CheckoutCommand {
+    fn exec(self) -> MgitResult {
+        ops::checkout_repos(self.into())
+    }
+}
+
+impl From<CheckoutCommand> for CheckoutOptions {
+    fn from(value: CheckoutCommand) -> Self {
+        CheckoutOptions::new(
+            value.path,
+            value.config,
+            value.name,
+            value.create,
+            value.ignore,
+        )
+    }
+}
@@ -0,0 +1,49 @@
+use clap::Args;
+use std::path::PathBuf;
+
+use mgit::ops::{self, CloneOptions};
+use mgit::utils::error::MgitResult;
+
+use crate::utils::progress::MultiProgress;
+use crate::CliCommad;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default, Args)]
+pub(crate) struct CloneCommand {
+    /// The work directory
+    pub path: Option<PathBuf>,
+
+    /// Use specified config file
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Sets the number of threads to be used
+    #[arg(short, long, default_value_t = 4, value_name = "NUMBER")]
+    thread: usize,
+
+    /// Clone shallow with the history truncated to the number of commits
+    #[arg(short, long, value_name = "NUMBER")]
+    depth: Option<usize>,
+
+    /// Ignore specified repositories for clone
+    #[arg(long)]
+    ignore: Option<Vec<String>>,
+}
+
+impl CliCommad for CloneCommand {
+    fn exec(self) -> MgitResult {
+        let progress = MultiProgress::default();
+        ops::clone_repos(self.into(), progress)
+    }
+}
+
+impl From<CloneCommand> for CloneOptions {
+    fn from(value: CloneCommand) -> Self {
+        CloneOptions::new(
+            value.path,
+            value.config,
+            Some(value.thread),
+            value.depth,
+            value.ignore,
+        )
+    }
+}
@@ -0,0 +1,26 @@
+use clap::{Args, CommandFactory};
+use clap_complete::Shell;
+
+use mgit::utils::error::MgitResult;
+use mgit::utils::StyleMessage;
+
+use crate::cli::Cli;
+use crate::CliCommad;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Args)]
+/// Generate shell completions
+pub(crate) struct CompletionsCommand {
+    /// The shell to generate completions for
+    #[arg(value_enum)]
+    pub shell: Shell,
+}
+
+impl CliCommad for CompletionsCommand {
+    fn exec(self) -> MgitResult {
+        let mut command = Cli::command();
+        let name = command.get_name().to_string();
+        clap_complete::generate(self.shell, &mut command, name, &mut std::io::stdout());
+
+        Ok(StyleMessage::default())
+    }
+}
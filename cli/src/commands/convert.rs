@@ -0,0 +1,68 @@
+use clap::{Args, ValueEnum};
+use std::path::PathBuf;
+
+use mgit::core::repos::ConfigFormat;
+use mgit::ops::{self, ConvertOptions};
+use mgit::utils::error::MgitResult;
+use mgit::utils::path::PathExtension;
+use mgit::utils::StyleMessage;
+
+use crate::CliCommad;
+
+/// formats a config file can be written in
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Default, ValueEnum)]
+pub(crate) enum ConvertFormatArg {
+    #[default]
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl From<ConvertFormatArg> for ConfigFormat {
+    fn from(value: ConvertFormatArg) -> Self {
+        match value {
+            ConvertFormatArg::Toml => ConfigFormat::Toml,
+            ConvertFormatArg::Yaml => ConfigFormat::Yaml,
+            ConvertFormatArg::Json => ConfigFormat::Json,
+        }
+    }
+}
+
+/// Write the config file in another format
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default, Args)]
+pub(crate) struct ConvertCommand {
+    /// Format to write
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    pub to: ConvertFormatArg,
+
+    /// Config file to read, in any format, defaults to .gitrepos
+    #[arg(long = "in", value_name = "FILE")]
+    pub input: Option<PathBuf>,
+
+    /// File to write, defaults to stdout
+    #[arg(long, value_name = "FILE")]
+    pub out: Option<PathBuf>,
+}
+
+impl CliCommad for ConvertCommand {
+    fn exec(self) -> MgitResult {
+        let out = self.out.clone();
+        let content = ops::convert_config(self.into())?;
+
+        match out {
+            Some(out) => Ok(format!("converted config written to {}", out.display_path()).into()),
+            None => {
+                print!("{}", content);
+                Ok(StyleMessage::default())
+            }
+        }
+    }
+}
+
+impl From<ConvertCommand> for ConvertOptions {
+    fn from(value: ConvertCommand) -> Self {
+        let mut options = ConvertOptions::new(value.input, value.to.into());
+        options.out = value.out;
+        options
+    }
+}
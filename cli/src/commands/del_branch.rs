@@ -1,8 +1,12 @@
-use clap::Args;
+use anyhow::anyhow;
+use clap::{ArgAction, Args};
+use console::Term;
 use std::path::PathBuf;
 
 use mgit::ops::{self, DelBranchOptions};
 use mgit::utils::error::MgitResult;
+use mgit::utils::logger::get_logger;
+use mgit::utils::style_message::StyleMessage;
 
 use crate::CliCommad;
 
@@ -23,16 +27,60 @@ pub(crate) struct DelRemoteBranchCommand {
     /// Ignore specified repositories to create new branch
     #[arg(long)]
     ignore: Option<Vec<String>>,
+
+    /// Sets the number of threads to be used
+    #[arg(short, long, default_value_t = 4, value_name = "NUMBER")]
+    thread: usize,
+
+    /// Delete without asking for confirmation
+    #[arg(short, long, action = ArgAction::SetTrue)]
+    yes: bool,
 }
 
 impl CliCommad for DelRemoteBranchCommand {
     fn exec(self) -> MgitResult {
-        ops::del_remote_branch(self.into())
+        let yes = self.yes;
+        let mut options: DelBranchOptions = self.into();
+
+        // list every repo losing the branch, then delete them all at once or none
+        let scan = ops::find_remote_branch(&options)?;
+        for error in &scan.errors {
+            get_logger().error(error.clone());
+        }
+        if scan.found.is_empty() {
+            return Ok(format!("origin/{} not found in any repo", options.branch).into());
+        }
+        get_logger().info(StyleMessage::remote_branch_found(
+            &options.branch,
+            &scan.found,
+        ));
+
+        if !yes && !confirm(scan.found.len())? {
+            return Ok("Nothing deleted".into());
+        }
+
+        options.confirmed = Some(scan);
+        ops::del_remote_branch(options)
     }
 }
 
+/// ask once for all repos, anything but `y`/`yes` declines
+fn confirm(count: usize) -> MgitResult<bool> {
+    let term = Term::stderr();
+    if !term.is_term() {
+        return Err(anyhow!("no terminal to confirm the deletion, pass --yes"));
+    }
+
+    term.write_str(&format!("Delete the branch in {} repo(s)? [y/N] ", count))?;
+    let answer = term.read_line()?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
 impl From<DelRemoteBranchCommand> for DelBranchOptions {
     fn from(value: DelRemoteBranchCommand) -> Self {
-        DelBranchOptions::new(value.path, value.config, value.branch, value.ignore)
+        let mut options =
+            DelBranchOptions::new(value.path, value.config, value.branch, value.ignore);
+        options.thread_count = value.thread;
+        options
     }
 }
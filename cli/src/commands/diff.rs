@@ -0,0 +1,39 @@
+use clap::{ArgAction, Args};
+use std::path::PathBuf;
+
+use mgit::ops::{self, DiffOptions};
+use mgit::utils::error::MgitResult;
+use mgit::utils::StyleMessage;
+
+use crate::CliCommad;
+
+/// Show a combined diff stat of git repos
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default, Args)]
+pub(crate) struct DiffCommand {
+    /// The work directory
+    pub path: Option<PathBuf>,
+
+    /// Use specified config file
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Diff the staged changes instead of the work tree
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub staged: bool,
+}
+
+impl CliCommad for DiffCommand {
+    fn exec(self) -> MgitResult {
+        let diff_stat = ops::diff_repos(self.into())?;
+        print!("{}", diff_stat);
+        Ok(StyleMessage::default())
+    }
+}
+
+impl From<DiffCommand> for DiffOptions {
+    fn from(value: DiffCommand) -> Self {
+        let mut options = DiffOptions::new(value.path, value.config);
+        options.staged = value.staged;
+        options
+    }
+}
@@ -31,6 +31,18 @@ pub(crate) struct FetchCommand {
     /// Ignore specified repositories for fetch
     #[arg(long)]
     ignore: Option<Vec<String>>,
+
+    /// Only fetch repos in the group
+    #[arg(long)]
+    group: Option<Vec<String>>,
+
+    /// Speed up fetching long histories with skipping negotiation and protocol v2
+    #[arg(long, action = ArgAction::SetTrue)]
+    fast_fetch: bool,
+
+    /// Keep remote-tracking branches deleted on the remote, which fetch removes by default
+    #[arg(long, action = ArgAction::SetTrue)]
+    no_prune: bool,
 }
 
 impl CliCommad for FetchCommand {
@@ -42,13 +54,17 @@ impl CliCommad for FetchCommand {
 
 impl From<FetchCommand> for FetchOptions {
     fn from(value: FetchCommand) -> Self {
-        FetchOptions::new(
+        let mut options = FetchOptions::new(
             value.path,
             value.config,
             Some(value.thread),
             Some(value.silent),
             value.depth,
             value.ignore,
-        )
+        );
+        options.fast_fetch = value.fast_fetch;
+        options.prune = !value.no_prune;
+        options.groups = value.group;
+        options
     }
 }
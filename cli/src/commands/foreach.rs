@@ -0,0 +1,52 @@
+use clap::{ArgAction, Args};
+use std::path::PathBuf;
+
+use mgit::ops::{self, ForeachOptions};
+use mgit::utils::error::MgitResult;
+
+use crate::CliCommad;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default, Args)]
+pub(crate) struct ForeachCommand {
+    /// Shell command to run in every repo, with `MGIT_REPO_LOCAL` and `MGIT_REPO_REMOTE` set
+    pub command: String,
+
+    /// The work directory
+    pub path: Option<PathBuf>,
+
+    /// Use specified config file
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Sets the number of threads to be used
+    #[arg(short, long, default_value_t = 4, value_name = "NUMBER")]
+    thread: usize,
+
+    /// Ignore specified repositories for foreach
+    #[arg(long)]
+    ignore: Option<Vec<String>>,
+
+    /// Keep running the command in the other repos when it exits non-zero
+    #[arg(long, action = ArgAction::SetTrue)]
+    continue_on_error: bool,
+}
+
+impl CliCommad for ForeachCommand {
+    fn exec(self) -> MgitResult {
+        ops::foreach_repo(self.into())
+    }
+}
+
+impl From<ForeachCommand> for ForeachOptions {
+    fn from(value: ForeachCommand) -> Self {
+        let mut options = ForeachOptions::new(
+            value.path,
+            value.config,
+            value.command,
+            Some(value.thread),
+            value.ignore,
+        );
+        options.continue_on_error = value.continue_on_error;
+        options
+    }
+}
@@ -0,0 +1,68 @@
+use clap::{ArgAction, Args, Subcommand};
+use std::path::PathBuf;
+
+use mgit::ops::{self, InstallHooksOptions};
+use mgit::utils::error::MgitResult;
+
+use crate::CliCommad;
+
+#[derive(Debug, Clone, Args)]
+pub(crate) struct HooksCommand {
+    #[command(subcommand)]
+    command: HooksSubcommand,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum HooksSubcommand {
+    /// Copy hook scripts of a directory into .git/hooks of every repo
+    Install(HooksInstallCommand),
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default, Args)]
+struct HooksInstallCommand {
+    /// The work directory
+    path: Option<PathBuf>,
+
+    /// Use specified config file
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Directory of the hook scripts, e.g. pre-commit and commit-msg
+    #[arg(long, value_name = "DIR")]
+    source: PathBuf,
+
+    /// Overwrite hooks already present in a repo
+    #[arg(long, action = ArgAction::SetTrue)]
+    force: bool,
+
+    /// Link to the scripts instead of copying them
+    #[arg(long, action = ArgAction::SetTrue)]
+    symlink: bool,
+
+    /// Show which hooks would be installed without installing them
+    #[arg(long, action = ArgAction::SetTrue)]
+    dry_run: bool,
+
+    /// Ignore specified repositories
+    #[arg(long)]
+    ignore: Option<Vec<String>>,
+}
+
+impl CliCommad for HooksCommand {
+    fn exec(self) -> MgitResult {
+        match self.command {
+            HooksSubcommand::Install(cmd) => ops::install_hooks(cmd.into()),
+        }
+    }
+}
+
+impl From<HooksInstallCommand> for InstallHooksOptions {
+    fn from(value: HooksInstallCommand) -> Self {
+        let mut options = InstallHooksOptions::new(value.path, value.config, value.source);
+        options.force = value.force;
+        options.symlink = value.symlink;
+        options.dry_run = value.dry_run;
+        options.ignore = value.ignore;
+        options
+    }
+}
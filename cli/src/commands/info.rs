@@ -0,0 +1,60 @@
+use clap::{ArgAction, Args};
+use std::path::PathBuf;
+
+use mgit::ops::{self, InfoOptions};
+use mgit::utils::error::MgitResult;
+use mgit::utils::path::PathExtension;
+use mgit::utils::StyleMessage;
+
+use crate::CliCommad;
+
+/// Show what the config file describes
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default, Args)]
+pub(crate) struct InfoCommand {
+    /// The work directory
+    pub path: Option<PathBuf>,
+
+    /// Use specified config file
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Print the info as JSON
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub json: bool,
+}
+
+impl CliCommad for InfoCommand {
+    fn exec(self) -> MgitResult {
+        let json = self.json;
+        let info = ops::workspace_info(self.into())?;
+
+        if json {
+            println!("{}", serde_json::to_string_pretty(&info)?);
+            return Ok(StyleMessage::default());
+        }
+
+        if let Some(name) = &info.name {
+            println!("name:           {}", name);
+        }
+        if let Some(description) = &info.description {
+            println!("description:    {}", description);
+        }
+        println!("config:         {}", info.config_path.display_path());
+        println!(
+            "default branch: {}",
+            info.default_branch.as_deref().unwrap_or("-")
+        );
+        println!(
+            "repos:          {} ({} present, {} missing)",
+            info.repo_count, info.present, info.missing
+        );
+        println!("remotes:        {}", info.remote_count);
+        Ok(StyleMessage::default())
+    }
+}
+
+impl From<InfoCommand> for InfoOptions {
+    fn from(value: InfoCommand) -> Self {
+        InfoOptions::new(value.path, value.config)
+    }
+}
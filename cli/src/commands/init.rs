@@ -4,6 +4,7 @@ use std::path::PathBuf;
 use mgit::ops::{self, InitOptions};
 use mgit::utils::error::MgitResult;
 
+use crate::utils::progress::MultiProgress;
 use crate::CliCommad;
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Default, Args)]
@@ -11,19 +12,38 @@ pub(crate) struct InitCommand {
     /// The work directory
     pub path: Option<PathBuf>,
 
+    /// Write the config file to this path, `.yaml`/`.yml` writes yaml instead of toml
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
     /// Force remove git repos without prompt
     #[arg(long, action = ArgAction::SetTrue)]
     pub force: bool,
+
+    /// Also search repos under hidden directories like .config
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub scan_hidden: bool,
+
+    /// Sets the number of threads to be used
+    #[arg(short, long, default_value_t = 4, value_name = "NUMBER")]
+    thread: usize,
 }
 
 impl CliCommad for InitCommand {
     fn exec(self) -> MgitResult {
-        ops::init_repo(self.into())
+        let progress = MultiProgress::default();
+        ops::init_repo(self.into(), progress)
     }
 }
 
 impl From<InitCommand> for InitOptions {
     fn from(value: InitCommand) -> Self {
-        InitOptions::new(value.path, Some(value.force))
+        let mut options = InitOptions::new(value.path, Some(value.force));
+        if let Some(config) = value.config {
+            options.config_path = config;
+        }
+        options.scan_hidden = value.scan_hidden;
+        options.thread_count = value.thread;
+        options
     }
 }
@@ -1,4 +1,5 @@
-use clap::Args;
+use clap::{Args, ValueEnum};
+use std::io::{self, BufWriter, Write};
 use std::path::PathBuf;
 
 use mgit::ops::{self, ListFilesOptions};
@@ -7,6 +8,16 @@ use mgit::utils::StyleMessage;
 
 use crate::CliCommad;
 
+/// output format of ls-files
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Default, ValueEnum)]
+pub(crate) enum ListFilesFormat {
+    /// `mode object stage<TAB>path` lines, as `git ls-files -s` prints them
+    #[default]
+    Text,
+    /// JSON array of files with their repo, mode, object and path
+    Json,
+}
+
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Default, Args)]
 /// List tree
 pub(crate) struct ListFilesCommand {
@@ -16,12 +27,30 @@ pub(crate) struct ListFilesCommand {
     /// Use specified config file
     #[arg(long, value_name = "FILE")]
     pub config: Option<PathBuf>,
+
+    /// Only list files matching the glob, like "**/*.rs"
+    #[arg(long, value_name = "PATTERN")]
+    pub glob: Option<String>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = ListFilesFormat::Text)]
+    pub format: ListFilesFormat,
 }
 
 impl CliCommad for ListFilesCommand {
     fn exec(self) -> MgitResult {
-        let files = ops::list_files(self.into())?;
-        println!("{}", files.join("\n"));
+        let mut stdout = BufWriter::new(io::stdout().lock());
+        match self.format {
+            ListFilesFormat::Text => {
+                ops::list_files_to_writer(self.into(), stdout)?;
+            }
+            ListFilesFormat::Json => {
+                let files = ops::list_files(self.into())?;
+                serde_json::to_writer_pretty(&mut stdout, &files)?;
+                writeln!(stdout)?;
+                stdout.flush()?;
+            }
+        }
 
         Ok(StyleMessage::default())
     }
@@ -29,6 +58,8 @@ impl CliCommad for ListFilesCommand {
 
 impl From<ListFilesCommand> for ListFilesOptions {
     fn from(value: ListFilesCommand) -> Self {
-        ListFilesOptions::new(value.path, value.config)
+        let mut options = ListFilesOptions::new(value.path, value.config);
+        options.glob = value.glob;
+        options
     }
 }
@@ -0,0 +1,102 @@
+use clap::{Args, ValueEnum};
+use console::{measure_text_width, pad_str, Alignment};
+use std::path::PathBuf;
+
+use mgit::ops::{self, ListReposOptions, RepoEntry};
+use mgit::utils::error::MgitResult;
+use mgit::utils::StyleMessage;
+
+use crate::CliCommad;
+
+/// output format of list-repos
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Default, ValueEnum)]
+pub(crate) enum ListFormat {
+    /// Aligned columns of local path, remote and ref
+    #[default]
+    Table,
+    /// JSON array of repos
+    Json,
+    /// Local paths only, one per line
+    Paths,
+}
+
+/// List repos of the config file
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default, Args)]
+pub(crate) struct ListReposCommand {
+    /// The work directory
+    pub path: Option<PathBuf>,
+
+    /// Use specified config file
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = ListFormat::Table)]
+    pub format: ListFormat,
+
+    /// Only list repos in the group
+    #[arg(long)]
+    pub group: Option<Vec<String>>,
+
+    /// Ignore specified repositories
+    #[arg(long)]
+    pub ignore: Option<Vec<String>>,
+
+    /// Only list specified repositories
+    #[arg(long)]
+    pub only: Option<Vec<String>>,
+}
+
+impl CliCommad for ListReposCommand {
+    fn exec(self) -> MgitResult {
+        let format = self.format;
+        let repos = ops::list_repos(self.into())?;
+
+        match format {
+            ListFormat::Table => print_table(&repos),
+            ListFormat::Json => println!("{}", serde_json::to_string_pretty(&repos)?),
+            ListFormat::Paths => repos.iter().for_each(|repo| println!("{}", repo.local)),
+        }
+        Ok(StyleMessage::default())
+    }
+}
+
+impl From<ListReposCommand> for ListReposOptions {
+    fn from(value: ListReposCommand) -> Self {
+        let mut options = ListReposOptions::new(value.path, value.config);
+        options.ignore = value.ignore;
+        options.only = value.only;
+        options.groups = value.group;
+        options
+    }
+}
+
+fn print_table(repos: &[RepoEntry]) {
+    let rows = repos
+        .iter()
+        .map(|repo| {
+            [
+                repo.local.as_str(),
+                repo.remote.as_deref().unwrap_or("-"),
+                repo.remote_ref.as_deref().unwrap_or("-"),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    // pad by display width, so CJK paths still line up
+    let width = |column: usize| {
+        rows.iter()
+            .map(|row| measure_text_width(row[column]))
+            .max()
+            .unwrap_or(0)
+    };
+    let (local_width, remote_width) = (width(0), width(1));
+    for [local, remote, remote_ref] in rows {
+        println!(
+            "{}  {}  {}",
+            pad_str(local, local_width, Alignment::Left, None),
+            pad_str(remote, remote_width, Alignment::Left, None),
+            remote_ref
+        );
+    }
+}
@@ -1,28 +1,64 @@
 use mgit::utils::error::MgitResult;
 
+pub(crate) use add::AddCommand;
+pub(crate) use checkout::CheckoutCommand;
 pub(crate) use clean::CleanCommand;
+pub(crate) use clone::CloneCommand;
+pub(crate) use completions::CompletionsCommand;
+pub(crate) use convert::ConvertCommand;
 pub(crate) use del_branch::DelRemoteBranchCommand;
+pub(crate) use diff::DiffCommand;
 pub(crate) use fetch::FetchCommand;
+pub(crate) use foreach::ForeachCommand;
+pub(crate) use hooks::HooksCommand;
+pub(crate) use info::InfoCommand;
 pub(crate) use init::InitCommand;
 pub(crate) use list_files::ListFilesCommand;
+pub(crate) use list_repos::ListReposCommand;
 pub(crate) use log_repos::LogReposCommand;
 pub(crate) use new_branch::NewRemoteBranchCommand;
 pub(crate) use new_tag::NewTagCommand;
+pub(crate) use open::OpenCommand;
+pub(crate) use pull::PullCommand;
+pub(crate) use push::PushCommand;
+pub(crate) use remove::RemoveCommand;
+pub(crate) use repair::RepairCommand;
 pub(crate) use snapshot::SnapshotCommand;
+pub(crate) use status::StatusCommand;
 pub(crate) use sync::SyncCommand;
 pub(crate) use track::TrackCommand;
+pub(crate) use validate::ValidateCommand;
+pub(crate) use verify_remote::VerifyRemoteCommand;
 
+mod add;
+mod checkout;
 mod clean;
+mod clone;
+mod completions;
+mod convert;
 mod del_branch;
+mod diff;
 mod fetch;
+mod foreach;
+mod hooks;
+mod info;
 mod init;
 mod list_files;
+mod list_repos;
 mod log_repos;
 mod new_branch;
 mod new_tag;
+mod open;
+mod pull;
+mod push;
+mod remove;
+mod repair;
 mod snapshot;
+mod status;
 mod sync;
 mod track;
+mod validate;
+mod verify_remote;
 
 pub trait CliCommad {
     fn exec(self) -> MgitResult;
@@ -31,6 +31,10 @@ pub(crate) struct NewRemoteBranchCommand {
     /// Ignore specified repositories to create new branch
     #[arg(long)]
     ignore: Option<Vec<String>>,
+
+    /// Only retry the repositories that failed the last run
+    #[arg(long, action = ArgAction::SetTrue)]
+    retry_failed: bool,
 }
 
 impl CliCommad for NewRemoteBranchCommand {
@@ -41,13 +45,15 @@ impl CliCommad for NewRemoteBranchCommand {
 
 impl From<NewRemoteBranchCommand> for NewBranchOptions {
     fn from(value: NewRemoteBranchCommand) -> Self {
-        NewBranchOptions::new(
+        let mut options = NewBranchOptions::new(
             value.path,
             value.config,
             value.new_config,
             value.branch,
             value.force,
             value.ignore,
-        )
+        );
+        options.retry_failed = value.retry_failed;
+        options
     }
 }
@@ -27,6 +27,22 @@ pub(crate) struct NewTagCommand {
     /// Ignore specified repositories to create new branch
     #[arg(long)]
     ignore: Option<Vec<String>>,
+
+    /// Only retry the repositories that failed the last run
+    #[arg(long, action = ArgAction::SetTrue)]
+    retry_failed: bool,
+
+    /// Create annotated tags with the message
+    #[arg(short, long, value_name = "MSG")]
+    message: Option<String>,
+
+    /// Create GPG-signed tags
+    #[arg(short, long, action = ArgAction::SetTrue)]
+    sign: bool,
+
+    /// Don't replace existing tags, locally or on the remote
+    #[arg(long, action = ArgAction::SetTrue)]
+    no_force: bool,
 }
 
 impl CliCommad for NewTagCommand {
@@ -37,12 +53,17 @@ impl CliCommad for NewTagCommand {
 
 impl From<NewTagCommand> for NewTagOptions {
     fn from(value: NewTagCommand) -> Self {
-        NewTagOptions::new(
+        let mut options = NewTagOptions::new(
             value.path,
             value.config,
             value.tag,
             value.push,
             value.ignore,
-        )
+        );
+        options.retry_failed = value.retry_failed;
+        options.message = value.message;
+        options.sign = value.sign;
+        options.force = !value.no_force;
+        options
     }
 }
@@ -0,0 +1,76 @@
+use anyhow::Context;
+use clap::{ArgAction, Args};
+use std::path::PathBuf;
+use std::process::Command;
+
+use mgit::ops::{self, OpenOptions};
+use mgit::utils::error::MgitResult;
+use mgit::utils::StyleMessage;
+
+use crate::CliCommad;
+
+/// Print or open the web page of repos
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default, Args)]
+pub(crate) struct OpenCommand {
+    /// Local path of the repo in the config file
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    pub local: Option<String>,
+
+    /// The work directory
+    #[arg(long)]
+    pub path: Option<PathBuf>,
+
+    /// Use specified config file
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Print the web page of every repo
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub all: bool,
+
+    /// Open the web page in the default browser
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub launch: bool,
+}
+
+impl CliCommad for OpenCommand {
+    fn exec(self) -> MgitResult {
+        let all = self.all;
+        let launch = self.launch;
+        let urls = ops::open_repo(self.into())?;
+
+        for repo_url in urls {
+            match all {
+                true => println!("{}\t{}", repo_url.local, repo_url.url),
+                false => println!("{}", repo_url.url),
+            }
+            if launch {
+                launch_browser(&repo_url.url)?;
+            }
+        }
+        Ok(StyleMessage::default())
+    }
+}
+
+impl From<OpenCommand> for OpenOptions {
+    fn from(value: OpenCommand) -> Self {
+        OpenOptions::new(value.path, value.config, value.local)
+    }
+}
+
+fn launch_browser(url: &str) -> anyhow::Result<()> {
+    let mut command = match std::env::consts::OS {
+        "windows" => {
+            let mut command = Command::new("cmd");
+            command.args(["/C", "start", ""]);
+            command
+        }
+        "macos" => Command::new("open"),
+        _ => Command::new("xdg-open"),
+    };
+    command
+        .arg(url)
+        .spawn()
+        .with_context(|| format!("failed to open {}", url))?;
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use clap::{ArgAction, Args};
+use std::path::PathBuf;
+
+use mgit::ops::{self, PullOptions};
+use mgit::utils::error::MgitResult;
+
+use crate::utils::progress::MultiProgress;
+use crate::CliCommad;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default, Args)]
+pub(crate) struct PullCommand {
+    /// The work directory
+    pub path: Option<PathBuf>,
+
+    /// Use specified config file
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Sets the number of threads to be used
+    #[arg(short, long, default_value_t = 4, value_name = "NUMBER")]
+    thread: usize,
+
+    /// Ignore specified repositories for pull
+    #[arg(long)]
+    ignore: Option<Vec<String>>,
+
+    /// Rebase local commits onto upstream branches instead of fast-forward only
+    #[arg(long, action = ArgAction::SetTrue)]
+    rebase: bool,
+}
+
+impl CliCommad for PullCommand {
+    fn exec(self) -> MgitResult {
+        let progress = MultiProgress::default();
+        ops::pull_repos(self.into(), progress)
+    }
+}
+
+impl From<PullCommand> for PullOptions {
+    fn from(value: PullCommand) -> Self {
+        let mut options =
+            PullOptions::new(value.path, value.config, Some(value.thread), value.ignore);
+        options.rebase = value.rebase;
+        options
+    }
+}
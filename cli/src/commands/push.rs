@@ -0,0 +1,51 @@
+use clap::{ArgAction, Args};
+use std::path::PathBuf;
+
+use mgit::ops::{self, PushOptions};
+use mgit::utils::error::MgitResult;
+
+use crate::utils::progress::MultiProgress;
+use crate::CliCommad;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default, Args)]
+pub(crate) struct PushCommand {
+    /// The work directory
+    pub path: Option<PathBuf>,
+
+    /// Use specified config file
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Sets the number of threads to be used
+    #[arg(short, long, default_value_t = 4, value_name = "NUMBER")]
+    thread: usize,
+
+    /// Ignore specified repositories for push
+    #[arg(long)]
+    ignore: Option<Vec<String>>,
+
+    /// Overwrite upstream branches unless they moved since the last fetch
+    #[arg(long, action = ArgAction::SetTrue)]
+    force_with_lease: bool,
+
+    /// Show what would be pushed without pushing
+    #[arg(long, action = ArgAction::SetTrue)]
+    dry_run: bool,
+}
+
+impl CliCommad for PushCommand {
+    fn exec(self) -> MgitResult {
+        let progress = MultiProgress::default();
+        ops::push_repos(self.into(), progress)
+    }
+}
+
+impl From<PushCommand> for PushOptions {
+    fn from(value: PushCommand) -> Self {
+        let mut options =
+            PushOptions::new(value.path, value.config, Some(value.thread), value.ignore);
+        options.force_with_lease = value.force_with_lease;
+        options.dry_run = value.dry_run;
+        options
+    }
+}
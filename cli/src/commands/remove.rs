@@ -0,0 +1,39 @@
+use clap::{ArgAction, Args};
+use std::path::PathBuf;
+
+use mgit::ops::{self, RemoveOptions};
+use mgit::utils::error::MgitResult;
+
+use crate::CliCommad;
+
+/// Remove repos from the config file
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default, Args)]
+pub(crate) struct RemoveCommand {
+    /// Local paths of the repos in the config file
+    #[arg(required = true, value_name = "LOCAL")]
+    pub locals: Vec<String>,
+
+    /// The work directory
+    #[arg(long)]
+    pub path: Option<PathBuf>,
+
+    /// Use specified config file
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Also delete the repo directories, refused for repos with uncommitted changes
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub delete: bool,
+}
+
+impl CliCommad for RemoveCommand {
+    fn exec(self) -> MgitResult {
+        ops::remove_repos(self.into())
+    }
+}
+
+impl From<RemoveCommand> for RemoveOptions {
+    fn from(value: RemoveCommand) -> Self {
+        RemoveOptions::new(value.path, value.config, value.locals, value.delete)
+    }
+}
@@ -0,0 +1,33 @@
+use clap::Args;
+use std::path::PathBuf;
+
+use mgit::ops::{self, RepairOptions};
+use mgit::utils::error::MgitResult;
+
+use crate::CliCommad;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default, Args)]
+pub(crate) struct RepairCommand {
+    /// The work directory
+    pub path: Option<PathBuf>,
+
+    /// Use specified config file
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Ignore specified repositories for repair
+    #[arg(long)]
+    ignore: Option<Vec<String>>,
+}
+
+impl CliCommad for RepairCommand {
+    fn exec(self) -> MgitResult {
+        ops::repair_repos(self.into())
+    }
+}
+
+impl From<RepairCommand> for RepairOptions {
+    fn from(value: RepairCommand) -> Self {
+        RepairOptions::new(value.path, value.config, value.ignore)
+    }
+}
@@ -4,6 +4,7 @@ use std::path::PathBuf;
 use mgit::ops::{self, SnapshotOptions, SnapshotType};
 use mgit::utils::error::MgitResult;
 
+use crate::utils::progress::MultiProgress;
 use crate::CliCommad;
 
 /// Snapshot git repos
@@ -27,17 +28,34 @@ pub(crate) struct SnapshotCommand {
     /// Ignore specified repositories for snapshot
     #[arg(long)]
     pub ignore: Option<Vec<String>>,
+
+    /// Also search repos under hidden directories like .config
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub scan_hidden: bool,
+
+    /// Sets the number of threads to be used
+    #[arg(short, long, default_value_t = 4, value_name = "NUMBER")]
+    thread: usize,
+
+    /// Only refresh commit/tag/branch of repos already in the config file
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with = "force")]
+    pub amend: bool,
+
+    /// With --amend, also add repos missing from the config file
+    #[arg(long, action = ArgAction::SetTrue, requires = "amend")]
+    pub add_new: bool,
 }
 
 impl CliCommad for SnapshotCommand {
     fn exec(self) -> MgitResult {
-        ops::snapshot_repo(self.into())
+        let progress = MultiProgress::default();
+        ops::snapshot_repo(self.into(), progress)
     }
 }
 
 impl From<SnapshotCommand> for SnapshotOptions {
     fn from(value: SnapshotCommand) -> Self {
-        SnapshotOptions::new(
+        let mut options = SnapshotOptions::new(
             value.path,
             value.config,
             Some(value.force),
@@ -46,6 +64,11 @@ impl From<SnapshotCommand> for SnapshotOptions {
                 false => Some(SnapshotType::Commit),
             },
             value.ignore,
-        )
+        );
+        options.scan_hidden = value.scan_hidden;
+        options.thread_count = value.thread;
+        options.amend = value.amend;
+        options.add_new = value.add_new;
+        options
     }
 }
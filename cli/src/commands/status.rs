@@ -0,0 +1,134 @@
+use clap::{ArgAction, Args};
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
+
+use mgit::core::repo::RepoStatus;
+use mgit::ops::{self, StatusOptions};
+use mgit::utils::error::MgitResult;
+use mgit::utils::StyleMessage;
+
+use crate::utils::progress::MultiProgress;
+use crate::CliCommad;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default, Args)]
+pub(crate) struct StatusCommand {
+    /// The work directory
+    pub path: Option<PathBuf>,
+
+    /// Use specified config file
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Sets the number of threads to be used
+    #[arg(short, long, default_value_t = 4, value_name = "NUMBER")]
+    thread: usize,
+
+    /// Fetch remotes before comparing, without touching the work tree
+    #[arg(long, action = ArgAction::SetTrue)]
+    fetch: bool,
+
+    /// Also show how far each repo is from its upstream branch
+    #[arg(long, action = ArgAction::SetTrue)]
+    upstream: bool,
+
+    /// Compare with the upstream branch of each repo instead of the config file ref
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with = "upstream")]
+    tracking: bool,
+
+    /// Ignore specified repositories for status
+    #[arg(long)]
+    ignore: Option<Vec<String>>,
+
+    /// Only report repos in the group
+    #[arg(long)]
+    group: Option<Vec<String>>,
+
+    /// Print the status as JSON, listing the staged, changed and untracked files of each repo
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with = "upstream")]
+    json: bool,
+
+    /// Print a table of branch, ahead/behind and local changes once all repos are compared
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with_all = ["upstream", "json"])]
+    table: bool,
+}
+
+impl CliCommad for StatusCommand {
+    fn exec(self) -> MgitResult {
+        if self.json {
+            let stdout = BufWriter::new(io::stdout().lock());
+            ops::status_files_to_writer(self.into(), stdout)?;
+            return Ok(StyleMessage::default());
+        }
+
+        if self.table {
+            print_table(&ops::status(self.into())?);
+            return Ok(StyleMessage::default());
+        }
+
+        let progress = MultiProgress::default();
+        ops::status_repos(self.into(), progress)
+    }
+}
+
+impl From<StatusCommand> for StatusOptions {
+    fn from(value: StatusCommand) -> Self {
+        let mut options = StatusOptions::new(
+            value.path,
+            value.config,
+            Some(value.thread),
+            Some(value.fetch),
+            value.ignore,
+        );
+        options.upstream = value.upstream;
+        options.tracking = value.tracking;
+        options.groups = value.group;
+        options
+    }
+}
+
+fn print_table(statuses: &[RepoStatus]) {
+    let count = |value: Option<usize>| value.map_or("-".to_string(), |value| value.to_string());
+    let rows = statuses
+        .iter()
+        .map(|status| match status.missing {
+            true => [&status.local, "missing", "-", "-", "-"].map(String::from),
+            false => [
+                status.local.clone(),
+                match status.branch.is_empty() {
+                    true => "-".to_string(),
+                    false => status.branch.clone(),
+                },
+                count(status.ahead),
+                count(status.behind),
+                match status.changes {
+                    0 => "clean".to_string(),
+                    changes => format!("{} changed", changes),
+                },
+            ],
+        })
+        .collect::<Vec<_>>();
+
+    let header = ["LOCAL", "BRANCH", "AHEAD", "BEHIND", "CHANGES"].map(String::from);
+    let widths = std::iter::once(&header)
+        .chain(&rows)
+        .fold([0; 5], |mut widths, row| {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+            widths
+        });
+    for row in std::iter::once(&header).chain(&rows) {
+        println!(
+            "{:<w0$}  {:<w1$}  {:>w2$}  {:>w3$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            row[4],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3],
+        );
+    }
+}
@@ -1,20 +1,75 @@
-use clap::{ArgAction, Args};
-use std::path::PathBuf;
+use anyhow::anyhow;
+use clap::{ArgAction, Args, ValueEnum};
+use console::Term;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use mgit::ops::{self, SyncOptions};
+use mgit::core::git::RemoteRef;
+use mgit::core::repo::{include_groups, parse_ref_override, repo_status, TomlRepo};
+use mgit::core::repos::{TomlConfig, WorkspacePaths};
+
+use mgit::ops::{self, OnMissing, OnStashConflict, SyncOptions};
 use mgit::utils::error::MgitResult;
+use mgit::utils::path::PathExtension;
+use mgit::utils::style_message::StyleMessage;
 
+use crate::utils::logger;
+use crate::utils::picker::{self, PickItem};
 use crate::utils::progress::MultiProgress;
 use crate::CliCommad;
 
+/// what to do with repos whose directory is missing
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Default, ValueEnum)]
+pub(crate) enum OnMissingArg {
+    /// Leave missing repos out of the sync
+    Skip,
+    /// Clone missing repos
+    #[default]
+    Clone,
+    /// Fail missing repos
+    Error,
+}
+
+impl From<OnMissingArg> for OnMissing {
+    fn from(value: OnMissingArg) -> Self {
+        match value {
+            OnMissingArg::Skip => OnMissing::Skip,
+            OnMissingArg::Clone => OnMissing::Clone,
+            OnMissingArg::Error => OnMissing::Error,
+        }
+    }
+}
+
+/// what to do when popping the stash back conflicts
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Default, ValueEnum)]
+pub(crate) enum OnStashConflictArg {
+    /// Leave the conflicts in the work tree
+    #[default]
+    Keep,
+    /// Undo the pop and leave the changes in the stash
+    Restash,
+    /// Undo the pop and move the changes to a new branch
+    Branch,
+}
+
+impl From<OnStashConflictArg> for OnStashConflict {
+    fn from(value: OnStashConflictArg) -> Self {
+        match value {
+            OnStashConflictArg::Keep => OnStashConflict::Keep,
+            OnStashConflictArg::Restash => OnStashConflict::Restash,
+            OnStashConflictArg::Branch => OnStashConflict::Branch,
+        }
+    }
+}
+
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Default, Args)]
 pub(crate) struct SyncCommand {
     /// The work directory
     pub path: Option<PathBuf>,
 
-    /// Use specified config file
+    /// Use specified config file, repeat it to sync the repos of several files at once
     #[arg(long, value_name = "FILE")]
-    pub config: Option<PathBuf>,
+    pub config: Vec<PathBuf>,
 
     /// Stash local changes after sync
     #[arg(long, action = ArgAction::SetTrue)]
@@ -44,23 +99,275 @@ pub(crate) struct SyncCommand {
     #[arg(short, long, value_name = "NUMBER")]
     depth: Option<usize>,
 
+    /// Fail instead of fetching the full history when a depth meets pinned commits
+    #[arg(long, action = ArgAction::SetTrue)]
+    strict: bool,
+
     /// Ignore specified repositories for sync
     #[arg(long)]
     ignore: Option<Vec<String>>,
+
+    /// Only sync repos in the group
+    #[arg(long)]
+    group: Option<Vec<String>>,
+
+    /// Keep untracked files matching the pattern when cleaning with --hard
+    #[arg(long, value_name = "PATTERN")]
+    clean_exclude: Vec<String>,
+
+    /// Skip fresh clones larger than the given size in MB
+    #[arg(long, value_name = "MB")]
+    max_clone_mb: Option<u64>,
+
+    /// Fetch repos pinned to a commit even if the commit is already present
+    #[arg(long, action = ArgAction::SetTrue)]
+    fetch_pinned_commit: bool,
+
+    /// What to do with repos whose directory is missing
+    #[arg(long, value_enum, default_value_t = OnMissingArg::Clone, value_name = "MODE")]
+    on_missing: OnMissingArg,
+
+    /// Report failures of repos listed in the file as warnings instead of errors
+    #[arg(long, value_name = "FILE")]
+    ignore_errors_from: Option<PathBuf>,
+
+    /// Check out the git repo holding the config file at this ref, then sync with its config
+    #[arg(long, visible_alias = "manifest-branch", value_name = "REF")]
+    self_update: Option<String>,
+
+    /// Create and check out this branch at the synced ref in every repo
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["no_checkout", "mirror"])]
+    work_branch: Option<String>,
+
+    /// Sync repos already up to date too, and move an existing --work-branch to the synced ref
+    /// instead of skipping the repo
+    #[arg(long, action = ArgAction::SetTrue)]
+    force: bool,
+
+    /// Stash, check out and reset repos already at the synced ref with a clean work tree too,
+    /// like --force without moving an existing --work-branch
+    #[arg(long, action = ArgAction::SetTrue)]
+    no_skip_up_to_date: bool,
+
+    /// Leave LFS files as pointers instead of downloading them
+    #[arg(long, action = ArgAction::SetTrue)]
+    skip_lfs_smudge: bool,
+
+    /// Kill any git command running longer than this many seconds, failing only its repo
+    #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    timeout: Option<u64>,
+
+    /// Remove and clone again repos whose checkout fails twice, if they have no local changes
+    #[arg(long, action = ArgAction::SetTrue)]
+    reclone_on_failure: bool,
+
+    /// Stop starting new repos after the first failure, instead of syncing all of them
+    #[arg(long, action = ArgAction::SetTrue)]
+    fail_fast: bool,
+
+    /// What to do when popping the stash back after checkout conflicts
+    #[arg(long, value_enum, default_value_t = OnStashConflictArg::Keep, value_name = "MODE")]
+    on_stash_conflict: OnStashConflictArg,
+
+    /// Sync the repo at another ref for this run, like libs/foo=feature-x or foo=tag:v1
+    #[arg(long = "override", value_name = "LOCAL=REF", value_parser = parse_override)]
+    overrides: Vec<(String, RemoteRef)>,
+
+    /// Only sync repos whose remote, branch, tag or commit changed since this config file
+    #[arg(long, value_name = "FILE")]
+    since_snapshot: Option<PathBuf>,
+
+    /// Apply sparse checkouts as patterns instead of the faster cone mode
+    #[arg(long, action = ArgAction::SetTrue)]
+    no_cone: bool,
+
+    /// Init and update submodules of the repos after the checkout
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with = "mirror")]
+    recurse_submodules: bool,
+
+    /// Speed up fetching long histories with skipping negotiation and protocol v2
+    #[arg(long, action = ArgAction::SetTrue)]
+    fast_fetch: bool,
+
+    /// Keep remote-tracking branches deleted on the remote, which fetching removes by default
+    #[arg(long, action = ArgAction::SetTrue)]
+    no_prune: bool,
+
+    /// Limit concurrent fetches against the same remote host
+    #[arg(long, value_name = "NUMBER")]
+    jobs_per_host: Option<usize>,
+
+    /// Keep bare mirror clones updated instead of work trees
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with_all = ["stash", "hard", "depth"])]
+    mirror: bool,
+
+    /// Stash only tracked changes, leaving untracked files in place
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with = "hard")]
+    keep_untracked: bool,
+
+    /// List untracked, modified and staged files swept into the stash
+    #[arg(long, action = ArgAction::SetTrue)]
+    report_untracked: bool,
+
+    /// Print local paths of failed repos one per line to stderr, or to FILE
+    #[arg(long, value_name = "FILE", num_args = 0..=1, require_equals = true)]
+    print_failed_paths: Option<Option<PathBuf>>,
+
+    /// Print nothing when all repos succeed, and all output only when some fail
+    #[arg(long, action = ArgAction::SetTrue)]
+    summary_only_on_error: bool,
+
+    /// Write the files of commit/tag pins without moving HEAD or creating branches, for throwaway checkouts
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with_all = ["stash", "no_checkout", "mirror", "work_branch"])]
+    checkout_index_only: bool,
+
+    /// Print the output of git commands prefixed with the repo, instead of progress bars
+    #[arg(long, action = ArgAction::SetTrue)]
+    verbose_git: bool,
+
+    /// Print wall time of clone/fetch/checkout/reset per repo after sync
+    #[arg(long, action = ArgAction::SetTrue)]
+    timings: bool,
+
+    /// Write the resolved sync plan as JSON to the file without executing it
+    #[arg(long, value_name = "FILE")]
+    dump_plan: Option<PathBuf>,
+
+    /// Print what sync would do to every repo, like resets, clones and stashes, without doing it
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with_all = ["dump_plan", "write_lock", "self_update", "continue_sync", "abort"])]
+    dry_run: bool,
+
+    /// After syncing, write the config with every repo pinned to the commit it ended up on
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["dump_plan", "checkout_index_only", "mirror"])]
+    write_lock: Option<PathBuf>,
+
+    /// Proxy of clone and fetch for the repos without a `proxy` in the config
+    #[arg(long, value_name = "URL")]
+    proxy: Option<String>,
+
+    /// Pick the repos to sync from a checkbox list, dirty or diverged ones are checked
+    #[arg(long, alias = "interactive-ignore", action = ArgAction::SetTrue)]
+    pick: bool,
+
+    /// Continue repos stopped on conflicts after resolving them
+    #[arg(long = "continue", action = ArgAction::SetTrue, conflicts_with_all = ["stash", "hard"])]
+    continue_sync: bool,
+
+    /// Restore repos left halfway by an interrupted sync to their branch from before it
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with_all = ["stash", "hard", "continue_sync"])]
+    abort: bool,
 }
 
 impl CliCommad for SyncCommand {
-    fn exec(self) -> MgitResult {
-        let progress = MultiProgress::default();
-        ops::sync_repo(self.into(), progress)
+    fn exec(mut self) -> MgitResult {
+        if self.pick {
+            self.pick_repos()?;
+        }
+
+        // bars redrawn over the raw git output would garble it
+        if self.verbose_git && !self.summary_only_on_error {
+            return ops::sync_repo(self.into(), MultiProgress::hidden());
+        }
+
+        if !self.summary_only_on_error {
+            let progress = MultiProgress::default();
+            return ops::sync_repo(self.into(), progress);
+        }
+
+        // keep the whole output until the result is known
+        logger::start_buffering();
+        match ops::sync_repo(self.into(), MultiProgress::buffered()) {
+            Ok(_) => {
+                logger::discard_buffer();
+                Ok(StyleMessage::default())
+            }
+            Err(e) => {
+                logger::flush_buffer();
+                Err(e)
+            }
+        }
+    }
+}
+
+impl SyncCommand {
+    /// ignore the repos left unchecked in the picker
+    fn pick_repos(&mut self) -> MgitResult<()> {
+        let term = Term::stderr();
+        if !term.is_term() {
+            return Err(anyhow!(
+                "--pick needs a terminal, use --ignore to leave repos out instead"
+            ));
+        }
+
+        let WorkspacePaths { path, config_path } =
+            WorkspacePaths::resolve(self.path.as_ref(), self.config.first());
+        let config_paths = std::iter::once(&config_path)
+            .chain(self.config.iter().skip(1))
+            .collect::<Vec<_>>();
+        let toml_config = TomlConfig::load_merged(&config_paths)?;
+
+        let ignore = self.ignore.clone().unwrap_or_default();
+        let repos = include_groups(toml_config.repos.unwrap_or_default(), self.group.as_ref())
+            .into_iter()
+            .filter(|repo| matches!(&repo.local, Some(local) if !ignore.contains(local)))
+            .collect::<Vec<_>>();
+        if repos.is_empty() {
+            return Ok(());
+        }
+
+        let default_branch = toml_config.default_branch;
+        let items = repos
+            .iter()
+            .map(|repo| pick_item(&path, repo, &default_branch))
+            .collect();
+        let picked = picker::pick(&term, "Select repos to sync:", items)?;
+
+        let ignore = self.ignore.get_or_insert_with(Vec::new);
+        for (idx, repo) in repos.into_iter().enumerate() {
+            if !picked.contains(&idx) {
+                ignore.push(repo.local.unwrap());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_override(value: &str) -> Result<(String, RemoteRef), String> {
+    parse_ref_override(value).map_err(|e| e.to_string())
+}
+
+/// repos which are missing, dirty or off their manifest ref start checked
+fn pick_item(path: &Path, repo: &TomlRepo, default_branch: &Option<String>) -> PickItem {
+    let local = repo.local.as_ref().unwrap();
+    let (desc, checked) = match repo_status(path, repo, default_branch, false) {
+        _ if !path.join(local).is_dir() => ("missing".to_string(), true),
+        Ok(status) => {
+            let mut desc = Vec::new();
+            if status.changes > 0 {
+                desc.push(format!("{} changes", status.changes));
+            }
+            if !status.matches_ref {
+                desc.push("diverged".to_string());
+            }
+            match desc.is_empty() {
+                true => ("up to date".to_string(), false),
+                false => (desc.join(", "), true),
+            }
+        }
+        Err(_) => ("unknown".to_string(), true),
+    };
+
+    PickItem {
+        label: format!("{} ({})", local.display_path(), desc),
+        checked,
     }
 }
 
 impl From<SyncCommand> for SyncOptions {
     fn from(value: SyncCommand) -> Self {
-        SyncOptions::new(
+        let mut options = SyncOptions::new(
             value.path,
-            value.config,
+            value.config.first(),
             Some(value.thread),
             Some(value.silent),
             value.depth,
@@ -69,6 +376,47 @@ impl From<SyncCommand> for SyncOptions {
             Some(value.stash),
             Some(value.no_track),
             Some(value.no_checkout),
-        )
+        );
+        options.max_clone_mb = value.max_clone_mb;
+        options.continue_sync = value.continue_sync;
+        options.abort = value.abort;
+        options.fetch_pinned_commit = value.fetch_pinned_commit;
+        options.dump_plan = value.dump_plan;
+        options.dry_run = value.dry_run;
+        options.write_lock = value.write_lock;
+        options.proxy = value.proxy;
+        options.groups = value.group;
+        options.jobs_per_host = value.jobs_per_host;
+        options.ignore_errors_from = value.ignore_errors_from;
+        options.on_missing = value.on_missing.into();
+        options.on_stash_conflict = value.on_stash_conflict.into();
+        options.reclone_on_failure = value.reclone_on_failure;
+        options.fail_fast = value.fail_fast;
+        options.skip_lfs_smudge = value.skip_lfs_smudge;
+        options.timeout = value.timeout.map(Duration::from_secs);
+        options.self_update = value.self_update;
+        options.checkout_index_only = value.checkout_index_only;
+        options.verbose_git = value.verbose_git;
+        options.work_branch = value.work_branch;
+        options.force_work_branch = value.force;
+        options.skip_up_to_date = !(value.force || value.no_skip_up_to_date);
+        options.timings = value.timings;
+        options.clean_excludes = value.clean_exclude;
+        options.report_untracked = value.report_untracked;
+        options.strict = value.strict;
+        options.keep_untracked = value.keep_untracked;
+        options.mirror = value.mirror;
+        options.fast_fetch = value.fast_fetch;
+        options.prune = !value.no_prune;
+        options.no_cone = value.no_cone;
+        options.recurse_submodules = value.recurse_submodules;
+        options.ref_overrides = value.overrides;
+        options.since_snapshot = value.since_snapshot;
+        options.extra_config_paths = value.config.iter().skip(1).cloned().collect();
+        if let Some(file) = value.print_failed_paths {
+            options.print_failed_paths = true;
+            options.failed_paths_file = file;
+        }
+        options
     }
 }
@@ -0,0 +1,36 @@
+use clap::{ArgAction, Args};
+use std::path::PathBuf;
+
+use mgit::ops::{self, ValidateOptions};
+use mgit::utils::error::MgitResult;
+
+use crate::CliCommad;
+
+/// Check the config file for consistency problems
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default, Args)]
+pub(crate) struct ValidateCommand {
+    /// The work directory
+    pub path: Option<PathBuf>,
+
+    /// Use specified config file
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Fail on warnings too, like repos without any ref and no default-branch
+    #[arg(long, action = ArgAction::SetTrue)]
+    strict: bool,
+}
+
+impl CliCommad for ValidateCommand {
+    fn exec(self) -> MgitResult {
+        ops::validate(self.into())
+    }
+}
+
+impl From<ValidateCommand> for ValidateOptions {
+    fn from(value: ValidateCommand) -> Self {
+        let mut options = ValidateOptions::new(value.path, value.config);
+        options.strict = value.strict;
+        options
+    }
+}
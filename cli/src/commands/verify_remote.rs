@@ -0,0 +1,37 @@
+use clap::{ArgAction, Args};
+use std::path::PathBuf;
+
+use mgit::ops::{self, VerifyRemoteOptions};
+use mgit::utils::error::MgitResult;
+
+use crate::CliCommad;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default, Args)]
+pub(crate) struct VerifyRemoteCommand {
+    /// The work directory
+    pub path: Option<PathBuf>,
+
+    /// Use specified config file
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Set drifted remote urls back to the manifest
+    #[arg(long, action = ArgAction::SetTrue)]
+    fix: bool,
+
+    /// Ignore specified repositories for verify
+    #[arg(long)]
+    ignore: Option<Vec<String>>,
+}
+
+impl CliCommad for VerifyRemoteCommand {
+    fn exec(self) -> MgitResult {
+        ops::verify_remote(self.into())
+    }
+}
+
+impl From<VerifyRemoteCommand> for VerifyRemoteOptions {
+    fn from(value: VerifyRemoteCommand) -> Self {
+        VerifyRemoteOptions::new(value.path, value.config, Some(value.fix), value.ignore)
+    }
+}
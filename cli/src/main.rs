@@ -21,23 +21,49 @@ fn main() {
     init_log();
 
     let cli = Cli::parse();
+    mgit::utils::cmd::set_trace_git(cli.trace_git);
+    utils::progress::set_progress_style(cli.progress_style);
+    mgit::core::repos::set_config_format(cli.config_format.into());
+
     let result: MgitResult = match cli.command {
         Commands::Init(cmd) => cmd.exec(),
         Commands::Snapshot(cmd) => cmd.exec(),
         Commands::Fetch(cmd) => cmd.exec(),
-        Commands::Sync(cmd) => cmd.exec(),
+        Commands::Pull(cmd) => cmd.exec(),
+        Commands::Push(cmd) => cmd.exec(),
+        Commands::Sync(cmd) => (*cmd).exec(),
+        Commands::Clone(cmd) => cmd.exec(),
+        Commands::Status(cmd) => cmd.exec(),
+        Commands::Diff(cmd) => cmd.exec(),
+        Commands::Repair(cmd) => cmd.exec(),
+        Commands::Checkout(cmd) => cmd.exec(),
+        Commands::VerifyRemote(cmd) => cmd.exec(),
+        Commands::Validate(cmd) => cmd.exec(),
         Commands::Clean(cmd) => cmd.exec(),
         Commands::ListFiles(cmd) => cmd.exec(),
         Commands::Track(cmd) => cmd.exec(),
+        Commands::Open(cmd) => cmd.exec(),
+        Commands::Foreach(cmd) => cmd.exec(),
+        Commands::Hooks(cmd) => cmd.exec(),
+        Commands::Info(cmd) => cmd.exec(),
+        Commands::Convert(cmd) => cmd.exec(),
+        Commands::ListRepos(cmd) => cmd.exec(),
+        Commands::Add(cmd) => cmd.exec(),
+        Commands::Remove(cmd) => cmd.exec(),
         Commands::LogRepos(cmd) => cmd.exec(),
         Commands::NewRemoteBranch(cmd) => cmd.exec(),
         Commands::DelRemoteBranch(cmd) => cmd.exec(),
         Commands::NewTag(cmd) => cmd.exec(),
+        Commands::Completions(cmd) => cmd.exec(),
     };
+    mgit::core::repos::remove_stdin_config();
 
     match result {
         Ok(msg) => {
-            println!("{}", msg);
+            // commands return nothing when they have to stay silent
+            if !msg.is_empty() {
+                println!("{}", msg);
+            }
             std::process::exit(0);
         }
         Err(e) => {
@@ -1,17 +1,54 @@
+use std::sync::Mutex;
+
 use mgit::utils::logger;
 use mgit::utils::style_message::StyleMessage;
 
 pub(crate) static TERM_LOGGER: TermLogger = TermLogger {};
 
+/// output held back by `start_buffering`, none when writing straight to the terminal
+static BUFFER: Mutex<Option<Vec<BufferedLine>>> = Mutex::new(None);
+
+enum BufferedLine {
+    Info(String),
+    Error(String),
+}
+
+/// hold back all log output until `flush_buffer` or `discard_buffer`
+pub(crate) fn start_buffering() {
+    *BUFFER.lock().unwrap() = Some(Vec::new());
+}
+
+/// write the held back output to the terminal and stop buffering
+pub(crate) fn flush_buffer() {
+    let lines = BUFFER.lock().unwrap().take().unwrap_or_default();
+    for line in lines {
+        match line {
+            BufferedLine::Info(message) => println!("{}", message),
+            BufferedLine::Error(message) => eprintln!("{}", message),
+        }
+    }
+}
+
+/// drop the held back output and stop buffering
+pub(crate) fn discard_buffer() {
+    BUFFER.lock().unwrap().take();
+}
+
 #[derive(Clone, Default)]
 pub struct TermLogger;
 
 impl logger::Log for TermLogger {
     fn info(&self, message: StyleMessage) {
-        println!("{}", message)
+        match BUFFER.lock().unwrap().as_mut() {
+            Some(buffer) => buffer.push(BufferedLine::Info(message.to_string())),
+            None => println!("{}", message),
+        }
     }
 
     fn error(&self, message: StyleMessage) {
-        eprintln!("{}", message)
+        match BUFFER.lock().unwrap().as_mut() {
+            Some(buffer) => buffer.push(BufferedLine::Error(message.to_string())),
+            None => eprintln!("{}", message),
+        }
     }
 }
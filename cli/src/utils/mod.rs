@@ -1,2 +1,3 @@
 pub(crate) mod logger;
+pub(crate) mod picker;
 pub(crate) mod progress;
@@ -0,0 +1,60 @@
+use std::io;
+
+use console::{Key, Term};
+
+/// an entry of the checkbox list
+pub(crate) struct PickItem {
+    pub label: String,
+    pub checked: bool,
+}
+
+/// let the user toggle the items on the terminal, return the checked ones by index.
+///
+/// keys: up/down (or k/j) move, space toggles, `a` toggles all, enter confirms,
+/// esc/q aborts.
+pub(crate) fn pick(term: &Term, prompt: &str, mut items: Vec<PickItem>) -> io::Result<Vec<usize>> {
+    let mut cursor = 0;
+    term.write_line(prompt)?;
+    term.hide_cursor()?;
+    render(term, &items, cursor)?;
+
+    let result = loop {
+        let key = match term.read_key() {
+            Ok(key) => key,
+            Err(e) => break Err(e),
+        };
+        match key {
+            Key::ArrowUp | Key::Char('k') => {
+                cursor = cursor.checked_sub(1).unwrap_or(items.len() - 1)
+            }
+            Key::ArrowDown | Key::Char('j') => cursor = (cursor + 1) % items.len(),
+            Key::Char(' ') => items[cursor].checked = !items[cursor].checked,
+            Key::Char('a') => {
+                let checked = !items.iter().all(|item| item.checked);
+                items.iter_mut().for_each(|item| item.checked = checked);
+            }
+            Key::Enter => {
+                let picked = items.iter().enumerate().filter(|(_, item)| item.checked);
+                break Ok(picked.map(|(idx, _)| idx).collect());
+            }
+            Key::Escape | Key::CtrlC | Key::Char('q') => {
+                break Err(io::Error::new(io::ErrorKind::Interrupted, "aborted"));
+            }
+            _ => continue,
+        }
+        term.clear_last_lines(items.len())?;
+        render(term, &items, cursor)?;
+    };
+
+    term.show_cursor()?;
+    result
+}
+
+fn render(term: &Term, items: &[PickItem], cursor: usize) -> io::Result<()> {
+    for (idx, item) in items.iter().enumerate() {
+        let pointer = if idx == cursor { ">" } else { " " };
+        let check = if item.checked { "x" } else { " " };
+        term.write_line(&format!("{} [{}] {}", pointer, check, item.label))?;
+    }
+    Ok(())
+}
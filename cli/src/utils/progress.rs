@@ -1,13 +1,76 @@
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
 
-use indicatif::{ProgressBar, ProgressStyle};
+use clap::ValueEnum;
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
 use mgit::utils::logger::get_logger;
 use mgit::utils::path::PathExtension;
 
 use mgit::utils::progress::{Progress, RepoInfo};
-use mgit::utils::style_message::{StyleMessage, GREEN_BOLD, PURPLE_BOLD};
+use mgit::utils::style_message::{StyleMessage, GREEN_BOLD, PURPLE_BOLD, RED_BOLD};
+
+static PROGRESS_STYLE: OnceLock<ProgressStyleKind> = OnceLock::new();
+
+/// predefined looks of the progress bars
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ProgressStyleKind {
+    /// Colored bars and spinners
+    #[default]
+    Default,
+    /// Only ASCII characters, for terminals without unicode support
+    Ascii,
+    /// No spinners, only the repo messages
+    Minimal,
+}
+
+impl ProgressStyleKind {
+    fn total_bar(self) -> ProgressStyle {
+        match self {
+            ProgressStyleKind::Default => ProgressStyle::default_bar()
+                .template("[{elapsed_precise}] {percent}% [{bar:30.green/white}] {pos}/{len}")
+                .unwrap()
+                .progress_chars("=>-"),
+            ProgressStyleKind::Ascii => ProgressStyle::default_bar()
+                .template("[{elapsed_precise}] {percent}% [{bar:30}] {pos}/{len}")
+                .unwrap()
+                .progress_chars("#>-"),
+            ProgressStyleKind::Minimal => ProgressStyle::default_bar()
+                .template("{pos}/{len}")
+                .unwrap(),
+        }
+    }
+
+    fn spinner(self) -> ProgressStyle {
+        match self {
+            ProgressStyleKind::Default => {
+                ProgressStyle::with_template("{spinner:.green.dim.bold} {msg} ")
+                    .unwrap()
+                    .tick_chars("/-\\| ")
+            }
+            ProgressStyleKind::Ascii => ProgressStyle::with_template("{spinner} {msg} ")
+                .unwrap()
+                .tick_chars("|/-\\ "),
+            ProgressStyleKind::Minimal => ProgressStyle::with_template("{msg}").unwrap(),
+        }
+    }
+
+    fn end_sign(self, is_success: bool) -> String {
+        match (self, is_success) {
+            (ProgressStyleKind::Ascii, true) => GREEN_BOLD.paint("ok").to_string(),
+            (ProgressStyleKind::Ascii, false) => RED_BOLD.paint("x").to_string(),
+            _ => StyleMessage::repo_end(is_success).to_string(),
+        }
+    }
+}
+
+pub(crate) fn set_progress_style(style: ProgressStyleKind) {
+    let _ = PROGRESS_STYLE.set(style);
+}
+
+fn progress_style() -> ProgressStyleKind {
+    PROGRESS_STYLE.get().copied().unwrap_or_default()
+}
 
 #[derive(Clone, Default)]
 pub(crate) struct MultiProgress {
@@ -15,21 +78,34 @@ pub(crate) struct MultiProgress {
     main_progress_bar: Arc<Mutex<Option<ProgressBar>>>,
     spinner_progress_bars: Arc<Mutex<HashMap<usize, ProgressBar>>>,
     total_repos: Arc<AtomicUsize>,
+    /// bars are not drawn, the final line of each repo goes to the logger instead
+    buffered: bool,
 }
 
 impl MultiProgress {
+    /// progress for buffered output, see `logger::start_buffering`
+    pub(crate) fn buffered() -> Self {
+        Self::hidden()
+    }
+
+    /// progress without bars, only the final line of each repo is logged
+    pub(crate) fn hidden() -> Self {
+        Self {
+            multi_progress: Arc::new(Mutex::new(indicatif::MultiProgress::with_draw_target(
+                ProgressDrawTarget::hidden(),
+            ))),
+            buffered: true,
+            ..Default::default()
+        }
+    }
+
     fn create_total_bar(&self, total: usize) {
         let main_progress_bar = self
             .multi_progress
             .lock()
             .unwrap()
             .add(ProgressBar::new(total as u64));
-        main_progress_bar.set_style(
-            ProgressStyle::default_bar()
-                .template("[{elapsed_precise}] {percent}% [{bar:30.green/white}] {pos}/{len}")
-                .unwrap()
-                .progress_chars("=>-"),
-        );
+        main_progress_bar.set_style(progress_style().total_bar());
         main_progress_bar.enable_steady_tick(std::time::Duration::from_millis(500));
         let _ = self
             .main_progress_bar
@@ -44,11 +120,7 @@ impl MultiProgress {
             .lock()
             .unwrap()
             .insert(id, ProgressBar::new_spinner());
-        progress_bar.set_style(
-            ProgressStyle::with_template("{spinner:.green.dim.bold} {msg} ")
-                .unwrap()
-                .tick_chars("/-\\| "),
-        );
+        progress_bar.set_style(progress_style().spinner());
         progress_bar.enable_steady_tick(std::time::Duration::from_millis(500));
         self.spinner_progress_bars
             .lock()
@@ -82,7 +154,7 @@ impl MultiProgress {
     fn spinner_end(&self, repo_info: &RepoInfo, status: StyleMessage, is_success: bool) -> String {
         format!(
             "{:>9} {} {}: {}",
-            StyleMessage::repo_end(is_success),
+            progress_style().end_sign(is_success),
             Self::prefix(repo_info.index, self.total_repos.load(Ordering::Relaxed)),
             &GREEN_BOLD.paint(repo_info.rel_path().display_path()),
             status,
@@ -127,9 +199,11 @@ impl Progress for MultiProgress {
         let locked = self.spinner_progress_bars.lock().unwrap();
         let pb = locked.get(&repo_info.index).unwrap();
         if !pb.is_finished() {
-            pb.finish_with_message(truncate_spinner_msg(
-                self.spinner_end(repo_info, message, true),
-            ));
+            let msg = self.spinner_end(repo_info, message, true);
+            if self.buffered {
+                get_logger().info(msg.as_str().into());
+            }
+            pb.finish_with_message(truncate_spinner_msg(msg));
         }
 
         self.main_progress_bar
@@ -144,7 +218,11 @@ impl Progress for MultiProgress {
         let locked = self.spinner_progress_bars.lock().unwrap();
         let pb = locked.get(&repo_info.index).unwrap();
         if !pb.is_finished() {
-            pb.finish_with_message(self.spinner_end(repo_info, message, false));
+            let msg = self.spinner_end(repo_info, message, false);
+            if self.buffered {
+                get_logger().info(msg.as_str().into());
+            }
+            pb.finish_with_message(msg);
         }
 
         self.main_progress_bar
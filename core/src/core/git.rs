@@ -1,40 +1,234 @@
-use std::path::Path;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use thiserror::Error;
 
 use crate::utils::cmd::exec_cmd;
 use crate::utils::style_message::StyleMessage;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum StashMode {
     Normal,
     Stash,
     Hard,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ResetType {
     Soft,
     Mixed,
     Hard,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// why a directory is not a usable repository
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RepositoryError {
+    /// no `.git` here, a new repository can be created
+    #[error("repository not found!")]
+    NotFound,
+    /// `.git` exists but git can't use it, e.g. corrupt or unreadable
+    #[error("repository is broken: {0}")]
+    Broken(String),
+}
+
+/// checkout failures whose git message gives no hint how to get out of them
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CheckoutError {
+    /// on case-insensitive filesystems this is mostly a tracked path differing only in case
+    #[error(
+        "checkout would overwrite untracked files: {}\n\
+         if they differ only in case from tracked files, the filesystem is case-insensitive: \
+         rename them upstream or check `git config core.ignorecase`; \
+         otherwise move or remove them",
+        .0.join(", ")
+    )]
+    UntrackedOverwritten(Vec<String>),
+    /// paths differing only in case, of which only one can exist in the work tree
+    #[error(
+        "paths differ only in case and collide on this case-insensitive filesystem: {}\n\
+         rename them upstream or check `git config core.ignorecase`",
+        .0.join(", ")
+    )]
+    CaseCollision(Vec<String>),
+}
+
+/// map git's checkout output to a `CheckoutError`, if it is one of the known failures
+pub fn parse_checkout_error(output: &str) -> Option<CheckoutError> {
+    let mut lines = output.lines();
+    let header = lines.find(|line| {
+        line.contains("untracked working tree files would be")
+            || line.contains("the following paths have collided")
+    })?;
+
+    // affected paths are listed indented below the header, which may wrap
+    let is_indented = |line: &&str| line.starts_with(char::is_whitespace);
+    let paths = lines
+        .skip_while(|line| !is_indented(line))
+        .take_while(is_indented)
+        .map(|line| line.trim().trim_matches('\'').to_string())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+    match header.contains("collided") {
+        true => Some(CheckoutError::CaseCollision(paths)),
+        false => Some(CheckoutError::UntrackedOverwritten(paths)),
+    }
+}
+
+/// version of the git executable, compared field by field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GitVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl GitVersion {
+    /// `sparse-checkout set` takes `--cone`/`--no-cone` since git 2.35
+    pub const SPARSE_CONE: GitVersion = GitVersion::new(2, 35, 0);
+
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// parse `git --version` output, like `git version 2.39.3 (Apple Git-145)`
+    /// or `git version 2.41.0.windows.1`
+    pub fn parse(output: &str) -> Option<Self> {
+        let version = output.trim().strip_prefix("git version ")?;
+        let mut numbers = version
+            .split(|c: char| !c.is_ascii_digit())
+            .map(|n| n.parse::<u32>().ok());
+        let major = numbers.next()??;
+        let minor = numbers.next()??;
+        let patch = numbers.next().flatten().unwrap_or(0);
+        Some(Self::new(major, minor, patch))
+    }
+}
+
+/// version of the git executable, detected once; none if it can't be told
+pub fn git_version() -> Option<GitVersion> {
+    static VERSION: OnceLock<Option<GitVersion>> = OnceLock::new();
+    *VERSION.get_or_init(|| {
+        exec_cmd(".", "git", &["--version"])
+            .ok()
+            .and_then(|output| GitVersion::parse(&output))
+    })
+}
+
+/// which url of a remote to query, a remote may have distinct fetch and push urls
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FetchOrPush {
+    Fetch,
+    Push,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
 pub enum RemoteRef {
     Commit(String),
     Tag(String),
     Branch(String),
 }
 
-pub fn is_repository(path: impl AsRef<Path>) -> Result<(), anyhow::Error> {
-    if path.as_ref().join(".git").is_dir() {
-        let args = ["rev-parse", "--show-cdup"];
-        if let Ok(output) = exec_cmd(path, "git", &args) {
-            if output.trim().is_empty() {
-                return Ok(());
-            }
+/// a commit as reported by `git log`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct CommitInfo {
+    pub hash: String,
+    pub author: String,
+    pub email: String,
+    /// committed date in local time, formatted as `%Y-%m-%d %H:%M:%S`
+    pub date: String,
+    /// first paragraph of the message, joined into one line by git
+    pub subject: String,
+}
+
+/// how `new_local_tag` creates the tag
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagOptions {
+    /// annotated tag with the message
+    pub message: Option<String>,
+    /// GPG-signed tag, annotated with the tag name when there is no message
+    pub sign: bool,
+    /// replace an existing tag of the same name
+    pub force: bool,
+}
+
+/// an unfinished operation left behind by conflicts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConflictState {
+    Rebase,
+    Merge,
+    CherryPick,
+    StashPop,
+}
+
+impl Display for ConflictState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let desc = match self {
+            ConflictState::Rebase => "rebase",
+            ConflictState::Merge => "merge",
+            ConflictState::CherryPick => "cherry-pick",
+            ConflictState::StashPop => "stash pop",
+        };
+        write!(f, "{}", desc)
+    }
+}
+
+impl RemoteRef {
+    /// short description of the ref: abbreviated commit, tag or branch name
+    pub fn remote_desc(&self) -> String {
+        match self {
+            RemoteRef::Commit(commit) => commit.get(..7).unwrap_or(commit).to_string(),
+            RemoteRef::Tag(r) | RemoteRef::Branch(r) => r.to_string(),
         }
     }
+}
+
+impl Display for RemoteRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RemoteRef::Commit(r) | RemoteRef::Tag(r) | RemoteRef::Branch(r) => write!(f, "{}", r),
+        }
+    }
+}
+
+pub fn is_repository(path: impl AsRef<Path>) -> Result<(), RepositoryError> {
+    let git_dir = path.as_ref().join(".git");
+    // an empty `.git` holds nothing to lose, like one left by an interrupted init
+    let is_empty = std::fs::read_dir(&git_dir).is_ok_and(|mut entries| entries.next().is_none());
+    if !git_dir.is_dir() || is_empty {
+        return Err(RepositoryError::NotFound);
+    }
 
-    Err(anyhow::anyhow!("repository not found!"))
+    let args = ["rev-parse", "--show-cdup"];
+    match exec_cmd(path, "git", &args) {
+        Ok(output) if output.trim().is_empty() => Ok(()),
+        // git skipped the unusable `.git` and found a parent repository
+        Ok(_) => Err(RepositoryError::Broken("not a git repository".to_string())),
+        Err(e) => Err(RepositoryError::Broken(e.to_string().trim().to_string())),
+    }
+}
+
+/// check if the path is the root of a bare repository, like a mirror clone
+pub fn is_bare_repository(path: impl AsRef<Path>) -> bool {
+    if !path.as_ref().is_dir() {
+        return false;
+    }
+
+    let args = ["rev-parse", "--is-bare-repository", "--git-dir"];
+    match exec_cmd(path, "git", &args) {
+        Ok(output) => {
+            let mut lines = output.lines();
+            lines.next() == Some("true") && lines.next() == Some(".")
+        }
+        Err(_) => false,
+    }
 }
 
 #[allow(dead_code)]
@@ -47,8 +241,7 @@ pub fn is_remote_ref_valid(
     remote_ref: impl AsRef<str>,
 ) -> Result<(), anyhow::Error> {
     let remote_ref = remote_ref.as_ref();
-    let args = ["branch", "--contains", remote_ref, "-r"];
-    match exec_cmd(path, "git", &args) {
+    match rev_parse(path, remote_ref) {
         Ok(_) => Ok(()),
         Err(_) => Err(anyhow::anyhow!(StyleMessage::git_remote_not_found(
             remote_ref
@@ -80,13 +273,18 @@ pub fn find_remote_name_by_url(
 pub fn find_remote_url_by_name(
     path: impl AsRef<Path>,
     name: impl AsRef<str>,
+    which: FetchOrPush,
 ) -> Result<String, anyhow::Error> {
     is_repository(&path)?;
 
     let name = name.as_ref();
-    let args = ["remote", "get-url", name];
+    let args = match which {
+        FetchOrPush::Fetch => vec!["remote", "get-url", name],
+        FetchOrPush::Push => vec!["remote", "get-url", "--push", name],
+    };
     let output = exec_cmd(path, "git", &args)?;
 
+    // without `--all` git prints the first configured url only
     if let Some(remote_url) = output.trim().lines().next() {
         return Ok(remote_url.trim().to_string());
     }
@@ -96,14 +294,33 @@ pub fn find_remote_url_by_name(
 
 pub fn get_current_commit(path: impl AsRef<Path>) -> Result<String, anyhow::Error> {
     is_repository(&path)?;
-    let args = ["rev-parse", "HEAD"];
-    let output = exec_cmd(path, "git", &args)?;
+    rev_parse(path, "HEAD").map_err(|_| anyhow::anyhow!("current commit not found."))
+}
 
-    if let Some(oid) = output.trim().lines().next() {
-        return Ok(oid.to_string());
+/// resolve a commit/tag/branch to the full sha1 of the commit it points to
+pub fn rev_parse(
+    path: impl AsRef<Path>,
+    git_ref: impl AsRef<str>,
+) -> Result<String, anyhow::Error> {
+    let object = format!("{}^{{commit}}", git_ref.as_ref());
+    let args = ["rev-parse", "--verify", "--quiet", object.as_str()];
+    match exec_cmd(path, "git", &args) {
+        Ok(output) if !output.trim().is_empty() => Ok(output.trim().to_string()),
+        _ => Err(anyhow::anyhow!("unknown revision {}", git_ref.as_ref())),
     }
+}
 
-    Err(anyhow::anyhow!("current commit not found."))
+/// check if the commit object is already present locally
+pub fn commit_exists(path: impl AsRef<Path>, commit: impl AsRef<str>) -> bool {
+    let object = format!("{}^{{commit}}", commit.as_ref());
+    let args = ["cat-file", "-e", object.as_str()];
+    exec_cmd(path, "git", &args).is_ok()
+}
+
+pub fn is_shallow(path: impl AsRef<Path>) -> Result<bool, anyhow::Error> {
+    let args = ["rev-parse", "--is-shallow-repository"];
+    let output = exec_cmd(path, "git", &args)?;
+    Ok(output.trim() == "true")
 }
 
 pub fn get_tracking_branch(path: impl AsRef<Path>) -> Result<String, anyhow::Error> {
@@ -140,21 +357,12 @@ pub fn get_current_branch(path: impl AsRef<Path>) -> Result<String, anyhow::Erro
     is_repository(&path)?;
     let args = ["branch", "--show-current"];
     let output = exec_cmd(&path, "git", &args)?;
-
-    for line in output.trim().lines() {
-        let branch = line.to_string();
-        // check if th branch exists
-        let branch_output = exec_cmd(&path, "git", &["branch", "-l", &branch])?;
-        if branch_output.contains(&branch) {
-            return Ok(branch);
-        }
-    }
-    Err(anyhow::anyhow!("current branch not found."))
+    Ok(output.trim().to_string())
 }
 
 pub fn get_branch_log(path: impl AsRef<Path>, branch: String) -> String {
     let args = ["show-branch", "--sha1-name", &branch];
-    let output = exec_cmd(path, "git", &args).unwrap_or(String::new());
+    let output = exec_cmd(path, "git", &args).unwrap_or_default();
     output.trim().to_string()
 }
 
@@ -168,6 +376,113 @@ pub fn get_changed_files(path: impl AsRef<Path>) -> Result<String, anyhow::Error
     exec_cmd(path, "git", &args)
 }
 
+/// what removing the repo would lose besides its local changes: commits of local branches
+/// no remote-tracking branch has, or stash entries. none when all of it is on a remote
+pub fn unpushed_work(path: impl AsRef<Path>) -> Result<Option<String>, anyhow::Error> {
+    let path = path.as_ref();
+    let args = ["log", "--branches", "--not", "--remotes", "--format=%h"];
+    let commits = exec_cmd(path, "git", &args)?.lines().count();
+    if commits > 0 {
+        return Ok(Some(format!(
+            "{} commit(s) of local branches are on no remote",
+            commits
+        )));
+    }
+
+    let stashes = exec_cmd(path, "git", &["stash", "list"])?.lines().count();
+    if stashes > 0 {
+        return Ok(Some(format!("{} stash entry(ies) would be lost", stashes)));
+    }
+    Ok(None)
+}
+
+/// check for untracked, changed or staged files
+pub fn has_local_changes(path: impl AsRef<Path>) -> Result<bool, anyhow::Error> {
+    let path = path.as_ref();
+    Ok(!get_untrack_files(path)?.trim().is_empty() || has_tracked_changes(path)?)
+}
+
+/// check for changed or staged files, ignoring untracked ones
+pub fn has_tracked_changes(path: impl AsRef<Path>) -> Result<bool, anyhow::Error> {
+    let path = path.as_ref();
+    let has_changes =
+        !get_changed_files(path)?.trim().is_empty() || !get_staged_files(path)?.trim().is_empty();
+    Ok(has_changes)
+}
+
+pub fn get_unmerged_files(path: impl AsRef<Path>) -> Result<String, anyhow::Error> {
+    let args = ["diff", "--name-only", "--diff-filter=U"];
+    exec_cmd(path, "git", &args)
+}
+
+/// detect an unfinished rebase/merge/cherry-pick, or a stash pop stopped on conflicts
+pub fn get_conflict_state(path: impl AsRef<Path>) -> Result<Option<ConflictState>, anyhow::Error> {
+    let path = path.as_ref();
+    let git_dir = path.join(".git");
+
+    if git_dir.join("rebase-merge").is_dir() || git_dir.join("rebase-apply").is_dir() {
+        return Ok(Some(ConflictState::Rebase));
+    }
+    if git_dir.join("MERGE_HEAD").is_file() {
+        return Ok(Some(ConflictState::Merge));
+    }
+    if git_dir.join("CHERRY_PICK_HEAD").is_file() {
+        return Ok(Some(ConflictState::CherryPick));
+    }
+
+    // a conflicted stash pop leaves unmerged paths without any of the heads above
+    match get_unmerged_files(path)?.trim().is_empty() {
+        true => Ok(None),
+        false => Ok(Some(ConflictState::StashPop)),
+    }
+}
+
+/// give up the unfinished operation, back to where it started
+pub fn abort_conflict(path: impl AsRef<Path>, state: ConflictState) -> Result<(), anyhow::Error> {
+    let path = path.as_ref();
+    let args = match state {
+        ConflictState::Rebase => ["rebase", "--abort"],
+        ConflictState::Merge => ["merge", "--abort"],
+        ConflictState::CherryPick => ["cherry-pick", "--abort"],
+        ConflictState::StashPop => return abort_stash_pop(path),
+    };
+    exec_cmd(path, "git", &args).map(|_| ())
+}
+
+/// finish the unfinished operation once conflicts are resolved
+pub fn continue_conflict(
+    path: impl AsRef<Path>,
+    state: ConflictState,
+) -> Result<(), anyhow::Error> {
+    let path = path.as_ref();
+    let args = match state {
+        ConflictState::Rebase => vec!["-c", "core.editor=true", "rebase", "--continue"],
+        ConflictState::Merge => vec!["-c", "core.editor=true", "merge", "--continue"],
+        ConflictState::CherryPick => vec!["-c", "core.editor=true", "cherry-pick", "--continue"],
+        ConflictState::StashPop => {
+            let output = get_unmerged_files(path)?;
+            let files = output.trim().lines().collect::<Vec<_>>();
+
+            // git doesn't track stash pop, so check conflict markers by ourselves
+            for file in &files {
+                let content = std::fs::read_to_string(path.join(file)).unwrap_or_default();
+                if content.lines().any(|line| line.starts_with("<<<<<<< ")) {
+                    return Err(anyhow::anyhow!("unresolved conflict in {}", file));
+                }
+            }
+
+            // mark as resolved but keep changes unstaged, like a clean stash pop
+            let mut args = vec!["reset", "-q", "--"];
+            args.extend(files);
+            exec_cmd(path, "git", &args)?;
+
+            vec!["stash", "drop"]
+        }
+    };
+
+    exec_cmd(path, "git", &args).map(|_| ())
+}
+
 pub fn get_staged_files(path: impl AsRef<Path>) -> Result<String, anyhow::Error> {
     let args = ["diff", "--cached", "--name-only"];
     exec_cmd(path, "git", &args)
@@ -181,6 +496,24 @@ pub fn get_rev_list_count(
     exec_cmd(path, "git", &args)
 }
 
+/// commits only on the left and only on the right side of `branch_pair`, like `a...b`
+pub fn rev_list_ahead_behind(
+    path: impl AsRef<Path>,
+    branch_pair: impl AsRef<str>,
+) -> Result<(usize, usize), anyhow::Error> {
+    static COUNTS: OnceLock<Regex> = OnceLock::new();
+    let re = COUNTS.get_or_init(|| Regex::new(r"(\d+)\s+(\d+)").unwrap());
+
+    let output = get_rev_list_count(path, branch_pair)?;
+    let Some(caps) = re.captures(&output) else {
+        return Err(anyhow::anyhow!(
+            "unexpected rev-list output: {}",
+            output.trim()
+        ));
+    };
+    Ok((caps[1].parse()?, caps[2].parse()?))
+}
+
 // 由于不同平台、不同用户的全局git config配置会有不同的git init [defaultBranch]
 // 可能是main、master又或者用户自定义的
 // 此处固定将git init的初始分支命名为master，以避免产生歧义
@@ -189,15 +522,28 @@ pub fn init(path: impl AsRef<Path>) -> anyhow::Result<()> {
     exec_cmd(path, "git", &args).map(|_| ())
 }
 
-pub fn add_remote_url(path: impl AsRef<Path>, url: impl AsRef<str>) -> anyhow::Result<()> {
-    // git remote add origin {url}
-    let args = ["remote", "add", "origin", url.as_ref()];
+pub fn add_remote_url(
+    path: impl AsRef<Path>,
+    name: impl AsRef<str>,
+    url: impl AsRef<str>,
+) -> anyhow::Result<()> {
+    // git remote add {name} {url}
+    let args = ["remote", "add", name.as_ref(), url.as_ref()];
     exec_cmd(path, "git", &args).map(|_| ())
 }
 
-pub fn clean(path: impl AsRef<Path>) -> anyhow::Result<()> {
-    let args = ["clean", "-fd"];
-    exec_cmd(path, "git", &args).map(|_| ())
+pub fn clean(path: impl AsRef<Path>, excludes: &[String]) -> anyhow::Result<()> {
+    exec_cmd(path, "git", &clean_args(excludes)).map(|_| ())
+}
+
+/// git clean -fd, keeping untracked files matching any of the `-e` patterns
+pub(crate) fn clean_args(excludes: &[String]) -> Vec<&str> {
+    let mut args = vec!["clean", "-fd"];
+    for pattern in excludes {
+        args.push("-e");
+        args.push(pattern);
+    }
+    args
 }
 
 pub fn reset(
@@ -230,14 +576,24 @@ pub fn add_untracked_files(path: impl AsRef<Path>) -> Result<String, anyhow::Err
     exec_cmd(path, "git", &args)
 }
 
-pub fn stash(path: impl AsRef<Path>) -> Result<String, anyhow::Error> {
+pub fn stash(path: impl AsRef<Path>, include_untracked: bool) -> Result<String, anyhow::Error> {
     let path = path.as_ref();
     log::debug!("stash");
 
-    add_untracked_files(path)?;
+    if include_untracked {
+        add_untracked_files(path)?;
+    }
 
-    let args = ["stash", "-u"];
-    exec_cmd(path, "git", &args)
+    exec_cmd(path, "git", &stash_args(include_untracked))
+}
+
+/// git stash, taking untracked files along with `-u`
+pub(crate) fn stash_args(include_untracked: bool) -> Vec<&'static str> {
+    let mut args = vec!["stash"];
+    if include_untracked {
+        args.push("-u");
+    }
+    args
 }
 
 pub fn stash_pop(path: impl AsRef<Path>) -> Result<String, anyhow::Error> {
@@ -245,6 +601,46 @@ pub fn stash_pop(path: impl AsRef<Path>) -> Result<String, anyhow::Error> {
     exec_cmd(path, "git", &args)
 }
 
+/// undo a stash pop stopped on conflicts, git keeps the stash entry in that case.
+///
+/// untracked files restored by the pop are removed as well, so the entry applies again later.
+pub fn abort_stash_pop(path: impl AsRef<Path>) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    exec_cmd(path, "git", &["reset", "--hard", "-q"])?;
+
+    // the third parent of a stash commit holds its untracked files
+    let args = ["ls-tree", "-r", "--name-only", "stash@{0}^3"];
+    if let Ok(files) = exec_cmd(path, "git", &args) {
+        for file in files.lines() {
+            let _ = std::fs::remove_file(path.join(file));
+        }
+    }
+    Ok(())
+}
+
+/// `stash@{n}` of the stash entry whose commit is `stash`, none once it is popped or dropped
+pub fn find_stash(
+    path: impl AsRef<Path>,
+    stash: impl AsRef<str>,
+) -> anyhow::Result<Option<String>> {
+    let output = exec_cmd(path, "git", &["stash", "list", "--format=%H"])?;
+    let index = output
+        .lines()
+        .position(|line| line.trim() == stash.as_ref());
+    Ok(index.map(|index| format!("stash@{{{}}}", index)))
+}
+
+pub fn stash_pop_entry(path: impl AsRef<Path>, stash: impl AsRef<str>) -> anyhow::Result<()> {
+    exec_cmd(path, "git", &["stash", "pop", stash.as_ref()]).map(|_| ())
+}
+
+/// move the top stash entry to a branch, it can be restored with `git stash apply <branch>`
+pub fn stash_to_branch(path: impl AsRef<Path>, branch: impl AsRef<str>) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    exec_cmd(path, "git", &["branch", branch.as_ref(), "stash@{0}"])?;
+    exec_cmd(path, "git", &["stash", "drop", "-q"]).map(|_| ())
+}
+
 pub fn local_branch_already_exist(
     path: impl AsRef<Path>,
     branch: impl AsRef<str>,
@@ -257,7 +653,68 @@ pub fn local_branch_already_exist(
 }
 
 pub fn checkout(path: impl AsRef<Path>, args: &[&str]) -> anyhow::Result<()> {
-    exec_cmd(path, "git", args).map(|_| ())
+    exec_cmd(path, "git", args).map(|_| ()).map_err(|e| {
+        match parse_checkout_error(&e.to_string()) {
+            Some(checkout_error) => anyhow::anyhow!(checkout_error),
+            None => e,
+        }
+    })
+}
+
+/// check out `branch` pointing at `head` again, or detach at `head` without a branch.
+///
+/// the work tree and index are overwritten, whatever a partial checkout left in them.
+pub fn restore_head(
+    path: impl AsRef<Path>,
+    branch: impl AsRef<str>,
+    head: impl AsRef<str>,
+) -> anyhow::Result<()> {
+    let (branch, head) = (branch.as_ref(), head.as_ref());
+    let args = match branch.is_empty() {
+        true => vec!["checkout", "-q", "-f", "--detach", head],
+        false => vec!["checkout", "-q", "-f", "-B", branch, head],
+    };
+    exec_cmd(path, "git", &args).map(|_| ())
+}
+
+/// create `branch` at HEAD and switch to it, `force` moves an existing branch to HEAD.
+///
+/// the work tree is left as it is, so it also carries local changes over to the new branch.
+pub fn checkout_new_branch(
+    path: impl AsRef<Path>,
+    branch: impl AsRef<str>,
+    force: bool,
+) -> anyhow::Result<()> {
+    let flag = if force { "-B" } else { "-b" };
+    checkout(path, &["checkout", flag, branch.as_ref(), "--no-track"])
+}
+
+/// root of the work tree containing `path`
+pub fn toplevel(path: impl AsRef<Path>) -> anyhow::Result<PathBuf> {
+    let args = ["rev-parse", "--show-toplevel"];
+    let output = exec_cmd(path, "git", &args)?;
+    Ok(PathBuf::from(output.trim()))
+}
+
+/// move a repo to `git_ref` after fetching `origin`, if it has one.
+///
+/// a branch of `origin` is checked out as the local branch of the same name,
+/// a tag, commit or local-only branch is checked out detached.
+pub fn update_to_ref(path: impl AsRef<Path>, git_ref: impl AsRef<str>) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    let git_ref = git_ref.as_ref();
+
+    if find_remote_url_by_name(path, "origin", FetchOrPush::Fetch).is_ok() {
+        exec_cmd(path, "git", &["fetch", "origin", "--tags", "--prune"])?;
+    }
+
+    let remote_branch = format!("origin/{}", git_ref);
+    let remote_branch_ref = format!("refs/remotes/{}", remote_branch);
+    let args = ["rev-parse", "--verify", "--quiet", &remote_branch_ref];
+    match exec_cmd(path, "git", &args) {
+        Ok(_) => checkout(path, &["checkout", "-B", git_ref, &remote_branch]),
+        Err(_) => checkout(path, &["checkout", "--detach", git_ref]),
+    }
 }
 
 #[allow(dead_code)]
@@ -293,17 +750,78 @@ pub fn set_tracking_remote_branch(
 
 pub fn update_remote_url(
     path: impl AsRef<Path>,
+    name: impl AsRef<str>,
     url: impl AsRef<str>,
 ) -> Result<String, anyhow::Error> {
-    let args = ["remote", "set-url", "origin", url.as_ref()];
+    let args = ["remote", "set-url", name.as_ref(), url.as_ref()];
     exec_cmd(path, "git", &args)
 }
 
+/// apply `url.<base>.insteadOf` rewrites to a url, as git does before fetching
+pub fn rewrite_url(path: impl AsRef<Path>, url: impl AsRef<str>) -> Result<String, anyhow::Error> {
+    let args = ["ls-remote", "--get-url", url.as_ref()];
+    let output = exec_cmd(path, "git", &args)?;
+    Ok(output.trim().to_string())
+}
+
+/// set the url of a remote, adding the remote when it doesn't exist
+pub fn set_remote_url(
+    path: impl AsRef<Path>,
+    name: impl AsRef<str>,
+    url: impl AsRef<str>,
+) -> anyhow::Result<()> {
+    match find_remote_url_by_name(&path, &name, FetchOrPush::Fetch) {
+        Ok(_) => update_remote_url(path, name, url).map(|_| ()),
+        Err(_) => add_remote_url(path, name, url),
+    }
+}
+
+/// git remote set-url --push, or drop the push url to push where the remote fetches from
+pub fn set_remote_push_url(
+    path: impl AsRef<Path>,
+    name: impl AsRef<str>,
+    url: Option<impl AsRef<str>>,
+) -> anyhow::Result<()> {
+    let name = name.as_ref();
+    match url {
+        Some(url) => {
+            let args = ["remote", "set-url", "--push", name, url.as_ref()];
+            exec_cmd(path, "git", &args).map(|_| ())
+        }
+        None => {
+            // exits with 5 when no push url is configured
+            let key = format!("remote.{}.pushurl", name);
+            let _ = exec_cmd(path, "git", &["config", "--unset-all", &key]);
+            Ok(())
+        }
+    }
+}
+
+/// git config branch.<branch>.pushRemote <remote>
+pub fn set_push_remote(
+    path: impl AsRef<Path>,
+    branch: impl AsRef<str>,
+    remote_name: impl AsRef<str>,
+) -> anyhow::Result<()> {
+    let key = format!("branch.{}.pushRemote", branch.as_ref());
+    let args = ["config", key.as_str(), remote_name.as_ref()];
+    exec_cmd(path, "git", &args).map(|_| ())
+}
+
 pub fn ls_files(path: impl AsRef<Path>) -> Result<String, anyhow::Error> {
     let args = ["ls-files", "-s"];
     exec_cmd(path, "git", &args)
 }
 
+/// `git diff --stat` of the work tree, or of the index when `staged`. empty without changes
+pub fn diff_stat(path: impl AsRef<Path>, staged: bool) -> Result<String, anyhow::Error> {
+    let mut args = vec!["diff", "--stat"];
+    if staged {
+        args.push("--cached");
+    }
+    exec_cmd(path, "git", &args)
+}
+
 pub fn log_current(path: impl AsRef<Path>) -> Result<String, anyhow::Error> {
     let args = [
         "log",
@@ -314,15 +832,141 @@ pub fn log_current(path: impl AsRef<Path>) -> Result<String, anyhow::Error> {
     exec_cmd(path, "git", &args)
 }
 
+/// last commit of HEAD, fields are NUL separated so no value can be mistaken for a separator
+pub fn head_commit(path: impl AsRef<Path>) -> Result<CommitInfo, anyhow::Error> {
+    let args = [
+        "log",
+        "-1",
+        "--pretty=format:%H%x00%an%x00%ae%x00%ad%x00%s",
+        "--date=format-local:%Y-%m-%d %H:%M:%S",
+    ];
+    let output = exec_cmd(path, "git", &args)?;
+    let fields: Vec<&str> = output.split('\0').collect();
+    let [hash, author, email, date, subject] = fields[..] else {
+        return Err(anyhow::anyhow!("unexpected git log output: {:?}", output));
+    };
+    Ok(CommitInfo {
+        hash: hash.to_string(),
+        author: author.to_string(),
+        email: email.to_string(),
+        date: date.to_string(),
+        subject: subject.trim_end().to_string(),
+    })
+}
+
+/// directory git runs hooks from, honoring `core.hooksPath`
+pub fn hooks_dir(path: impl AsRef<Path>) -> Result<PathBuf, anyhow::Error> {
+    let path = path.as_ref();
+    let args = ["rev-parse", "--git-path", "hooks"];
+    let output = exec_cmd(path, "git", &args)?;
+    Ok(path.join(output.trim()))
+}
+
+/// lock file of the index, inside the git dir a `.git` file of worktrees and submodules points to
+pub fn index_lock_path(path: impl AsRef<Path>) -> Result<PathBuf, anyhow::Error> {
+    let path = path.as_ref();
+    let args = ["rev-parse", "--git-path", "index.lock"];
+    let output = exec_cmd(path, "git", &args)?;
+    Ok(path.join(output.trim()))
+}
+
+/// `modules` dir of the repo containing `path`, where git keeps the repos of its submodules
+fn modules_dir(path: &Path) -> Option<PathBuf> {
+    let args = ["rev-parse", "--git-path", "modules"];
+    let output = exec_cmd(path, "git", &args).ok()?;
+    path.join(output.trim()).canonicalize().ok()
+}
+
+/// whether the `.git` file of `dir` points into `modules_dir`
+fn points_to_modules(dir: &Path, modules_dir: &Path) -> bool {
+    let Ok(dot_git) = std::fs::read_to_string(dir.join(".git")) else {
+        return false;
+    };
+    let Some(gitdir) = dot_git.trim().strip_prefix("gitdir:") else {
+        return false;
+    };
+    let gitdir = dir.join(gitdir.trim()).canonicalize();
+    gitdir.is_ok_and(|gitdir| gitdir.starts_with(modules_dir))
+}
+
+/// whether `dir` is the work tree of a submodule of the repo around it, whose `.git` file
+/// points into the `modules` of that repo
+pub fn is_submodule_checkout(dir: impl AsRef<Path>) -> bool {
+    let dir = dir.as_ref();
+    if !dir.join(".git").is_file() {
+        return false;
+    }
+    let Some(modules_dir) = dir.parent().and_then(modules_dir) else {
+        return false;
+    };
+    points_to_modules(dir, &modules_dir)
+}
+
+/// work tree dirs of submodules HEAD no longer has: dirs under untracked paths whose
+/// `.git` file points into the `modules` of this repo, unlike nested repos with a `.git`
+/// dir of their own, which are left alone together with everything inside them
+pub fn stale_submodule_dirs(path: impl AsRef<Path>) -> Result<Vec<String>, anyhow::Error> {
+    let path = path.as_ref();
+    let Some(modules_dir) = modules_dir(path) else {
+        return Ok(Vec::new());
+    };
+
+    // a removed submodule may be listed as one of its untracked parent dirs
+    let args = ["ls-files", "--others", "--directory", "--exclude-standard"];
+    let output = exec_cmd(path, "git", &args)?;
+    let mut dirs = Vec::new();
+    for untracked in output.lines().filter_map(|line| line.strip_suffix('/')) {
+        let mut it = walkdir::WalkDir::new(path.join(untracked)).into_iter();
+        while let Some(Ok(entry)) = it.next() {
+            if !entry.file_type().is_dir() {
+                continue;
+            }
+            let dir = entry.path();
+            if points_to_modules(dir, &modules_dir) {
+                let rel_path = dir.strip_prefix(path).unwrap_or(dir);
+                dirs.push(rel_path.to_string_lossy().replace('\\', "/"));
+                it.skip_current_dir();
+            } else if dir.join(".git").exists() {
+                it.skip_current_dir();
+            }
+        }
+    }
+    Ok(dirs)
+}
+
+/// point the remotes of submodules to the urls of `.gitmodules`
+pub fn submodule_sync(path: impl AsRef<Path>) -> Result<(), anyhow::Error> {
+    let args = ["submodule", "sync", "--recursive"];
+    exec_cmd(path, "git", &args).map(|_| ())
+}
+
+/// `sparse-checkout set` arguments, in the faster cone mode unless `no_cone` is
+/// asked, git is too old, or some entry is a pattern rather than a directory
+pub(crate) fn sparse_checkout_set_args(dirs: &[String], no_cone: bool) -> Vec<String> {
+    let is_dir = |dir: &String| {
+        !dir.is_empty() && !dir.starts_with(['/', '!']) && !dir.contains(['*', '?', '[', '\\'])
+    };
+    let cone = !no_cone
+        && git_version().is_some_and(|version| version >= GitVersion::SPARSE_CONE)
+        && dirs.iter().all(is_dir);
+
+    let mode = if cone { "--cone" } else { "--no-cone" };
+    let mut args = vec![
+        "sparse-checkout".to_string(),
+        "set".to_string(),
+        mode.to_string(),
+    ];
+    args.extend(dirs.iter().cloned());
+    args
+}
+
 pub fn sparse_checkout_set(
     path: impl AsRef<Path>,
-    dirs: &Vec<String>,
+    dirs: &[String],
+    no_cone: bool,
 ) -> Result<(), anyhow::Error> {
-    let mut args = vec!["sparse-checkout", "set", "--no-cone"];
-    for dir in dirs {
-        args.push(dir)
-    }
-
+    let args = sparse_checkout_set_args(dirs, no_cone);
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
     exec_cmd(path, "git", &args).map(|_| ())
 }
 
@@ -336,45 +980,199 @@ pub fn sparse_checkout_list(path: impl AsRef<Path>) -> Result<String, anyhow::Er
     exec_cmd(path, "git", &args)
 }
 
+pub(crate) fn submodule_update_args(init: bool, recursive: bool) -> Vec<String> {
+    let mut args = vec!["submodule".to_string(), "update".to_string()];
+    if init {
+        args.push("--init".to_string());
+    }
+    if recursive {
+        args.push("--recursive".to_string());
+    }
+    args
+}
+
+/// check out the commits the superproject records for its submodules
+pub fn submodule_update(
+    path: impl AsRef<Path>,
+    init: bool,
+    recursive: bool,
+) -> Result<(), anyhow::Error> {
+    let args = submodule_update_args(init, recursive);
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    exec_cmd(path, "git", &args).map(|_| ())
+}
+
+/// replace index and work tree with the contents of `subdir` at `tree_ish`, flattened to the root
+pub fn read_subtree(
+    path: impl AsRef<Path>,
+    tree_ish: impl AsRef<str>,
+    subdir: impl AsRef<str>,
+) -> Result<(), anyhow::Error> {
+    let tree = format!(
+        "{}:{}",
+        tree_ish.as_ref(),
+        subdir.as_ref().trim_matches('/')
+    );
+    let args = ["read-tree", "-u", "--reset", &tree];
+    exec_cmd(path, "git", &args).map(|_| ())
+}
+
+/// point the index at `tree_ish`, leaving HEAD and the work tree alone
+pub fn read_tree(path: impl AsRef<Path>, tree_ish: impl AsRef<str>) -> Result<(), anyhow::Error> {
+    let args = ["read-tree", "--reset", tree_ish.as_ref()];
+    exec_cmd(path, "git", &args).map(|_| ())
+}
+
+/// write every file of the index to the work tree, overwriting existing ones
+pub fn checkout_index(path: impl AsRef<Path>) -> Result<(), anyhow::Error> {
+    let args = ["checkout-index", "-a", "-f"];
+    exec_cmd(path, "git", &args).map(|_| ())
+}
+
 pub fn new_remote_branch(
     path: impl AsRef<Path>,
+    remote: &str,
     base_branch: &str,
     new_branch: &str,
 ) -> Result<(), anyhow::Error> {
-    let arg = format!("origin/{}:refs/heads/{}", base_branch, new_branch);
-    let args = vec!["push", "origin", arg.as_str(), "--force"];
+    let arg = format!("{}/{}:refs/heads/{}", remote, base_branch, new_branch);
+    let args = vec!["push", remote, arg.as_str(), "--force"];
     exec_cmd(path, "git", &args).map(|_| ())
 }
 
-pub fn del_remote_branch(path: impl AsRef<Path>, branch: &str) -> Result<(), anyhow::Error> {
-    let args = vec!["push", "origin", "--delete", branch];
+pub fn del_remote_branch(
+    path: impl AsRef<Path>,
+    remote: &str,
+    branch: &str,
+) -> Result<(), anyhow::Error> {
+    let args = vec!["push", remote, "--delete", branch];
     exec_cmd(path, "git", &args).map(|_| ())
 }
 
 pub fn check_remote_branch_exist(
     path: impl AsRef<Path>,
+    remote: &str,
     branch: &str,
 ) -> Result<bool, anyhow::Error> {
     let head = format!("refs/heads/{}", branch);
-    let args = vec!["ls-remote", "--heads", "origin", head.as_str()];
+    let args = vec!["ls-remote", "--heads", remote, head.as_str()];
     let output = exec_cmd(path, "git", &args)?;
     Ok(output.contains(&head))
 }
 
+pub(crate) fn new_tag_args<'a>(
+    local_ref: &'a str,
+    tag: &'a str,
+    options: &'a TagOptions,
+) -> Vec<&'a str> {
+    let mut args = vec!["tag", tag];
+    if options.force {
+        args.push("--force");
+    }
+    if options.sign {
+        args.push("-s");
+    } else if options.message.is_some() {
+        args.push("-a");
+    }
+    // a signed tag needs a message, git would open an editor without one
+    match options.message.as_deref() {
+        Some(message) => args.extend(["-m", message]),
+        None if options.sign => args.extend(["-m", tag]),
+        None => {}
+    }
+    if !local_ref.is_empty() {
+        args.push(local_ref);
+    }
+    args
+}
+
 pub fn new_local_tag(
     path: impl AsRef<Path>,
     local_ref: &str,
     tag: &str,
+    options: &TagOptions,
 ) -> Result<(), anyhow::Error> {
-    let mut args = vec!["tag", tag, "--force"];
-    if !local_ref.is_empty() {
-        args.push(local_ref);
-    }
-
+    let args = new_tag_args(local_ref, tag, options);
     exec_cmd(path, "git", &args).map(|_| ())
 }
 
-pub fn push_tag(path: impl AsRef<Path>, tag: &str) -> Result<(), anyhow::Error> {
-    let args = vec!["push", "origin", tag, "--force"];
+/// push the tag, `force` replaces a tag of the same name on the remote
+pub fn push_tag(
+    path: impl AsRef<Path>,
+    remote: &str,
+    tag: &str,
+    force: bool,
+) -> Result<(), anyhow::Error> {
+    let mut args = vec!["push", remote, tag];
+    if force {
+        args.push("--force");
+    }
     exec_cmd(path, "git", &args).map(|_| ())
 }
+
+/// remote and refspec `git push` sends the current branch with, none when no
+/// branch is checked out or it has no upstream.
+///
+/// the remote is `branch.<name>.pushRemote` if set, like git does for forks.
+pub fn get_push_target(path: impl AsRef<Path>) -> anyhow::Result<Option<(String, String)>> {
+    let path = path.as_ref();
+    is_repository(path)?;
+
+    let config = |key: String| {
+        exec_cmd(path, "git", &["config", "--get", &key])
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    let Ok(branch) = exec_cmd(path, "git", &["symbolic-ref", "--quiet", "--short", "HEAD"]) else {
+        return Ok(None);
+    };
+    let branch = branch.trim();
+    let Some(merge) = config(format!("branch.{}.merge", branch)) else {
+        return Ok(None);
+    };
+    let remote = config(format!("branch.{}.pushRemote", branch))
+        .or_else(|| config("remote.pushDefault".to_string()))
+        .or_else(|| config(format!("branch.{}.remote", branch)));
+    let Some(remote) = remote else {
+        return Ok(None);
+    };
+
+    Ok(Some((remote, format!("refs/heads/{}:{}", branch, merge))))
+}
+
+/// git push --porcelain <remote> <refspec>, returns the status line of the ref
+pub fn push(
+    path: impl AsRef<Path>,
+    remote: &str,
+    refspec: &str,
+    force: bool,
+    dry_run: bool,
+) -> Result<String, anyhow::Error> {
+    let mut args = vec!["push", "--porcelain", remote, refspec];
+    if force {
+        args.push("--force-with-lease");
+    }
+    if dry_run {
+        args.push("--dry-run");
+    }
+
+    // lines are "<flag>\t<from>:<to>\t<summary>" between "To <url>" and "Done"
+    let output = exec_cmd(path, "git", &args)?;
+    let summary = output
+        .lines()
+        .filter_map(|line| line.split('\t').nth(2))
+        .next()
+        .unwrap_or_default();
+    Ok(summary.to_string())
+}
+
+/// git pull --ff-only, or git pull --rebase when fast-forward isn't required
+pub fn pull(path: impl AsRef<Path>, ff_only: bool) -> Result<(), anyhow::Error> {
+    let mode = match ff_only {
+        true => "--ff-only",
+        false => "--rebase",
+    };
+    exec_cmd(path, "git", &["pull", mode]).map(|_| ())
+}
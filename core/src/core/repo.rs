@@ -1,11 +1,14 @@
 use anyhow::Context;
-use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 use std::{collections::HashSet, path::Path};
 
 use crate::core::git;
 use crate::core::git::RemoteRef;
+use crate::core::repos::expand_env;
+use crate::utils::cmd;
+use crate::utils::path::PathExtension;
 use crate::utils::style_message::StyleMessage;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -14,7 +17,7 @@ pub struct RepoId {
     pub repo: String,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "kebab-case")]
 pub struct TomlRepo {
     pub local: Option<String>,
@@ -23,6 +26,33 @@ pub struct TomlRepo {
     pub tag: Option<String>,
     pub commit: Option<String>,
     pub sparse: Option<Vec<String>>,
+    /// materialize only this directory of the repository, flattened to the work tree root
+    pub subdir: Option<String>,
+    /// approximate size of a fresh clone in MB, checked by `--max-clone-mb`
+    pub max_size: Option<u64>,
+    /// url to fetch from, set up as `upstream` for fork workflows
+    pub fetch_remote: Option<String>,
+    /// url `origin` pushes to, if it differs from `remote`
+    pub push_remote: Option<String>,
+    /// name `remote` is set up as instead of `origin`, `default-remote` of the config by default
+    pub remote_name: Option<String>,
+    /// labels to select repos by, like `--group` of list-repos
+    pub groups: Option<Vec<String>>,
+    /// keep a bare `--mirror` clone with all refs instead of a work tree
+    pub mirror: Option<bool>,
+    /// extra attempts of clone and fetch for this repo, instead of the built-in ones
+    pub retries: Option<usize>,
+    /// wait between attempts of clone and fetch for this repo
+    pub retry_delay_ms: Option<u64>,
+    /// `http.proxy` of clone and fetch for this repo, may hold credentials
+    pub proxy: Option<String>,
+    /// shallow clone and fetch depth of this repo, instead of `--depth`
+    pub depth: Option<usize>,
+    /// update the submodules of this repo after sync, instead of `--recurse-submodules`
+    pub recurse_submodules: Option<bool>,
+    /// `default-remote` of the config file the repo is read from, never written back
+    #[serde(skip)]
+    pub default_remote: Option<String>,
 }
 
 impl RepoId {
@@ -35,16 +65,146 @@ impl RepoId {
 }
 
 impl TomlRepo {
+    /// whether ops process this repo under the ignore set of `ignore_set`,
+    /// progress totals count exactly these repos
+    pub fn no_ignore(&self, ignore_paths: &HashSet<String>) -> bool {
+        matches!(&self.local, Some(local) if !ignore_paths.contains(local))
+    }
+
+    /// whether the repo is in any of the groups, a repo without groups is in none
+    pub fn in_groups(&self, groups: &[String]) -> bool {
+        matches!(&self.groups, Some(repo_groups) if groups.iter().any(|g| repo_groups.contains(g)))
+    }
+
     pub fn get_remote_name(&self, path: impl AsRef<Path>) -> Result<String, anyhow::Error> {
-        let remote_url = self
-            .remote
-            .as_ref()
-            .with_context(|| "remote url is null.")?;
+        let remote_url = self.fetch_url().with_context(|| "remote url is null.")?;
         git::find_remote_name_by_url(path, remote_url)
     }
 
+    /// url git is called with for `remote`, its `${VAR}` expanded from the environment
+    pub fn remote_url(&self) -> Option<String> {
+        let remote = self.remote.as_ref()?;
+        Some(expand_env(remote).unwrap_or(remote.to_string()))
+    }
+
+    /// url to fetch from, `fetch-remote` of a fork or `remote`
+    pub fn fetch_url(&self) -> Option<String> {
+        self.upstream_url().cloned().or_else(|| self.remote_url())
+    }
+
+    /// `fetch_url` as written in the config, for output that must not hold the variables
+    pub fn raw_fetch_url(&self) -> Option<&String> {
+        self.upstream_url().or(self.remote.as_ref())
+    }
+
+    /// name of the remote set up with `remote`, `origin` unless `remote-name` says otherwise
+    pub fn origin_name(&self) -> &str {
+        self.remote_name
+            .as_deref()
+            .or(self.default_remote.as_deref())
+            .unwrap_or("origin")
+    }
+
+    /// name of the remote to fetch from, before the repository exists
+    pub fn fetch_remote_name(&self) -> &str {
+        match self.upstream_url() {
+            Some(_) => "upstream",
+            None => self.origin_name(),
+        }
+    }
+
+    /// whether fetching goes to `upstream` of a fork rather than to `remote`
+    pub fn is_fork(&self) -> bool {
+        self.upstream_url().is_some()
+    }
+
+    /// url of `upstream`, only when fetching from another remote than `origin`
+    fn upstream_url(&self) -> Option<&String> {
+        self.fetch_remote.as_ref().filter(|url| {
+            Some(*url) != self.remote.as_ref() && Some(*url) != self.remote_url().as_ref()
+        })
+    }
+
+    /// point `origin` (and `upstream` of a fork) to the manifest urls, adding missing remotes
+    pub fn update_remotes(&self, path: impl AsRef<Path>) -> Result<(), anyhow::Error> {
+        let path = path.as_ref();
+        let remote_url = self.remote_url().with_context(|| "remote url is null.")?;
+        git::set_remote_url(path, self.origin_name(), &remote_url)?;
+        git::set_remote_push_url(path, self.origin_name(), self.push_remote.as_ref())?;
+
+        if let Some(upstream_url) = self.upstream_url() {
+            git::set_remote_url(path, "upstream", upstream_url)?;
+        }
+        Ok(())
+    }
+
+    /// replace the manifest ref, for one run without editing the config file
+    pub fn override_ref(&mut self, remote_ref: RemoteRef) {
+        match remote_ref {
+            RemoteRef::Commit(commit) => {
+                self.commit = Some(commit);
+                self.tag = None;
+            }
+            RemoteRef::Tag(tag) => {
+                self.commit = None;
+                self.tag = Some(tag);
+            }
+            RemoteRef::Branch(branch) => {
+                self.commit = None;
+                self.tag = None;
+                self.branch = Some(branch);
+            }
+        }
+    }
+
+    /// attempts and delay between them for clone and fetch, `retries` counts the attempts
+    /// after the first one
+    pub fn retry_policy(&self) -> (usize, Duration) {
+        let times = self.retries.map_or(cmd::RETRY_TIMES, |retries| retries + 1);
+        let delay = self
+            .retry_delay_ms
+            .map_or(cmd::RETRY_DELAY, Duration::from_millis);
+        (times, delay)
+    }
+
+    /// `-c` overrides of clone and fetch, they go before the git subcommand
+    pub fn config_args(&self) -> Vec<String> {
+        match self.proxy.as_ref() {
+            Some(proxy) => vec!["-c".to_string(), format!("http.proxy={}", proxy)],
+            None => Vec::new(),
+        }
+    }
+
+    /// depth of clone and fetch, the one of this repo goes before `--depth`
+    pub fn fetch_depth<'a>(&'a self, depth: Option<&'a usize>) -> Option<&'a usize> {
+        self.depth.as_ref().or(depth)
+    }
+
+    /// host of the fetch url, local paths are grouped as "local"
+    pub fn get_remote_host(&self) -> Option<String> {
+        self.fetch_url().map(remote_host)
+    }
+
     pub fn get_remote_ref(&self, path: &Path) -> Result<RemoteRef, anyhow::Error> {
-        let remote_name = &self.get_remote_name(path)?;
+        self.get_remote_ref_by_name(self.resolve_remote_name(path)?)
+    }
+
+    /// name of the remote the remote ref is on
+    pub fn resolve_remote_name(&self, path: impl AsRef<Path>) -> Result<String, anyhow::Error> {
+        // a configured name is trusted, otherwise look for the remote with the url
+        let configured = self.remote_name.as_ref().or(self.default_remote.as_ref());
+        match (configured, self.is_fork()) {
+            (Some(remote_name), false) => Ok(remote_name.to_string()),
+            _ => self.get_remote_name(path),
+        }
+    }
+
+    /// same as `get_remote_ref`, for a remote name known without looking into the repository
+    pub fn get_remote_ref_by_name(
+        &self,
+        remote_name: impl AsRef<str>,
+    ) -> Result<RemoteRef, anyhow::Error> {
+        let remote_name = remote_name.as_ref();
         // priority: commit/tag/branch(default-branch)
         let remote_ref = {
             if let Some(commit) = &self.commit {
@@ -62,44 +222,161 @@ impl TomlRepo {
     }
 }
 
-pub fn repos_to_map_with_ignore(
-    repos: Vec<TomlRepo>,
-    ignore: Option<&Vec<String>>,
-) -> HashMap<usize, TomlRepo> {
-    let mut map = HashMap::new();
+/// parse `<local>=<ref>` of `sync --override`.
+///
+/// the ref is `branch:<name>`, `tag:<name>` or `commit:<sha>`; without a prefix
+/// a full sha is a commit and anything else a branch.
+pub fn parse_ref_override(value: &str) -> Result<(String, RemoteRef), anyhow::Error> {
+    let Some((local, git_ref)) = value.split_once('=') else {
+        return Err(anyhow::anyhow!("expected <LOCAL>=<REF>, got {}", value));
+    };
+    if local.is_empty() || git_ref.is_empty() {
+        return Err(anyhow::anyhow!("expected <LOCAL>=<REF>, got {}", value));
+    }
+
+    let remote_ref = match git_ref.split_once(':') {
+        Some(("branch", name)) => RemoteRef::Branch(name.to_string()),
+        Some(("tag", name)) => RemoteRef::Tag(name.to_string()),
+        Some(("commit", sha)) => RemoteRef::Commit(sha.to_string()),
+        _ if git_ref.len() == 40 && git_ref.chars().all(|c| c.is_ascii_hexdigit()) => {
+            RemoteRef::Commit(git_ref.to_string())
+        }
+        _ => RemoteRef::Branch(git_ref.to_string()),
+    };
+    Ok((local.norm_path().display_path(), remote_ref))
+}
+
+/// parse the host from `scheme://[user@]host[:port]/path` or scp-like `[user@]host:path`
+pub fn remote_host(url: impl AsRef<str>) -> String {
+    let url = url.as_ref();
+
+    let host = match url.split_once("://") {
+        Some(("file", _)) => None,
+        Some((_, rest)) => {
+            let authority = rest.split('/').next().unwrap_or_default();
+            let host = authority.rsplit('@').next().unwrap_or_default();
+            host.split(':').next()
+        }
+        None => match url.split_once(':') {
+            // a single letter before ':' is a windows drive
+            Some((prefix, _)) if prefix.len() > 1 && !prefix.contains('/') => {
+                prefix.rsplit('@').next()
+            }
+            _ => None,
+        },
+    };
+
+    match host {
+        Some(host) if !host.is_empty() => host.to_lowercase(),
+        _ => "local".to_string(),
+    }
+}
+
+/// web page of a remote url, ssh and scp-like urls are turned into https.
+///
+/// local paths have no web page.
+pub fn browse_url(url: impl AsRef<str>) -> Option<String> {
+    let url = url.as_ref();
+
+    let (scheme, host, path) = match url.split_once("://") {
+        Some(("file", _)) => return None,
+        Some((scheme, rest)) => {
+            let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+            let host = authority.rsplit('@').next().unwrap_or_default();
+            match scheme {
+                // keep the port of a web server, like a local gitea
+                "http" | "https" => (scheme, host, path),
+                _ => ("https", host.split(':').next().unwrap_or_default(), path),
+            }
+        }
+        None => match url.split_once(':') {
+            // a single letter before ':' is a windows drive
+            Some((prefix, path)) if prefix.len() > 1 && !prefix.contains('/') => {
+                ("https", prefix.rsplit('@').next().unwrap_or_default(), path)
+            }
+            _ => return None,
+        },
+    };
 
+    if host.is_empty() {
+        return None;
+    }
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    Some(format!("{}://{}/{}", scheme, host.to_lowercase(), path))
+}
+
+/// paths of `--ignore`, with "." also matching the repo at the project root
+pub fn ignore_set(ignore: Option<&Vec<String>>) -> HashSet<String> {
     let mut ignore_paths = match ignore {
-        Some(ignore_paths) => ignore_paths
-            .iter()
-            .map(|p| p.clone())
-            .collect::<HashSet<_>>(),
+        Some(ignore_paths) => ignore_paths.iter().cloned().collect::<HashSet<_>>(),
         None => HashSet::new(),
     };
 
     if ignore_paths.contains(".") {
         ignore_paths.insert("".to_string());
     }
+    ignore_paths
+}
 
-    for (idx, repo) in repos.into_iter().enumerate() {
-        if repo.local.is_none() {
-            continue;
-        }
+pub fn repos_to_map_with_ignore(
+    repos: Vec<TomlRepo>,
+    ignore: Option<&Vec<String>>,
+) -> HashMap<usize, TomlRepo> {
+    let ignore_paths = ignore_set(ignore);
+    repos
+        .into_iter()
+        .enumerate()
+        .filter(|(_, repo)| repo.no_ignore(&ignore_paths))
+        .collect()
+}
 
-        if ignore_paths.contains(repo.local.as_ref().unwrap()) {
-            continue;
-        }
-        map.insert(idx, repo);
+/// keep the repos in any of the groups, all of them when no groups are given.
+///
+/// applied before `repos_to_map_with_ignore`, so `--group` and `--ignore` compose
+pub fn include_groups(repos: Vec<TomlRepo>, groups: Option<&Vec<String>>) -> Vec<TomlRepo> {
+    match groups {
+        Some(groups) => repos
+            .into_iter()
+            .filter(|repo| repo.in_groups(groups))
+            .collect(),
+        None => repos,
     }
-    map
+}
+
+/// ahead/behind and local changes of a repo against its manifest ref
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RepoStatus {
+    pub local: String,
+    /// not cloned yet, nothing else is filled
+    pub missing: bool,
+    /// current local branch, empty before the initial commit
+    pub branch: String,
+    /// manifest (or tracking) ref compared against, none when not tracking
+    pub remote_ref: Option<RemoteRef>,
+    /// commits of the local branch not in the remote ref, none for an unknown revision
+    pub ahead: Option<usize>,
+    /// commits of the remote ref not in the local branch, none for an unknown revision
+    pub behind: Option<usize>,
+    /// count of untracked, changed and staged files
+    pub changes: usize,
+    /// whether the local branch points to the same commit as the remote ref
+    pub matches_ref: bool,
+    /// upstream of the local branch, none when it tracks nothing
+    pub upstream: Option<String>,
+    /// commits of the local branch not in its upstream
+    pub upstream_ahead: Option<usize>,
+    /// commits of the upstream not in the local branch
+    pub upstream_behind: Option<usize>,
 }
 
 /// get full ahead/behind values between branches
-pub fn cmp_local_remote(
+pub fn repo_status(
     input_path: impl AsRef<Path>,
     toml_repo: &TomlRepo,
     default_branch: &Option<String>,
     use_tracking_remote: bool,
-) -> Result<StyleMessage, anyhow::Error> {
+) -> Result<RepoStatus, anyhow::Error> {
     let rel_path = toml_repo.local.as_ref().unwrap();
     let full_path = input_path.as_ref().join(rel_path);
 
@@ -110,26 +387,19 @@ pub fn cmp_local_remote(
     }
 
     // priority: commit/tag/branch(default-branch)
-    let (remote_ref_str, remote_desc) = {
-        if use_tracking_remote {
-            let remote_ref_str = git::get_tracking_branch(&full_path)?;
-            (remote_ref_str.clone(), remote_ref_str)
-        } else {
-            let remote_ref = toml_repo.get_remote_ref(&full_path)?;
-            let remote_ref_str = match remote_ref.clone() {
-                RemoteRef::Commit(r) | RemoteRef::Tag(r) | RemoteRef::Branch(r) => r,
-            };
-            let remote_desc = match remote_ref {
-                RemoteRef::Commit(commit) => commit[..7].to_string(),
-                RemoteRef::Tag(r) | RemoteRef::Branch(r) => r,
-            };
-            (remote_ref_str, remote_desc)
-        }
+    let remote_ref = match use_tracking_remote {
+        true => RemoteRef::Branch(git::get_tracking_branch(&full_path)?),
+        false => toml_repo.get_remote_ref(&full_path)?,
+    };
+
+    let mut status = RepoStatus {
+        local: Path::new(rel_path).display_path(),
+        ..Default::default()
     };
 
     // if specified remote commit/tag/branch is null
-    if remote_desc.is_empty() {
-        return Ok("not tracking".into());
+    if remote_ref.remote_desc().is_empty() {
+        return Ok(status);
     }
 
     let mut changed_files: HashSet<String> = HashSet::new();
@@ -154,46 +424,114 @@ pub fn cmp_local_remote(
             changed_files.insert(file.to_string());
         }
     }
+    status.changes = changed_files.len();
+
+    // get local branch
+    status.branch = git::get_current_branch(&full_path)?;
+    let remote_ref_str = remote_ref.to_string();
+    status.remote_ref = Some(remote_ref);
+
+    if status.branch.is_empty() {
+        return Ok(status);
+    }
+
+    // get rev-list between local branch and specified remote commit/tag/branch
+    (status.ahead, status.behind) = rev_list_divergence(&full_path, &status.branch, remote_ref_str);
+    status.matches_ref = status.ahead == Some(0) && status.behind == Some(0);
 
-    let mut changes_desc: Option<StyleMessage> = None;
-    if !changed_files.is_empty() {
-        // format changes tooltip
-        changes_desc = StyleMessage::git_changes(changed_files.len());
+    // the upstream may lag behind or run ahead of the manifest ref
+    if let Ok(upstream) = git::get_tracking_branch(&full_path) {
+        (status.upstream_ahead, status.upstream_behind) =
+            rev_list_divergence(&full_path, &status.branch, &upstream);
+        status.upstream = Some(upstream);
     }
 
-    // get local branch
-    let branch = git::get_current_branch(&full_path)?;
+    Ok(status)
+}
 
-    if branch.is_empty() {
+/// ahead/behind counts of `branch` against `target`, none for an unknown revision
+fn rev_list_divergence(
+    full_path: &Path,
+    branch: impl AsRef<str>,
+    target: impl AsRef<str>,
+) -> (Option<usize>, Option<usize>) {
+    let branch_pair = format!("{}...{}", branch.as_ref(), target.as_ref());
+    match git::rev_list_ahead_behind(full_path, branch_pair) {
+        Ok((ahead, behind)) => (Some(ahead), Some(behind)),
+        Err(_) => (None, None),
+    }
+}
+
+/// describe ahead/behind values between branches and local changes
+pub fn cmp_local_remote(
+    input_path: impl AsRef<Path>,
+    toml_repo: &TomlRepo,
+    default_branch: &Option<String>,
+    use_tracking_remote: bool,
+) -> Result<StyleMessage, anyhow::Error> {
+    let status = repo_status(&input_path, toml_repo, default_branch, use_tracking_remote)?;
+
+    let Some(remote_ref) = status.remote_ref.as_ref() else {
+        return Ok("not tracking".into());
+    };
+
+    if status.branch.is_empty() {
         return Ok("init commit".into());
     }
 
-    // get rev-list between local branch and specified remote commit/tag/branch
-    let branch_pair = format!("{}...{}", &branch, &remote_ref_str);
-    let mut commit_desc: Option<StyleMessage> = None;
-    if let Ok(output) = git::get_rev_list_count(&full_path, branch_pair) {
-        let re = Regex::new(r"(\d+)\s*(\d+)").unwrap();
+    // format changes tooltip
+    let changes_desc = StyleMessage::git_changes(status.changes);
 
-        if let Some(caps) = re.captures(&output) {
-            // format commit tooltip
-            let (ahead, behind) = (&caps[1], &caps[2]);
-            commit_desc = StyleMessage::git_commits(ahead, behind);
+    // format commit tooltip, if git rev-list find "unknown revision" error
+    let commit_desc = match (status.ahead, status.behind) {
+        (Some(ahead), Some(behind)) => {
+            StyleMessage::git_commits(ahead.to_string(), behind.to_string())
         }
-    } else {
-        // if git rev-list find "unknown revision" error
-        commit_desc = Some(StyleMessage::git_unknown_revision());
-    }
+        _ => Some(StyleMessage::git_unknown_revision()),
+    };
 
     // show diff overview
     let desc = match (commit_desc, changes_desc) {
         (None, None) => {
-            let branch_log = git::get_branch_log(&full_path, branch);
+            let full_path = input_path.as_ref().join(toml_repo.local.as_ref().unwrap());
+            let branch_log = git::get_branch_log(&full_path, status.branch);
             StyleMessage::git_update_to_date(branch_log)
         }
         (commit_desc, changes_desc) => {
-            StyleMessage::git_diff(remote_desc, commit_desc, changes_desc)
+            StyleMessage::git_diff(remote_ref.remote_desc(), commit_desc, changes_desc)
         }
     };
 
     Ok(desc)
 }
+
+/// describe how far the local branch is from both its upstream and the manifest ref,
+/// like `↑2↓0 vs upstream, ↑0↓5 vs manifest`
+pub fn cmp_upstream_manifest(
+    input_path: impl AsRef<Path>,
+    toml_repo: &TomlRepo,
+    default_branch: &Option<String>,
+) -> Result<StyleMessage, anyhow::Error> {
+    let status = repo_status(&input_path, toml_repo, default_branch, false)?;
+
+    if status.remote_ref.is_some() && status.branch.is_empty() {
+        return Ok("init commit".into());
+    }
+
+    let upstream_desc = match status.upstream {
+        Some(_) => {
+            StyleMessage::git_divergence(status.upstream_ahead, status.upstream_behind, "upstream")
+        }
+        None => "no upstream".into(),
+    };
+    let manifest_desc = match status.remote_ref {
+        Some(_) => StyleMessage::git_divergence(status.ahead, status.behind, "manifest"),
+        None => "not tracking".into(),
+    };
+
+    let mut desc = upstream_desc.plain_text(", ").join(manifest_desc);
+    if let Some(changes_desc) = StyleMessage::git_changes(status.changes) {
+        desc = desc.plain_text(", ").join(changes_desc);
+    }
+    Ok(desc)
+}
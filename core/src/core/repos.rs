@@ -1,42 +1,476 @@
+use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
-use std::{fs, path::Path};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use toml_edit::{Document, Item, Table};
 
+use crate::core::git::StashMode;
 use crate::core::repo::TomlRepo;
+use crate::utils::error::MgitError;
+use crate::utils::logger;
+use crate::utils::path::PathExtension;
+use crate::utils::style_message::StyleMessage;
+
+/// work directory and config file used by a command.
+///
+/// every command resolves its `path`/`--config` arguments through here, so the
+/// semantics are identical everywhere:
+/// - `path` defaults to the current directory
+/// - `config` defaults to `<path>/.gitrepos`
+/// - an explicit `config` never changes the work directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspacePaths {
+    pub path: PathBuf,
+    pub config_path: PathBuf,
+}
+
+impl WorkspacePaths {
+    /// `config` of `-` reads the config file from stdin
+    pub fn resolve(path: Option<impl AsRef<Path>>, config: Option<impl AsRef<Path>>) -> Self {
+        let path = path.map_or_else(|| env::current_dir().unwrap(), |p| p.as_ref().to_path_buf());
+        let config_path = match config {
+            Some(config) if config.as_ref() == Path::new("-") => stdin_config_path(),
+            Some(config) => config.as_ref().to_path_buf(),
+            None => default_config_path(&path),
+        };
+        Self { path, config_path }
+    }
+}
+
+/// `.gitrepos` of the work directory, or `.gitrepos.yaml`/`.gitrepos.yml` when only one of
+/// those exists
+fn default_config_path(path: &Path) -> PathBuf {
+    [".gitrepos", ".gitrepos.yaml", ".gitrepos.yml"]
+        .iter()
+        .map(|name| path.join(name))
+        .find(|config_path| config_path.is_file())
+        .unwrap_or_else(|| path.join(".gitrepos"))
+}
+
+/// drop unset fields, which toml leaves out as well
+fn without_nulls(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => map
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| (key, without_nulls(value)))
+            .collect(),
+        serde_json::Value::Array(list) => list.into_iter().map(without_nulls).collect(),
+        value => value,
+    }
+}
+
+/// replace every `${VAR}` in `value` with the variable from the process environment
+pub(crate) fn expand_env(value: &str) -> anyhow::Result<String> {
+    let mut out = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 2..start + 2 + len];
+        let var = env::var(name)
+            .map_err(|_| anyhow!("uses ${{{}}}, which is not set in the environment", name))?;
+        out.push_str(&rest[..start]);
+        out.push_str(&var);
+        rest = &rest[start + 3 + len..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// update the values of `table` to the ones of `fresh`, keeping the comments and
+/// formatting of the values that are unchanged, and the trailing comment of the changed ones
+fn merge_values(table: &mut Table, fresh: &Table) {
+    let same = |a: &Item, b: &Item| {
+        let a = toml_edit::de::from_item::<toml_edit::easy::Value>(a.clone());
+        let b = toml_edit::de::from_item::<toml_edit::easy::Value>(b.clone());
+        matches!((a, b), (Ok(a), Ok(b)) if a == b)
+    };
+
+    let stale = table
+        .iter()
+        .filter(|(key, item)| item.is_value() && !fresh.contains_key(key))
+        .map(|(key, _)| key.to_string())
+        .collect::<Vec<_>>();
+    for key in stale {
+        table.remove(&key);
+    }
+
+    for (key, item) in fresh.iter().filter(|(_, item)| item.is_value()) {
+        match table.get_mut(key) {
+            Some(old) if same(old, item) => {}
+            Some(old) => {
+                let mut item = item.clone();
+                if let (Some(value), Some(old_value)) = (item.as_value_mut(), old.as_value()) {
+                    *value.decor_mut() = old_value.decor().clone();
+                }
+                *old = item;
+            }
+            None => {
+                table.insert(key, item.clone());
+            }
+        }
+    }
+}
+
+/// temporary file holding the config read from stdin, removed on drop
+struct StdinConfig {
+    path: PathBuf,
+}
+
+impl Drop for StdinConfig {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+static STDIN_CONFIG: Mutex<Option<StdinConfig>> = Mutex::new(None);
+
+/// stdin can be read only once, keep it in a temporary file without extension.
+///
+/// when the file can't be written, the path returned doesn't exist, so the command
+/// fails with config file not found
+fn stdin_config_path() -> PathBuf {
+    let mut stdin_config = STDIN_CONFIG.lock().unwrap();
+    if let Some(stdin_config) = stdin_config.as_ref() {
+        return stdin_config.path.clone();
+    }
+
+    match write_stdin_config() {
+        Ok(path) => {
+            *stdin_config = Some(StdinConfig { path: path.clone() });
+            path
+        }
+        Err(e) => {
+            logger::error(format!("Failed to read config from stdin: {}", e));
+            PathBuf::from("-")
+        }
+    }
+}
+
+/// write stdin to a new file only the user can read, an existing file or symlink of the
+/// same name is never followed
+fn write_stdin_config() -> anyhow::Result<PathBuf> {
+    let mut content = String::new();
+    std::io::stdin().read_to_string(&mut content)?;
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut attempt = 0;
+    loop {
+        let name = format!("mgit-stdin-{}-{}-{}", std::process::id(), nanos, attempt);
+        let config_path = env::temp_dir().join(name);
+        match options.open(&config_path) {
+            Ok(mut file) => {
+                let written = file.write_all(content.as_bytes());
+                if let Err(e) = written {
+                    let _ = fs::remove_file(&config_path);
+                    return Err(e.into());
+                }
+                return Ok(config_path);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists && attempt < 100 => {
+                attempt += 1
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// remove the temporary file of a config read from stdin, call it before the process exits
+pub fn remove_stdin_config() {
+    STDIN_CONFIG.lock().unwrap().take();
+}
+
+/// how config files are parsed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConfigFormat {
+    /// by the file extension, or by the content if there is none
+    #[default]
+    Auto,
+    Toml,
+    Yaml,
+    Json,
+}
+
+static CONFIG_FORMAT: Mutex<ConfigFormat> = Mutex::new(ConfigFormat::Auto);
+
+/// format used by `TomlConfig::load` for every config file
+pub fn set_config_format(format: ConfigFormat) {
+    *CONFIG_FORMAT.lock().unwrap() = format;
+}
+
+/// format of the config file as `TomlConfig::load` would parse it
+pub(crate) fn config_format(path: &Path, content: &str) -> ConfigFormat {
+    CONFIG_FORMAT.lock().unwrap().detect(path, content)
+}
+
+impl ConfigFormat {
+    /// resolve `Auto` by the extension of the file, then by sniffing the content
+    fn detect(self, path: &Path, content: &str) -> Self {
+        if self != ConfigFormat::Auto {
+            return self;
+        }
+
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        match extension.map(str::to_lowercase).as_deref() {
+            Some("toml") => ConfigFormat::Toml,
+            Some("yaml" | "yml") => ConfigFormat::Yaml,
+            Some("json") => ConfigFormat::Json,
+            _ if content.trim_start().starts_with('{') => ConfigFormat::Json,
+            _ if toml::from_str::<toml::Value>(content).is_ok() => ConfigFormat::Toml,
+            _ => ConfigFormat::Yaml,
+        }
+    }
+}
 
 /// this type is used to deserialize `.gitrepos` files.
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub struct TomlConfig {
     pub version: Option<String>,
+    /// what the workspace is, shown by `mgit info`
+    pub name: Option<String>,
+    pub description: Option<String>,
     pub default_branch: Option<String>,
     pub default_remote: Option<String>,
+    /// stash mode of sync when neither `--stash` nor `--hard` is given
+    pub sync_strategy: Option<StashMode>,
+    /// untracked files matching these patterns survive the clean of a hard sync
+    pub clean_exclude: Option<Vec<String>>,
     pub repos: Option<Vec<TomlRepo>>,
 }
 
 impl TomlConfig {
-    /// deserialize config file (.gitrepos) with full file path
+    /// deserialize config file (.gitrepos) with full file path, in the format set by `set_config_format`
     pub fn load(path: impl AsRef<Path>) -> Option<Self> {
-        if !path.as_ref().is_file() {
+        let format = *CONFIG_FORMAT.lock().unwrap();
+        Self::load_with_format(path, format)
+    }
+
+    /// load several config files as one repo list, each repo without a branch
+    /// gets the `default-branch` of its own file.
+    ///
+    /// the other settings come from the first file; the same `local` in two
+    /// files is an error.
+    pub fn load_merged(paths: &[impl AsRef<Path>]) -> anyhow::Result<Self> {
+        let mut merged: Option<TomlConfig> = None;
+        let mut owners: HashMap<String, &Path> = HashMap::new();
+        for path in paths {
+            let path = path.as_ref();
+            if !path.is_file() {
+                return Err(anyhow!(MgitError::ConfigFileNotFound(
+                    StyleMessage::config_file_not_found()
+                )));
+            }
+            let Some(mut toml_config) = Self::load(path) else {
+                return Err(anyhow!(MgitError::LoadConfigFailed));
+            };
+            if paths.len() == 1 {
+                return Ok(toml_config);
+            }
+
+            let mut repos = toml_config.repos.take().unwrap_or_default();
+            for repo in &mut repos {
+                let Some(local) = repo.local.as_ref() else {
+                    continue;
+                };
+                if let Some(owner) = owners.insert(local.display_path(), path) {
+                    return Err(anyhow!(
+                        "repo {} is in both {} and {}",
+                        local.display_path(),
+                        owner.display(),
+                        path.display()
+                    ));
+                }
+                if repo.branch.is_none() {
+                    repo.branch = toml_config.default_branch.clone();
+                }
+            }
+
+            match merged.as_mut() {
+                None => {
+                    toml_config.repos = Some(repos);
+                    merged = Some(toml_config);
+                }
+                Some(merged) => {
+                    merged.repos.get_or_insert_with(Vec::new).extend(repos);
+                    if let Some(clean_exclude) = toml_config.clean_exclude {
+                        let excludes = merged.clean_exclude.get_or_insert_with(Vec::new);
+                        excludes.extend(clean_exclude);
+                    }
+                }
+            }
+        }
+        merged.ok_or(anyhow!(MgitError::LoadConfigFailed))
+    }
+
+    /// deserialize config file in toml, yaml or json
+    pub fn load_with_format(path: impl AsRef<Path>, format: ConfigFormat) -> Option<Self> {
+        let path = path.as_ref();
+        if !path.is_file() {
             return None;
         }
 
         // NOTE: mac not recognize "."
-        let content = fs::read_to_string(path).unwrap().replace("\".\"", "\"\"");
+        let content = fs::read_to_string(path).ok()?.replace("\".\"", "\"\"");
 
-        let Ok(mut toml_config) = toml::from_str::<TomlConfig>(&content) else {
-            return None;
+        Self::parse(&content, format.detect(path, &content))
+    }
+
+    /// deserialize config content in the format, `Auto` is read as toml.
+    ///
+    /// `${VAR}` in the `remote` of repos stays as written, it's only expanded where git
+    /// is called. a variable that is not set fails the parse.
+    ///
+    /// repos without a `remote-name` fall back to `default-remote` when they are used
+    pub fn parse(content: &str, format: ConfigFormat) -> Option<Self> {
+        let mut toml_config = match format {
+            ConfigFormat::Json => serde_json::from_str::<TomlConfig>(content).ok()?,
+            ConfigFormat::Yaml => serde_yaml::from_str::<TomlConfig>(content).ok()?,
+            _ => toml::from_str::<TomlConfig>(content).ok()?,
         };
 
         if let Some(item) = toml_config.repos.as_mut() {
             item.sort();
         }
 
+        for repo in toml_config.repos.iter_mut().flatten() {
+            repo.default_remote = toml_config.default_remote.clone();
+            if let Err(e) = repo.remote.as_deref().map(expand_env).transpose() {
+                let local = repo.local.as_deref().unwrap_or_default().display_path();
+                logger::error(format!("remote of repo {} {}", local, e));
+                return None;
+            }
+        }
+
         Some(toml_config)
     }
 
-    // serialize config file .gitrepos
-    pub fn serialize(&self) -> String {
-        let toml = toml_edit::ser::to_item(self).unwrap();
+    /// serialize in the format, `Auto` is written as toml. unset fields are left out
+    pub fn serialize_as(&self, format: ConfigFormat) -> anyhow::Result<String> {
+        match format {
+            ConfigFormat::Auto | ConfigFormat::Toml => self.serialize(),
+            ConfigFormat::Json => {
+                let value = without_nulls(serde_json::to_value(self)?);
+                Ok(format!("{}\n", serde_json::to_string_pretty(&value)?))
+            }
+            ConfigFormat::Yaml => {
+                let value = without_nulls(serde_json::to_value(self)?);
+                Ok(serde_yaml::to_string(&value)?)
+            }
+        }
+    }
+
+    /// write the config file in the format of its extension or existing content, toml by
+    /// default. an existing toml file is updated in place, so its comments, formatting and
+    /// the order of keys and repos survive
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let existing = fs::read_to_string(path).unwrap_or_default();
+        let content = match config_format(path, &existing) {
+            ConfigFormat::Toml if !existing.trim().is_empty() => self.serialize_onto(&existing)?,
+            format => self.serialize_as(format)?,
+        };
+        fs::write(path, content).map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// serialize onto the content of an existing toml file, only the fields that
+    /// changed are touched. repos are matched by `local`, new ones are appended
+    pub fn serialize_onto(&self, existing: &str) -> anyhow::Result<String> {
+        let toml_string = self.serialize()?;
+        let (Ok(mut doc), Ok(fresh)) = (
+            existing.parse::<Document>(),
+            toml_string.parse::<Document>(),
+        ) else {
+            return Ok(toml_string);
+        };
+        merge_values(doc.as_table_mut(), fresh.as_table());
+
+        let fresh_repos = fresh.get("repos").and_then(Item::as_array_of_tables);
+        let repos = doc.get_mut("repos").and_then(Item::as_array_of_tables_mut);
+        match (repos, fresh_repos) {
+            (_, None) => {
+                doc.remove("repos");
+            }
+            (None, Some(fresh_repos)) => {
+                doc["repos"] = Item::ArrayOfTables(fresh_repos.clone());
+            }
+            (Some(tables), Some(fresh_repos)) => {
+                let local_of = |table: &Table| {
+                    table
+                        .get("local")
+                        .and_then(Item::as_str)
+                        .map(|local| local.norm_path().display_path())
+                };
+                let mut fresh_tables = fresh_repos.iter().collect::<Vec<_>>();
+
+                let mut idx = 0;
+                while let Some(table) = tables.get_mut(idx) {
+                    let pos = local_of(table).and_then(|local| {
+                        fresh_tables
+                            .iter()
+                            .position(|fresh| local_of(fresh).as_ref() == Some(&local))
+                    });
+                    match pos {
+                        Some(pos) => {
+                            merge_values(table, fresh_tables.remove(pos));
+                            idx += 1;
+                        }
+                        None => tables.remove(idx),
+                    }
+                }
+
+                // a new table has no position, so it's written after the existing ones
+                for fresh_table in fresh_tables {
+                    let mut table = Table::new();
+                    for (key, item) in fresh_table.iter() {
+                        table.insert(key, item.clone());
+                    }
+                    tables.push(table);
+                }
+            }
+        }
+
+        Ok(doc.to_string())
+    }
+
+    /// serialize config file .gitrepos, fails when a value has no toml form
+    pub fn serialize(&self) -> anyhow::Result<String> {
+        // toml integers are i64, toml_edit would wrap larger values around silently
+        for toml_repo in self.repos.iter().flatten() {
+            let values = [
+                toml_repo.max_size,
+                toml_repo.retries.map(|v| v as u64),
+                toml_repo.retry_delay_ms,
+                toml_repo.depth.map(|v| v as u64),
+            ];
+            if values
+                .into_iter()
+                .flatten()
+                .any(|v| i64::try_from(v).is_err())
+            {
+                return Err(anyhow!(
+                    "Failed to serialize config: repo {} has a number out of range",
+                    toml_repo.local.as_deref().unwrap_or_default()
+                ));
+            }
+        }
+
+        let toml = toml_edit::ser::to_item(self)
+            .map_err(|e| anyhow!("Failed to serialize config: {}", e))?;
         let mut out = String::new();
 
         out.push_str("# This file is automatically @generated by mgit.\n");
@@ -47,6 +481,16 @@ impl TomlConfig {
             out.push_str(&format!("version = {}\n", item));
         }
 
+        // name = "your workspace"
+        if let Some(item) = toml.get("name") {
+            out.push_str(&format!("name = {}\n", item));
+        }
+
+        // description = "what the workspace is for"
+        if let Some(item) = toml.get("description") {
+            out.push_str(&format!("description = {}\n", item));
+        }
+
         // default-branch = "your_branch"
         if let Some(item) = toml.get("default-branch") {
             out.push_str(&format!("default-branch = {}\n", item));
@@ -57,15 +501,29 @@ impl TomlConfig {
             out.push_str(&format!("default-remote = {}\n", item));
         }
 
+        // sync-strategy = "normal" | "stash" | "hard"
+        if let Some(item) = toml.get("sync-strategy") {
+            out.push_str(&format!("sync-strategy = {}\n", item));
+        }
+
+        // clean-exclude = ["node_modules", ".venv"]
+        if let Some(item) = toml.get("clean-exclude") {
+            out.push_str(&format!("clean-exclude = {}\n", item));
+        }
+
         out.push('\n');
 
         // [[repos]]
         if let Some(repos) = toml.get("repos") {
-            let list = repos.as_array().expect("repos must be an array");
+            let list = repos
+                .as_array()
+                .ok_or_else(|| anyhow!("Failed to serialize config: repos must be an array"))?;
 
             for entry in list {
                 out.push_str("[[repos]]\n");
-                let table = entry.as_inline_table().expect("repo must be table");
+                let table = entry
+                    .as_inline_table()
+                    .ok_or_else(|| anyhow!("Failed to serialize config: repo must be a table"))?;
 
                 // local = "your/local/path"
                 if let Some(item) = table.get("local") {
@@ -77,6 +535,21 @@ impl TomlConfig {
                     out.push_str(&format!("remote = {}\n", item));
                 }
 
+                // fetch-remote = "your://upstream/url"
+                if let Some(item) = table.get("fetch-remote") {
+                    out.push_str(&format!("fetch-remote = {}\n", item));
+                }
+
+                // push-remote = "your://fork/url"
+                if let Some(item) = table.get("push-remote") {
+                    out.push_str(&format!("push-remote = {}\n", item));
+                }
+
+                // remote-name = "your_remote_name"
+                if let Some(item) = table.get("remote-name") {
+                    out.push_str(&format!("remote-name = {}\n", item));
+                }
+
                 // branch = "your_branch"
                 if let Some(item) = table.get("branch") {
                     out.push_str(&format!("branch = {}\n", item));
@@ -97,10 +570,55 @@ impl TomlConfig {
                     out.push_str(&format!("sparse = {}\n", item));
                 }
 
+                // subdir = "your/sub/dir"
+                if let Some(item) = table.get("subdir") {
+                    out.push_str(&format!("subdir = {}\n", item));
+                }
+
+                // max-size = 1024
+                if let Some(item) = table.get("max-size") {
+                    out.push_str(&format!("max-size = {}\n", item));
+                }
+
+                // groups = ["client", "tools"]
+                if let Some(item) = table.get("groups") {
+                    out.push_str(&format!("groups = {}\n", item));
+                }
+
+                // mirror = true
+                if let Some(item) = table.get("mirror") {
+                    out.push_str(&format!("mirror = {}\n", item));
+                }
+
+                // retries = 5
+                if let Some(item) = table.get("retries") {
+                    out.push_str(&format!("retries = {}\n", item));
+                }
+
+                // retry-delay-ms = 2000
+                if let Some(item) = table.get("retry-delay-ms") {
+                    out.push_str(&format!("retry-delay-ms = {}\n", item));
+                }
+
+                // proxy = "http://proxy.example.com:8080"
+                if let Some(item) = table.get("proxy") {
+                    out.push_str(&format!("proxy = {}\n", item));
+                }
+
+                // depth = 1
+                if let Some(item) = table.get("depth") {
+                    out.push_str(&format!("depth = {}\n", item));
+                }
+
+                // recurse-submodules = true
+                if let Some(item) = table.get("recurse-submodules") {
+                    out.push_str(&format!("recurse-submodules = {}\n", item));
+                }
+
                 out.push('\n');
             }
         }
 
-        out
+        Ok(out)
     }
 }
@@ -0,0 +1,113 @@
+use anyhow::anyhow;
+use std::path::{Path, PathBuf};
+
+use crate::core::git::RemoteRef;
+use crate::core::repo::TomlRepo;
+use crate::core::repos::{TomlConfig, WorkspacePaths};
+use crate::ops::validate::normalize_local;
+use crate::ops::{clone_repos, CloneOptions};
+use crate::utils::error::{MgitError, MgitResult};
+use crate::utils::logger;
+use crate::utils::progress::Progress;
+use crate::utils::StyleMessage;
+
+pub struct AddOptions {
+    pub path: PathBuf,
+    pub config_path: PathBuf,
+    pub local: String,
+    pub remote: String,
+    pub remote_ref: RemoteRef,
+    /// clone the new repo right away
+    pub sync: bool,
+}
+
+impl AddOptions {
+    pub fn new(
+        path: Option<impl AsRef<Path>>,
+        config_path: Option<impl AsRef<Path>>,
+        local: String,
+        remote: String,
+        remote_ref: RemoteRef,
+    ) -> Self {
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
+        Self {
+            path,
+            config_path,
+            local,
+            remote,
+            remote_ref,
+            sync: false,
+        }
+    }
+}
+
+/// append a repo to the config file, the entries and comments already there are kept as they are
+pub fn add_repo(options: AddOptions, progress: impl Progress) -> MgitResult {
+    let path = &options.path;
+    let config_path = &options.config_path;
+
+    // if directory doesn't exist, return
+    if !path.is_dir() {
+        return Err(anyhow!(MgitError::DirNotFound(
+            StyleMessage::dir_not_found(path)
+        )));
+    }
+
+    // check if .gitrepos exists
+    if !config_path.is_file() {
+        return Err(anyhow!(MgitError::ConfigFileNotFound(
+            StyleMessage::config_file_not_found()
+        )));
+    }
+
+    // load config file(like .gitrepos)
+    let Some(mut toml_config) = TomlConfig::load(config_path) else {
+        return Err(anyhow!(MgitError::LoadConfigFailed));
+    };
+
+    let Some(components) = normalize_local(&options.local) else {
+        return Err(anyhow!(
+            "local {} is outside the work directory",
+            options.local
+        ));
+    };
+    let local = match components.is_empty() {
+        true => ".".to_string(),
+        false => components.join("/"),
+    };
+
+    let repos = toml_config.repos.get_or_insert_with(Vec::new);
+    let others = repos
+        .iter()
+        .filter_map(|repo| repo.local.clone())
+        .collect::<Vec<_>>();
+    let duplicate = repos.iter().any(|repo| {
+        let existing = repo.local.as_deref().and_then(normalize_local);
+        existing == Some(components.clone())
+    });
+    if duplicate {
+        return Err(anyhow!(
+            "repo {} is already in {}",
+            local,
+            config_path.display()
+        ));
+    }
+
+    let mut toml_repo = TomlRepo {
+        local: Some(local.clone()),
+        remote: Some(options.remote),
+        ..TomlRepo::default()
+    };
+    toml_repo.override_ref(options.remote_ref);
+    repos.push(toml_repo);
+    toml_config.save(config_path)?;
+
+    logger::info(format!("  + {}", local));
+    if !options.sync {
+        return Ok(StyleMessage::ops_success("add"));
+    }
+
+    // only the new repo is cloned, missing repos added before are left alone
+    let clone_options = CloneOptions::new(Some(path), Some(config_path), None, None, Some(others));
+    clone_repos(clone_options, progress)
+}
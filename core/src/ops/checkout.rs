@@ -0,0 +1,173 @@
+use anyhow::anyhow;
+use std::path::{Path, PathBuf};
+
+use crate::core::git::{self, RemoteRef, RepositoryError};
+use crate::core::repo::{repos_to_map_with_ignore, TomlRepo};
+use crate::core::repos::{TomlConfig, WorkspacePaths};
+use crate::ops::sync::{checkout_args, checkout_branch_name};
+use crate::utils::error::{MgitError, MgitResult, OpsErrors};
+use crate::utils::logger;
+use crate::utils::path::PathExtension;
+use crate::utils::StyleMessage;
+
+pub struct CheckoutOptions {
+    pub path: PathBuf,
+    pub config_path: PathBuf,
+    /// branch or tag to check out, whatever the config file pins
+    pub name: String,
+    /// create the branch from HEAD in repos which have neither the branch nor the tag
+    pub create: bool,
+    pub ignore: Option<Vec<String>>,
+}
+
+impl CheckoutOptions {
+    pub fn new(
+        path: Option<impl AsRef<Path>>,
+        config_path: Option<impl AsRef<Path>>,
+        name: String,
+        create: bool,
+        ignore: Option<Vec<String>>,
+    ) -> Self {
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
+        Self {
+            path,
+            config_path,
+            name,
+            create,
+            ignore,
+        }
+    }
+}
+
+enum CheckoutResult {
+    Switched(String, String),
+    AlreadyOn,
+    Skip(StyleMessage),
+}
+
+/// checkout every repo to the same branch or tag from the refs already fetched, repos without
+/// it are skipped
+pub fn checkout_repos(options: CheckoutOptions) -> MgitResult {
+    let path = &options.path;
+    let config_path = &options.config_path;
+    let ignore = options.ignore.as_ref();
+
+    logger::info(StyleMessage::ops_start("checkout repos", path));
+
+    // if directory doesn't exist, return
+    if !path.is_dir() {
+        return Err(anyhow!(MgitError::DirNotFound(
+            StyleMessage::dir_not_found(path)
+        )));
+    }
+
+    // check if .gitrepos exists
+    if !config_path.is_file() {
+        return Err(anyhow!(MgitError::ConfigFileNotFound(
+            StyleMessage::config_file_not_found()
+        )));
+    }
+
+    // load config file(like .gitrepos)
+    let Some(toml_config) = TomlConfig::load(config_path) else {
+        return Err(anyhow!(MgitError::LoadConfigFailed));
+    };
+
+    let Some(toml_repos) = toml_config.repos else {
+        return Ok("No repos to checkout".into());
+    };
+
+    let mut repos = repos_to_map_with_ignore(toml_repos, ignore)
+        .into_iter()
+        .collect::<Vec<_>>();
+    repos.sort_by_key(|(id, _)| *id);
+
+    let mut skip_status = StyleMessage::new();
+    let mut errors = Vec::new();
+    for (_, toml_repo) in repos {
+        let rel_path = toml_repo.local.as_ref().unwrap().display_path();
+        match checkout_repo(path, &toml_repo, &options.name, options.create) {
+            Ok(CheckoutResult::Switched(from, to)) => {
+                logger::info(StyleMessage::git_repair(&rel_path, from, to))
+            }
+            Ok(CheckoutResult::AlreadyOn) => {}
+            Ok(CheckoutResult::Skip(desc)) => {
+                let msg = StyleMessage::git_skip(&rel_path, desc);
+                skip_status = skip_status.join(format!("  {}\n", msg).into());
+            }
+            Err(e) => errors.push(StyleMessage::git_error(&rel_path, &e)),
+        }
+    }
+
+    // repos without the ref are reported whether checkout succeeded or not
+    if !skip_status.is_empty() {
+        skip_status = StyleMessage::from("Skip status:\n").join(skip_status);
+    }
+
+    match errors.len() {
+        0 => Ok(StyleMessage::ops_success("checkout").join(skip_status)),
+        _ => {
+            let msg = StyleMessage::ops_failed("checkout", errors.len()).join(skip_status);
+            Err(anyhow!(MgitError::OpsError {
+                prefix: msg,
+                errors: OpsErrors(errors),
+            }))
+        }
+    }
+}
+
+/// priority: local branch, remote branch, tag, then a new branch with `create`
+fn checkout_repo(
+    input_path: &Path,
+    toml_repo: &TomlRepo,
+    name: &str,
+    create: bool,
+) -> anyhow::Result<CheckoutResult> {
+    let full_path = input_path.join(toml_repo.local.as_ref().unwrap());
+    match git::is_repository(&full_path) {
+        Err(RepositoryError::NotFound) => {
+            return Ok(CheckoutResult::Skip(StyleMessage::git_repo_missing()))
+        }
+        res => res?,
+    }
+
+    let current = match git::get_current_branch(&full_path) {
+        Ok(current_branch) if current_branch == name => return Ok(CheckoutResult::AlreadyOn),
+        Ok(current_branch) if !current_branch.is_empty() => current_branch,
+        _ => {
+            let commit = git::get_current_commit(&full_path)?;
+            format!("detached HEAD at {}", &commit[..7])
+        }
+    };
+
+    // the remote branch of the repo's own remote, as sync would check it out
+    let mut target = toml_repo.clone();
+    target.override_ref(RemoteRef::Branch(name.to_string()));
+    let remote_branch = target.get_remote_ref(&full_path)?.to_string();
+
+    let (branch, args) = if git::local_branch_already_exist(&full_path, name)? {
+        (name.to_string(), checkout_args(name, name, true, false))
+    } else if git::rev_parse(&full_path, &remote_branch).is_ok() {
+        (
+            name.to_string(),
+            checkout_args(name, &remote_branch, false, false),
+        )
+    } else if git::rev_parse(&full_path, format!("refs/tags/{}", name)).is_ok() {
+        // like sync, a tag is checked out on a `tags/<tag>` branch
+        let branch = checkout_branch_name(toml_repo, &RemoteRef::Tag(name.to_string()));
+        let args = checkout_args(&branch, &format!("refs/tags/{}", name), false, false);
+        (branch, args)
+    } else if create {
+        let args = ["checkout", "-b", name].map(String::from).to_vec();
+        (name.to_string(), args)
+    } else {
+        return Ok(CheckoutResult::Skip(StyleMessage::git_ref_not_found(name)));
+    };
+
+    if branch == current {
+        return Ok(CheckoutResult::AlreadyOn);
+    }
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    git::checkout(&full_path, &args)?;
+    Ok(CheckoutResult::Switched(current, branch))
+}
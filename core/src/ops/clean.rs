@@ -1,10 +1,10 @@
 use anyhow::anyhow;
 use globset::GlobBuilder;
-use std::env;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
-use crate::core::repos::TomlConfig;
+use crate::core::git;
+use crate::core::repos::{TomlConfig, WorkspacePaths};
 use crate::utils::error::{MgitError, MgitResult};
 use crate::utils::logger;
 use crate::utils::style_message::StyleMessage;
@@ -12,13 +12,18 @@ use crate::utils::style_message::StyleMessage;
 pub struct CleanOptions {
     pub path: PathBuf,
     pub config_path: PathBuf,
+    /// repos of these config files are kept as well
+    pub extra_config_paths: Vec<PathBuf>,
 }
 
 impl CleanOptions {
     pub fn new(path: Option<impl AsRef<Path>>, config_path: Option<impl AsRef<Path>>) -> Self {
-        let path = path.map_or(env::current_dir().unwrap(), |p| p.as_ref().to_path_buf());
-        let config_path = config_path.map_or(path.join(".gitrepos"), |p| p.as_ref().to_path_buf());
-        Self { path, config_path }
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
+        Self {
+            path,
+            config_path,
+            extra_config_paths: Vec::new(),
+        }
     }
 }
 
@@ -41,10 +46,11 @@ pub fn clean_repo(options: CleanOptions) -> MgitResult {
             StyleMessage::config_file_not_found()
         )));
     }
-    // load config file(like .gitrepos)
-    let Some(toml_config) = TomlConfig::load(config_path) else {
-        return Err(anyhow!(MgitError::LoadConfigFailed));
-    };
+    // load config files(like .gitrepos)
+    let config_paths = std::iter::once(config_path)
+        .chain(&options.extra_config_paths)
+        .collect::<Vec<_>>();
+    let toml_config = TomlConfig::load_merged(&config_paths)?;
 
     let Some(toml_repos) = &toml_config.repos else {
         return Ok("No repos to clean".into());
@@ -82,7 +88,8 @@ pub fn clean_repo(options: CleanOptions) -> MgitResult {
             pb.pop();
             let rel_path = pb.strip_prefix(&input_path).unwrap().to_path_buf();
 
-            if !config_repo_paths.contains(&rel_path) {
+            // submodule checkouts belong to their superproject, sync cleans them up
+            if !config_repo_paths.contains(&rel_path) && !git::is_submodule_checkout(&pb) {
                 unused_paths.push(rel_path);
             }
 
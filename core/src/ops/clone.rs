@@ -0,0 +1,225 @@
+use atomic_counter::{AtomicCounter, RelaxedCounter};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use anyhow::{anyhow, Context};
+use std::path::{Path, PathBuf};
+
+use crate::core::git::{self, RepositoryError};
+use crate::core::repo::repos_to_map_with_ignore;
+use crate::core::repos::{TomlConfig, WorkspacePaths};
+use crate::ops::sync::{checkout_args, checkout_branch_name};
+use crate::ops::{exec_fetch, set_tracking_remote_branch};
+
+use crate::utils::error::{MgitError, MgitResult, OpsErrors};
+use crate::utils::path::PathExtension;
+use crate::utils::progress::{Progress, RepoInfo};
+use crate::utils::style_message::StyleMessage;
+use crate::utils::{fd_limit, logger};
+
+pub struct CloneOptions {
+    pub path: PathBuf,
+    pub config_path: PathBuf,
+    pub thread_count: usize,
+    pub depth: Option<usize>,
+    pub ignore: Option<Vec<String>>,
+}
+
+impl CloneOptions {
+    pub fn new(
+        path: Option<impl AsRef<Path>>,
+        config_path: Option<impl AsRef<Path>>,
+        thread: Option<usize>,
+        depth: Option<usize>,
+        ignore: Option<Vec<String>>,
+    ) -> Self {
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
+        Self {
+            path,
+            config_path,
+            thread_count: thread.unwrap_or(4),
+            depth,
+            ignore,
+        }
+    }
+}
+
+enum CloneResult {
+    Cloned,
+    Skip(StyleMessage),
+    Error(StyleMessage),
+}
+
+/// clone the repos whose directory is missing or empty.
+///
+/// unlike sync, existing repos and directories are left alone: nothing is stashed, reset or cleaned.
+pub fn clone_repos(options: CloneOptions, progress: impl Progress) -> MgitResult {
+    let path = &options.path;
+    let config_path = &options.config_path;
+    let thread_count = options.thread_count;
+    let depth = options.depth;
+    let ignore = options.ignore.as_ref();
+
+    logger::info(StyleMessage::ops_start("clone repos", path));
+
+    // check if .gitrepos exists
+    if !config_path.is_file() {
+        return Err(anyhow!(MgitError::ConfigFileNotFound(
+            StyleMessage::config_file_not_found(),
+        )));
+    }
+    // load config file(like .gitrepos)
+    let Some(toml_config) = TomlConfig::load(config_path) else {
+        return Err(anyhow!(MgitError::LoadConfigFailed));
+    };
+
+    let Some(toml_repos) = toml_config.repos else {
+        return Ok("No repos to clone".into());
+    };
+    let default_branch = toml_config.default_branch;
+
+    // retain repos exclude ignore repositories
+    let repos_map = repos_to_map_with_ignore(toml_repos, ignore);
+
+    progress.repos_start(repos_map.len());
+
+    let counter = RelaxedCounter::new(1);
+
+    let thread_builder =
+        rayon::ThreadPoolBuilder::new().num_threads(fd_limit::cap_thread_count(thread_count));
+    let Ok(thread_pool) = thread_builder.build() else {
+        return Err(anyhow!(MgitError::CreateThreadPoolFailed));
+    };
+
+    let results: Vec<_> = thread_pool.install(|| {
+        let res = repos_map
+            .iter()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|(id, toml_repo)| {
+                // use default branch when branch is null
+                let mut toml_repo = toml_repo.to_owned();
+                if toml_repo.branch.is_none() {
+                    toml_repo.branch = default_branch.to_owned();
+                }
+
+                let index = counter.inc();
+                let repo_info = RepoInfo::new(*id, index, &toml_repo);
+                let rel_path = repo_info.rel_path().display_path();
+
+                let progress = progress.clone();
+                progress.repo_start(&repo_info, "waiting...".into());
+
+                match inner_exec(path, &repo_info, depth.as_ref(), &default_branch, &progress) {
+                    Ok(Ok(msg)) => {
+                        progress.repo_end(&repo_info, msg);
+                        CloneResult::Cloned
+                    }
+                    Ok(Err(msg)) => {
+                        progress.repo_end(&repo_info, msg.clone());
+                        CloneResult::Skip(StyleMessage::git_skip(rel_path, msg))
+                    }
+                    Err(e) => {
+                        progress.repo_error(&repo_info, StyleMessage::new());
+                        CloneResult::Error(StyleMessage::git_error(rel_path, &e))
+                    }
+                }
+            })
+            .collect();
+
+        progress.repos_end();
+        res
+    });
+
+    let mut skip_status = StyleMessage::new();
+    let mut errors = Vec::new();
+    for result in results {
+        match result {
+            CloneResult::Cloned => {}
+            CloneResult::Skip(msg) => skip_status = skip_status.join(format!("  {}\n", msg).into()),
+            CloneResult::Error(msg) => errors.push(msg),
+        }
+    }
+    // repos already present are reported but not counted as failures
+    if !skip_status.is_empty() {
+        skip_status = StyleMessage::from("Skip status:\n").join(skip_status);
+    }
+
+    match errors.len() {
+        0 => Ok(StyleMessage::ops_success("clone").join(skip_status)),
+        _ => {
+            let msg = StyleMessage::ops_failed("clone", errors.len()).join(skip_status);
+            Err(anyhow!(MgitError::OpsError {
+                prefix: msg,
+                errors: OpsErrors(errors),
+            }))
+        }
+    }
+}
+
+/// clone the repo, the inner error is the reason it was skipped
+fn inner_exec(
+    input_path: &Path,
+    repo_info: &RepoInfo,
+    depth: Option<&usize>,
+    default_branch: &Option<String>,
+    progress: &impl Progress,
+) -> anyhow::Result<Result<StyleMessage, StyleMessage>> {
+    let full_path = input_path.join(repo_info.rel_path());
+
+    if git::is_repository(&full_path) != Err(RepositoryError::NotFound) {
+        return Ok(Err(StyleMessage::git_repo_present()));
+    }
+    // files of a directory which isn't a repo yet are never overwritten
+    if full_path
+        .read_dir()
+        .is_ok_and(|mut entries| entries.next().is_some())
+    {
+        return Ok(Err(StyleMessage::git_dir_not_empty()));
+    }
+
+    std::fs::create_dir_all(&full_path)
+        .with_context(|| format!("create dir {} failed.", full_path.display()))?;
+    progress.repo_info(repo_info, "initialize...".into());
+    git::init(&full_path)?;
+
+    // the directory was empty, so a failed clone leaves nothing but its `.git` behind
+    let res = exec_clone(input_path, &full_path, repo_info, depth, progress);
+    if res.is_err() {
+        let _ = std::fs::remove_dir_all(full_path.join(".git"));
+    }
+    res?;
+
+    // track the remote branch like sync, a pinned commit or tag has nothing to track
+    let _ = set_tracking_remote_branch(input_path, repo_info.toml_repo, default_branch);
+    Ok(Ok(StyleMessage::git_cloned()))
+}
+
+/// add the remotes, fetch and check out the remote ref in the freshly initialized repo
+fn exec_clone(
+    input_path: &Path,
+    full_path: &Path,
+    repo_info: &RepoInfo,
+    depth: Option<&usize>,
+    progress: &impl Progress,
+) -> anyhow::Result<()> {
+    let toml_repo = repo_info.toml_repo;
+    toml_repo.update_remotes(full_path)?;
+    exec_fetch(input_path, repo_info, depth, false, true, progress)?;
+
+    // priority: commit/tag/branch(default-branch)
+    let remote_ref = toml_repo.get_remote_ref(full_path)?;
+    let branch = checkout_branch_name(toml_repo, &remote_ref);
+    progress.repo_info(repo_info, StyleMessage::git_checking_out(&branch));
+    let args = checkout_args(&branch, &remote_ref.to_string(), false, false);
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    git::checkout(full_path, &args)?;
+
+    if let Some(dirs) = toml_repo.sparse.as_ref() {
+        git::sparse_checkout_set(full_path, dirs, false)?;
+    }
+    // flatten subdir after sparse-checkout, which would restore the full HEAD tree
+    if let Some(subdir) = toml_repo.subdir.as_ref() {
+        git::read_subtree(full_path, "HEAD", subdir)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use anyhow::anyhow;
+use std::path::{Path, PathBuf};
+
+use crate::core::repos::{ConfigFormat, TomlConfig, WorkspacePaths};
+use crate::utils::error::{MgitError, MgitResult};
+use crate::utils::style_message::StyleMessage;
+
+pub struct ConvertOptions {
+    pub config_path: PathBuf,
+    /// format to write, `Auto` writes toml
+    pub to: ConfigFormat,
+    /// file to write, the converted config is only returned without it
+    pub out: Option<PathBuf>,
+}
+
+impl ConvertOptions {
+    pub fn new(config_path: Option<impl AsRef<Path>>, to: ConfigFormat) -> Self {
+        let WorkspacePaths { config_path, .. } =
+            WorkspacePaths::resolve(None::<&Path>, config_path);
+        Self {
+            config_path,
+            to,
+            out: None,
+        }
+    }
+}
+
+/// write the config file in another format, failing when reading it back loses anything
+pub fn convert_config(options: ConvertOptions) -> MgitResult<String> {
+    let config_path = &options.config_path;
+
+    // check if .gitrepos exists
+    if !config_path.is_file() {
+        return Err(anyhow!(MgitError::ConfigFileNotFound(
+            StyleMessage::config_file_not_found()
+        )));
+    }
+
+    // load config file in any format, like `--config-format` says
+    let Some(toml_config) = TomlConfig::load(config_path) else {
+        return Err(anyhow!(MgitError::LoadConfigFailed));
+    };
+
+    let content = toml_config
+        .serialize_as(options.to)
+        .map_err(|e| anyhow!("can't write the config as {:?}: {}", options.to, e))?;
+
+    // every field has to come back the same from the new format
+    if TomlConfig::parse(&content, options.to).as_ref() != Some(&toml_config) {
+        return Err(anyhow!(
+            "can't write the config as {:?}: some fields don't read back the same",
+            options.to
+        ));
+    }
+
+    if let Some(out) = options.out.as_ref() {
+        std::fs::write(out, &content)
+            .map_err(|e| anyhow!("failed to write {}: {}", out.display(), e))?;
+    }
+    Ok(content)
+}
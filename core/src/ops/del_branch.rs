@@ -1,19 +1,24 @@
 use anyhow::anyhow;
-use std::env;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use crate::core::git;
-use crate::core::repos::TomlConfig;
+use crate::core::repos::{TomlConfig, WorkspacePaths};
 use crate::utils::error::{MgitError, MgitResult, OpsErrors};
-use crate::utils::logger;
 use crate::utils::path::PathExtension;
 use crate::utils::StyleMessage;
+use crate::utils::{fd_limit, logger};
 
 pub struct DelBranchOptions {
     pub path: PathBuf,
     pub config_path: PathBuf,
     pub branch: String,
     pub ignore: Option<Vec<String>>,
+    pub thread_count: usize,
+    /// result of `find_remote_branch` the user agreed to, its repos are deleted without
+    /// checking again and its errors are reported with the ones of the deletion
+    pub confirmed: Option<RemoteBranchScan>,
 }
 
 impl DelBranchOptions {
@@ -23,24 +28,120 @@ impl DelBranchOptions {
         branch: String,
         ignore: Option<Vec<String>>,
     ) -> Self {
-        let path = path.map_or(env::current_dir().unwrap(), |p| p.as_ref().to_path_buf());
-        let config_path = config_path.map_or(path.join(".gitrepos"), |p| p.as_ref().to_path_buf());
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
         Self {
             path,
             config_path,
             branch,
             ignore,
+            thread_count: 4,
+            confirmed: None,
         }
     }
 }
 
+/// repos whose remote has the branch, and the ones which couldn't be checked
+#[derive(Debug, Clone, Default)]
+pub struct RemoteBranchScan {
+    pub found: Vec<String>,
+    pub errors: Vec<StyleMessage>,
+}
+
+/// check every repo of the config file for the branch on its remote, without deleting anything
+pub fn find_remote_branch(options: &DelBranchOptions) -> MgitResult<RemoteBranchScan> {
+    let path = &options.path;
+    let branch = &options.branch;
+    let repos = branch_repos(options)?;
+
+    let thread_pool = thread_pool(options.thread_count)?;
+    let results = thread_pool.install(|| {
+        repos
+            .into_par_iter()
+            .map(|(rel_path, remote_name)| {
+                let full_path = path.join(&rel_path);
+                let res = git::check_remote_branch_exist(full_path, &remote_name, branch);
+                (rel_path, res)
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let mut scan = RemoteBranchScan::default();
+    for (rel_path, res) in results {
+        match res {
+            Ok(true) => scan.found.push(rel_path.display_path()),
+            Ok(false) => {}
+            Err(e) => scan.errors.push(StyleMessage::git_error(&rel_path, &e)),
+        }
+    }
+    Ok(scan)
+}
+
 pub fn del_remote_branch(options: DelBranchOptions) -> MgitResult<StyleMessage> {
     let path = &options.path;
-    let config_path = &options.config_path;
-    let branch = options.branch;
-    let mut ignore = options.ignore.unwrap_or_default();
+    let branch = &options.branch;
+
+    let scan = match options.confirmed.clone() {
+        Some(scan) => scan,
+        None => find_remote_branch(&options)?,
+    };
+    let remote_names: HashMap<String, String> = branch_repos(&options)?
+        .into_iter()
+        .map(|(rel_path, remote_name)| (rel_path.display_path(), remote_name))
+        .collect();
 
     logger::info("Delete remote branch:");
+    let thread_pool = thread_pool(options.thread_count)?;
+    let results = thread_pool.install(|| {
+        scan.found
+            .into_par_iter()
+            .map(|rel_path| {
+                let full_path = path.join(&rel_path);
+                let remote_name = remote_names
+                    .get(&rel_path)
+                    .map_or("origin", |name| name.as_str());
+                let res = git::del_remote_branch(full_path, remote_name, branch);
+                (rel_path, remote_name, res)
+            })
+            .collect::<Vec<_>>()
+    });
+
+    // deletions are reported apart from the failures
+    let mut deleted = StyleMessage::new();
+    let mut errors = scan.errors;
+    for (rel_path, remote_name, res) in results {
+        match res {
+            Ok(_) => {
+                let msg =
+                    StyleMessage::git_del_branch(&rel_path, format!("{}/{}", remote_name, branch));
+                deleted = deleted.join(format!("  {}\n", msg).into());
+            }
+            Err(e) => errors.push(StyleMessage::git_error(&rel_path, &e)),
+        }
+    }
+    if !deleted.is_empty() {
+        deleted = StyleMessage::from("Deleted:\n").join(deleted);
+    }
+
+    if !errors.is_empty() {
+        let msg = StyleMessage::ops_failed("del-remote-branch", errors.len()).join(deleted);
+        let e = anyhow!(MgitError::OpsError {
+            prefix: msg,
+            errors: OpsErrors(errors),
+        });
+
+        return Err(e);
+    }
+
+    let msg = StyleMessage::ops_success("del-remote-branch").join(deleted);
+    Ok(msg)
+}
+
+/// local paths and remote names of the repos to look for the branch in, the ones with a manifest branch
+fn branch_repos(options: &DelBranchOptions) -> MgitResult<Vec<(String, String)>> {
+    let path = &options.path;
+    let config_path = &options.config_path;
+    let mut ignore = options.ignore.clone().unwrap_or_default();
+
     // if directory doesn't exist, finsh clean
     if !path.is_dir() {
         return Err(anyhow!(MgitError::DirNotFound(
@@ -56,71 +157,34 @@ pub fn del_remote_branch(options: DelBranchOptions) -> MgitResult<StyleMessage>
     }
 
     // load config file(like .gitrepos)
-    let Some(mut toml_config) = TomlConfig::load(config_path) else {
+    let Some(toml_config) = TomlConfig::load(config_path) else {
         return Err(anyhow!(MgitError::LoadConfigFailed));
     };
 
-    let Some(toml_repos) = toml_config.repos.as_mut() else {
-        return Ok("No repos to delete remote branch".into());
-    };
-
     if ignore.contains(&".".to_string()) {
         ignore.push("".to_string());
     }
 
-    let mut errors = Vec::new();
-    for toml_repo in toml_repos.iter_mut() {
-        let Some(local) = toml_repo.local.as_ref() else {
-            continue;
-        };
-
+    let repos = toml_config
+        .repos
+        .unwrap_or_default()
+        .into_iter()
         // only support new branch from exsit branch
-        if toml_repo.branch.is_none() {
-            continue;
-        }
-
-        if ignore.contains(local) {
-            continue;
-        }
-
-        let rel_path = toml_repo.local.as_ref().unwrap();
-        let full_path = Path::new(path).join(rel_path);
-
-        match git::check_remote_branch_exist(&full_path, &branch) {
-            Err(e) => {
-                let error = StyleMessage::git_error(rel_path, &e);
-                errors.push(error);
-                continue;
-            }
-
-            Ok(false) => {
-                continue;
-            }
-
-            Ok(true) => {}
-        }
-
-        if let Err(e) = git::del_remote_branch(full_path, &branch) {
-            let error = StyleMessage::git_error(rel_path, &e);
-            errors.push(error);
-            continue;
-        }
-
-        let rel_path_display = Path::new(rel_path).display_path();
-        let msg = StyleMessage::git_del_branch(rel_path_display, &format!("origin/{}", branch));
-        logger::info(msg);
-    }
-
-    if !errors.is_empty() {
-        let msg = StyleMessage::ops_failed("del-remote-branch", errors.len());
-        let e = anyhow!(MgitError::OpsError {
-            prefix: msg,
-            errors: OpsErrors(errors),
-        });
-
-        return Err(e);
-    }
+        .filter(|toml_repo| toml_repo.branch.is_some())
+        .filter_map(|toml_repo| {
+            let remote_name = toml_repo.origin_name().to_string();
+            toml_repo.local.map(|local| (local, remote_name))
+        })
+        .filter(|(local, _)| !ignore.contains(local))
+        .collect();
+    Ok(repos)
+}
 
-    let msg = StyleMessage::ops_success("del-remote-branch");
-    Ok(msg)
+fn thread_pool(thread_count: usize) -> MgitResult<rayon::ThreadPool> {
+    let thread_builder =
+        rayon::ThreadPoolBuilder::new().num_threads(fd_limit::cap_thread_count(thread_count));
+    let Ok(thread_pool) = thread_builder.build() else {
+        return Err(anyhow!(MgitError::CreateThreadPoolFailed));
+    };
+    Ok(thread_pool)
 }
@@ -0,0 +1,147 @@
+use anyhow::{anyhow, Context};
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::core::git;
+use crate::core::repos::{TomlConfig, WorkspacePaths};
+use crate::utils::error::{MgitError, MgitResult};
+use crate::utils::path::PathExtension;
+use crate::utils::style_message::StyleMessage;
+
+pub struct DiffOptions {
+    pub path: PathBuf,
+    pub config_path: PathBuf,
+    /// diff the index instead of the work tree
+    pub staged: bool,
+}
+
+impl DiffOptions {
+    pub fn new(path: Option<impl AsRef<Path>>, config_path: Option<impl AsRef<Path>>) -> Self {
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
+        Self {
+            path,
+            config_path,
+            staged: false,
+        }
+    }
+}
+
+/// `git diff --stat` of a repo with changes
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RepoDiff {
+    pub local: String,
+    /// the stat as git prints it, the summary line included
+    pub stat: String,
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// diff stats of all repos with changes, and their totals
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DiffStat {
+    pub repos: Vec<RepoDiff>,
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// a section per repo, then the totals
+impl fmt::Display for DiffStat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.repos.is_empty() {
+            return writeln!(f, "No changes");
+        }
+
+        for repo in &self.repos {
+            writeln!(f, "{}:", repo.local)?;
+            writeln!(f, "{}", repo.stat.trim_end())?;
+            writeln!(f)?;
+        }
+        writeln!(
+            f,
+            "Total: {} files changed, {} insertions(+), {} deletions(-) in {} repos",
+            self.files_changed,
+            self.insertions,
+            self.deletions,
+            self.repos.len()
+        )
+    }
+}
+
+/// combined `git diff --stat` of the repos, repos without changes or not cloned yet are left out
+pub fn diff_repos(options: DiffOptions) -> MgitResult<DiffStat> {
+    let path = &options.path;
+    let config_path = &options.config_path;
+
+    // if directory doesn't exist, return
+    if !path.is_dir() {
+        return Err(anyhow!(MgitError::DirNotFound(
+            StyleMessage::dir_not_found(path)
+        )));
+    }
+
+    // check if .gitrepos exists
+    if !config_path.is_file() {
+        return Err(anyhow!(MgitError::ConfigFileNotFound(
+            StyleMessage::config_file_not_found()
+        )));
+    }
+
+    // load config file(like .gitrepos)
+    let Some(toml_config) = TomlConfig::load(config_path) else {
+        return Err(anyhow!(MgitError::LoadConfigFailed));
+    };
+
+    let mut diff_stat = DiffStat::default();
+    for toml_repo in toml_config.repos.iter().flatten() {
+        let Some(local) = toml_repo.local.as_ref() else {
+            continue;
+        };
+        let full_path = path.join(local);
+        if git::is_repository(&full_path).is_err() {
+            continue;
+        }
+
+        let stat = git::diff_stat(&full_path, options.staged)
+            .with_context(|| format!("diff {} failed.", local.display_path()))?;
+        if stat.trim().is_empty() {
+            continue;
+        }
+
+        let (files_changed, insertions, deletions) = parse_summary(&stat);
+        diff_stat.files_changed += files_changed;
+        diff_stat.insertions += insertions;
+        diff_stat.deletions += deletions;
+        diff_stat.repos.push(RepoDiff {
+            local: local.display_path(),
+            stat,
+            files_changed,
+            insertions,
+            deletions,
+        });
+    }
+    Ok(diff_stat)
+}
+
+/// counts of the last line, like ` 2 files changed, 3 insertions(+), 1 deletion(-)`
+fn parse_summary(stat: &str) -> (usize, usize, usize) {
+    let (mut files_changed, mut insertions, mut deletions) = (0, 0, 0);
+    let summary = stat.trim_end().lines().last().unwrap_or_default();
+    for part in summary.split(',') {
+        let Some((count, kind)) = part.trim().split_once(' ') else {
+            continue;
+        };
+        let Ok(count) = count.parse::<usize>() else {
+            continue;
+        };
+        match kind {
+            _ if kind.starts_with("file") => files_changed = count,
+            _ if kind.starts_with("insertion") => insertions = count,
+            _ if kind.starts_with("deletion") => deletions = count,
+            _ => {}
+        }
+    }
+    (files_changed, insertions, deletions)
+}
@@ -2,22 +2,20 @@ use atomic_counter::{AtomicCounter, RelaxedCounter};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 use anyhow::anyhow;
-use std::env;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::time::Duration;
 
 use crate::core::git;
 use crate::core::git::RemoteRef;
-use crate::core::repo::{cmp_local_remote, repos_to_map_with_ignore};
-use crate::core::repos::TomlConfig;
+use crate::core::repo::{cmp_local_remote, include_groups, repos_to_map_with_ignore, TomlRepo};
+use crate::core::repos::{TomlConfig, WorkspacePaths};
 
 use crate::utils::cmd::retry;
 use crate::utils::error::{MgitError, MgitResult, OpsErrors};
 use crate::utils::path::PathExtension;
 use crate::utils::progress::{Progress, RepoInfo};
 use crate::utils::style_message::StyleMessage;
-use crate::utils::{cmd, logger};
+use crate::utils::{cmd, fd_limit, logger};
 
 pub struct FetchOptions {
     pub path: PathBuf,
@@ -26,8 +24,18 @@ pub struct FetchOptions {
     pub silent: bool,
     pub depth: Option<usize>,
     pub ignore: Option<Vec<String>>,
+    /// only fetch repos in any of these groups
+    pub groups: Option<Vec<String>>,
+    /// skip most of the negotiation on large histories, see `FAST_FETCH_CONFIG`
+    pub fast_fetch: bool,
+    /// remove remote-tracking branches whose upstream branch was deleted, on by default
+    pub prune: bool,
 }
 
+/// config overrides of `--fast-fetch`: the skipping negotiator sends far fewer
+/// "have" lines on long histories, and protocol v2 lets the server filter refs
+const FAST_FETCH_CONFIG: [&str; 2] = ["fetch.negotiationAlgorithm=skipping", "protocol.version=2"];
+
 impl FetchOptions {
     pub fn new(
         path: Option<impl AsRef<Path>>,
@@ -37,8 +45,7 @@ impl FetchOptions {
         depth: Option<usize>,
         ignore: Option<Vec<String>>,
     ) -> Self {
-        let path = path.map_or(env::current_dir().unwrap(), |p| p.as_ref().to_path_buf());
-        let config_path = config_path.map_or(path.join(".gitrepos"), |p| p.as_ref().to_path_buf());
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
         Self {
             path,
             config_path,
@@ -46,6 +53,9 @@ impl FetchOptions {
             silent: silent.unwrap_or(false),
             depth,
             ignore,
+            groups: None,
+            fast_fetch: false,
+            prune: true,
         }
     }
 }
@@ -57,6 +67,8 @@ pub fn fetch_repos(options: FetchOptions, progress: impl Progress) -> MgitResult
     let silent = options.silent;
     let depth = options.depth.as_ref().copied();
     let ignore = options.ignore.as_ref();
+    let fast_fetch = options.fast_fetch;
+    let prune = options.prune;
 
     // start fetching repos
     logger::info(StyleMessage::ops_start("fetch repos", path));
@@ -77,7 +89,8 @@ pub fn fetch_repos(options: FetchOptions, progress: impl Progress) -> MgitResult
     };
     let default_branch = toml_config.default_branch;
 
-    // retain repos exclude ignore repositories
+    // retain repos of the groups, exclude ignore repositories
+    let toml_repos = include_groups(toml_repos, options.groups.as_ref());
     let repos_map = repos_to_map_with_ignore(toml_repos, ignore);
 
     progress.repos_start(repos_map.len());
@@ -86,7 +99,8 @@ pub fn fetch_repos(options: FetchOptions, progress: impl Progress) -> MgitResult
     let counter = RelaxedCounter::new(1);
 
     // create thread pool, and set the number of thread to use by using `.num_threads(count)`
-    let thread_builder = rayon::ThreadPoolBuilder::new().num_threads(thread_count);
+    let thread_builder =
+        rayon::ThreadPoolBuilder::new().num_threads(fd_limit::cap_thread_count(thread_count));
     let Ok(thread_pool) = thread_builder.build() else {
         return Err(anyhow!(MgitError::CreateThreadPoolFailed));
     };
@@ -105,7 +119,14 @@ pub fn fetch_repos(options: FetchOptions, progress: impl Progress) -> MgitResult
                 progress.repo_start(&repo_info, "waiting...".into());
 
                 // execute fetch command with progress
-                let exec_res = inner_exec(path, &repo_info, depth.as_ref(), &progress);
+                let exec_res = inner_exec(
+                    path,
+                    &repo_info,
+                    depth.as_ref(),
+                    fast_fetch,
+                    prune,
+                    &progress,
+                );
 
                 // handle result
                 match exec_res {
@@ -151,54 +172,95 @@ fn inner_exec(
     input_path: impl AsRef<Path>,
     repo_info: &RepoInfo,
     depth: Option<&usize>,
+    fast_fetch: bool,
+    prune: bool,
     progress: &impl Progress,
 ) -> anyhow::Result<()> {
     let full_path = input_path.as_ref().join(repo_info.rel_path());
-    let remote_url = repo_info.toml_repo.remote.as_ref().unwrap();
 
-    git::update_remote_url(full_path, remote_url)?;
-    exec_fetch(input_path, repo_info, depth, progress)
+    repo_info.toml_repo.update_remotes(full_path)?;
+    exec_fetch(input_path, repo_info, depth, fast_fetch, prune, progress)
 }
 
-pub fn exec_fetch(
-    input_path: impl AsRef<Path>,
-    repo_info: &RepoInfo,
-    depth: Option<&usize>,
-    progress: &impl Progress,
-) -> anyhow::Result<()> {
-    let full_path = input_path.as_ref().join(repo_info.rel_path());
+/// depth of `git fetch` in the repository, a clone with full history is never made shallow
+pub(crate) fn shallow_depth<'a>(
+    path: &Path,
+    toml_repo: &'a TomlRepo,
+    depth: Option<&'a usize>,
+) -> Option<&'a usize> {
+    let depth = toml_repo.fetch_depth(depth)?;
+    let full_history =
+        git::get_current_commit(path).is_ok() && !git::is_shallow(path).unwrap_or(true);
+    (!full_history).then_some(depth)
+}
 
-    // get remote name from url
-    let remote_name = repo_info.toml_repo.get_remote_name(full_path.as_path())?;
-    let mut args = vec!["fetch", &remote_name];
+/// arguments of `git fetch` for the repository, with `prune` stale remote-tracking
+/// branches are removed
+pub(crate) fn fetch_args(
+    remote_name: &str,
+    toml_repo: &TomlRepo,
+    depth: Option<&usize>,
+    fast_fetch: bool,
+    prune: bool,
+) -> anyhow::Result<Vec<String>> {
+    // `-c` overrides must come before the subcommand
+    let mut args = toml_repo.config_args();
+    if fast_fetch {
+        for config in FAST_FETCH_CONFIG {
+            args.push("-c".to_string());
+            args.push(config.to_string());
+        }
+    }
+    args.push("fetch".to_string());
+    args.push(remote_name.to_string());
 
     if let Some(depth) = depth {
         // priority: commit/tag/branch(default-branch)
-        let remote_ref = repo_info.toml_repo.get_remote_ref(full_path.as_path())?;
+        let remote_ref = toml_repo.get_remote_ref_by_name(remote_name)?;
         match remote_ref {
             RemoteRef::Commit(commit) => {
-                args.push(Box::leak(commit.into_boxed_str()));
+                args.push(commit);
             }
             RemoteRef::Tag(tag) => {
-                args.push("tag");
-                args.push(Box::leak(tag.into_boxed_str()));
-                args.push("--no-tags");
+                args.push("tag".to_string());
+                args.push(tag);
+                args.push("--no-tags".to_string());
             }
             RemoteRef::Branch(_) => {
-                let branch = repo_info.toml_repo.branch.as_ref().expect("invalid-branch");
-                args.push(branch);
+                let branch = toml_repo.branch.as_ref().expect("invalid-branch");
+                args.push(branch.to_string());
             }
         };
 
-        args.push("--depth");
-        args.push(Box::leak(depth.to_string().into_boxed_str()));
+        args.push("--depth".to_string());
+        args.push(depth.to_string());
     }
 
-    args.push("--prune");
-    args.push("--recurse-submodules=on-demand");
-    args.push("--progress");
+    if prune {
+        args.push("--prune".to_string());
+    }
+    args.push("--recurse-submodules=on-demand".to_string());
+    args.push("--progress".to_string());
+    Ok(args)
+}
+
+pub fn exec_fetch(
+    input_path: impl AsRef<Path>,
+    repo_info: &RepoInfo,
+    depth: Option<&usize>,
+    fast_fetch: bool,
+    prune: bool,
+    progress: &impl Progress,
+) -> anyhow::Result<()> {
+    let full_path = input_path.as_ref().join(repo_info.rel_path());
+
+    // get remote name from url
+    let remote_name = repo_info.toml_repo.get_remote_name(full_path.as_path())?;
+    let depth = shallow_depth(&full_path, repo_info.toml_repo, depth);
+    let args = fetch_args(&remote_name, repo_info.toml_repo, depth, fast_fetch, prune)?;
 
-    retry(10, Duration::from_millis(400), || {
+    let (times, delay) = repo_info.toml_repo.retry_policy();
+    retry(times, delay, || {
         let args = args.clone();
         let full_path = full_path.clone();
         let mut command = Command::new("git");
@@ -0,0 +1,176 @@
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use anyhow::anyhow;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::core::repo::repos_to_map_with_ignore;
+use crate::core::repos::{TomlConfig, WorkspacePaths};
+
+use crate::utils::cmd;
+use crate::utils::error::{MgitError, MgitResult, OpsErrors};
+use crate::utils::path::PathExtension;
+use crate::utils::style_message::StyleMessage;
+use crate::utils::{fd_limit, logger};
+
+pub struct ForeachOptions {
+    pub path: PathBuf,
+    pub config_path: PathBuf,
+    /// shell command run in the directory of every repo
+    pub command: String,
+    pub thread_count: usize,
+    pub ignore: Option<Vec<String>>,
+    /// start the command in the remaining repos after it failed in one
+    pub continue_on_error: bool,
+}
+
+impl ForeachOptions {
+    pub fn new(
+        path: Option<impl AsRef<Path>>,
+        config_path: Option<impl AsRef<Path>>,
+        command: String,
+        thread: Option<usize>,
+        ignore: Option<Vec<String>>,
+    ) -> Self {
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
+        Self {
+            path,
+            config_path,
+            command,
+            thread_count: thread.unwrap_or(4),
+            ignore,
+            continue_on_error: false,
+        }
+    }
+}
+
+/// how the command ended in a repo
+enum ForeachResult {
+    Exited(Option<i32>),
+    /// why the command wasn't started
+    NotRun(&'static str),
+    Error(anyhow::Error),
+}
+
+/// run a shell command in every repo, its output prefixed with the local path of the repo.
+///
+/// the command gets `MGIT_REPO_LOCAL` and `MGIT_REPO_REMOTE` of the repo in its environment.
+pub fn foreach_repo(options: ForeachOptions) -> MgitResult {
+    let path = &options.path;
+    let config_path = &options.config_path;
+    let command = options.command.as_str();
+    let thread_count = options.thread_count;
+    let ignore = options.ignore.as_ref();
+    let continue_on_error = options.continue_on_error;
+
+    // check if .gitrepos exists
+    if !config_path.is_file() {
+        return Err(anyhow!(MgitError::ConfigFileNotFound(
+            StyleMessage::config_file_not_found(),
+        )));
+    }
+    // load config file(like .gitrepos)
+    let Some(toml_config) = TomlConfig::load(config_path) else {
+        return Err(anyhow!(MgitError::LoadConfigFailed));
+    };
+
+    let Some(toml_repos) = toml_config.repos else {
+        return Ok("No repos to run command in".into());
+    };
+
+    // retain repos exclude ignore repositories
+    let repos_map = repos_to_map_with_ignore(toml_repos, ignore);
+
+    let thread_builder =
+        rayon::ThreadPoolBuilder::new().num_threads(fd_limit::cap_thread_count(thread_count));
+    let Ok(thread_pool) = thread_builder.build() else {
+        return Err(anyhow!(MgitError::CreateThreadPoolFailed));
+    };
+
+    // repos not started yet are left alone once the command failed somewhere
+    let failed = AtomicBool::new(false);
+    let results: Vec<_> = thread_pool.install(|| {
+        repos_map
+            .values()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|toml_repo| {
+                let local = toml_repo.local.clone().unwrap_or_default();
+                let rel_path = local.display_path();
+                let full_path = path.join(&local);
+
+                if !continue_on_error && failed.load(Ordering::Relaxed) {
+                    return (rel_path, ForeachResult::NotRun("not run"));
+                }
+                if !full_path.is_dir() {
+                    return (rel_path, ForeachResult::NotRun("missing"));
+                }
+
+                let remote = toml_repo.remote_url().unwrap_or_default();
+                let envs = [
+                    ("MGIT_REPO_LOCAL", local.as_str()),
+                    ("MGIT_REPO_REMOTE", remote.as_str()),
+                ];
+                let result = match cmd::exec_shell(&full_path, command, &envs) {
+                    Ok(output) => {
+                        // one message per repo, so parallel output isn't interleaved line by line
+                        let mut lines = output.stdout.lines().chain(output.stderr.lines());
+                        if let Some(first) = lines.next() {
+                            let msg = lines.fold(
+                                StyleMessage::cmd_output(&rel_path, first),
+                                |msg, line| {
+                                    msg.plain_text("\n")
+                                        .join(StyleMessage::cmd_output(&rel_path, line))
+                                },
+                            );
+                            logger::info(msg);
+                        }
+                        ForeachResult::Exited(output.code)
+                    }
+                    Err(e) => ForeachResult::Error(e),
+                };
+                if !matches!(result, ForeachResult::Exited(Some(0))) {
+                    failed.store(true, Ordering::Relaxed);
+                }
+                (rel_path, result)
+            })
+            .collect()
+    });
+
+    // repos by how the command ended, for the summary
+    let mut exit_codes: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut errors = Vec::new();
+    for (rel_path, result) in results {
+        let code = match result {
+            ForeachResult::Exited(Some(0)) => "0".to_string(),
+            ForeachResult::Exited(code) => {
+                let code = code.map_or("signal".to_string(), |code| code.to_string());
+                let e = anyhow!("command exited with code {}", code);
+                errors.push(StyleMessage::git_error(&rel_path, &e));
+                code
+            }
+            ForeachResult::NotRun(reason) => reason.to_string(),
+            ForeachResult::Error(e) => {
+                errors.push(StyleMessage::git_error(&rel_path, &e));
+                "error".to_string()
+            }
+        };
+        exit_codes.entry(code).or_default().push(rel_path);
+    }
+    for rel_paths in exit_codes.values_mut() {
+        rel_paths.sort();
+    }
+    let summary = StyleMessage::foreach_exit_codes(&exit_codes);
+
+    match errors.len() {
+        0 => Ok(StyleMessage::ops_success("foreach").join(summary)),
+        _ => {
+            let msg = StyleMessage::ops_failed("foreach", errors.len()).join(summary);
+            Err(anyhow!(MgitError::OpsError {
+                prefix: msg,
+                errors: OpsErrors(errors),
+            }))
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use anyhow::{anyhow, Context};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::git;
+use crate::core::repo::repos_to_map_with_ignore;
+use crate::core::repos::{TomlConfig, WorkspacePaths};
+use crate::utils::error::{MgitError, MgitResult, OpsErrors};
+use crate::utils::logger;
+use crate::utils::path::PathExtension;
+use crate::utils::StyleMessage;
+
+pub struct InstallHooksOptions {
+    pub path: PathBuf,
+    pub config_path: PathBuf,
+    /// directory of the hook scripts, each file is installed under its own name
+    pub source: PathBuf,
+    /// overwrite hooks already present in a repo
+    pub force: bool,
+    /// link to the scripts instead of copying them
+    pub symlink: bool,
+    /// report what would be installed without touching any repo
+    pub dry_run: bool,
+    pub ignore: Option<Vec<String>>,
+}
+
+impl InstallHooksOptions {
+    pub fn new(
+        path: Option<impl AsRef<Path>>,
+        config_path: Option<impl AsRef<Path>>,
+        source: impl AsRef<Path>,
+    ) -> Self {
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
+        Self {
+            path,
+            config_path,
+            source: source.as_ref().to_path_buf(),
+            force: false,
+            symlink: false,
+            dry_run: false,
+            ignore: None,
+        }
+    }
+}
+
+/// install the hook scripts of the source directory into every repo
+pub fn install_hooks(options: InstallHooksOptions) -> MgitResult {
+    let path = &options.path;
+    let config_path = &options.config_path;
+
+    logger::info(StyleMessage::ops_start("install hooks", path));
+
+    // if directory doesn't exist, return
+    if !path.is_dir() {
+        return Err(anyhow!(MgitError::DirNotFound(
+            StyleMessage::dir_not_found(path)
+        )));
+    }
+
+    // check if .gitrepos exists
+    if !config_path.is_file() {
+        return Err(anyhow!(MgitError::ConfigFileNotFound(
+            StyleMessage::config_file_not_found()
+        )));
+    }
+
+    // load config file(like .gitrepos)
+    let Some(toml_config) = TomlConfig::load(config_path) else {
+        return Err(anyhow!(MgitError::LoadConfigFailed));
+    };
+
+    let hooks = hook_scripts(&options.source)?;
+    if hooks.is_empty() {
+        return Err(anyhow!(
+            "no hook scripts found in {}",
+            options.source.display()
+        ));
+    }
+
+    let Some(toml_repos) = toml_config.repos else {
+        return Ok("No repos to install hooks".into());
+    };
+
+    let mut repos = repos_to_map_with_ignore(toml_repos, options.ignore.as_ref())
+        .into_iter()
+        .collect::<Vec<_>>();
+    repos.sort_by_key(|(id, _)| *id);
+
+    let mut errors = Vec::new();
+    for (_, toml_repo) in repos {
+        let rel_path = toml_repo.local.as_ref().unwrap().display_path();
+        let full_path = path.join(&rel_path);
+
+        match install_repo_hooks(&full_path, &hooks, &options) {
+            Ok((installed, skipped)) => logger::info(StyleMessage::git_hooks(
+                &rel_path,
+                &installed,
+                &skipped,
+                options.dry_run,
+            )),
+            Err(e) => errors.push(StyleMessage::git_error(&rel_path, &e)),
+        }
+    }
+
+    match errors.len() {
+        0 => Ok(StyleMessage::ops_success("install hooks")),
+        _ => {
+            let msg = StyleMessage::ops_failed("install hooks", errors.len());
+            Err(anyhow!(MgitError::OpsError {
+                prefix: msg,
+                errors: OpsErrors(errors),
+            }))
+        }
+    }
+}
+
+/// files of the source directory, sorted by name
+fn hook_scripts(source: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let entries = fs::read_dir(source)
+        .with_context(|| format!("failed to read hooks from {}", source.display()))?;
+
+    let mut hooks = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            hooks.push(entry.path());
+        }
+    }
+    hooks.sort();
+    Ok(hooks)
+}
+
+/// names of the installed and of the skipped hooks
+fn install_repo_hooks(
+    full_path: &Path,
+    hooks: &[PathBuf],
+    options: &InstallHooksOptions,
+) -> anyhow::Result<(Vec<String>, Vec<String>)> {
+    git::is_repository(full_path)?;
+    let hooks_dir = git::hooks_dir(full_path)?;
+
+    let mut installed = Vec::new();
+    let mut skipped = Vec::new();
+    for hook in hooks {
+        let name = hook.file_name().unwrap().to_string_lossy().to_string();
+        let target = hooks_dir.join(&name);
+
+        // a dangling symlink is still an existing hook
+        if target.symlink_metadata().is_ok() && !options.force {
+            skipped.push(name);
+            continue;
+        }
+
+        if !options.dry_run {
+            fs::create_dir_all(&hooks_dir)?;
+            if target.symlink_metadata().is_ok() {
+                fs::remove_file(&target)?;
+            }
+            match options.symlink {
+                true => symlink(&fs::canonicalize(hook)?, &target)?,
+                // copy keeps the executable bit of the script
+                false => fs::copy(hook, &target).map(|_| ())?,
+            }
+        }
+        installed.push(name);
+    }
+    Ok((installed, skipped))
+}
+
+#[cfg(unix)]
+fn symlink(original: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(windows)]
+fn symlink(original: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(original, link)
+}
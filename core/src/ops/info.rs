@@ -0,0 +1,84 @@
+use anyhow::anyhow;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::core::git;
+use crate::core::git::RepositoryError;
+use crate::core::repos::{TomlConfig, WorkspacePaths};
+use crate::utils::error::{MgitError, MgitResult};
+use crate::utils::style_message::StyleMessage;
+
+pub struct InfoOptions {
+    pub path: PathBuf,
+    pub config_path: PathBuf,
+}
+
+impl InfoOptions {
+    pub fn new(path: Option<impl AsRef<Path>>, config_path: Option<impl AsRef<Path>>) -> Self {
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
+        Self { path, config_path }
+    }
+}
+
+/// description of the config file and how much of it is on disk
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WorkspaceInfo {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub config_path: PathBuf,
+    pub default_branch: Option<String>,
+    pub repo_count: usize,
+    /// distinct remote urls of the repos
+    pub remote_count: usize,
+    /// repos cloned in the work directory
+    pub present: usize,
+    pub missing: usize,
+}
+
+/// summarize the config file, only checking whether repos exist on disk
+pub fn workspace_info(options: InfoOptions) -> MgitResult<WorkspaceInfo> {
+    let path = &options.path;
+    let config_path = &options.config_path;
+
+    // check if .gitrepos exists
+    if !config_path.is_file() {
+        return Err(anyhow!(MgitError::ConfigFileNotFound(
+            StyleMessage::config_file_not_found()
+        )));
+    }
+
+    // load config file(like .gitrepos)
+    let Some(toml_config) = TomlConfig::load(config_path) else {
+        return Err(anyhow!(MgitError::LoadConfigFailed));
+    };
+
+    let toml_repos = toml_config.repos.unwrap_or_default();
+    let remotes = toml_repos
+        .iter()
+        .filter_map(|toml_repo| toml_repo.remote.as_ref())
+        .collect::<HashSet<_>>();
+
+    let present = toml_repos
+        .iter()
+        .filter_map(|toml_repo| toml_repo.local.as_ref().map(|local| (toml_repo, local)))
+        .filter(|(toml_repo, local)| {
+            let full_path = path.join(local);
+            match toml_repo.mirror.unwrap_or(false) {
+                true => git::is_bare_repository(&full_path),
+                false => git::is_repository(&full_path) != Err(RepositoryError::NotFound),
+            }
+        })
+        .count();
+
+    Ok(WorkspaceInfo {
+        name: toml_config.name,
+        description: toml_config.description,
+        config_path: config_path.clone(),
+        default_branch: toml_config.default_branch,
+        repo_count: toml_repos.len(),
+        remote_count: remotes.len(),
+        present,
+        missing: toml_repos.len() - present,
+    })
+}
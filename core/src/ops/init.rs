@@ -1,5 +1,5 @@
+use crate::core::repos::WorkspacePaths;
 use crate::ops::snapshot::SnapshotType;
-use std::env;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -8,37 +8,47 @@ use crate::ops::SnapshotOptions;
 use crate::utils::error::MgitResult;
 
 use crate::utils::logger;
+use crate::utils::progress::Progress;
 use crate::utils::style_message::StyleMessage;
 
 pub struct InitOptions {
     pub path: PathBuf,
+    pub config_path: PathBuf,
     pub force: bool,
+    pub scan_hidden: bool,
+    /// threads reading the repos found
+    pub thread_count: usize,
 }
 
 impl InitOptions {
     pub fn new(path: Option<impl AsRef<Path>>, force: Option<bool>) -> Self {
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, None::<PathBuf>);
         Self {
-            path: path
-                .map(|p| PathBuf::from(p.as_ref()))
-                .map_or(env::current_dir().unwrap(), |p| p),
+            path,
+            config_path,
             force: force.unwrap_or(true),
+            scan_hidden: false,
+            thread_count: 4,
         }
     }
 }
 
-pub fn init_repo(options: InitOptions) -> MgitResult {
+pub fn init_repo(options: InitOptions, progress: impl Progress) -> MgitResult {
     let path = &options.path;
     let force = options.force;
     let snapshot_type = SnapshotType::Branch;
-    let config_file = path.join(".gitrepos");
+    let config_file = &options.config_path;
 
     logger::info(StyleMessage::ops_start("init", path));
 
-    snapshot_repo(SnapshotOptions::new(
+    let mut snapshot_options = SnapshotOptions::new(
         Some(path.to_path_buf()),
         Some(config_file),
         Some(force),
         Some(snapshot_type),
         None,
-    ))
+    );
+    snapshot_options.scan_hidden = options.scan_hidden;
+    snapshot_options.thread_count = options.thread_count;
+    snapshot_repo(snapshot_options, progress)
 }
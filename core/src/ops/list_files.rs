@@ -1,9 +1,12 @@
-use anyhow::anyhow;
+use anyhow::{anyhow, Context};
+use globset::{GlobBuilder, GlobMatcher};
+use serde::Serialize;
+use std::fmt;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 
 use crate::core::git;
-use crate::core::repos::TomlConfig;
-use crate::ops::CleanOptions;
+use crate::core::repos::{TomlConfig, WorkspacePaths};
 use crate::utils::error::{MgitError, MgitResult};
 use crate::utils::path::PathExtension;
 use crate::utils::style_message::StyleMessage;
@@ -11,19 +14,74 @@ use crate::utils::style_message::StyleMessage;
 pub struct ListFilesOptions {
     pub path: PathBuf,
     pub config_path: PathBuf,
+    /// only list files whose path matches the glob, like `**/*.rs`
+    pub glob: Option<String>,
 }
 
 impl ListFilesOptions {
     pub fn new(path: Option<impl AsRef<Path>>, config_path: Option<impl AsRef<Path>>) -> Self {
-        let clean_options = CleanOptions::new(path, config_path);
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
         Self {
-            path: clean_options.path,
-            config_path: clean_options.config_path,
+            path,
+            config_path,
+            glob: None,
         }
     }
 }
 
-pub fn list_files(options: ListFilesOptions) -> MgitResult<Vec<String>> {
+/// a file of the index of a repo
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ListedFile {
+    /// local path of the repo, as in the config file
+    pub local: String,
+    pub mode: String,
+    /// hash of the blob, or of the commit for a submodule
+    pub object: String,
+    /// merge stage, non-zero only for conflicted files
+    pub stage: u8,
+    /// path relative to the work directory, with `/` separators
+    pub path: String,
+}
+
+/// `mode object stage<TAB>path`, as `git ls-files -s` prints it
+impl fmt::Display for ListedFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}\t{}",
+            self.mode, self.object, self.stage, self.path
+        )
+    }
+}
+
+pub fn list_files(options: ListFilesOptions) -> MgitResult<Vec<ListedFile>> {
+    let mut files = Vec::new();
+    for_each_file(&options, |file| {
+        files.push(file);
+        Ok(())
+    })?;
+    Ok(files)
+}
+
+/// write files line by line as they are listed, return the number of lines written
+pub fn list_files_to_writer(
+    options: ListFilesOptions,
+    mut writer: impl Write,
+) -> MgitResult<usize> {
+    let mut count = 0;
+    for_each_file(&options, |file| {
+        writeln!(writer, "{}", file)?;
+        count += 1;
+        Ok(())
+    })?;
+    writer.flush()?;
+    Ok(count)
+}
+
+fn for_each_file(
+    options: &ListFilesOptions,
+    mut f: impl FnMut(ListedFile) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
     let path = &options.path;
     let config_path = &options.config_path;
 
@@ -47,38 +105,58 @@ pub fn list_files(options: ListFilesOptions) -> MgitResult<Vec<String>> {
     };
 
     let Some(toml_repos) = toml_config.repos else {
-        return Ok(vec![]);
+        return Ok(());
     };
 
-    let files = toml_repos
-        .iter()
-        .flat_map(|toml_repo| {
-            let rel_path = toml_repo.local.as_ref().unwrap();
-            let full_path = path.join(rel_path);
-            let Ok(content) = git::ls_files(full_path) else {
-                return vec![];
+    let matcher = match options.glob.as_ref() {
+        Some(glob) => Some(build_matcher(glob)?),
+        None => None,
+    };
+
+    for toml_repo in &toml_repos {
+        let rel_path = toml_repo.local.as_ref().unwrap();
+        let full_path = path.join(rel_path);
+        let Ok(content) = git::ls_files(full_path) else {
+            continue;
+        };
+
+        for line in content.trim().lines() {
+            let Some((left, right)) = line.rsplit_once('\t') else {
+                continue;
+            };
+            let mut fields = left.split_whitespace();
+            let (Some(mode), Some(object), Some(Ok(stage))) =
+                (fields.next(), fields.next(), fields.next().map(str::parse))
+            else {
+                continue;
             };
 
-            content
-                .trim()
-                .lines()
-                .flat_map(|line| {
-                    if let Some((left, right)) = line.rsplit_once('\t') {
-                        let split_str = match !rel_path.ends_with('\\') && !rel_path.ends_with('/')
-                        {
-                            true => "/",
-                            false => "",
-                        };
-
-                        let path = format!("{}{}{}", rel_path, split_str, right);
-                        let path = path.norm_path().trim_matches('/').to_string();
-                        Some(format!("{}\t{}", left, path))
-                    } else {
-                        None
-                    }
-                })
-                .collect::<Vec<String>>()
-        })
-        .collect::<Vec<String>>();
-    Ok(files)
+            let split_str = match !rel_path.ends_with('\\') && !rel_path.ends_with('/') {
+                true => "/",
+                false => "",
+            };
+
+            let path = format!("{}{}{}", rel_path, split_str, right);
+            let path = path.norm_path().trim_matches('/').to_string();
+            if matches!(&matcher, Some(matcher) if !matcher.is_match(&path)) {
+                continue;
+            }
+            f(ListedFile {
+                local: rel_path.norm_path(),
+                mode: mode.to_string(),
+                object: object.to_string(),
+                stage,
+                path,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+fn build_matcher(glob: &str) -> anyhow::Result<GlobMatcher> {
+    let glob = GlobBuilder::new(glob)
+        .literal_separator(true)
+        .build()
+        .with_context(|| format!("Invalid glob {}", glob))?;
+    Ok(glob.compile_matcher())
 }
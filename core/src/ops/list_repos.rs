@@ -0,0 +1,114 @@
+use anyhow::anyhow;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::core::repo::repos_to_map_with_ignore;
+use crate::core::repos::{TomlConfig, WorkspacePaths};
+use crate::utils::error::{MgitError, MgitResult};
+use crate::utils::path::PathExtension;
+use crate::utils::style_message::StyleMessage;
+
+pub struct ListReposOptions {
+    pub path: PathBuf,
+    pub config_path: PathBuf,
+    pub ignore: Option<Vec<String>>,
+    /// only list repos with these local paths
+    pub only: Option<Vec<String>>,
+    /// only list repos in any of these groups
+    pub groups: Option<Vec<String>>,
+}
+
+impl ListReposOptions {
+    pub fn new(path: Option<impl AsRef<Path>>, config_path: Option<impl AsRef<Path>>) -> Self {
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
+        Self {
+            path,
+            config_path,
+            ignore: None,
+            only: None,
+            groups: None,
+        }
+    }
+}
+
+/// a repo of the config file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RepoEntry {
+    pub local: String,
+    pub remote: Option<String>,
+    /// commit, tag or branch to sync to, with the default branch applied
+    pub remote_ref: Option<String>,
+    pub groups: Vec<String>,
+}
+
+/// list repos of the config file in order, without touching the repositories
+pub fn list_repos(options: ListReposOptions) -> MgitResult<Vec<RepoEntry>> {
+    let path = &options.path;
+    let config_path = &options.config_path;
+
+    // if directory doesn't exist, return
+    if !path.is_dir() {
+        return Err(anyhow!(MgitError::DirNotFound(
+            StyleMessage::dir_not_found(path)
+        )));
+    }
+
+    // check if .gitrepos exists
+    if !config_path.is_file() {
+        return Err(anyhow!(MgitError::ConfigFileNotFound(
+            StyleMessage::config_file_not_found()
+        )));
+    }
+
+    // load config file(like .gitrepos)
+    let Some(toml_config) = TomlConfig::load(config_path) else {
+        return Err(anyhow!(MgitError::LoadConfigFailed));
+    };
+
+    let Some(toml_repos) = toml_config.repos else {
+        return Ok(Vec::new());
+    };
+
+    let only = options.only.as_ref().map(|only| {
+        only.iter()
+            .map(|local| local.norm_path().display_path())
+            .collect::<HashSet<_>>()
+    });
+    let groups = options.groups.as_ref();
+
+    let mut repos = repos_to_map_with_ignore(toml_repos, options.ignore.as_ref())
+        .into_iter()
+        .collect::<Vec<_>>();
+    repos.sort_by_key(|(id, _)| *id);
+
+    let mut entries = Vec::new();
+    for (_, mut toml_repo) in repos {
+        let rel_path = toml_repo.local.as_ref().unwrap().display_path();
+        if matches!(&only, Some(only) if !only.contains(&rel_path)) {
+            continue;
+        }
+
+        if matches!(groups, Some(groups) if !toml_repo.in_groups(groups)) {
+            continue;
+        }
+        let repo_groups = toml_repo.groups.take().unwrap_or_default();
+
+        // use default branch when branch is null
+        if toml_repo.branch.is_none() {
+            toml_repo.branch = toml_config.default_branch.clone();
+        }
+        let remote_ref = toml_repo
+            .get_remote_ref_by_name(toml_repo.fetch_remote_name())
+            .ok()
+            .map(|remote_ref| remote_ref.to_string());
+
+        entries.push(RepoEntry {
+            local: rel_path,
+            remote: toml_repo.remote,
+            remote_ref,
+            groups: repo_groups,
+        });
+    }
+    Ok(entries)
+}
@@ -1,4 +1,3 @@
-use std::env;
 use std::fmt::{Display, Formatter};
 use std::path::{Path, PathBuf};
 
@@ -7,11 +6,11 @@ use rayon::iter::IntoParallelRefIterator;
 use rayon::iter::ParallelIterator;
 use serde::{Deserialize, Serialize};
 
-use crate::core::git::log_current;
-use crate::core::repos::TomlConfig;
+use crate::core::git::head_commit;
+use crate::core::repos::{TomlConfig, WorkspacePaths};
 use crate::utils::error::{MgitError, MgitResult};
 use crate::utils::path::PathExtension;
-use crate::utils::{logger, StyleMessage};
+use crate::utils::{fd_limit, logger, StyleMessage};
 
 pub struct LogReposOptions {
     pub path: PathBuf,
@@ -25,8 +24,7 @@ impl LogReposOptions {
         config_path: Option<impl AsRef<Path>>,
         thread_count: Option<usize>,
     ) -> Self {
-        let path = path.map_or(env::current_dir().unwrap(), |p| p.as_ref().to_path_buf());
-        let config_path = config_path.map_or(path.join(".gitrepos"), |p| p.as_ref().to_path_buf());
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
         Self {
             path,
             config_path,
@@ -93,7 +91,7 @@ pub fn log_repos(options: LogReposOptions) -> MgitResult<Vec<MgitResult<RepoLog>
     let toml_repos = toml_config.repos.unwrap_or_default();
 
     let thread_pool = rayon::ThreadPoolBuilder::new()
-        .num_threads(thread_count)
+        .num_threads(fd_limit::cap_thread_count(thread_count))
         .build()?;
     let repo_logs = thread_pool.install(|| {
         toml_repos
@@ -102,19 +100,14 @@ pub fn log_repos(options: LogReposOptions) -> MgitResult<Vec<MgitResult<RepoLog>
                 let local = toml_repo.local.as_ref().unwrap().to_string();
                 let remote = toml_repo.remote.as_ref().unwrap().to_string();
                 let rel_path = path.join(&local);
-                let log = log_current(rel_path)?;
-                let mut logs = log.trim_matches('"').split('\n');
-                let sha1 = logs.next().unwrap().trim_matches('"').to_string();
-                let author = logs.next().unwrap().trim_matches('"').to_string();
-                let date = logs.next().unwrap().trim_matches('"').to_string();
-                let log = logs.next().unwrap().trim_matches('"').to_string();
+                let commit = head_commit(rel_path)?;
                 let repo_log = RepoLog {
                     local: local.display_path(),
                     remote,
-                    sha1,
-                    author,
-                    date,
-                    log,
+                    sha1: commit.hash,
+                    author: format!("{} <{}>", commit.author, commit.email),
+                    date: commit.date,
+                    log: commit.subject,
                 };
                 Ok(repo_log)
             })
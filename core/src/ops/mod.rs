@@ -1,23 +1,59 @@
+pub use add::{add_repo, AddOptions};
+pub use checkout::{checkout_repos, CheckoutOptions};
 pub use clean::{clean_repo, CleanOptions};
-pub use del_branch::{del_remote_branch, DelBranchOptions};
+pub use clone::{clone_repos, CloneOptions};
+pub use convert::{convert_config, ConvertOptions};
+pub use del_branch::{del_remote_branch, find_remote_branch, DelBranchOptions, RemoteBranchScan};
+pub use diff::{diff_repos, DiffOptions, DiffStat, RepoDiff};
 pub use fetch::{exec_fetch, fetch_repos, FetchOptions};
+pub use foreach::{foreach_repo, ForeachOptions};
+pub use hooks::{install_hooks, InstallHooksOptions};
+pub use info::{workspace_info, InfoOptions, WorkspaceInfo};
 pub use init::{init_repo, InitOptions};
-pub use list_files::{list_files, ListFilesOptions};
+pub use list_files::{list_files, list_files_to_writer, ListFilesOptions, ListedFile};
+pub use list_repos::{list_repos, ListReposOptions, RepoEntry};
 pub use log_repos::*;
 pub use new_branch::{new_remote_branch, NewBranchOptions};
 pub use new_tag::{new_tag, NewTagOptions};
+pub use open::{open_repo, OpenOptions, RepoUrl};
+pub use pull::{pull_repos, PullOptions};
+pub use push::{push_repos, PushOptions};
+pub use remove::{remove_repos, RemoveOptions};
+pub use repair::{repair_repos, RepairOptions};
 pub use snapshot::{snapshot_repo, SnapshotOptions, SnapshotType};
-pub use sync::{sync_repo, SyncOptions};
+pub use status::{
+    status, status_files, status_files_to_writer, status_repos, RepoFilesStatus, StatusOptions,
+};
+pub use sync::{sync_repo, OnMissing, OnStashConflict, RepoPlan, SyncOptions, SyncPlan};
 pub use track::{set_tracking_remote_branch, track, TrackOptions};
+pub use validate::{validate, ValidateOptions};
+pub use verify_remote::{verify_remote, VerifyRemoteOptions};
 
+mod add;
+mod checkout;
 mod clean;
+mod clone;
+mod convert;
 mod del_branch;
+mod diff;
 mod fetch;
+mod foreach;
+mod hooks;
+mod info;
 mod init;
 mod list_files;
+mod list_repos;
 mod log_repos;
 mod new_branch;
 mod new_tag;
+mod open;
+mod pull;
+mod push;
+mod remove;
+mod repair;
 mod snapshot;
+mod status;
 mod sync;
 mod track;
+mod validate;
+mod verify_remote;
@@ -1,12 +1,14 @@
 use anyhow::anyhow;
-use std::env;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use crate::core::git;
-use crate::core::repos::TomlConfig;
+use crate::core::repos::{TomlConfig, WorkspacePaths};
+use crate::utils::cmd::retry;
 use crate::utils::error::{MgitError, MgitResult, OpsErrors};
 use crate::utils::logger;
 use crate::utils::path::PathExtension;
+use crate::utils::repo_list::{load_failed_repos, save_failed_repos};
 use crate::utils::StyleMessage;
 
 pub struct NewBranchOptions {
@@ -16,6 +18,8 @@ pub struct NewBranchOptions {
     pub new_branch: String,
     pub force: bool,
     pub ignore: Option<Vec<String>>,
+    /// only create the branch in the repos that failed the last run
+    pub retry_failed: bool,
 }
 
 impl NewBranchOptions {
@@ -27,8 +31,7 @@ impl NewBranchOptions {
         force: bool,
         ignore: Option<Vec<String>>,
     ) -> Self {
-        let path = path.map_or(env::current_dir().unwrap(), |p| p.as_ref().to_path_buf());
-        let config_path = config_path.map_or(path.join(".gitrepos"), |p| p.as_ref().to_path_buf());
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
         Self {
             path,
             config_path,
@@ -36,6 +39,7 @@ impl NewBranchOptions {
             new_branch,
             force,
             ignore,
+            retry_failed: false,
         }
     }
 }
@@ -74,7 +78,13 @@ pub fn new_remote_branch(options: NewBranchOptions) -> MgitResult<StyleMessage>
         ignore.push("".to_string());
     }
 
+    let retry_repos = match options.retry_failed {
+        true => Some(load_failed_repos(path, "new-remote-branch")?),
+        false => None,
+    };
+
     let mut errors = Vec::new();
+    let mut failed_repos = Vec::new();
     for toml_repo in toml_repos.iter_mut() {
         let Some(local) = toml_repo.local.as_ref() else {
             continue;
@@ -92,20 +102,33 @@ pub fn new_remote_branch(options: NewBranchOptions) -> MgitResult<StyleMessage>
         let rel_path = toml_repo.local.as_ref().unwrap();
         let full_path = Path::new(path).join(rel_path);
         let base_branch = toml_repo.branch.as_ref().unwrap();
+        let rel_path_display = Path::new(rel_path).display_path();
+        let remote_name = toml_repo.origin_name().to_string();
+
+        // succeeded last run, keep the new branch in the new config file
+        if matches!(&retry_repos, Some(repos) if !repos.contains(&rel_path_display)) {
+            toml_repo.branch = Some(new_branch.clone());
+            continue;
+        }
 
         if !force {
-            match git::check_remote_branch_exist(&full_path, &new_branch) {
+            match git::check_remote_branch_exist(&full_path, &remote_name, &new_branch) {
                 Err(e) => {
                     let error = StyleMessage::git_error(rel_path, &e);
                     errors.push(error);
+                    failed_repos.push(rel_path_display);
                     continue;
                 }
 
                 Ok(true) => {
-                    let e: anyhow::Error =
-                        anyhow!("origin/{} already exist, try force mode again", &new_branch);
+                    let e: anyhow::Error = anyhow!(
+                        "{}/{} already exist, try force mode again",
+                        remote_name,
+                        &new_branch
+                    );
                     let error = StyleMessage::git_error(rel_path, &e);
                     errors.push(error);
+                    failed_repos.push(rel_path_display);
                     continue;
                 }
 
@@ -113,19 +136,24 @@ pub fn new_remote_branch(options: NewBranchOptions) -> MgitResult<StyleMessage>
             }
         }
 
-        if let Err(e) = git::new_remote_branch(full_path, base_branch, &new_branch) {
+        let result = retry(10, Duration::from_millis(400), || {
+            git::new_remote_branch(&full_path, &remote_name, base_branch, &new_branch)
+        });
+        if let Err(e) = result {
             let error = StyleMessage::git_error(rel_path, &e);
             errors.push(error);
+            failed_repos.push(rel_path_display);
             continue;
         }
 
         toml_repo.branch = Some(new_branch.clone());
-        let rel_path_display = Path::new(rel_path).display_path();
 
         let msg = StyleMessage::git_new_branch(rel_path_display, &new_branch);
         logger::info(msg);
     }
 
+    save_failed_repos(path, "new-remote-branch", &failed_repos)?;
+
     if !errors.is_empty() {
         let msg = StyleMessage::ops_failed("new-remote-branch", errors.len());
         let e = anyhow!(MgitError::OpsError {
@@ -137,8 +165,7 @@ pub fn new_remote_branch(options: NewBranchOptions) -> MgitResult<StyleMessage>
     }
 
     if let Some(new_config_path) = new_config_path {
-        let toml_string = toml_config.serialize();
-        std::fs::write(new_config_path, toml_string).expect("Failed to write file .gitrepos!");
+        toml_config.save(new_config_path)?;
     }
 
     let msg = StyleMessage::ops_success("new-remote-branch");
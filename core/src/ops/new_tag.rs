@@ -1,12 +1,13 @@
 use anyhow::anyhow;
-use std::env;
 use std::path::{Path, PathBuf};
 
-use crate::core::git;
-use crate::core::repos::TomlConfig;
+use crate::core::git::{self, TagOptions};
+use crate::core::repos::{TomlConfig, WorkspacePaths};
+use crate::utils::cmd::retry_unless;
 use crate::utils::error::{MgitError, MgitResult, OpsErrors};
 use crate::utils::logger;
 use crate::utils::path::PathExtension;
+use crate::utils::repo_list::{load_failed_repos, save_failed_repos};
 use crate::utils::StyleMessage;
 
 #[derive(Debug)]
@@ -16,6 +17,14 @@ pub struct NewTagOptions {
     pub new_tag: String,
     pub push: bool,
     pub ignore: Option<Vec<String>>,
+    /// only tag the repos that failed the last run
+    pub retry_failed: bool,
+    /// create annotated tags with the message
+    pub message: Option<String>,
+    /// create GPG-signed tags
+    pub sign: bool,
+    /// replace existing tags of the same name, locally and on the remote
+    pub force: bool,
 }
 
 impl NewTagOptions {
@@ -26,14 +35,17 @@ impl NewTagOptions {
         push: bool,
         ignore: Option<Vec<String>>,
     ) -> Self {
-        let path = path.map_or(env::current_dir().unwrap(), |p| p.as_ref().to_path_buf());
-        let config_path = config_path.map_or(path.join(".gitrepos"), |p| p.as_ref().to_path_buf());
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
         Self {
             path,
             config_path,
             new_tag,
             push,
             ignore,
+            retry_failed: false,
+            message: None,
+            sign: false,
+            force: true,
         }
     }
 }
@@ -43,6 +55,11 @@ pub fn new_tag(options: NewTagOptions) -> MgitResult<StyleMessage> {
     let config_path = &options.config_path;
     let new_tag = options.new_tag;
     let push = options.push;
+    let tag_options = TagOptions {
+        message: options.message,
+        sign: options.sign,
+        force: options.force,
+    };
     let mut ignore = options.ignore.unwrap_or_default();
 
     logger::info("New tag:");
@@ -71,7 +88,13 @@ pub fn new_tag(options: NewTagOptions) -> MgitResult<StyleMessage> {
         ignore.push("".to_string());
     }
 
+    let retry_repos = match options.retry_failed {
+        true => Some(load_failed_repos(path, "new-tag")?),
+        false => None,
+    };
+
     let mut errors = Vec::new();
+    let mut failed_repos = Vec::new();
     for toml_repo in toml_repos.iter_mut() {
         let Some(local) = toml_repo.local.as_ref() else {
             continue;
@@ -83,29 +106,57 @@ pub fn new_tag(options: NewTagOptions) -> MgitResult<StyleMessage> {
 
         let rel_path = toml_repo.local.as_ref().unwrap();
         let full_path = Path::new(path).join(rel_path);
+        let rel_path_display = Path::new(rel_path).display_path();
+
+        if matches!(&retry_repos, Some(repos) if !repos.contains(&rel_path_display)) {
+            continue;
+        }
 
         // NOTE: current head ref
         let target_ref = "";
 
-        if let Err(e) = git::new_local_tag(&full_path, &target_ref, &new_tag) {
+        // a retry only pushes again a tag the last run already created on the same commit
+        let is_tagged = options.retry_failed && is_head_tagged(&full_path, &new_tag);
+        let tag_res = match is_tagged {
+            true => Ok(()),
+            false => git::new_local_tag(&full_path, target_ref, &new_tag, &tag_options),
+        };
+        if let Err(e) = tag_res {
+            // a missing signing key only shows up as a gpg error
+            let e = match tag_options.sign {
+                true => e.context("signing the tag failed, check user.signingKey and gpg"),
+                false => e,
+            };
             let error = StyleMessage::git_error(rel_path, &e);
             errors.push(error);
+            failed_repos.push(rel_path_display);
             continue;
         }
 
         if push {
-            if let Err(e) = git::push_tag(path, &new_tag) {
+            let (times, delay) = toml_repo.retry_policy();
+            let result = retry_unless(times, delay, is_push_refused, || {
+                git::push_tag(
+                    &full_path,
+                    toml_repo.origin_name(),
+                    &new_tag,
+                    tag_options.force,
+                )
+            });
+            if let Err(e) = result {
                 let error = StyleMessage::git_error(rel_path, &e);
                 errors.push(error);
+                failed_repos.push(rel_path_display);
                 continue;
             }
         }
 
-        let rel_path_display = Path::new(rel_path).display_path();
         let msg = StyleMessage::git_new_tag(rel_path_display, &new_tag);
         logger::info(msg);
     }
 
+    save_failed_repos(path, "new-tag", &failed_repos)?;
+
     if !errors.is_empty() {
         let msg = StyleMessage::ops_failed("new-tag", errors.len());
         let e = anyhow!(MgitError::OpsError {
@@ -119,3 +170,23 @@ pub fn new_tag(options: NewTagOptions) -> MgitResult<StyleMessage> {
     let msg = StyleMessage::ops_success("new-tag");
     Ok(msg)
 }
+
+/// the local tag exists and points to the current commit
+fn is_head_tagged(path: &Path, tag: &str) -> bool {
+    let tag_commit = git::rev_parse(path, format!("refs/tags/{}", tag));
+    let head_commit = git::get_current_commit(path);
+    matches!((tag_commit, head_commit), (Ok(tag), Ok(head)) if tag == head)
+}
+
+/// errors the remote answers the same way however many times the push is tried
+fn is_push_refused(e: &anyhow::Error) -> bool {
+    let msg = format!("{:#}", e);
+    [
+        "rejected",
+        "already exists",
+        "Authentication failed",
+        "Permission denied",
+    ]
+    .iter()
+    .any(|refused| msg.contains(refused))
+}
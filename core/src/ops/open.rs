@@ -0,0 +1,94 @@
+use anyhow::anyhow;
+use std::path::{Path, PathBuf};
+
+use crate::core::git;
+use crate::core::git::FetchOrPush;
+use crate::core::repo::browse_url;
+use crate::core::repos::{TomlConfig, WorkspacePaths};
+use crate::utils::error::{MgitError, MgitResult};
+use crate::utils::path::PathExtension;
+use crate::utils::style_message::StyleMessage;
+
+pub struct OpenOptions {
+    pub path: PathBuf,
+    pub config_path: PathBuf,
+    /// local path of the repo in the config file, all repos when none
+    pub local: Option<String>,
+}
+
+impl OpenOptions {
+    pub fn new(
+        path: Option<impl AsRef<Path>>,
+        config_path: Option<impl AsRef<Path>>,
+        local: Option<String>,
+    ) -> Self {
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
+        Self {
+            path,
+            config_path,
+            local,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoUrl {
+    pub local: String,
+    /// web page of the remote, or the remote url itself when it has none
+    pub url: String,
+}
+
+/// resolve the web page of each repo from its origin, or the config file if not cloned yet
+pub fn open_repo(options: OpenOptions) -> MgitResult<Vec<RepoUrl>> {
+    let path = &options.path;
+    let config_path = &options.config_path;
+
+    // if directory doesn't exist, return
+    if !path.is_dir() {
+        return Err(anyhow!(MgitError::DirNotFound(
+            StyleMessage::dir_not_found(path)
+        )));
+    }
+
+    // check if .gitrepos exists
+    if !config_path.is_file() {
+        return Err(anyhow!(MgitError::ConfigFileNotFound(
+            StyleMessage::config_file_not_found()
+        )));
+    }
+
+    // load config file(like .gitrepos)
+    let Some(toml_config) = TomlConfig::load(config_path) else {
+        return Err(anyhow!(MgitError::LoadConfigFailed));
+    };
+
+    let toml_repos = toml_config.repos.unwrap_or_default();
+    let local = options.local.as_ref().map(|local| local.norm_path());
+    let mut urls = Vec::new();
+    for toml_repo in &toml_repos {
+        let rel_path = toml_repo.local.as_ref().unwrap().display_path();
+        if matches!(&local, Some(local) if local.display_path() != rel_path) {
+            continue;
+        }
+
+        let full_path = path.join(&rel_path);
+        let remote =
+            git::find_remote_url_by_name(&full_path, toml_repo.origin_name(), FetchOrPush::Fetch)
+                .ok()
+                .or(toml_repo.remote_url());
+        let Some(remote) = remote else {
+            continue;
+        };
+
+        let url = browse_url(&remote).unwrap_or(remote);
+        urls.push(RepoUrl {
+            local: rel_path,
+            url,
+        });
+    }
+
+    if let (Some(local), true) = (local, urls.is_empty()) {
+        return Err(anyhow!("repo {} not found in config file", local));
+    }
+    Ok(urls)
+}
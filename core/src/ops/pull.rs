@@ -0,0 +1,189 @@
+use atomic_counter::{AtomicCounter, RelaxedCounter};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use anyhow::anyhow;
+use std::path::{Path, PathBuf};
+
+use crate::core::git::{self, RepositoryError};
+use crate::core::repo::repos_to_map_with_ignore;
+use crate::core::repos::{TomlConfig, WorkspacePaths};
+
+use crate::utils::error::{MgitError, MgitResult, OpsErrors};
+use crate::utils::path::PathExtension;
+use crate::utils::progress::{Progress, RepoInfo};
+use crate::utils::style_message::StyleMessage;
+use crate::utils::{fd_limit, logger};
+
+pub struct PullOptions {
+    pub path: PathBuf,
+    pub config_path: PathBuf,
+    pub thread_count: usize,
+    pub ignore: Option<Vec<String>>,
+    /// rebase local commits onto the upstream branch instead of only fast-forwarding
+    pub rebase: bool,
+}
+
+impl PullOptions {
+    pub fn new(
+        path: Option<impl AsRef<Path>>,
+        config_path: Option<impl AsRef<Path>>,
+        thread: Option<usize>,
+        ignore: Option<Vec<String>>,
+    ) -> Self {
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
+        Self {
+            path,
+            config_path,
+            thread_count: thread.unwrap_or(4),
+            ignore,
+            rebase: false,
+        }
+    }
+}
+
+enum PullResult {
+    Pulled,
+    Skip(StyleMessage),
+    Error(StyleMessage),
+}
+
+/// pull the upstream branch into the current branch of every repo.
+///
+/// unlike sync, repos stay on the branch they are on and nothing is reset or stashed.
+pub fn pull_repos(options: PullOptions, progress: impl Progress) -> MgitResult {
+    let path = &options.path;
+    let config_path = &options.config_path;
+    let thread_count = options.thread_count;
+    let ignore = options.ignore.as_ref();
+    let rebase = options.rebase;
+
+    logger::info(StyleMessage::ops_start("pull repos", path));
+
+    // check if .gitrepos exists
+    if !config_path.is_file() {
+        return Err(anyhow!(MgitError::ConfigFileNotFound(
+            StyleMessage::config_file_not_found(),
+        )));
+    }
+    // load config file(like .gitrepos)
+    let Some(toml_config) = TomlConfig::load(config_path) else {
+        return Err(anyhow!(MgitError::LoadConfigFailed));
+    };
+
+    let Some(toml_repos) = toml_config.repos else {
+        return Ok("No repos to pull".into());
+    };
+
+    // retain repos exclude ignore repositories
+    let repos_map = repos_to_map_with_ignore(toml_repos, ignore);
+
+    progress.repos_start(repos_map.len());
+
+    let counter = RelaxedCounter::new(1);
+
+    let thread_builder =
+        rayon::ThreadPoolBuilder::new().num_threads(fd_limit::cap_thread_count(thread_count));
+    let Ok(thread_pool) = thread_builder.build() else {
+        return Err(anyhow!(MgitError::CreateThreadPoolFailed));
+    };
+
+    let results: Vec<_> = thread_pool.install(|| {
+        let res = repos_map
+            .iter()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|(id, toml_repo)| {
+                let index = counter.inc();
+                let repo_info = RepoInfo::new(*id, index, toml_repo);
+                let rel_path = repo_info.rel_path().display_path();
+
+                let progress = progress.clone();
+                progress.repo_start(&repo_info, "pulling...".into());
+
+                match inner_exec(path, &repo_info, rebase) {
+                    Ok(Ok(msg)) => {
+                        progress.repo_end(&repo_info, msg);
+                        PullResult::Pulled
+                    }
+                    Ok(Err(msg)) => {
+                        progress.repo_end(&repo_info, msg.clone());
+                        PullResult::Skip(StyleMessage::git_skip(rel_path, msg))
+                    }
+                    Err(e) => {
+                        progress.repo_error(&repo_info, StyleMessage::new());
+                        PullResult::Error(StyleMessage::git_error(rel_path, &e))
+                    }
+                }
+            })
+            .collect();
+
+        progress.repos_end();
+        res
+    });
+
+    let mut skip_status = StyleMessage::new();
+    let mut errors = Vec::new();
+    for result in results {
+        match result {
+            PullResult::Pulled => {}
+            PullResult::Skip(msg) => skip_status = skip_status.join(format!("  {}\n", msg).into()),
+            PullResult::Error(msg) => errors.push(msg),
+        }
+    }
+    // detached repos and repos without an upstream are reported but not counted as failures
+    if !skip_status.is_empty() {
+        skip_status = StyleMessage::from("Skip status:\n").join(skip_status);
+    }
+
+    match errors.len() {
+        0 => Ok(StyleMessage::ops_success("pull").join(skip_status)),
+        _ => {
+            let msg = StyleMessage::ops_failed("pull", errors.len()).join(skip_status);
+            Err(anyhow!(MgitError::OpsError {
+                prefix: msg,
+                errors: OpsErrors(errors),
+            }))
+        }
+    }
+}
+
+/// pull the repo, the inner error is the reason it was skipped
+fn inner_exec(
+    input_path: impl AsRef<Path>,
+    repo_info: &RepoInfo,
+    rebase: bool,
+) -> anyhow::Result<Result<StyleMessage, StyleMessage>> {
+    let full_path = input_path.as_ref().join(repo_info.rel_path());
+
+    if git::is_repository(&full_path) == Err(RepositoryError::NotFound) {
+        return Ok(Err(StyleMessage::git_repo_missing()));
+    }
+    // a repo pinned to a commit or tag has nothing to pull into
+    let branch = git::get_current_branch(&full_path)?;
+    if branch.is_empty() {
+        return Ok(Err(StyleMessage::git_detached_head()));
+    }
+    if git::get_tracking_branch(&full_path).is_err() {
+        return Ok(Err(StyleMessage::git_no_upstream()));
+    }
+
+    let head = git::get_current_commit(&full_path)?;
+    if let Err(e) = git::pull(&full_path, !rebase) {
+        // the rebase is left for the user to continue or abort
+        let unmerged = git::get_unmerged_files(&full_path).unwrap_or_default();
+        if !unmerged.trim().is_empty() {
+            let files = unmerged.trim().lines().collect::<Vec<_>>().join(", ");
+            return Err(anyhow!(
+                "rebase stopped on conflicts in {}, resolve them and run `git rebase --continue`, or `git rebase --abort`",
+                files
+            ));
+        }
+        return Err(e);
+    }
+
+    let branch_log = git::get_branch_log(&full_path, branch);
+    match git::get_current_commit(&full_path)? == head {
+        true => Ok(Ok(StyleMessage::git_update_to_date(branch_log))),
+        false => Ok(Ok(StyleMessage::git_update_to(branch_log.into()))),
+    }
+}
@@ -0,0 +1,172 @@
+use atomic_counter::{AtomicCounter, RelaxedCounter};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use anyhow::anyhow;
+use std::path::{Path, PathBuf};
+
+use crate::core::git::{self, RepositoryError};
+use crate::core::repo::repos_to_map_with_ignore;
+use crate::core::repos::{TomlConfig, WorkspacePaths};
+
+use crate::utils::error::{MgitError, MgitResult, OpsErrors};
+use crate::utils::path::PathExtension;
+use crate::utils::progress::{Progress, RepoInfo};
+use crate::utils::style_message::StyleMessage;
+use crate::utils::{fd_limit, logger};
+
+pub struct PushOptions {
+    pub path: PathBuf,
+    pub config_path: PathBuf,
+    pub thread_count: usize,
+    pub ignore: Option<Vec<String>>,
+    /// overwrite the upstream branch unless it moved since the last fetch
+    pub force_with_lease: bool,
+    /// report what would be pushed without pushing
+    pub dry_run: bool,
+}
+
+impl PushOptions {
+    pub fn new(
+        path: Option<impl AsRef<Path>>,
+        config_path: Option<impl AsRef<Path>>,
+        thread: Option<usize>,
+        ignore: Option<Vec<String>>,
+    ) -> Self {
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
+        Self {
+            path,
+            config_path,
+            thread_count: thread.unwrap_or(4),
+            ignore,
+            force_with_lease: false,
+            dry_run: false,
+        }
+    }
+}
+
+enum PushResult {
+    Pushed,
+    Skip(StyleMessage),
+    Error(StyleMessage),
+}
+
+/// push the current branch of every repo to its upstream branch
+pub fn push_repos(options: PushOptions, progress: impl Progress) -> MgitResult {
+    let path = &options.path;
+    let config_path = &options.config_path;
+    let thread_count = options.thread_count;
+    let ignore = options.ignore.as_ref();
+    let force = options.force_with_lease;
+    let dry_run = options.dry_run;
+
+    logger::info(StyleMessage::ops_start("push repos", path));
+
+    // check if .gitrepos exists
+    if !config_path.is_file() {
+        return Err(anyhow!(MgitError::ConfigFileNotFound(
+            StyleMessage::config_file_not_found(),
+        )));
+    }
+    // load config file(like .gitrepos)
+    let Some(toml_config) = TomlConfig::load(config_path) else {
+        return Err(anyhow!(MgitError::LoadConfigFailed));
+    };
+
+    let Some(toml_repos) = toml_config.repos else {
+        return Ok("No repos to push".into());
+    };
+
+    // retain repos exclude ignore repositories
+    let repos_map = repos_to_map_with_ignore(toml_repos, ignore);
+
+    progress.repos_start(repos_map.len());
+
+    let counter = RelaxedCounter::new(1);
+
+    let thread_builder =
+        rayon::ThreadPoolBuilder::new().num_threads(fd_limit::cap_thread_count(thread_count));
+    let Ok(thread_pool) = thread_builder.build() else {
+        return Err(anyhow!(MgitError::CreateThreadPoolFailed));
+    };
+
+    let results: Vec<_> = thread_pool.install(|| {
+        let res = repos_map
+            .iter()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|(id, toml_repo)| {
+                let index = counter.inc();
+                let repo_info = RepoInfo::new(*id, index, toml_repo);
+                let rel_path = repo_info.rel_path().display_path();
+
+                let progress = progress.clone();
+                progress.repo_start(&repo_info, "pushing...".into());
+
+                match inner_exec(path, &repo_info, force, dry_run) {
+                    Ok(Ok(msg)) => {
+                        progress.repo_end(&repo_info, msg);
+                        PushResult::Pushed
+                    }
+                    Ok(Err(msg)) => {
+                        progress.repo_end(&repo_info, msg.clone());
+                        PushResult::Skip(StyleMessage::git_skip(rel_path, msg))
+                    }
+                    Err(e) => {
+                        progress.repo_error(&repo_info, StyleMessage::new());
+                        PushResult::Error(StyleMessage::git_error(rel_path, &e))
+                    }
+                }
+            })
+            .collect();
+
+        progress.repos_end();
+        res
+    });
+
+    let mut skip_status = StyleMessage::new();
+    let mut errors = Vec::new();
+    for result in results {
+        match result {
+            PushResult::Pushed => {}
+            PushResult::Skip(msg) => skip_status = skip_status.join(format!("  {}\n", msg).into()),
+            PushResult::Error(msg) => errors.push(msg),
+        }
+    }
+    // repos without an upstream are reported but not counted as failures
+    if !skip_status.is_empty() {
+        skip_status = StyleMessage::from("Skip status:\n").join(skip_status);
+    }
+
+    match errors.len() {
+        0 => Ok(StyleMessage::ops_success("push").join(skip_status)),
+        _ => {
+            let msg = StyleMessage::ops_failed("push", errors.len()).join(skip_status);
+            Err(anyhow!(MgitError::OpsError {
+                prefix: msg,
+                errors: OpsErrors(errors),
+            }))
+        }
+    }
+}
+
+/// push the repo, the inner error is the reason it was skipped
+fn inner_exec(
+    input_path: impl AsRef<Path>,
+    repo_info: &RepoInfo,
+    force: bool,
+    dry_run: bool,
+) -> anyhow::Result<Result<StyleMessage, StyleMessage>> {
+    let full_path = input_path.as_ref().join(repo_info.rel_path());
+
+    if git::is_repository(&full_path) == Err(RepositoryError::NotFound) {
+        return Ok(Err(StyleMessage::git_repo_missing()));
+    }
+    let Some((remote, refspec)) = git::get_push_target(&full_path)? else {
+        return Ok(Err(StyleMessage::git_no_upstream()));
+    };
+
+    let summary = git::push(&full_path, &remote, &refspec, force, dry_run)?;
+    let branch = refspec.rsplit(':').next().unwrap_or_default();
+    let target = format!("{}/{}", remote, branch.trim_start_matches("refs/heads/"));
+    Ok(Ok(StyleMessage::git_pushed(target, summary, dry_run)))
+}
@@ -0,0 +1,175 @@
+use anyhow::{anyhow, Context};
+use std::path::{Path, PathBuf};
+
+use crate::core::git;
+use crate::core::repos::{TomlConfig, WorkspacePaths};
+use crate::utils::error::{MgitError, MgitResult, OpsErrors};
+use crate::utils::logger;
+use crate::utils::path::PathExtension;
+use crate::utils::StyleMessage;
+
+pub struct RemoveOptions {
+    pub path: PathBuf,
+    pub config_path: PathBuf,
+    /// `local` of the repos to remove
+    pub locals: Vec<String>,
+    /// delete the repo directories too
+    pub delete: bool,
+}
+
+impl RemoveOptions {
+    pub fn new(
+        path: Option<impl AsRef<Path>>,
+        config_path: Option<impl AsRef<Path>>,
+        locals: Vec<String>,
+        delete: bool,
+    ) -> Self {
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
+        Self {
+            path,
+            config_path,
+            locals,
+            delete,
+        }
+    }
+}
+
+/// remove repos from the config file, and with `delete` their directories as well.
+/// nothing is changed when a repo is missing from the config or can't be deleted safely
+pub fn remove_repos(options: RemoveOptions) -> MgitResult {
+    let path = &options.path;
+    let config_path = &options.config_path;
+
+    // if directory doesn't exist, return
+    if !path.is_dir() {
+        return Err(anyhow!(MgitError::DirNotFound(
+            StyleMessage::dir_not_found(path)
+        )));
+    }
+
+    // check if .gitrepos exists
+    if !config_path.is_file() {
+        return Err(anyhow!(MgitError::ConfigFileNotFound(
+            StyleMessage::config_file_not_found()
+        )));
+    }
+
+    // load config file(like .gitrepos)
+    let Some(mut toml_config) = TomlConfig::load(config_path) else {
+        return Err(anyhow!(MgitError::LoadConfigFailed));
+    };
+
+    let configured = toml_config
+        .repos
+        .iter()
+        .flatten()
+        .map(|repo| repo.local.as_deref().unwrap_or_default().norm_path())
+        .collect::<Vec<_>>();
+    let mut locals = Vec::new();
+    for local in &options.locals {
+        let local = local.trim_start_matches("./").norm_path();
+        if !locals.contains(&local) {
+            locals.push(local);
+        }
+    }
+
+    let missing = locals
+        .iter()
+        .filter(|local| !configured.contains(local))
+        .map(|local| local.display_path())
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        return Err(anyhow!(
+            "repo(s) not found in {}: {}",
+            config_path.display(),
+            missing.join(", ")
+        ));
+    }
+
+    if options.delete {
+        let errors = locals
+            .iter()
+            .filter_map(|local| {
+                check_deletable(path, local, &configured, &locals)
+                    .err()
+                    .map(|e| StyleMessage::git_error(local.display_path(), &e))
+            })
+            .collect::<Vec<_>>();
+        if !errors.is_empty() {
+            return Err(anyhow!(MgitError::OpsError {
+                prefix: StyleMessage::ops_failed("remove", errors.len()),
+                errors: OpsErrors(errors),
+            }));
+        }
+    }
+
+    if let Some(repos) = toml_config.repos.as_mut() {
+        repos.retain(|repo| {
+            let local = repo.local.as_deref().unwrap_or_default().norm_path();
+            !locals.contains(&local)
+        });
+    }
+    toml_config.save(config_path)?;
+
+    logger::info("Remove repos:");
+    let mut errors = Vec::new();
+    for local in &locals {
+        let full_path = path.join(local);
+        if !options.delete || !full_path.exists() {
+            logger::info(format!("  {}: removed from config", local.display_path()));
+            continue;
+        }
+
+        match std::fs::remove_dir_all(&full_path)
+            .with_context(|| format!("delete {} failed", full_path.display()))
+        {
+            Ok(_) => logger::info(format!("  {}: removed and deleted", local.display_path())),
+            Err(e) => errors.push(StyleMessage::git_error(local.display_path(), &e)),
+        }
+    }
+
+    match errors.is_empty() {
+        true => Ok(StyleMessage::ops_success("remove")),
+        false => Err(anyhow!(MgitError::OpsError {
+            prefix: StyleMessage::ops_failed("remove", errors.len()),
+            errors: OpsErrors(errors),
+        })),
+    }
+}
+
+/// only a directory holding nothing but a clean clone of the repo, whose commits and
+/// stashes are all on a remote, is deleted
+fn check_deletable(
+    path: &Path,
+    local: &str,
+    configured: &[String],
+    removed: &[String],
+) -> anyhow::Result<()> {
+    if local.is_empty() || local == "." {
+        return Err(anyhow!("the workspace itself can't be deleted"));
+    }
+
+    let full_path = path.join(local);
+    if !full_path.exists() {
+        return Ok(());
+    }
+
+    let prefix = format!("{}/", local);
+    if let Some(nested) = configured
+        .iter()
+        .find(|other| other.starts_with(&prefix) && !removed.contains(other))
+    {
+        return Err(anyhow!("holds repo {}, which is kept", nested));
+    }
+
+    git::is_repository(&full_path).map_err(|e| anyhow!("can't check for local changes, {}", e))?;
+    if git::has_local_changes(&full_path)? {
+        return Err(anyhow!(
+            "has uncommitted changes, commit or discard them first"
+        ));
+    }
+    if let Some(unpushed) = git::unpushed_work(&full_path)? {
+        return Err(anyhow!("{}, push or drop them first", unpushed));
+    }
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use anyhow::anyhow;
+use std::path::{Path, PathBuf};
+
+use crate::core::git;
+use crate::core::repo::{repos_to_map_with_ignore, TomlRepo};
+use crate::core::repos::{TomlConfig, WorkspacePaths};
+use crate::ops::sync::{checkout_args, checkout_branch_name};
+use crate::utils::error::{MgitError, MgitResult, OpsErrors};
+use crate::utils::logger;
+use crate::utils::path::PathExtension;
+use crate::utils::StyleMessage;
+
+pub struct RepairOptions {
+    pub path: PathBuf,
+    pub config_path: PathBuf,
+    pub ignore: Option<Vec<String>>,
+}
+
+impl RepairOptions {
+    pub fn new(
+        path: Option<impl AsRef<Path>>,
+        config_path: Option<impl AsRef<Path>>,
+        ignore: Option<Vec<String>>,
+    ) -> Self {
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
+        Self {
+            path,
+            config_path,
+            ignore,
+        }
+    }
+}
+
+/// checkout every repo to the branch of its manifest entry, without fetching or resetting
+pub fn repair_repos(options: RepairOptions) -> MgitResult {
+    let path = &options.path;
+    let config_path = &options.config_path;
+    let ignore = options.ignore.as_ref();
+
+    logger::info(StyleMessage::ops_start("repair repos", path));
+
+    // if directory doesn't exist, return
+    if !path.is_dir() {
+        return Err(anyhow!(MgitError::DirNotFound(
+            StyleMessage::dir_not_found(path)
+        )));
+    }
+
+    // check if .gitrepos exists
+    if !config_path.is_file() {
+        return Err(anyhow!(MgitError::ConfigFileNotFound(
+            StyleMessage::config_file_not_found()
+        )));
+    }
+
+    // load config file(like .gitrepos)
+    let Some(toml_config) = TomlConfig::load(config_path) else {
+        return Err(anyhow!(MgitError::LoadConfigFailed));
+    };
+
+    let Some(toml_repos) = toml_config.repos else {
+        return Ok("No repos to repair".into());
+    };
+    let default_branch = toml_config.default_branch;
+
+    let mut repos = repos_to_map_with_ignore(toml_repos, ignore)
+        .into_iter()
+        .collect::<Vec<_>>();
+    repos.sort_by_key(|(id, _)| *id);
+
+    let mut errors = Vec::new();
+    for (_, mut toml_repo) in repos {
+        // use default branch when branch is null
+        if toml_repo.branch.is_none() {
+            toml_repo.branch = default_branch.to_owned();
+        }
+
+        let rel_path = toml_repo.local.as_ref().unwrap().display_path();
+        match repair_repo(path, &toml_repo) {
+            Ok(Some((from, to))) => logger::info(StyleMessage::git_repair(&rel_path, from, to)),
+            Ok(None) => {}
+            Err(e) => errors.push(StyleMessage::git_error(&rel_path, &e)),
+        }
+    }
+
+    match errors.len() {
+        0 => Ok(StyleMessage::ops_success("repair")),
+        _ => {
+            let msg = StyleMessage::ops_failed("repair", errors.len());
+            Err(anyhow!(MgitError::OpsError {
+                prefix: msg,
+                errors: OpsErrors(errors),
+            }))
+        }
+    }
+}
+
+/// return the branches moved from and to, or none if the repo is already on its branch
+fn repair_repo(
+    input_path: &Path,
+    toml_repo: &TomlRepo,
+) -> anyhow::Result<Option<(String, String)>> {
+    let full_path = input_path.join(toml_repo.local.as_ref().unwrap());
+    git::is_repository(&full_path)?;
+
+    // priority: commit/tag/branch(default-branch)
+    let remote_ref = toml_repo.get_remote_ref(&full_path)?;
+    let branch = checkout_branch_name(toml_repo, &remote_ref);
+
+    let current = match git::get_current_branch(&full_path) {
+        Ok(current_branch) if current_branch == branch => return Ok(None),
+        Ok(current_branch) if !current_branch.is_empty() => current_branch,
+        _ => {
+            let commit = git::get_current_commit(&full_path)?;
+            format!("detached HEAD at {}", &commit[..7])
+        }
+    };
+
+    // create the local branch from the fetched remote ref, never reset an existing one
+    let branch_exist = git::local_branch_already_exist(&full_path, &branch)?;
+    let args = checkout_args(&branch, &remote_ref.to_string(), branch_exist, false);
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    git::checkout(&full_path, &args)?;
+
+    Ok(Some((current, branch)))
+}
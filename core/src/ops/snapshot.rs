@@ -1,17 +1,22 @@
 use anyhow::anyhow;
+use atomic_counter::{AtomicCounter, RelaxedCounter};
 use globset::GlobBuilder;
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+use std::fs;
 use std::path::{Path, PathBuf};
-use std::{env, fs};
+use toml_edit::{value, Array, ArrayOfTables, Document, Item, Table};
 use walkdir::WalkDir;
 
 use crate::core::git;
+use crate::core::git::FetchOrPush;
 use crate::core::repo::TomlRepo;
-use crate::core::repos::TomlConfig;
+use crate::core::repos::{config_format, ConfigFormat, TomlConfig, WorkspacePaths};
 use crate::utils::error::{MgitError, MgitResult};
 
-use crate::utils::logger;
 use crate::utils::path::PathExtension;
+use crate::utils::progress::{Progress, RepoInfo};
 use crate::utils::style_message::StyleMessage;
+use crate::utils::{fd_limit, logger};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum SnapshotType {
@@ -25,6 +30,13 @@ pub struct SnapshotOptions {
     force: bool,
     snapshot_type: SnapshotType,
     ignore: Option<Vec<String>>,
+    pub scan_hidden: bool,
+    /// threads reading the repos found
+    pub thread_count: usize,
+    /// refresh only the refs of repos already in the config file, keeping everything else
+    pub amend: bool,
+    /// with `amend`, also append repos found on disk but missing from the config file
+    pub add_new: bool,
 }
 
 impl SnapshotOptions {
@@ -35,24 +47,28 @@ impl SnapshotOptions {
         snapshot_type: Option<SnapshotType>,
         ignore: Option<Vec<String>>,
     ) -> Self {
-        let path = path.map_or(env::current_dir().unwrap(), |p| p.as_ref().to_path_buf());
-        let config_path = config_path.map_or(path.join(".gitrepos"), |p| p.as_ref().to_path_buf());
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
         Self {
             path,
             config_path,
             force: force.unwrap_or(false),
             snapshot_type: snapshot_type.unwrap_or(SnapshotType::Commit),
             ignore,
+            scan_hidden: false,
+            thread_count: 4,
+            amend: false,
+            add_new: false,
         }
     }
 }
 
-pub fn snapshot_repo(options: SnapshotOptions) -> MgitResult {
+pub fn snapshot_repo(options: SnapshotOptions, progress: impl Progress) -> MgitResult {
     let path = &options.path;
     let config_path = &options.config_path;
     let force = options.force;
     let snapshot_type = &options.snapshot_type;
     let ignore = &options.ignore;
+    let scan_hidden = options.scan_hidden;
 
     // start taking snapshot repos
     logger::info(StyleMessage::ops_start("take snapshot", path));
@@ -65,8 +81,13 @@ pub fn snapshot_repo(options: SnapshotOptions) -> MgitResult {
         )));
     }
 
-    // check if .gitrepos exists
-    if config_path.is_file() && !force {
+    // check if .gitrepos exists, amend needs one to update
+    if options.amend && !config_path.is_file() {
+        return Err(anyhow!(MgitError::ConfigFileNotFound(
+            StyleMessage::config_file_not_found()
+        )));
+    }
+    if config_path.is_file() && !force && !options.amend {
         return Err(anyhow!(MgitError::DirAlreadyInited(
             StyleMessage::dir_already_inited(path)
         )));
@@ -74,8 +95,12 @@ pub fn snapshot_repo(options: SnapshotOptions) -> MgitResult {
 
     let mut toml_config = TomlConfig {
         version: None,
+        name: None,
+        description: None,
         default_branch: Some(String::from("develop")),
         default_remote: None,
+        sync_strategy: None,
+        clean_exclude: None,
         repos: None,
     };
 
@@ -91,7 +116,7 @@ pub fn snapshot_repo(options: SnapshotOptions) -> MgitResult {
     let mut file_count = 0;
     let input_path = path.to_owned();
     let mut it = WalkDir::new(&input_path).into_iter();
-    let mut repos: Vec<TomlRepo> = Vec::new();
+    let mut found: Vec<(PathBuf, String)> = Vec::new();
     loop {
         let entry = match it.next() {
             None => break,
@@ -117,66 +142,60 @@ pub fn snapshot_repo(options: SnapshotOptions) -> MgitResult {
                 continue;
             }
 
-            // check repository valid
-            if git::is_repository(pb.as_path()).is_err() {
-                logger::error(format!("Failed to open repo {}!", &norm_str));
-                continue;
-            }
-
-            // get remote
-            let remote = git::find_remote_url_by_name(&pb, "origin").ok();
-            let mut commit: Option<String> = None;
-            let mut branch: Option<String> = None;
-
-            // snapshot commit or remote-branch
-            match snapshot_type {
-                SnapshotType::Commit => {
-                    // get local head commit id
-                    if let Ok(oid) = git::get_current_commit(pb.as_path()) {
-                        commit = Some(oid);
-                    }
-                }
-                SnapshotType::Branch => {
-                    // get tracking brach
-                    if let Ok(refname) = git::get_tracking_branch(pb.as_path()) {
-                        // split, like origin/master
-                        if let Some((_, branch_ref)) = refname.split_once('/') {
-                            branch = Some(branch_ref.trim().to_string());
-                        }
-                    }
-                }
-            }
-
-            // get sparse checkout list
-            let sparse = match git::sparse_checkout_list(pb.as_path()) {
-                Err(_) => None,
-                Ok(content) if content.trim().is_empty() => None,
-                Ok(content) => {
-                    let list: Vec<_> = content.trim().lines().map(|s| s.to_string()).collect();
-                    Some(list)
-                }
-            };
-
-            // set toml repo
-            let toml_repo = TomlRepo {
-                local: Some(norm_str.clone()),
-                remote,
-                branch,
-                tag: None,
-                commit,
-                sparse,
-            };
-            repos.push(toml_repo);
-            logger::info(format!("  + {}", norm_str));
+            found.push((pb, norm_str));
 
             // just skip go into .git/ folder and continue
             it.skip_current_dir();
             continue;
         }
 
+        // skip dot-directories like tool caches unless asked to scan them
+        if !scan_hidden && entry.depth() > 0 && entry.file_type().is_dir() && is_hidden(&entry) {
+            it.skip_current_dir();
+            continue;
+        }
+
         file_count += 1;
     }
 
+    // read remote, branch and commit of the repos in parallel, the git calls are the slow part
+    progress.repos_start(found.len());
+    let counter = RelaxedCounter::new(1);
+    let thread_builder = rayon::ThreadPoolBuilder::new()
+        .num_threads(fd_limit::cap_thread_count(options.thread_count));
+    let Ok(thread_pool) = thread_builder.build() else {
+        return Err(anyhow!(MgitError::CreateThreadPoolFailed));
+    };
+    // an indexed collect keeps the walk order, so the sort below gives the same result as before
+    let mut repos: Vec<TomlRepo> = thread_pool
+        .install(|| {
+            let res = found
+                .into_par_iter()
+                .enumerate()
+                .map(|(id, (pb, norm_str))| {
+                    let local_repo = TomlRepo {
+                        local: Some(norm_str.clone()),
+                        ..Default::default()
+                    };
+                    let repo_info = RepoInfo::new(id, counter.inc(), &local_repo);
+                    progress.repo_start(&repo_info, "reading...".into());
+
+                    let toml_repo = repo_snapshot(&pb, norm_str, snapshot_type);
+                    match toml_repo.is_some() {
+                        true => progress.repo_end(&repo_info, "added".into()),
+                        false => progress.repo_error(&repo_info, "failed to open repo".into()),
+                    }
+                    toml_repo
+                })
+                .collect::<Vec<_>>();
+
+            progress.repos_end();
+            res
+        })
+        .into_iter()
+        .flatten()
+        .collect();
+
     // keep list sort same on different device
     repos.sort_by(|a, b| {
         a.local
@@ -186,6 +205,23 @@ pub fn snapshot_repo(options: SnapshotOptions) -> MgitResult {
             .cmp(&b.local.as_ref().unwrap().to_lowercase())
     });
 
+    for toml_repo in &repos {
+        logger::info(format!("  + {}", toml_repo.local.as_ref().unwrap()));
+    }
+
+    // git only knows the url a ${VAR} remote expanded to, keep the config file's remote
+    if let Some(existing) = config_path
+        .is_file()
+        .then(|| TomlConfig::load(config_path))
+        .flatten()
+    {
+        keep_remote_templates(&mut repos, &existing.repos.unwrap_or_default());
+    }
+
+    if options.amend {
+        return amend_config(config_path, snapshot_type, repos, options.add_new);
+    }
+
     let repo_count = repos.len();
     toml_config.repos = Some(repos);
     logger::info(format!(
@@ -193,8 +229,195 @@ pub fn snapshot_repo(options: SnapshotOptions) -> MgitResult {
         repo_count, file_count
     ));
 
-    // serialize .gitrepos
-    let toml_string = toml_config.serialize();
-    fs::write(config_path, toml_string).expect("Failed to write file .gitrepos!");
+    // serialize .gitrepos, keeping the comments of an existing one
+    if let Err(e) = toml_config.save(config_path) {
+        logger::error(e.to_string());
+        return Err(e);
+    }
     Ok(StyleMessage::update_config_succ())
 }
+
+/// the config entry of the repo at `pb`, none if it can't be opened
+fn repo_snapshot(pb: &Path, norm_str: String, snapshot_type: &SnapshotType) -> Option<TomlRepo> {
+    // check repository valid
+    if git::is_repository(pb).is_err() {
+        logger::error(format!("Failed to open repo {}!", &norm_str));
+        return None;
+    }
+
+    // get remote
+    let remote = git::find_remote_url_by_name(pb, "origin", FetchOrPush::Fetch).ok();
+    let mut commit: Option<String> = None;
+    let mut branch: Option<String> = None;
+
+    // snapshot commit or remote-branch
+    match snapshot_type {
+        SnapshotType::Commit => {
+            // get local head commit id
+            if let Ok(oid) = git::get_current_commit(pb) {
+                commit = Some(oid);
+            }
+        }
+        SnapshotType::Branch => {
+            // get tracking brach
+            if let Ok(refname) = git::get_tracking_branch(pb) {
+                // split, like origin/master
+                if let Some((_, branch_ref)) = refname.split_once('/') {
+                    branch = Some(branch_ref.trim().to_string());
+                }
+            }
+        }
+    }
+
+    // get sparse checkout list
+    let sparse = match git::sparse_checkout_list(pb) {
+        Err(_) => None,
+        Ok(content) if content.trim().is_empty() => None,
+        Ok(content) => {
+            let list: Vec<_> = content.trim().lines().map(|s| s.to_string()).collect();
+            Some(list)
+        }
+    };
+
+    // set toml repo
+    Some(TomlRepo {
+        local: Some(norm_str),
+        remote,
+        branch,
+        tag: None,
+        commit,
+        sparse,
+        ..Default::default()
+    })
+}
+
+/// put back the `remote` with environment variables of the repos in the config file,
+/// as long as it still expands to the url of the repo
+fn keep_remote_templates(repos: &mut [TomlRepo], existing: &[TomlRepo]) {
+    let rel_path = |toml_repo: &TomlRepo| {
+        (toml_repo.local.as_ref()).map(|local| local.norm_path().display_path())
+    };
+    for toml_repo in repos {
+        let template = existing
+            .iter()
+            .filter(|existing| {
+                (existing.remote.as_ref()).is_some_and(|remote| remote.contains("${"))
+            })
+            .find(|existing| rel_path(existing) == rel_path(toml_repo));
+        if let Some(template) = template {
+            if template.remote_url() == toml_repo.remote {
+                toml_repo.remote = template.remote.clone();
+            }
+        }
+    }
+}
+
+/// update the refs of repos in the config file in place, so comments and
+/// fields snapshot knows nothing about survive
+fn amend_config(
+    config_path: &Path,
+    snapshot_type: &SnapshotType,
+    repos: Vec<TomlRepo>,
+    add_new: bool,
+) -> MgitResult {
+    let content = fs::read_to_string(config_path)?;
+    if config_format(config_path, &content) != ConfigFormat::Toml {
+        return Err(anyhow!("--amend only supports toml config files"));
+    }
+    let mut doc = content
+        .parse::<Document>()
+        .map_err(|_| anyhow!(MgitError::LoadConfigFailed))?;
+
+    let mut repos = repos
+        .into_iter()
+        .map(|repo| (repo.local.as_ref().unwrap().display_path(), repo))
+        .collect::<Vec<_>>();
+
+    let repos_item = doc
+        .as_table_mut()
+        .entry("repos")
+        .or_insert(Item::ArrayOfTables(ArrayOfTables::new()));
+    let Some(tables) = repos_item.as_array_of_tables_mut() else {
+        return Err(anyhow!(MgitError::LoadConfigFailed));
+    };
+
+    logger::info("update refs of repos:");
+    let mut update_count = 0;
+    for table in tables.iter_mut() {
+        let Some(local) = table.get("local").and_then(|item| item.as_str()) else {
+            continue;
+        };
+        let local = local.norm_path().display_path();
+        let Some(index) = repos.iter().position(|(rel_path, _)| *rel_path == local) else {
+            continue;
+        };
+        let (_, repo) = repos.remove(index);
+
+        match snapshot_type {
+            // commit takes priority over tag and branch, the branch is still tracked
+            SnapshotType::Commit => {
+                let Some(commit) = repo.commit else {
+                    continue;
+                };
+                table["commit"] = value(commit);
+            }
+            // commit and tag would take priority over the branch
+            SnapshotType::Branch => {
+                let Some(branch) = repo.branch else {
+                    continue;
+                };
+                table["branch"] = value(branch);
+                table.remove("commit");
+                table.remove("tag");
+            }
+        }
+        update_count += 1;
+        logger::info(format!("  ~ {}", local));
+    }
+
+    // repos on disk the config file doesn't know about
+    let mut result = StyleMessage::update_config_succ();
+    if !repos.is_empty() {
+        let title = match add_new {
+            true => "\nAdded repos:\n",
+            false => "\nRepos not in config file (use --add-new to add them):\n",
+        };
+        result = result.join(title.into());
+        for (rel_path, repo) in repos {
+            result = result.join(format!("  {}\n", rel_path).into());
+            if add_new {
+                tables.push(repo_table(repo));
+            }
+        }
+    }
+
+    logger::info(format!("{} repos are updated.", update_count));
+    fs::write(config_path, doc.to_string()).expect("Failed to write file .gitrepos!");
+    Ok(result)
+}
+
+fn repo_table(repo: TomlRepo) -> Table {
+    let mut table = Table::new();
+    let fields = [
+        ("local", repo.local),
+        ("remote", repo.remote),
+        ("branch", repo.branch),
+        ("commit", repo.commit),
+    ];
+    for (key, field) in fields {
+        if let Some(field) = field {
+            table[key] = value(field);
+        }
+    }
+    if let Some(sparse) = repo.sparse {
+        table["sparse"] = value(Array::from_iter(sparse));
+    }
+    table
+}
+
+fn is_hidden(entry: &walkdir::DirEntry) -> bool {
+    entry
+        .file_name()
+        .to_str()
+        .is_some_and(|name| name.starts_with('.'))
+}
@@ -0,0 +1,366 @@
+use atomic_counter::{AtomicCounter, RelaxedCounter};
+use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+
+use anyhow::anyhow;
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::core::git;
+use crate::core::git::RepositoryError;
+use crate::core::repo::{
+    cmp_local_remote, cmp_upstream_manifest, include_groups, repo_status, repos_to_map_with_ignore,
+    RepoStatus, TomlRepo,
+};
+use crate::core::repos::{TomlConfig, WorkspacePaths};
+use crate::ops::exec_fetch;
+
+use crate::utils::error::{MgitError, MgitResult, OpsErrors};
+use crate::utils::path::PathExtension;
+use crate::utils::progress::{Progress, RepoInfo};
+use crate::utils::style_message::StyleMessage;
+use crate::utils::{fd_limit, logger};
+
+pub struct StatusOptions {
+    pub path: PathBuf,
+    pub config_path: PathBuf,
+    pub thread_count: usize,
+    pub fetch: bool,
+    pub ignore: Option<Vec<String>>,
+    /// only report repos in any of these groups
+    pub groups: Option<Vec<String>>,
+    /// report the divergence from the upstream branch next to the manifest ref
+    pub upstream: bool,
+    /// compare with the upstream branch instead of the manifest ref
+    pub tracking: bool,
+}
+
+impl StatusOptions {
+    pub fn new(
+        path: Option<impl AsRef<Path>>,
+        config_path: Option<impl AsRef<Path>>,
+        thread: Option<usize>,
+        fetch: Option<bool>,
+        ignore: Option<Vec<String>>,
+    ) -> Self {
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
+        Self {
+            path,
+            config_path,
+            thread_count: thread.unwrap_or(4),
+            fetch: fetch.unwrap_or(false),
+            ignore,
+            groups: None,
+            upstream: false,
+            tracking: false,
+        }
+    }
+}
+
+pub fn status_repos(options: StatusOptions, progress: impl Progress) -> MgitResult {
+    let path = &options.path;
+    let config_path = &options.config_path;
+    let thread_count = options.thread_count;
+    let fetch = options.fetch;
+    let ignore = options.ignore.as_ref();
+    let upstream = options.upstream;
+    let tracking = options.tracking;
+
+    logger::info(StyleMessage::ops_start("status repos", path));
+
+    // check if .gitrepos exists
+    if !config_path.is_file() {
+        return Err(anyhow!(MgitError::ConfigFileNotFound(
+            StyleMessage::config_file_not_found(),
+        )));
+    }
+    // load config file(like .gitrepos)
+    let Some(toml_config) = TomlConfig::load(config_path) else {
+        return Err(anyhow!(MgitError::LoadConfigFailed));
+    };
+
+    let Some(toml_repos) = toml_config.repos else {
+        return Ok("No repos to report".into());
+    };
+    let default_branch = toml_config.default_branch;
+
+    // retain repos of the groups, exclude ignore repositories
+    let toml_repos = include_groups(toml_repos, options.groups.as_ref());
+    let repos_map = repos_to_map_with_ignore(toml_repos, ignore);
+
+    progress.repos_start(repos_map.len());
+
+    // use a counter
+    let counter = RelaxedCounter::new(1);
+
+    // create thread pool, and set the number of thread to use by using `.num_threads(count)`
+    let thread_builder =
+        rayon::ThreadPoolBuilder::new().num_threads(fd_limit::cap_thread_count(thread_count));
+    let Ok(thread_pool) = thread_builder.build() else {
+        return Err(anyhow!(MgitError::CreateThreadPoolFailed));
+    };
+
+    // pool.install means that `.par_iter()` will use the thread pool we've built above.
+    let errors: Vec<_> = thread_pool.install(|| {
+        let res = repos_map
+            .iter()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|(id, toml_repo)| {
+                let index = counter.inc();
+                let repo_info = RepoInfo::new(*id, index, toml_repo);
+
+                let progress = progress.clone();
+                progress.repo_start(&repo_info, "waiting...".into());
+
+                // repos not cloned yet have nothing to compare
+                if is_missing(path, toml_repo) {
+                    progress.repo_end(&repo_info, StyleMessage::git_repo_missing());
+                    return Ok(());
+                }
+
+                // only update remote-tracking refs, never touch the work tree
+                let exec_res = match fetch {
+                    true => exec_fetch(path, &repo_info, None, false, true, &progress),
+                    false => Ok(()),
+                }
+                .and_then(|_| match upstream {
+                    true => cmp_upstream_manifest(path, toml_repo, &default_branch),
+                    false => cmp_local_remote(path, toml_repo, &default_branch, tracking),
+                });
+
+                match exec_res {
+                    Ok(msg) => {
+                        progress.repo_end(&repo_info, msg);
+                        Ok(())
+                    }
+                    Err(e) => {
+                        progress.repo_error(&repo_info, StyleMessage::new());
+                        Err(StyleMessage::git_error(
+                            toml_repo.local.as_ref().unwrap().display_path(),
+                            &e,
+                        ))
+                    }
+                }
+            })
+            .filter_map(Result::err)
+            .collect();
+
+        progress.repos_end();
+        res
+    });
+
+    match errors.len() {
+        0 => Ok(StyleMessage::ops_success("status")),
+        _ => {
+            let msg = StyleMessage::ops_failed("status", errors.len());
+            Err(anyhow!(MgitError::OpsError {
+                prefix: msg,
+                errors: OpsErrors(errors),
+            }))
+        }
+    }
+}
+
+/// ahead/behind and local changes of each repo, in the order of the config file.
+///
+/// the library counterpart of `status_repos`, leaving rendering to the caller.
+pub fn status(options: StatusOptions) -> MgitResult<Vec<RepoStatus>> {
+    let path = &options.path;
+    let config_path = &options.config_path;
+    let fetch = options.fetch;
+    let ignore = options.ignore.as_ref();
+    let tracking = options.tracking;
+
+    // check if .gitrepos exists
+    if !config_path.is_file() {
+        return Err(anyhow!(MgitError::ConfigFileNotFound(
+            StyleMessage::config_file_not_found(),
+        )));
+    }
+    // load config file(like .gitrepos)
+    let Some(toml_config) = TomlConfig::load(config_path) else {
+        return Err(anyhow!(MgitError::LoadConfigFailed));
+    };
+
+    let Some(toml_repos) = toml_config.repos else {
+        return Ok(Vec::new());
+    };
+    let default_branch = toml_config.default_branch;
+
+    // retain repos of the groups, exclude ignore repositories
+    let toml_repos = include_groups(toml_repos, options.groups.as_ref());
+    let mut repos = repos_to_map_with_ignore(toml_repos, ignore)
+        .into_iter()
+        .collect::<Vec<_>>();
+    repos.sort_by_key(|(id, _)| *id);
+
+    // create thread pool, and set the number of thread to use by using `.num_threads(count)`
+    let thread_builder = rayon::ThreadPoolBuilder::new()
+        .num_threads(fd_limit::cap_thread_count(options.thread_count));
+    let Ok(thread_pool) = thread_builder.build() else {
+        return Err(anyhow!(MgitError::CreateThreadPoolFailed));
+    };
+
+    let results: Vec<_> = thread_pool.install(|| {
+        repos
+            .par_iter()
+            .map(|(id, toml_repo)| {
+                let repo_info = RepoInfo::new(*id, *id, toml_repo);
+                if is_missing(path, toml_repo) {
+                    return Ok(RepoStatus {
+                        local: repo_info.rel_path().display_path(),
+                        missing: true,
+                        ..Default::default()
+                    });
+                }
+
+                match fetch {
+                    true => exec_fetch(path, &repo_info, None, false, true, &SilentProgress),
+                    false => Ok(()),
+                }
+                .and_then(|_| repo_status(path, toml_repo, &default_branch, tracking))
+                .map_err(|e| {
+                    StyleMessage::git_error(toml_repo.local.as_ref().unwrap().display_path(), &e)
+                })
+            })
+            .collect()
+    });
+
+    let mut statuses = Vec::new();
+    let mut errors = Vec::new();
+    for result in results {
+        match result {
+            Ok(status) => statuses.push(status),
+            Err(e) => errors.push(e),
+        }
+    }
+
+    match errors.len() {
+        0 => Ok(statuses),
+        _ => {
+            let msg = StyleMessage::ops_failed("status", errors.len());
+            Err(anyhow!(MgitError::OpsError {
+                prefix: msg,
+                errors: OpsErrors(errors),
+            }))
+        }
+    }
+}
+
+/// divergence of a repo from its manifest ref along with the files making it dirty
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RepoFilesStatus {
+    pub local: String,
+    pub branch: String,
+    pub ahead: Option<usize>,
+    pub behind: Option<usize>,
+    pub staged: Vec<String>,
+    pub changed: Vec<String>,
+    pub untracked: Vec<String>,
+    /// whether the local branch points to the same commit as the manifest ref
+    pub matches_manifest: bool,
+}
+
+/// like `status`, listing the staged, changed and untracked files instead of counting them
+pub fn status_files(options: StatusOptions) -> MgitResult<Vec<RepoFilesStatus>> {
+    let mut statuses = Vec::new();
+    for_each_files_status(options, |status| {
+        statuses.push(status);
+        Ok(())
+    })?;
+    Ok(statuses)
+}
+
+/// write the `status_files` of each repo as one JSON array, a repo at a time so only the
+/// file lists of one repo are held in memory, return the number of repos written
+pub fn status_files_to_writer(options: StatusOptions, mut writer: impl Write) -> MgitResult<usize> {
+    let mut count = 0;
+    write!(writer, "[")?;
+    for_each_files_status(options, |status| {
+        if count > 0 {
+            write!(writer, ",")?;
+        }
+        write!(writer, "\n  ")?;
+        serde_json::to_writer(&mut writer, &status)?;
+        count += 1;
+        Ok(())
+    })?;
+    match count {
+        0 => writeln!(writer, "]")?,
+        _ => writeln!(writer, "\n]")?,
+    }
+    writer.flush()?;
+    Ok(count)
+}
+
+fn for_each_files_status(
+    options: StatusOptions,
+    mut f: impl FnMut(RepoFilesStatus) -> anyhow::Result<()>,
+) -> MgitResult<()> {
+    let path = options.path.clone();
+    let lines = |output: String| output.lines().map(str::to_string).collect::<Vec<_>>();
+
+    // divergence is compared in parallel, then files are listed repo by repo
+    let mut errors = Vec::new();
+    for status in self::status(options)? {
+        // a repo not cloned yet has no files to list
+        if status.missing {
+            continue;
+        }
+        let full_path = path.join(&status.local);
+        let files = git::get_staged_files(&full_path).and_then(|staged| {
+            let changed = git::get_changed_files(&full_path)?;
+            let untracked = git::get_untrack_files(&full_path)?;
+            Ok((staged, changed, untracked))
+        });
+
+        match files {
+            Ok((staged, changed, untracked)) => f(RepoFilesStatus {
+                staged: lines(staged),
+                changed: lines(changed),
+                untracked: lines(untracked),
+                local: status.local,
+                branch: status.branch,
+                ahead: status.ahead,
+                behind: status.behind,
+                matches_manifest: status.matches_ref,
+            })?,
+            Err(e) => errors.push(StyleMessage::git_error(&status.local, &e)),
+        }
+    }
+
+    match errors.len() {
+        0 => Ok(()),
+        _ => {
+            let msg = StyleMessage::ops_failed("status", errors.len());
+            Err(anyhow!(MgitError::OpsError {
+                prefix: msg,
+                errors: OpsErrors(errors),
+            }))
+        }
+    }
+}
+
+fn is_missing(path: &Path, toml_repo: &TomlRepo) -> bool {
+    let full_path = path.join(toml_repo.local.as_ref().unwrap());
+    git::is_repository(full_path) == Err(RepositoryError::NotFound)
+}
+
+/// fetch without reporting, for callers that render the result themselves
+#[derive(Clone)]
+struct SilentProgress;
+
+impl Progress for SilentProgress {
+    fn repos_start(&self, _total: usize) {}
+
+    fn repos_end(&self) {}
+
+    fn repo_start(&self, _repo_info: &RepoInfo, _message: StyleMessage) {}
+
+    fn repo_info(&self, _repo_info: &RepoInfo, _message: StyleMessage) {}
+
+    fn repo_end(&self, _repo_info: &RepoInfo, _message: StyleMessage) {}
+
+    fn repo_error(&self, _repo_info: &RepoInfo, _message: StyleMessage) {}
+}
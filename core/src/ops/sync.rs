@@ -2,26 +2,57 @@ use anyhow::{anyhow, Context};
 use atomic_counter::{AtomicCounter, RelaxedCounter};
 use rayon::iter::IntoParallelIterator;
 use rayon::iter::ParallelIterator;
-use std::env;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use walkdir::WalkDir;
 
 use crate::core::git;
-use crate::core::git::{RemoteRef, ResetType, StashMode};
-use crate::core::repo::{cmp_local_remote, repos_to_map_with_ignore};
-use crate::core::repos::TomlConfig;
+use crate::core::git::{RemoteRef, RepositoryError, ResetType, StashMode};
+use crate::core::repo::{
+    cmp_local_remote, include_groups, repo_status, repos_to_map_with_ignore, RepoStatus, TomlRepo,
+};
+use crate::core::repos::{TomlConfig, WorkspacePaths};
 
+use crate::ops::fetch::{fetch_args, shallow_depth};
 use crate::ops::CleanOptions;
 use crate::ops::{clean_repo, exec_fetch, set_tracking_remote_branch};
 use crate::utils::error::{MgitError, MgitResult, OpsErrors};
+use crate::utils::host_limiter::{HostLimiter, HostPermit};
 
-use crate::utils::logger;
+use crate::utils::cmd::retry;
 use crate::utils::path::PathExtension;
 use crate::utils::progress::{Progress, RepoInfo};
+use crate::utils::repo_list::load_repo_list;
 use crate::utils::style_message::StyleMessage;
+use crate::utils::sync_state::{RepoSyncState, SyncState};
+use crate::utils::timings::{Phase, Timings};
+use crate::utils::{cmd, fd_limit, logger};
 
 #[derive(Debug, Default)]
 struct InnerExecResponse {
     stash: Option<InnerStashResponse>,
+    /// local changes swept into the stash, with `--report-untracked`
+    stashed_files: Vec<String>,
+    /// what happened to a stash whose pop conflicted
+    stash_conflict: Option<String>,
+    /// checkouts of submodules the hard reset dropped, removed afterwards
+    stale_submodules: Vec<String>,
+}
+
+/// marks failures of the stash/checkout/reset phase, which a fresh clone may recover.
+///
+/// it is attached as context with an empty message, so the reported error is unchanged.
+#[derive(Debug)]
+struct CheckoutFailed;
+
+impl std::fmt::Display for CheckoutFailed {
+    fn fmt(&self, _: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -30,6 +61,52 @@ enum InnerStashResponse {
     Stash(String),
 }
 
+/// resolved execution plan of sync, written by `--dump-plan`
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncPlan {
+    pub repos: Vec<RepoPlan>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RepoPlan {
+    pub local: String,
+    pub remote: String,
+    pub remote_name: String,
+    pub remote_ref: RemoteRef,
+    pub stash_mode: StashMode,
+    /// args of the git commands in execution order, written as command lines
+    #[serde(serialize_with = "serialize_commands")]
+    pub commands: Vec<Vec<String>>,
+}
+
+fn serialize_commands<S: serde::Serializer>(
+    commands: &[Vec<String>],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(commands.iter().map(|args| cmd::display_args("git", args)))
+}
+
+/// what to do with a manifest repo whose directory is missing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OnMissing {
+    Skip,
+    #[default]
+    Clone,
+    Error,
+}
+
+/// what to do when popping the stash back after checkout conflicts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OnStashConflict {
+    /// leave the conflicts in the work tree, to resolve and `sync --continue`
+    #[default]
+    Keep,
+    /// undo the pop and leave the changes in the stash
+    Restash,
+    /// undo the pop and move the changes to a `mgit-stash/<time>` branch
+    Branch,
+}
+
 pub struct SyncOptions {
     pub path: PathBuf,
     pub config_path: PathBuf,
@@ -37,10 +114,75 @@ pub struct SyncOptions {
     pub silent: bool,
     pub depth: Option<usize>,
     pub ignore: Option<Vec<String>>,
+    /// only sync repos in any of these groups
+    pub groups: Option<Vec<String>>,
     pub hard: bool,
     pub stash: bool,
     pub no_track: bool,
     pub no_checkout: bool,
+    pub max_clone_mb: Option<u64>,
+    pub continue_sync: bool,
+    pub fetch_pinned_commit: bool,
+    pub dump_plan: Option<PathBuf>,
+    pub jobs_per_host: Option<usize>,
+    pub ignore_errors_from: Option<PathBuf>,
+    pub on_missing: OnMissing,
+    pub timings: bool,
+    pub clean_excludes: Vec<String>,
+    pub report_untracked: bool,
+    pub strict: bool,
+    /// stash only tracked changes, leaving untracked files in the work tree
+    pub keep_untracked: bool,
+    /// keep bare `--mirror` clones of all repos instead of work trees
+    pub mirror: bool,
+    /// print the local paths of failed repos, one per line, to stderr
+    pub print_failed_paths: bool,
+    /// write the failed paths to the file instead of stderr
+    pub failed_paths_file: Option<PathBuf>,
+    /// more config files whose repos are synced together with the ones of `config_path`
+    pub extra_config_paths: Vec<PathBuf>,
+    /// fetch with the skipping negotiator and protocol v2
+    pub fast_fetch: bool,
+    /// remove remote-tracking branches whose upstream branch was deleted when fetching,
+    /// on by default
+    pub prune: bool,
+    /// keep sparse checkouts in pattern mode even when cone mode is possible
+    pub no_cone: bool,
+    /// init and update submodules after the checkout, for repos without `recurse-submodules`
+    pub recurse_submodules: bool,
+    /// refs used instead of the manifest ones, by repo local path
+    pub ref_overrides: Vec<(String, RemoteRef)>,
+    /// previous config file, only repos whose pin changed since it are synced
+    pub since_snapshot: Option<PathBuf>,
+    pub on_stash_conflict: OnStashConflict,
+    /// remove and clone again repos whose checkout keeps failing, if they have no local changes
+    pub reclone_on_failure: bool,
+    /// leave the repos not started yet once a repo fails, instead of syncing all of them
+    pub fail_fast: bool,
+    /// clone and checkout without downloading LFS files, `git lfs pull` fetches them later
+    pub skip_lfs_smudge: bool,
+    /// kill each git command still running after this long, the repo fails with a timeout error
+    pub timeout: Option<Duration>,
+    /// check out the git repo holding the config file at this ref before reading it
+    pub self_update: Option<String>,
+    /// materialize commit/tag pins from the index, without moving HEAD or creating branches
+    pub checkout_index_only: bool,
+    /// pass the output of clone/fetch and of failed git commands through, prefixed with the repo
+    pub verbose_git: bool,
+    /// branch created at the synced ref in every repo, to start work across repos
+    pub work_branch: Option<String>,
+    /// move an existing `work_branch` instead of skipping the repo
+    pub force_work_branch: bool,
+    /// leave repos already at the remote ref with a clean work tree as they are
+    pub skip_up_to_date: bool,
+    /// after a successful sync, write the config with every repo pinned to its commit there
+    pub write_lock: Option<PathBuf>,
+    /// restore the repos an interrupted sync left behind, instead of syncing
+    pub abort: bool,
+    /// `http.proxy` of clone and fetch for the repos without a `proxy` in the config
+    pub proxy: Option<String>,
+    /// describe what sync would do to every repo, without touching them
+    pub dry_run: bool,
 }
 
 impl SyncOptions {
@@ -57,8 +199,7 @@ impl SyncOptions {
         no_track: Option<bool>,
         no_checkout: Option<bool>,
     ) -> Self {
-        let path = path.map_or(env::current_dir().unwrap(), |p| p.as_ref().to_path_buf());
-        let config_path = config_path.map_or(path.join(".gitrepos"), |p| p.as_ref().to_path_buf());
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
         Self {
             path,
             config_path,
@@ -66,10 +207,48 @@ impl SyncOptions {
             silent: silent.unwrap_or(false),
             depth,
             ignore,
+            groups: None,
             hard: hard.unwrap_or(false),
             stash: stash.unwrap_or(false),
             no_track: no_track.unwrap_or(false),
             no_checkout: no_checkout.unwrap_or(false),
+            max_clone_mb: None,
+            continue_sync: false,
+            fetch_pinned_commit: false,
+            dump_plan: None,
+            jobs_per_host: None,
+            ignore_errors_from: None,
+            on_missing: OnMissing::Clone,
+            timings: false,
+            clean_excludes: Vec::new(),
+            report_untracked: false,
+            strict: false,
+            keep_untracked: false,
+            mirror: false,
+            print_failed_paths: false,
+            failed_paths_file: None,
+            extra_config_paths: Vec::new(),
+            fast_fetch: false,
+            prune: true,
+            no_cone: false,
+            recurse_submodules: false,
+            ref_overrides: Vec::new(),
+            since_snapshot: None,
+            on_stash_conflict: OnStashConflict::Keep,
+            reclone_on_failure: false,
+            fail_fast: false,
+            skip_lfs_smudge: false,
+            timeout: None,
+            self_update: None,
+            checkout_index_only: false,
+            verbose_git: false,
+            work_branch: None,
+            force_work_branch: false,
+            skip_up_to_date: true,
+            write_lock: None,
+            proxy: None,
+            dry_run: false,
+            abort: false,
         }
     }
 }
@@ -85,15 +264,27 @@ pub fn sync_repo(options: SyncOptions, progress: impl Progress) -> MgitResult {
     let no_checkout = options.no_checkout;
     let depth = options.depth.as_ref().copied();
     let ignore = options.ignore.as_ref();
+    let max_clone_mb = options.max_clone_mb;
+    let on_missing = options.on_missing;
+    let reclone_on_failure = options.reclone_on_failure;
+    let work_branch = options.work_branch.as_ref();
 
     logger::info(StyleMessage::ops_start("sync repos", path));
-    let stash_mode = match (stash, hard) {
-        (false, false) => StashMode::Normal,
-        (true, false) => StashMode::Stash,
-        (false, true) => StashMode::Hard,
+    // set on every sync, so a previous run in the same process doesn't leak into this one
+    cmd::set_skip_lfs_smudge(options.skip_lfs_smudge);
+    cmd::set_verbose_git(options.verbose_git);
+    let stash_override = match (stash, hard) {
+        (false, false) => None,
+        (true, false) => Some(StashMode::Stash),
+        (false, true) => Some(StashMode::Hard),
         _ => panic!("'--stash' and '--hard' can't be used together."),
     };
 
+    // only restore repos recorded by an interrupted sync, the config file isn't needed
+    if options.abort {
+        return abort_repos(path);
+    }
+
     // check if .gitrepos exists
     if !config_path.is_file() {
         return Err(anyhow!(MgitError::ConfigFileNotFound(
@@ -101,18 +292,105 @@ pub fn sync_repo(options: SyncOptions, progress: impl Progress) -> MgitResult {
         )));
     }
 
-    // load config file(like .gitrepos)
-    let Some(toml_config) = TomlConfig::load(config_path) else {
-        return Err(anyhow!(MgitError::LoadConfigFailed));
+    // the pinned manifest drives everything, so update it before reading it
+    if let Some(git_ref) = options.self_update.as_ref() {
+        logger::info(self_update(config_path, git_ref)?);
+    }
+
+    // load config files(like .gitrepos), extra ones add their repos to the same run
+    let config_paths = std::iter::once(config_path)
+        .chain(&options.extra_config_paths)
+        .collect::<Vec<_>>();
+    let mut toml_config = TomlConfig::load_merged(&config_paths)?;
+    apply_ref_overrides(&mut toml_config, &options.ref_overrides)?;
+
+    // the lockfile keeps everything of the config but the refs
+    let lock_config = options.write_lock.as_ref().map(|_| toml_config.clone());
+
+    // taken after the lockfile, a proxy of the command line is never written to it
+    if let Some(proxy) = options.proxy.as_ref() {
+        for toml_repo in toml_config.repos.iter_mut().flatten() {
+            toml_repo.proxy.get_or_insert_with(|| proxy.clone());
+        }
+    }
+
+    // taken after the lockfile too, which keeps the repos of other groups
+    if let Some(toml_repos) = toml_config.repos.take() {
+        toml_config.repos = Some(include_groups(toml_repos, options.groups.as_ref()));
+    }
+
+    // `--stash`/`--hard` win over the manifest `sync-strategy`
+    let stash_mode = stash_override
+        .or(toml_config.sync_strategy.clone())
+        .unwrap_or(StashMode::Normal);
+
+    // failures of these repos are reported as warnings and don't fail the sync
+    let ignore_errors = match options.ignore_errors_from.as_ref() {
+        Some(list_path) => load_repo_list(list_path)?,
+        None => HashSet::new(),
+    };
+
+    // only finish repos stopped on conflicts, leave the others untouched
+    if options.continue_sync {
+        return continue_repos(path, toml_config, ignore);
+    }
+
+    // patterns of the manifest are kept together with the ones of the command line
+    let clean_excludes = toml_config
+        .clean_exclude
+        .iter()
+        .flatten()
+        .chain(&options.clean_excludes)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let host_limiter = options.jobs_per_host.map(HostLimiter::new);
+    let timings = options.timings.then(Timings::default);
+    let sync_state = SyncState::load(path)?;
+    let exec_options = InnerExecOptions {
+        stash_mode: &stash_mode,
+        no_checkout,
+        fetch_pinned_commit: options.fetch_pinned_commit,
+        depth: depth.as_ref(),
+        host_limiter: host_limiter.as_ref(),
+        timings: timings.as_ref(),
+        clean_excludes: &clean_excludes,
+        report_untracked: options.report_untracked,
+        keep_untracked: options.keep_untracked,
+        mirror: options.mirror,
+        fast_fetch: options.fast_fetch,
+        prune: options.prune,
+        no_cone: options.no_cone,
+        recurse_submodules: options.recurse_submodules,
+        on_stash_conflict: options.on_stash_conflict,
+        checkout_index_only: options.checkout_index_only,
+        skip_up_to_date: options.skip_up_to_date,
+        timeout: options.timeout,
+        sync_state: &sync_state,
     };
 
+    // write the plan without executing anything
+    if let Some(plan_path) = options.dump_plan.as_ref() {
+        return dump_plan(path, toml_config, ignore, &exec_options, plan_path);
+    }
+
+    // describe the plan without executing anything
+    if options.dry_run {
+        return dry_run(path, toml_config, ignore, &exec_options);
+    }
+
+    // a new sync would forget where the repos of an interrupted one came from
+    let interrupted = sync_state.repos().into_keys().collect::<Vec<_>>();
+    if !interrupted.is_empty() {
+        return Err(anyhow!(StyleMessage::sync_interrupted(&interrupted)));
+    }
+
     // remove unused repositories when use '--config' option
     // also if input_path not exists, skip this process
     if stash_mode == StashMode::Hard && path.is_dir() {
-        let res = clean_repo(CleanOptions::new(
-            Some(path.clone()),
-            Some(config_path.clone()),
-        ))?;
+        let mut clean_options = CleanOptions::new(Some(path.clone()), Some(config_path.clone()));
+        clean_options.extra_config_paths = options.extra_config_paths.clone();
+        let res = clean_repo(clean_options)?;
 
         logger::info(res);
     }
@@ -125,37 +403,166 @@ pub fn sync_repo(options: SyncOptions, progress: impl Progress) -> MgitResult {
     let default_branch = toml_config.default_branch;
 
     // retain repos exclude ignore repositories
-    let repos_map = repos_to_map_with_ignore(toml_repos, ignore);
+    let mut repos_map = repos_to_map_with_ignore(toml_repos, ignore);
+
+    // skip repos pinned the same way as in the previous config file
+    if let Some(snapshot_path) = options.since_snapshot.as_ref() {
+        let unchanged = unchanged_since_snapshot(snapshot_path, &repos_map, &default_branch)?;
+        if !unchanged.is_empty() {
+            let mut unchanged_repos = unchanged
+                .iter()
+                .filter_map(|id| repos_map.remove(id))
+                .map(|toml_repo| toml_repo.local.unwrap().display_path())
+                .collect::<Vec<_>>();
+            unchanged_repos.sort();
+            logger::info(StyleMessage::unchanged_since_snapshot(&unchanged_repos));
+        }
+    }
+
+    // a shallow fetch may not reach a pinned commit, decide before syncing anything
+    let mut pinned_repos = repos_map
+        .values()
+        .filter(|toml_repo| {
+            toml_repo.commit.is_some() && toml_repo.fetch_depth(depth.as_ref()).is_some()
+        })
+        .map(|toml_repo| toml_repo.local.as_ref().unwrap().display_path())
+        .collect::<Vec<_>>();
+    pinned_repos.sort();
+
+    if !pinned_repos.is_empty() {
+        if options.strict {
+            return Err(anyhow!(StyleMessage::depth_with_commit_pins(&pinned_repos)));
+        }
+        logger::info(StyleMessage::depth_unshallow_fallback(&pinned_repos));
+    }
 
     progress.repos_start(repos_map.len());
 
     // create thread pool, and set the number of thread to use by using `.num_threads(count)`
     let counter = RelaxedCounter::new(1);
-    let thread_builder = rayon::ThreadPoolBuilder::new().num_threads(thread_count);
+    let thread_builder =
+        rayon::ThreadPoolBuilder::new().num_threads(fd_limit::cap_thread_count(thread_count));
     let Ok(thread_pool) = thread_builder.build() else {
         return Err(anyhow!(MgitError::CreateThreadPoolFailed));
     };
 
     struct SuccRepoInfo {
         stash_status: StyleMessage,
+        stashed_files: StyleMessage,
+        stash_conflict: StyleMessage,
         track_status: StyleMessage,
+        /// local of a repo cloned again by `--reclone-on-failure`, and where its old
+        /// clone was moved to
+        recloned: Option<(String, String)>,
+        work_branch_status: StyleMessage,
+        stale_submodules: StyleMessage,
+    }
+
+    enum ParallelResult {
+        Succ(SuccRepoInfo),
+        Skip(StyleMessage),
+        Warn(StyleMessage),
+        Error(String, StyleMessage),
     }
 
-    type ParallelResult<'a> = Result<SuccRepoInfo, StyleMessage>;
+    // set by the first failure with `fail_fast`, repos starting after it are skipped
+    let cancelled = AtomicBool::new(false);
+
+    // failures of repos in the known-bad list are downgraded to warnings
+    let error_result = |repo_rel_path: &String, e: &anyhow::Error| {
+        let error_msg = StyleMessage::git_error(repo_rel_path, e);
+        match ignore_errors.contains(&repo_rel_path.norm_path()) {
+            true => ParallelResult::Warn(error_msg),
+            false => {
+                if options.fail_fast {
+                    cancelled.store(true, Ordering::Relaxed);
+                }
+                ParallelResult::Error(repo_rel_path.clone(), error_msg)
+            }
+        }
+    };
 
     // pool.install means that `.par_iter()` will use the thread pool we've built above.
-    let (succ_repos, error_repos) = thread_pool.install(|| {
+    let (succ_repos, skip_repos, warn_repos, error_repos) = thread_pool.install(|| {
         let res: Vec<ParallelResult> = repos_map
             .iter()
             .collect::<Vec<_>>()
             .into_par_iter()
             .map(|(id, toml_repo)| {
+                // only the git commands of this repo are limited, on the thread running them
+                let _timeout = cmd::GitTimeout::scoped(exec_options.timeout);
                 let index = counter.inc();
                 let mut repo_info = RepoInfo::new(*id, index, toml_repo);
 
                 let progress = progress.clone();
                 progress.repo_start(&repo_info, "waiting...".into());
 
+                if cancelled.load(Ordering::Relaxed) {
+                    let msg = StyleMessage::git_cancelled();
+                    progress.repo_end(&repo_info, msg.clone());
+                    let repo_rel_path = repo_info.rel_path().display_path();
+                    return ParallelResult::Skip(StyleMessage::git_skip(repo_rel_path, msg));
+                }
+
+                let full_path = path.join(repo_info.rel_path());
+                let is_mirror = is_mirror(&exec_options, toml_repo);
+                let is_missing = match is_mirror {
+                    true => !git::is_bare_repository(&full_path),
+                    false => git::is_repository(&full_path) == Err(RepositoryError::NotFound),
+                };
+
+                // only clone missing repos when asked to
+                if is_missing && on_missing != OnMissing::Clone {
+                    let msg = StyleMessage::git_repo_missing();
+                    let repo_rel_path = repo_info.rel_path().display_path();
+                    if on_missing == OnMissing::Error {
+                        progress.repo_error(&repo_info, msg.clone());
+                        return error_result(&repo_rel_path, &anyhow!(msg));
+                    }
+                    progress.repo_end(&repo_info, msg.clone());
+                    return ParallelResult::Skip(StyleMessage::git_skip(repo_rel_path, msg));
+                }
+
+                // skip fresh clones which would exceed the disk budget
+                if let Some(max_clone_mb) = max_clone_mb {
+                    if is_missing {
+                        if let Some(size) = estimate_clone_size_mb(&full_path, toml_repo) {
+                            if size > max_clone_mb {
+                                let msg = StyleMessage::git_clone_too_large(size, max_clone_mb);
+                                progress.repo_end(&repo_info, msg.clone());
+
+                                let repo_rel_path = repo_info.rel_path().display_path();
+                                return ParallelResult::Skip(StyleMessage::git_skip(
+                                    repo_rel_path,
+                                    msg,
+                                ));
+                            }
+                        }
+                    }
+                }
+
+                // a mirror has no work tree to compare, stash, checkout or track
+                if is_mirror {
+                    return match exec_mirror(path, &repo_info, &exec_options, &progress) {
+                        Ok(msg) => {
+                            progress.repo_end(&repo_info, msg);
+                            ParallelResult::Succ(SuccRepoInfo {
+                                stash_status: StyleMessage::new(),
+                                stashed_files: StyleMessage::new(),
+                                stash_conflict: StyleMessage::new(),
+                                track_status: StyleMessage::new(),
+                                recloned: None,
+                                work_branch_status: StyleMessage::new(),
+                                stale_submodules: StyleMessage::new(),
+                            })
+                        }
+                        Err(e) => {
+                            progress.repo_error(&repo_info, StyleMessage::new());
+                            error_result(&repo_info.rel_path().display_path(), &e)
+                        }
+                    };
+                }
+
                 // get compare stat betwwen local and specified commit/tag/branch/
                 let mut pre_cmp_msg = StyleMessage::new();
                 if !silent {
@@ -164,16 +571,54 @@ pub fn sync_repo(options: SyncOptions, progress: impl Progress) -> MgitResult {
                 }
 
                 // execute command according each repo status
-                let exec_res = inner_exec(
+                let mut exec_res = inner_exec(
                     path,
                     &mut repo_info,
-                    &stash_mode,
-                    no_checkout,
-                    depth.as_ref(),
+                    &exec_options,
                     &default_branch,
                     &progress,
                 );
 
+                // a wedged work tree: try once more, then start over from a fresh clone
+                let mut recloned = None;
+                if reclone_on_failure && !is_missing && is_checkout_failed(&exec_res) {
+                    exec_res = inner_exec(
+                        path,
+                        &mut repo_info,
+                        &exec_options,
+                        &default_branch,
+                        &progress,
+                    );
+                    let rel_path = repo_info.rel_path().display_path();
+                    if is_checkout_failed(&exec_res)
+                        && can_reclone(&full_path, &rel_path, &repos_map)
+                    {
+                        progress.repo_info(&repo_info, "reclone...".into());
+                        // the old clone is moved aside, ignored files like local settings
+                        // are still there after the reclone
+                        let backup = reclone_backup_path(&full_path);
+                        exec_res = std::fs::rename(&full_path, &backup)
+                            .with_context(|| format!("move {} aside failed.", rel_path))
+                            .and_then(|_| {
+                                inner_exec(
+                                    path,
+                                    &mut repo_info,
+                                    &exec_options,
+                                    &default_branch,
+                                    &progress,
+                                )
+                            });
+                        match exec_res.is_ok() {
+                            true => recloned = Some(backup),
+                            false if backup.exists() => {
+                                let _ = std::fs::remove_dir_all(&full_path);
+                                let _ = std::fs::rename(&backup, &full_path);
+                            }
+                            false => {}
+                        }
+                    }
+                }
+
                 // handle result
                 match exec_res {
                     Ok(response) => {
@@ -205,6 +650,33 @@ pub fn sync_repo(options: SyncOptions, progress: impl Progress) -> MgitResult {
                                 stash_status.join(StyleMessage::git_stash(repo_rel_path, msg));
                         }
 
+                        // files swept into the stash
+                        let mut stashed_files = StyleMessage::new();
+                        if !response.stashed_files.is_empty() {
+                            let repo_rel_path = toml_repo.local.as_ref().unwrap().display_path();
+                            stashed_files = StyleMessage::git_stashed_files(
+                                repo_rel_path,
+                                &response.stashed_files,
+                            );
+                        }
+
+                        // stash pop conflict: where the local changes are left
+                        let mut stash_conflict = StyleMessage::new();
+                        if let Some(desc) = response.stash_conflict {
+                            let repo_rel_path = toml_repo.local.as_ref().unwrap().display_path();
+                            stash_conflict = StyleMessage::git_stash(repo_rel_path, desc);
+                        }
+
+                        // submodule checkouts removed after the reset
+                        let mut stale_submodules = StyleMessage::new();
+                        if !response.stale_submodules.is_empty() {
+                            let repo_rel_path = toml_repo.local.as_ref().unwrap().display_path();
+                            stale_submodules = StyleMessage::git_stale_submodules(
+                                repo_rel_path,
+                                &response.stale_submodules,
+                            );
+                        }
+
                         // track status: track remote branch
                         let mut track_status = StyleMessage::new();
                         if !no_track {
@@ -213,18 +685,49 @@ pub fn sync_repo(options: SyncOptions, progress: impl Progress) -> MgitResult {
                             track_status = track_status.try_join(track_res.ok());
                         }
 
+                        // work branch: start from the synced ref, after tracking is set up
+                        // for the manifest branch so the new branch stays untracked
+                        let mut work_branch_status = StyleMessage::new();
+                        if let Some(work_branch) = work_branch {
+                            let repo_rel_path = toml_repo.local.as_ref().unwrap().display_path();
+                            let res = create_work_branch(
+                                &full_path,
+                                work_branch,
+                                options.force_work_branch,
+                            );
+                            work_branch_status = match res {
+                                Ok(created) => StyleMessage::git_work_branch(
+                                    repo_rel_path,
+                                    work_branch,
+                                    created,
+                                ),
+                                Err(e) => StyleMessage::git_error(repo_rel_path, &e),
+                            };
+                        }
+
                         let info = SuccRepoInfo {
                             stash_status,
+                            stashed_files,
+                            stash_conflict,
                             track_status,
+                            recloned: recloned.map(|backup| {
+                                let backup = backup.strip_prefix(path).unwrap_or(&backup);
+                                (
+                                    toml_repo.local.as_ref().unwrap().display_path(),
+                                    backup.display_path(),
+                                )
+                            }),
+                            work_branch_status,
+                            stale_submodules,
                         };
-                        Ok(info)
+                        ParallelResult::Succ(info)
                     }
                     Err(e) => {
                         // show message in progress bar
                         progress.repo_error(&repo_info, StyleMessage::new());
 
                         let repo_rel_path = toml_repo.local.as_ref().unwrap().display_path();
-                        Err(StyleMessage::git_error(repo_rel_path, &e))
+                        error_result(&repo_rel_path, &e)
                     }
                 }
             })
@@ -234,19 +737,99 @@ pub fn sync_repo(options: SyncOptions, progress: impl Progress) -> MgitResult {
 
         // collect repos
         let mut succ_repos = Vec::new();
+        let mut skip_repos = Vec::new();
+        let mut warn_repos = Vec::new();
         let mut error_repos = Vec::new();
         for r in res {
             match r {
-                Ok(info) => succ_repos.push(info),
-                Err(error_msg) => error_repos.push(error_msg),
+                ParallelResult::Succ(info) => succ_repos.push(info),
+                ParallelResult::Skip(skip_msg) => skip_repos.push(skip_msg),
+                ParallelResult::Warn(warn_msg) => warn_repos.push(warn_msg),
+                ParallelResult::Error(rel_path, error_msg) => {
+                    error_repos.push((rel_path, error_msg))
+                }
             }
         }
-        (succ_repos, error_repos)
+        (succ_repos, skip_repos, warn_repos, error_repos)
     });
 
+    let (error_paths, error_repos): (Vec<_>, Vec<_>) = error_repos.into_iter().unzip();
+
+    // skipped repos are reported whether sync succeeded or not
+    let mut skip_status = StyleMessage::new();
+    if !skip_repos.is_empty() {
+        skip_status = skip_status.join("Skip status:\n".into());
+        for msg in &skip_repos {
+            skip_status = skip_status.join(format!("  {}\n", msg).into());
+        }
+    }
+
+    // failures of known-bad repos are reported without counting as errors
+    if !warn_repos.is_empty() {
+        skip_status = skip_status.join("Warning status:\n".into());
+        for msg in &warn_repos {
+            skip_status = skip_status.join(format!("  {}\n", msg).into());
+        }
+    }
+
+    // recloned repos lost their old clone, report them whether sync succeeded or not
+    if succ_repos.iter().any(|info| info.recloned.is_some()) {
+        skip_status = skip_status.join("Recloned repos:\n".into());
+        for (rel_path, backup) in succ_repos.iter().filter_map(|info| info.recloned.as_ref()) {
+            skip_status = skip_status.join(StyleMessage::git_recloned(rel_path, backup));
+        }
+    }
+
+    // conflicted stash pops are reported whether sync succeeded or not
+    if succ_repos
+        .iter()
+        .any(|info| !info.stash_conflict.is_empty())
+    {
+        skip_status = skip_status.join("Stash conflicts:\n".into());
+        for info in &succ_repos {
+            if info.stash_conflict.is_empty() {
+                continue;
+            }
+            skip_status = skip_status.join(format!("  {}\n", info.stash_conflict).into());
+        }
+    }
+
+    // removed submodule checkouts are reported whether sync succeeded or not
+    if succ_repos
+        .iter()
+        .any(|info| !info.stale_submodules.is_empty())
+    {
+        skip_status = skip_status.join("Removed submodules:\n".into());
+        for info in &succ_repos {
+            skip_status = skip_status.join(info.stale_submodules.clone());
+        }
+    }
+
+    // created work branches are reported whether sync succeeded or not
+    if work_branch.is_some() {
+        skip_status = skip_status.join("Work branches:\n".into());
+        for info in &succ_repos {
+            if info.work_branch_status.is_empty() {
+                continue;
+            }
+            skip_status = skip_status.join(format!("  {}\n", info.work_branch_status).into());
+        }
+    }
+
+    // timings are reported last, whether sync succeeded or not
+    let mut timings_status = StyleMessage::new();
+    if let Some(timings) = timings.as_ref() {
+        timings_status = timings_status.join("\n".into()).join(timings.summary());
+    }
+
+    // bare list of failed repos for other tooling, kept apart from the styled report
+    if options.print_failed_paths || options.failed_paths_file.is_some() {
+        print_failed_paths(&error_paths, options.failed_paths_file.as_ref())?;
+    }
+
     match error_repos.len() {
         0 => {
-            let mut result = StyleMessage::ops_success("sync");
+            let mut result = StyleMessage::ops_success("sync").join(skip_status);
             // show track status
             if !silent {
                 // show stash status
@@ -268,10 +851,30 @@ pub fn sync_repo(options: SyncOptions, progress: impl Progress) -> MgitResult {
                     result = result.join(format!("  {}\n", info.track_status).into());
                 }
             }
-            Ok(result)
+
+            // show files swept into the stash
+            if succ_repos.iter().any(|info| !info.stashed_files.is_empty()) {
+                result = result.join("\n".into());
+                result = result.join("Stashed files:\n".into());
+                for info in &succ_repos {
+                    if info.stashed_files.is_empty() {
+                        continue;
+                    }
+                    result = result.join(info.stashed_files.clone());
+                }
+            }
+
+            // pin the commits just synced
+            if let (Some(lock_path), Some(lock_config)) = (options.write_lock.as_ref(), lock_config)
+            {
+                result = result.join(write_lock(path, lock_config, lock_path)?);
+            }
+            Ok(result.join(timings_status))
         }
         _ => {
-            let msg = StyleMessage::ops_failed("sync", error_repos.len());
+            let msg = StyleMessage::ops_failed("sync", error_repos.len())
+                .join(skip_status)
+                .join(timings_status);
             Err(anyhow!(MgitError::OpsError {
                 prefix: msg,
                 errors: OpsErrors(error_repos),
@@ -280,39 +883,654 @@ pub fn sync_repo(options: SyncOptions, progress: impl Progress) -> MgitResult {
     }
 }
 
+/// write `lock_config` with the commit of every repo in place of its tag, the branch stays
+/// for tracking; repos without a commit, like skipped missing ones, keep their refs
+fn write_lock(
+    path: &Path,
+    mut lock_config: TomlConfig,
+    lock_path: &Path,
+) -> anyhow::Result<StyleMessage> {
+    let mut unlocked = Vec::new();
+    for toml_repo in lock_config.repos.iter_mut().flatten() {
+        let local = toml_repo.local.as_ref().unwrap().display_path();
+        match git::get_current_commit(path.join(&local)) {
+            Ok(commit) => {
+                toml_repo.commit = Some(commit);
+                toml_repo.tag = None;
+            }
+            Err(_) => unlocked.push(local),
+        }
+    }
+
+    lock_config.save(lock_path)?;
+    Ok(StyleMessage::lock_written(lock_path, &unlocked))
+}
+
+/// create `branch` at the synced ref, returns false when it already exists and isn't forced
+fn create_work_branch(full_path: &Path, branch: &str, force: bool) -> anyhow::Result<bool> {
+    if !force && git::local_branch_already_exist(full_path, branch)? {
+        return Ok(false);
+    }
+    git::checkout_new_branch(full_path, branch, force)?;
+    Ok(true)
+}
+
+/// check out the repo holding the config file at `git_ref`, refusing to touch local changes
+fn self_update(config_path: &Path, git_ref: &str) -> anyhow::Result<StyleMessage> {
+    let config_dir = match config_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let manifest_path = git::toplevel(config_dir).map_err(|_| {
+        anyhow!(
+            "--self-update: {} is not in a git repo",
+            config_path.display()
+        )
+    })?;
+
+    // untracked files such as nested repos survive a checkout, changed ones may not
+    if git::has_tracked_changes(&manifest_path)? {
+        return Err(anyhow!(
+            "--self-update: manifest repo {} has local changes",
+            manifest_path.display()
+        ));
+    }
+
+    git::update_to_ref(&manifest_path, git_ref)
+        .with_context(|| format!("--self-update: failed to check out {}", git_ref))?;
+    Ok(StyleMessage::manifest_updated(&manifest_path, git_ref))
+}
+
+/// point repos to the `--override` refs, every override must name a repo of the config
+fn apply_ref_overrides(
+    toml_config: &mut TomlConfig,
+    overrides: &[(String, RemoteRef)],
+) -> anyhow::Result<()> {
+    let repos = toml_config.repos.get_or_insert_with(Vec::new);
+    for (local, remote_ref) in overrides {
+        let repo = repos.iter_mut().find(|repo| {
+            matches!(&repo.local, Some(repo_local) if repo_local.norm_path().display_path() == *local)
+        });
+        let Some(repo) = repo else {
+            return Err(anyhow!(
+                "override of {}: repo not found in config file",
+                local
+            ));
+        };
+        repo.override_ref(remote_ref.clone());
+    }
+    Ok(())
+}
+
+/// ids of repos whose remote, branch, tag and commit are the same in the snapshot,
+/// a repo without a branch uses the `default-branch` of its own config file
+fn unchanged_since_snapshot(
+    snapshot_path: &Path,
+    repos_map: &HashMap<usize, TomlRepo>,
+    default_branch: &Option<String>,
+) -> anyhow::Result<Vec<usize>> {
+    if !snapshot_path.is_file() {
+        return Err(anyhow!("snapshot {} not found", snapshot_path.display()));
+    }
+    let Some(snapshot) = TomlConfig::load(snapshot_path) else {
+        return Err(anyhow!(
+            "failed to load snapshot {}",
+            snapshot_path.display()
+        ));
+    };
+
+    let pin = |toml_repo: &TomlRepo, default_branch: &Option<String>| {
+        (
+            toml_repo.remote.clone(),
+            toml_repo.branch.clone().or(default_branch.clone()),
+            toml_repo.tag.clone(),
+            toml_repo.commit.clone(),
+        )
+    };
+    let old_pins = snapshot
+        .repos
+        .iter()
+        .flatten()
+        .filter_map(|toml_repo| {
+            let local = toml_repo.local.as_ref()?.norm_path().display_path();
+            Some((local, pin(toml_repo, &snapshot.default_branch)))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut unchanged = repos_map
+        .iter()
+        .filter(|(_, toml_repo)| {
+            let local = toml_repo.local.as_ref().unwrap().norm_path().display_path();
+            old_pins.get(&local) == Some(&pin(toml_repo, default_branch))
+        })
+        .map(|(id, _)| *id)
+        .collect::<Vec<_>>();
+    unchanged.sort();
+    Ok(unchanged)
+}
+
+fn print_failed_paths(error_paths: &[String], file: Option<&PathBuf>) -> anyhow::Result<()> {
+    let mut error_paths = error_paths.to_vec();
+    error_paths.sort();
+    match file {
+        // write the file even when nothing failed, so a stale list never survives
+        Some(file) => {
+            let content: String = error_paths.iter().map(|p| format!("{}\n", p)).collect();
+            std::fs::write(file, content)
+                .with_context(|| format!("failed to write {}", file.display()))?;
+        }
+        None => {
+            // errors of the logger go to stderr in the cli
+            for rel_path in error_paths {
+                logger::error(rel_path);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn continue_repos(
+    path: &Path,
+    toml_config: TomlConfig,
+    ignore: Option<&Vec<String>>,
+) -> MgitResult {
+    let Some(toml_repos) = toml_config.repos else {
+        return Ok("No repos to continue".into());
+    };
+
+    let mut repos = repos_to_map_with_ignore(toml_repos, ignore)
+        .into_iter()
+        .collect::<Vec<_>>();
+    repos.sort_by_key(|(id, _)| *id);
+
+    let mut errors = Vec::new();
+    for (_, toml_repo) in repos {
+        let rel_path = toml_repo.local.as_ref().unwrap();
+        let full_path = path.join(rel_path);
+
+        if git::is_repository(&full_path).is_err() {
+            continue;
+        }
+
+        let res = git::get_conflict_state(&full_path).and_then(|state| match state {
+            Some(state) => git::continue_conflict(&full_path, state).map(|_| Some(state)),
+            None => Ok(None),
+        });
+
+        match res {
+            Ok(Some(state)) => {
+                logger::info(StyleMessage::git_continue(rel_path, state.to_string()));
+            }
+            Ok(None) => {}
+            Err(e) => errors.push(StyleMessage::git_error(rel_path.display_path(), &e)),
+        }
+    }
+
+    match errors.len() {
+        0 => Ok(StyleMessage::ops_success("sync --continue")),
+        _ => {
+            let msg = StyleMessage::ops_failed("sync --continue", errors.len());
+            Err(anyhow!(MgitError::OpsError {
+                prefix: msg,
+                errors: OpsErrors(errors),
+            }))
+        }
+    }
+}
+
+/// restore the repos recorded in the sync state: abort an unfinished rebase/merge, check out
+/// the branch and commit of before the sync, then pop the stash sync created
+fn abort_repos(path: &Path) -> MgitResult {
+    let sync_state = SyncState::load(path)?;
+    let repos = sync_state.repos();
+    if repos.is_empty() {
+        return Ok("No interrupted sync to abort".into());
+    }
+
+    let mut errors = Vec::new();
+    for (rel_path, state) in repos {
+        let full_path = path.join(&rel_path);
+        let res = abort_repo(&full_path, &state).and_then(|desc| {
+            sync_state.finish(&rel_path)?;
+            Ok(desc)
+        });
+
+        match res {
+            Ok(desc) => logger::info(StyleMessage::git_abort(&rel_path, desc)),
+            Err(e) => errors.push(StyleMessage::git_error(&rel_path, &e)),
+        }
+    }
+
+    match errors.len() {
+        0 => Ok(StyleMessage::ops_success("sync --abort")),
+        _ => {
+            let msg = StyleMessage::ops_failed("sync --abort", errors.len());
+            Err(anyhow!(MgitError::OpsError {
+                prefix: msg,
+                errors: OpsErrors(errors),
+            }))
+        }
+    }
+}
+
+fn abort_repo(full_path: &Path, state: &RepoSyncState) -> anyhow::Result<String> {
+    // the git process holding the lock went away together with the sync
+    let index_lock = git::index_lock_path(full_path)?;
+    if index_lock.is_file() {
+        std::fs::remove_file(&index_lock)?;
+    }
+
+    if let Some(conflict_state) = git::get_conflict_state(full_path)? {
+        git::abort_conflict(full_path, conflict_state)?;
+    }
+
+    git::restore_head(full_path, &state.branch, &state.head)?;
+    let head = state.head.get(..7).unwrap_or(&state.head);
+    let mut desc = match state.branch.is_empty() {
+        true => format!("HEAD {}", head),
+        false => format!("{} at {}", state.branch, head),
+    };
+
+    // the stash may be popped already when sync stopped right after it
+    if let Some(stash) = state.stash.as_ref() {
+        if let Some(entry) = git::find_stash(full_path, stash)? {
+            git::stash_pop_entry(full_path, entry)?;
+            desc += " and popped its stash";
+        }
+    }
+    Ok(desc)
+}
+
+/// per-repo settings shared by execution and planning
+struct InnerExecOptions<'a> {
+    stash_mode: &'a StashMode,
+    no_checkout: bool,
+    fetch_pinned_commit: bool,
+    depth: Option<&'a usize>,
+    host_limiter: Option<&'a HostLimiter>,
+    timings: Option<&'a Timings>,
+    clean_excludes: &'a [String],
+    report_untracked: bool,
+    keep_untracked: bool,
+    mirror: bool,
+    fast_fetch: bool,
+    prune: bool,
+    no_cone: bool,
+    recurse_submodules: bool,
+    on_stash_conflict: OnStashConflict,
+    checkout_index_only: bool,
+    skip_up_to_date: bool,
+    /// limit of each git command of a repo, none by default
+    timeout: Option<Duration>,
+    /// repos being changed are recorded there, for `--abort`
+    sync_state: &'a SyncState,
+}
+
+fn dump_plan(
+    path: &Path,
+    toml_config: TomlConfig,
+    ignore: Option<&Vec<String>>,
+    exec_options: &InnerExecOptions,
+    plan_path: &Path,
+) -> MgitResult {
+    let Some(toml_repos) = toml_config.repos else {
+        return Ok("No repos to plan".into());
+    };
+    let default_branch = toml_config.default_branch;
+
+    let mut repos = repos_to_map_with_ignore(toml_repos, ignore)
+        .into_iter()
+        .collect::<Vec<_>>();
+    repos.sort_by_key(|(id, _)| *id);
+
+    let mut plans = Vec::new();
+    let mut errors = Vec::new();
+    for (_, mut toml_repo) in repos {
+        // use default branch when branch is null
+        if toml_repo.branch.is_none() {
+            toml_repo.branch = default_branch.to_owned();
+        }
+
+        match plan_repo(path, &toml_repo, exec_options) {
+            Ok(plan) => plans.push(plan),
+            Err(e) => {
+                let rel_path = toml_repo.local.as_ref().unwrap().display_path();
+                errors.push(StyleMessage::git_error(rel_path, &e));
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        let msg = StyleMessage::ops_failed("dump plan", errors.len());
+        return Err(anyhow!(MgitError::OpsError {
+            prefix: msg,
+            errors: OpsErrors(errors),
+        }));
+    }
+
+    let content = serde_json::to_string_pretty(&SyncPlan { repos: plans })?;
+    std::fs::write(plan_path, content)
+        .with_context(|| format!("write plan {} failed.", plan_path.display()))?;
+
+    Ok(StyleMessage::ops_success("dump plan"))
+}
+
+/// describe what sync would do to every repo, from the plan and the state of the repos
+fn dry_run(
+    path: &Path,
+    toml_config: TomlConfig,
+    ignore: Option<&Vec<String>>,
+    exec_options: &InnerExecOptions,
+) -> MgitResult {
+    let Some(toml_repos) = toml_config.repos else {
+        return Ok("No repos to sync".into());
+    };
+    let default_branch = toml_config.default_branch;
+
+    let mut repos = repos_to_map_with_ignore(toml_repos, ignore)
+        .into_iter()
+        .collect::<Vec<_>>();
+    repos.sort_by_key(|(id, _)| *id);
+
+    let mut planned = StyleMessage::new();
+    let mut errors = Vec::new();
+    for (_, mut toml_repo) in repos {
+        // use default branch when branch is null
+        if toml_repo.branch.is_none() {
+            toml_repo.branch = default_branch.to_owned();
+        }
+
+        let rel_path = toml_repo.local.as_ref().unwrap().display_path();
+        let plan = match plan_repo(path, &toml_repo, exec_options) {
+            Ok(plan) => plan,
+            Err(e) => {
+                errors.push(StyleMessage::git_error(rel_path, &e));
+                continue;
+            }
+        };
+        // how far the repo is from the manifest ref, as of the last fetch
+        let status = repo_status(path, &toml_repo, &default_branch, false).ok();
+        for line in dry_run_lines(&plan, status.as_ref()) {
+            planned = planned.join(format!("  {}\n", line).into());
+        }
+    }
+
+    if !errors.is_empty() {
+        let msg = StyleMessage::ops_failed("sync dry run", errors.len()).join(planned);
+        return Err(anyhow!(MgitError::OpsError {
+            prefix: msg,
+            errors: OpsErrors(errors),
+        }));
+    }
+
+    Ok(StyleMessage::ops_success("sync dry run").join(planned))
+}
+
+/// one line per work tree changing command of the plan, remotes and sparse settings are left out
+fn dry_run_lines(plan: &RepoPlan, status: Option<&RepoStatus>) -> Vec<StyleMessage> {
+    let local = &plan.local;
+    let remote_ref = plan.remote_ref.to_string();
+    let mut lines = Vec::new();
+    for command in &plan.commands {
+        // skip the `-c` overrides before the subcommand
+        let mut args = command.iter().map(String::as_str);
+        let mut subcommand = args.next().unwrap_or_default();
+        while subcommand == "-c" {
+            args.next();
+            subcommand = args.next().unwrap_or_default();
+        }
+        let args = args.collect::<Vec<_>>();
+
+        let desc = match subcommand {
+            "clone" => format!("would clone {} from {}", local, plan.remote),
+            "init" => format!("would init {}", local),
+            "fetch" => format!("would fetch {} in {}", plan.remote_name, local),
+            "stash" if args.first() == Some(&"pop") => format!("would pop the stash in {}", local),
+            "stash" => format!("would stash local changes of {}", local),
+            "clean" => format!("would remove untracked files of {}", local),
+            "submodule" => format!("would update submodules of {}", local),
+            "checkout" => {
+                let branch = args.iter().find(|arg| !arg.starts_with('-'));
+                format!("would check out {} in {}", branch.unwrap_or(&""), local)
+            }
+            "read-tree" if args.first() == Some(&"--reset") => {
+                format!("would reset index and files of {} to {}", local, remote_ref)
+            }
+            "reset" => {
+                let mode = args.first().unwrap_or(&"--hard").trim_start_matches('-');
+                let distance = match status {
+                    Some(RepoStatus {
+                        ahead: Some(ahead),
+                        behind: Some(behind),
+                        ..
+                    }) => match (ahead, behind) {
+                        (0, 0) => " (up to date)".to_string(),
+                        (0, behind) => format!(" (behind {})", behind),
+                        (ahead, 0) => format!(" (ahead {})", ahead),
+                        (ahead, behind) => format!(" (ahead {}, behind {})", ahead, behind),
+                    },
+                    _ => String::new(),
+                };
+                match mode {
+                    "hard" => format!("would reset {} to {}{}", local, remote_ref, distance),
+                    _ => format!(
+                        "would reset {} to {} with --{}{}",
+                        local, remote_ref, mode, distance
+                    ),
+                }
+            }
+            _ => continue,
+        };
+        lines.push(desc.into());
+    }
+    lines
+}
+
+/// resolve the git commands `inner_exec` would run for the repository, without running them
+fn plan_repo(
+    input_path: &Path,
+    toml_repo: &TomlRepo,
+    exec_options: &InnerExecOptions,
+) -> anyhow::Result<RepoPlan> {
+    let rel_path = toml_repo.local.as_ref().unwrap();
+    let full_path = input_path.join(rel_path);
+    // the plan is written out, so it keeps the `${VAR}` of the remotes
+    let url = toml_repo
+        .raw_fetch_url()
+        .with_context(|| "remote url is null.")?;
+
+    let mut commands: Vec<Vec<String>> = Vec::new();
+    let mut is_cloned = false;
+
+    if is_mirror(exec_options, toml_repo) {
+        let url = toml_repo
+            .remote
+            .as_ref()
+            .with_context(|| "remote url is null.")?;
+        match git::is_bare_repository(&full_path) {
+            true => {
+                commands.push(to_args(&["remote", "set-url", "origin", url]));
+                commands.push(mirror_update_args(toml_repo));
+            }
+            false => commands.push(mirror_clone_args(url, toml_repo)),
+        }
+
+        return Ok(RepoPlan {
+            local: rel_path.display_path(),
+            remote: url.to_string(),
+            remote_name: "origin".to_string(),
+            remote_ref: toml_repo.get_remote_ref_by_name("origin")?,
+            stash_mode: exec_options.stash_mode.to_owned(),
+            commands: commands
+                .into_iter()
+                .map(|args| cmd::redact_args("git", args))
+                .collect(),
+        });
+    }
+
+    let is_repo_none = is_repo_none(&full_path)?;
+    let remote_name = if is_repo_none {
+        if !full_path.exists() || is_empty_dir(&full_path) {
+            commands.push(clone_args(url, toml_repo, exec_options.depth));
+            is_cloned = true;
+        } else {
+            commands.push(to_args(&["init", "-b", "master"]));
+        }
+        commands.extend(remote_args(toml_repo, is_cloned, "add"));
+        toml_repo.fetch_remote_name().to_string()
+    } else {
+        commands.extend(remote_args(toml_repo, false, "set-url"));
+        // the remote matches the url once it's updated
+        toml_repo
+            .get_remote_name(&full_path)
+            .unwrap_or(toml_repo.fetch_remote_name().to_string())
+    };
+
+    let stash_mode = repo_stash_mode(exec_options, toml_repo, is_repo_none);
+    let mut refs = RepoRefs::with_remote(&full_path, toml_repo, remote_name, is_repo_none)?;
+    let remote_ref_str = refs.remote_ref_str.clone();
+
+    let need_fetch = match toml_repo.commit.as_ref() {
+        Some(commit) => {
+            exec_options.fetch_pinned_commit
+                || is_repo_none
+                || !git::commit_exists(&full_path, commit)
+        }
+        None => !is_cloned,
+    };
+    if need_fetch {
+        let depth = match is_repo_none {
+            true => toml_repo.fetch_depth(exec_options.depth),
+            false => shallow_depth(&full_path, toml_repo, exec_options.depth),
+        };
+        commands.push(fetch_args(
+            &refs.remote_name,
+            toml_repo,
+            depth,
+            exec_options.fast_fetch,
+            exec_options.prune,
+        )?);
+    }
+
+    // a fresh clone is on the cloned branch, a fresh init has no branch
+    if is_cloned && matches!(refs.remote_ref, RemoteRef::Branch(_)) {
+        refs.branch_exists = true;
+    }
+    let current_branch = match is_repo_none {
+        true => refs.branch_exists.then(|| refs.branch.clone()),
+        false => git::get_current_branch(&full_path).ok(),
+    };
+    let checkout = |force: bool| -> Option<Vec<String>> {
+        match refs.is_checked_out(current_branch.as_deref()) {
+            true => None,
+            false => Some(checkout_args(
+                &refs.branch,
+                &remote_ref_str,
+                refs.branch_exists,
+                force,
+            )),
+        }
+    };
+    let has_local_changes = !is_repo_none && has_changes_to_stash(&full_path, exec_options)?;
+    let steps = worktree_steps(
+        &full_path,
+        toml_repo,
+        exec_options,
+        &refs,
+        &stash_mode,
+        is_repo_none,
+    );
+    for step in steps {
+        match step {
+            WorktreeStep::Stash if has_local_changes => {
+                commands.push(to_args(&git::stash_args(!exec_options.keep_untracked)))
+            }
+            WorktreeStep::StashPop { always: true } if has_local_changes => {
+                commands.push(to_args(&["stash", "pop"]))
+            }
+            WorktreeStep::Clean => {
+                commands.push(to_args(&git::clean_args(exec_options.clean_excludes)))
+            }
+            WorktreeStep::Checkout { force } => commands.extend(checkout(force)),
+            WorktreeStep::Reset(reset_type) => {
+                commands.push(to_args(&["reset", reset_arg(reset_type), &remote_ref_str]))
+            }
+            WorktreeStep::CheckoutIndex => {
+                commands.push(to_args(&["read-tree", "--reset", &remote_ref_str]));
+                commands.push(to_args(&git::clean_args(exec_options.clean_excludes)));
+                commands.push(to_args(&["checkout-index", "-a", "-f"]));
+            }
+            // nothing to stash or pop, or no git command of its own
+            WorktreeStep::Stash
+            | WorktreeStep::StashPop { .. }
+            | WorktreeStep::SubmoduleCleanup => {}
+        }
+    }
+
+    match toml_repo.sparse.as_ref() {
+        Some(dirs) => commands.push(git::sparse_checkout_set_args(dirs, exec_options.no_cone)),
+        None => commands.push(to_args(&["sparse-checkout", "disable"])),
+    }
+
+    if let Some(subdir) = toml_repo.subdir.as_ref() {
+        let tree = format!("HEAD:{}", subdir.trim_matches('/'));
+        commands.push(to_args(&["read-tree", "-u", "--reset", &tree]));
+    } else if is_recurse_submodules(exec_options, toml_repo) {
+        commands.push(git::submodule_update_args(true, true));
+    }
+
+    Ok(RepoPlan {
+        local: rel_path.display_path(),
+        remote: url.to_string(),
+        remote_name: refs.remote_name,
+        remote_ref: refs.remote_ref,
+        stash_mode,
+        commands: commands
+            .into_iter()
+            .map(|args| cmd::redact_args("git", args))
+            .collect(),
+    })
+}
+
+/// commands of `TomlRepo::update_remotes`, `action` is `add` for a new repository
+fn remote_args(toml_repo: &TomlRepo, is_cloned: bool, action: &str) -> Vec<Vec<String>> {
+    let mut commands = Vec::new();
+    let is_fork = toml_repo.is_fork();
+    let origin_name = toml_repo.origin_name();
+
+    // a clone already set up the remote it fetched from
+    if let Some(url) = toml_repo.remote.as_ref().filter(|_| is_fork || !is_cloned) {
+        commands.push(to_args(&["remote", action, origin_name, url]));
+    }
+    if let Some(url) = toml_repo.raw_fetch_url().filter(|_| is_fork && !is_cloned) {
+        commands.push(to_args(&["remote", action, "upstream", url]));
+    }
+    if let Some(url) = toml_repo.push_remote.as_ref() {
+        commands.push(to_args(&["remote", "set-url", "--push", origin_name, url]));
+    }
+    commands
+}
+
+fn to_args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
 fn inner_exec(
     input_path: &Path,
     repo_info: &mut RepoInfo,
-    stash_mode: &StashMode,
-    no_checkout: bool,
-    depth: Option<&usize>,
+    exec_options: &InnerExecOptions,
     default_branch: &Option<String>,
     progress: &impl Progress,
 ) -> anyhow::Result<InnerExecResponse> {
     let full_path = &input_path.join(repo_info.rel_path());
+    let depth = exec_options.depth;
 
     let mut toml_repo = repo_info.toml_repo.to_owned();
     let mut owned_repo_info = repo_info.to_owned();
     let repo_info = &mut owned_repo_info;
     // make repo directory and skip clone the repository
     std::fs::create_dir_all(full_path)
-        .with_context(|| format!("create dir {} failed.", full_path.to_str().unwrap()))?;
-
-    let mut stash_mode = stash_mode.to_owned();
-    let is_repo_none = git::is_repository(full_path.as_path()).is_err();
-    // if repository not found, create new one
-    if is_repo_none {
-        // use --hard
-        stash_mode = StashMode::Hard;
-
-        // git init when dir exist
-        exec_init(input_path, repo_info, progress)?;
-        // git remote add url
-        exec_add_remote(input_path, repo_info, progress)?;
-    } else {
-        let remote_url = repo_info.toml_repo.remote.as_ref().unwrap();
-        git::update_remote_url(full_path, remote_url)?;
-    }
+        .with_context(|| format!("create dir {} failed.", full_path.display()))?;
 
     // use default branch when branch is null
     if repo_info.toml_repo.branch.is_none() {
@@ -320,99 +1538,669 @@ fn inner_exec(
         repo_info.toml_repo = &toml_repo;
     }
 
-    // fetch
-    exec_fetch(input_path, repo_info, depth, progress)?;
+    let mut is_cloned = false;
+    let is_repo_none = is_repo_none(full_path)?;
+    let stash_mode = repo_stash_mode(exec_options, repo_info.toml_repo, is_repo_none);
+    // if repository not found, create new one
+    if is_repo_none {
+        // git clone into an empty dir, which also sets up origin/HEAD
+        if is_empty_dir(full_path) {
+            let _permit = acquire_host(exec_options, repo_info, progress);
+            let clone_res = timed(exec_options, repo_info, Phase::Clone, || {
+                exec_clone(input_path, repo_info, depth, progress)
+            });
+            // a half-made .git would be taken for a repo by the next sync
+            if clone_res.is_err() {
+                let _ = std::fs::remove_dir_all(full_path.join(".git"));
+            }
+            clone_res?;
+            is_cloned = true;
+        } else {
+            // git init when dir exist with files in it, like nested repos
+            exec_init(input_path, repo_info, progress)?;
+            // git remote add url
+            exec_add_remote(input_path, repo_info, progress)?;
+        }
+    }
+
+    // a clone only sets up the remote it fetched from
+    if !is_repo_none || is_cloned {
+        repo_info.toml_repo.update_remotes(full_path)?;
+    }
 
-    // priority: commit/tag/branch(default-branch)
-    let remote_ref = repo_info.toml_repo.get_remote_ref(full_path.as_path())?;
-    let remote_ref_str = match remote_ref {
-        RemoteRef::Commit(r) | RemoteRef::Tag(r) | RemoteRef::Branch(r) => r,
+    // fetch, a fresh clone only misses a pinned commit.
+    // commits are immutable, so a pinned commit already present needs no fetch
+    let need_fetch = match repo_info.toml_repo.commit.as_ref() {
+        Some(commit) => exec_options.fetch_pinned_commit || !git::commit_exists(full_path, commit),
+        None => !is_cloned,
     };
+    if need_fetch {
+        // held through the unshallow fallback below too
+        let _permit = acquire_host(exec_options, repo_info, progress);
+        let fetch_res = timed(exec_options, repo_info, Phase::Fetch, || {
+            exec_fetch(
+                input_path,
+                repo_info,
+                depth,
+                exec_options.fast_fetch,
+                exec_options.prune,
+                progress,
+            )
+        });
+
+        // fall back to the full history when the shallow fetch misses the pinned commit
+        match (
+            repo_info.toml_repo.fetch_depth(depth),
+            repo_info.toml_repo.commit.as_ref(),
+        ) {
+            (Some(_), Some(commit))
+                if fetch_res.is_err() || !git::commit_exists(full_path, commit) =>
+            {
+                timed(exec_options, repo_info, Phase::Fetch, || {
+                    exec_unshallow(input_path, repo_info, exec_options, progress)
+                })?;
+            }
+            _ => fetch_res?,
+        }
+    }
 
-    // check remote-ref valid
-    git::is_remote_ref_valid(full_path, remote_ref_str)?;
+    // resolved once, the checkout and reset steps share them.
+    // fails when the remote ref is not valid
+    let refs = RepoRefs::resolve(full_path, repo_info.toml_repo)?;
 
     let mut exec_response = InnerExecResponse::default();
 
-    match stash_mode {
-        StashMode::Normal => {
-            // try stash → checkout → reset → stash pop
-            if !no_checkout {
-                // stash
-                let stash_response = exec_stash(input_path, repo_info, progress)?;
-
-                // checkout
-                let mut result = exec_checkout(input_path, repo_info, progress, false);
-
-                if result.is_ok() {
-                    // reset --hard
-                    result = exec_reset(input_path, repo_info, progress, ResetType::Hard);
-                }
+    let steps = worktree_steps(
+        full_path,
+        repo_info.toml_repo,
+        exec_options,
+        &refs,
+        &stash_mode,
+        is_repo_none,
+    );
+    if steps.is_empty() {
+        progress.repo_info(repo_info, "already up to date".into());
+    } else {
+        // record where the repo was, in case sync is killed halfway through the checkout
+        let rel_path = repo_info.rel_path().display_path();
+        let sync_state = exec_options.sync_state;
+        let head = match is_repo_none {
+            true => None,
+            false => git::get_current_commit(full_path).ok(),
+        };
+        let recorded = head.is_some();
+        if let Some(head) = head {
+            let state = RepoSyncState {
+                branch: git::get_current_branch(full_path).unwrap_or_default(),
+                head,
+                stash: None,
+            };
+            sync_state.begin(&rel_path, state)?;
+        }
 
-                // stash pop, whether checkout succ or failed, whether reset succ or failed
-                if matches!(stash_response, InnerStashResponse::Stash(_)) {
-                    let _ = exec_stash_pop(input_path, repo_info, progress);
-                }
-                result
-            } else {
-                // reset --soft
-                exec_reset(input_path, repo_info, progress, ResetType::Soft)
-            }
+        let checkout_res = exec_worktree(
+            input_path,
+            repo_info,
+            &refs,
+            exec_options,
+            &steps,
+            &mut exec_response,
+            progress,
+        );
+        // the work tree is in a known state again, whether the checkout succeeded or not
+        if recorded {
+            sync_state.finish(&rel_path)?;
         }
+        checkout_res.context(CheckoutFailed)?;
+    }
 
-        StashMode::Stash => {
-            // stash with `--stash` option, maybe return error if need to initial commit
-            let stash_response = exec_stash(input_path, repo_info, progress)?;
+    match repo_info.toml_repo.sparse.as_ref() {
+        Some(dirs) => git::sparse_checkout_set(full_path, dirs, exec_options.no_cone),
+        None => git::sparse_checkout_disable(full_path),
+    }?;
 
-            let mut result: Result<(), anyhow::Error> = Ok(());
-            let mut reset_type = ResetType::Mixed;
+    // flatten subdir after sparse-checkout, which would restore the full HEAD tree
+    if let Some(subdir) = repo_info.toml_repo.subdir.as_ref() {
+        progress.repo_info(repo_info, "extract subdir...".into());
+        git::read_subtree(full_path, "HEAD", subdir)?;
+    } else if is_recurse_submodules(exec_options, repo_info.toml_repo) {
+        exec_submodule_update(full_path, repo_info, progress)?;
+    }
 
-            // checkout
-            if !no_checkout {
-                result = exec_checkout(input_path, repo_info, progress, true);
-                reset_type = ResetType::Hard;
-            }
+    Ok(exec_response)
+}
 
-            if result.is_ok() {
-                result = exec_reset(input_path, repo_info, progress, reset_type);
-            }
+/// a repo at the remote ref with a clean work tree has nothing to stash, check out or reset.
+///
+/// shared by the sync and its plan, so `--dry-run` skips the same repos
+fn skips_worktree(
+    full_path: &Path,
+    toml_repo: &TomlRepo,
+    exec_options: &InnerExecOptions,
+    refs: &RepoRefs,
+    is_repo_none: bool,
+) -> bool {
+    exec_options.skip_up_to_date
+        && !is_repo_none
+        && toml_repo.subdir.is_none()
+        && !is_index_only(exec_options, toml_repo, &refs.remote_ref)
+        && is_up_to_date(full_path, refs)
+}
 
-            if matches!(stash_response, InnerStashResponse::Stash(_)) {
-                // undo if checkout failed or reset failed
-                if let Err(e) = result {
-                    // if reset failed, pop stash if stash something this time
-                    let _ = exec_stash_pop(input_path, repo_info, progress);
-                    return Err(e);
-                }
+/// whether the repo is on the branch of the remote ref, at its commit, without local changes.
+/// anything git can't answer, e.g. a repo without commits after a failed clone, is not up to date
+fn is_up_to_date(full_path: &Path, refs: &RepoRefs) -> bool {
+    let Some(target) = refs.target.as_ref() else {
+        return false;
+    };
+    refs.is_checked_out(git::get_current_branch(full_path).ok().as_deref())
+        && git::get_current_commit(full_path).is_ok_and(|head| head == *target)
+        && git::has_local_changes(full_path).is_ok_and(|changes| !changes)
+}
+
+/// `recurse-submodules` of the repo goes before `--recurse-submodules`;
+/// an extracted subdir has no submodules to update
+fn is_recurse_submodules(exec_options: &InnerExecOptions, toml_repo: &TomlRepo) -> bool {
+    toml_repo.subdir.is_none()
+        && toml_repo
+            .recurse_submodules
+            .unwrap_or(exec_options.recurse_submodules)
+}
+
+/// `--checkout-index-only` applies to commit/tag pins; branches and extracted subdirs,
+/// which are read from HEAD, take the usual checkout
+fn is_index_only(
+    exec_options: &InnerExecOptions,
+    toml_repo: &TomlRepo,
+    remote_ref: &RemoteRef,
+) -> bool {
+    exec_options.checkout_index_only
+        && toml_repo.subdir.is_none()
+        && matches!(remote_ref, RemoteRef::Commit(_) | RemoteRef::Tag(_))
+}
+
+/// write the files of the remote ref into the work tree, HEAD and branches stay as they are.
+///
+/// files of the previous tree become untracked once the index moves, so the clean in
+/// between removes them; local changes are overwritten.
+fn exec_checkout_index(
+    full_path: &Path,
+    remote_ref_str: &str,
+    exec_options: &InnerExecOptions,
+    repo_info: &RepoInfo,
+    progress: &impl Progress,
+) -> anyhow::Result<()> {
+    progress.repo_info(repo_info, "checkout index...".into());
+    git::read_tree(full_path, remote_ref_str)?;
+    git::clean(full_path, exec_options.clean_excludes)?;
+    git::checkout_index(full_path)
+}
+
+/// one step of bringing the work tree to the remote ref
+#[derive(Debug, Clone, Copy)]
+enum WorktreeStep {
+    /// stash local changes, if there are any
+    Stash,
+    /// pop the stash again after the checkout, or only to undo a failed one
+    StashPop {
+        always: bool,
+    },
+    Clean,
+    Checkout {
+        force: bool,
+    },
+    Reset(ResetType),
+    /// write the files of the remote ref, HEAD and branches stay as they are
+    CheckoutIndex,
+    /// remove checkouts of submodules the ref doesn't have
+    SubmoduleCleanup,
+}
 
-                // save stash message
-                exec_response.stash = Some(stash_response);
+/// the steps of the stash mode, which `inner_exec` runs and `plan_repo` lists.
+/// none for a repo already up to date
+fn worktree_steps(
+    full_path: &Path,
+    toml_repo: &TomlRepo,
+    exec_options: &InnerExecOptions,
+    refs: &RepoRefs,
+    stash_mode: &StashMode,
+    is_repo_none: bool,
+) -> Vec<WorktreeStep> {
+    if skips_worktree(full_path, toml_repo, exec_options, refs, is_repo_none) {
+        return Vec::new();
+    }
+    if is_index_only(exec_options, toml_repo, &refs.remote_ref) {
+        return vec![WorktreeStep::CheckoutIndex];
+    }
+
+    let checkout = !exec_options.no_checkout;
+    match stash_mode {
+        // try stash → checkout → reset → stash pop
+        StashMode::Normal if checkout => vec![
+            WorktreeStep::Stash,
+            WorktreeStep::Checkout { force: false },
+            WorktreeStep::Reset(ResetType::Hard),
+            WorktreeStep::StashPop { always: true },
+        ],
+        StashMode::Normal => vec![WorktreeStep::Reset(ResetType::Soft)],
+        // the stash is kept, unless the checkout fails
+        StashMode::Stash => {
+            let mut steps = vec![WorktreeStep::Stash];
+            match checkout {
+                true => steps.extend([
+                    WorktreeStep::Checkout { force: true },
+                    WorktreeStep::Reset(ResetType::Hard),
+                ]),
+                false => steps.push(WorktreeStep::Reset(ResetType::Mixed)),
             }
-            result
+            steps.push(WorktreeStep::StashPop { always: false });
+            steps
         }
-
+        // the tree has to match the ref exactly, including dropped submodules
         StashMode::Hard => {
-            // clean
+            let mut steps = Vec::new();
             if !is_repo_none {
-                exec_clean(input_path, repo_info, progress)?;
+                steps.push(WorktreeStep::Clean);
             }
+            if checkout {
+                steps.push(WorktreeStep::Checkout { force: true });
+            }
+            steps.extend([
+                WorktreeStep::Reset(ResetType::Hard),
+                WorktreeStep::SubmoduleCleanup,
+            ]);
+            steps
+        }
+    }
+}
 
-            // checkout
-            if !no_checkout {
-                exec_checkout(input_path, repo_info, progress, true)?;
+/// a new repository or an extracted subdir, which is not a real work tree, is always reset
+fn repo_stash_mode(
+    exec_options: &InnerExecOptions,
+    toml_repo: &TomlRepo,
+    is_repo_none: bool,
+) -> StashMode {
+    match is_repo_none || toml_repo.subdir.is_some() {
+        true => StashMode::Hard,
+        false => exec_options.stash_mode.to_owned(),
+    }
+}
+
+/// bring the work tree to the remote ref by running the steps of `worktree_steps`.
+/// after a failed step only the stash is popped again
+fn exec_worktree(
+    input_path: &Path,
+    repo_info: &RepoInfo,
+    refs: &RepoRefs,
+    exec_options: &InnerExecOptions,
+    steps: &[WorktreeStep],
+    exec_response: &mut InnerExecResponse,
+    progress: &impl Progress,
+) -> anyhow::Result<()> {
+    let full_path = &input_path.join(repo_info.rel_path());
+    let mut stash_response = InnerStashResponse::None;
+    let mut result: anyhow::Result<()> = Ok(());
+
+    for step in steps {
+        match *step {
+            WorktreeStep::Stash => {
+                exec_response.stashed_files = report_local_changes(full_path, exec_options)?;
+                stash_response = exec_stash(input_path, repo_info, exec_options, progress)?;
+            }
+            WorktreeStep::StashPop { .. }
+                if !matches!(stash_response, InnerStashResponse::Stash(_)) => {}
+            // stash pop, whether checkout succ or failed, whether reset succ or failed
+            WorktreeStep::StashPop { always: true } => {
+                match exec_stash_pop(input_path, repo_info, exec_options, progress) {
+                    Ok(stash_conflict) => exec_response.stash_conflict = stash_conflict,
+                    // a failed checkout or reset is the error worth reporting
+                    Err(e) => result = result.and(Err(e)),
+                }
+            }
+            // undo if checkout failed or reset failed, otherwise save the stash message
+            WorktreeStep::StashPop { always: false } => match result {
+                Err(e) => {
+                    let pop_res = exec_stash_pop(input_path, repo_info, exec_options, progress);
+                    result = match pop_res {
+                        Ok(Some(stash_conflict)) => Err(e.context(stash_conflict)),
+                        _ => Err(e),
+                    };
+                }
+                Ok(_) => {
+                    exec_response.stash = Some(std::mem::replace(
+                        &mut stash_response,
+                        InnerStashResponse::None,
+                    ));
+                }
+            },
+            // the other steps stop at the first failure
+            _ if result.is_err() => {}
+            WorktreeStep::Clean => {
+                result = exec_clean(input_path, repo_info, exec_options.clean_excludes, progress)
+            }
+            WorktreeStep::Checkout { force } => {
+                result = timed(exec_options, repo_info, Phase::Checkout, || {
+                    exec_checkout(input_path, repo_info, refs, progress, force)
+                })
+            }
+            WorktreeStep::Reset(reset_type) => {
+                result = timed(exec_options, repo_info, Phase::Reset, || {
+                    exec_reset(input_path, repo_info, refs, progress, reset_type)
+                })
+            }
+            WorktreeStep::CheckoutIndex => {
+                result = timed(exec_options, repo_info, Phase::Checkout, || {
+                    exec_checkout_index(
+                        full_path,
+                        &refs.remote_ref_str,
+                        exec_options,
+                        repo_info,
+                        progress,
+                    )
+                })
             }
+            WorktreeStep::SubmoduleCleanup => {
+                match exec_submodule_cleanup(full_path, repo_info, progress) {
+                    Ok(stale_submodules) => exec_response.stale_submodules = stale_submodules,
+                    Err(e) => result = Err(e),
+                }
+            }
+        }
+    }
+    result
+}
 
-            // reset --hard
-            exec_reset(input_path, repo_info, progress, ResetType::Hard)
+/// remove checkouts of submodules the ref doesn't have, and sync the urls of the ones it has
+fn exec_submodule_cleanup(
+    full_path: &Path,
+    repo_info: &RepoInfo,
+    progress: &impl Progress,
+) -> anyhow::Result<Vec<String>> {
+    let stale_dirs = git::stale_submodule_dirs(full_path)?;
+    if !stale_dirs.is_empty() {
+        progress.repo_info(repo_info, "clean submodules...".into());
+    }
+    for dir in &stale_dirs {
+        std::fs::remove_dir_all(full_path.join(dir))
+            .with_context(|| format!("remove submodule {} failed.", dir))?;
+
+        // parents left empty were only there for the submodule
+        for parent in Path::new(dir).ancestors().skip(1) {
+            if parent.as_os_str().is_empty() || std::fs::remove_dir(full_path.join(parent)).is_err()
+            {
+                break;
+            }
         }
-    }?;
+    }
 
-    match repo_info.toml_repo.sparse.as_ref() {
-        Some(dirs) => git::sparse_checkout_set(&full_path, dirs),
-        None => git::sparse_checkout_disable(&full_path),
-    }?;
+    if full_path.join(".gitmodules").is_file() {
+        git::submodule_sync(full_path)?;
+    }
+    Ok(stale_dirs)
+}
 
-    Ok(exec_response)
+fn is_checkout_failed<T>(res: &anyhow::Result<T>) -> bool {
+    matches!(res, Err(e) if e.downcast_ref::<CheckoutFailed>().is_some())
+}
+
+/// a repo can be moved aside for a fresh clone when nothing local would be lost:
+/// not the work directory itself, no other repo of the config inside, no local changes,
+/// commits on no remote or stashes
+fn can_reclone(full_path: &Path, rel_path: &str, repos_map: &HashMap<usize, TomlRepo>) -> bool {
+    if rel_path.is_empty() || rel_path == "." {
+        return false;
+    }
+
+    let prefix = format!("{}/", rel_path);
+    let has_nested = repos_map.values().any(|toml_repo| {
+        matches!(&toml_repo.local, Some(local) if local.display_path().starts_with(&prefix))
+    });
+    !has_nested
+        && matches!(git::has_local_changes(full_path), Ok(false))
+        && matches!(git::unpushed_work(full_path), Ok(None))
+}
+
+/// free path next to the repo to keep its old clone at
+fn reclone_backup_path(full_path: &Path) -> PathBuf {
+    let name = full_path.file_name().unwrap_or_default().to_string_lossy();
+    (0..)
+        .map(|i| match i {
+            0 => format!("{}.mgit-backup", name),
+            i => format!("{}.mgit-backup-{}", name, i),
+        })
+        .map(|backup| full_path.with_file_name(backup))
+        .find(|backup| !backup.exists())
+        .unwrap()
+}
+
+/// estimate the size of a fresh clone in MB.
+///
+/// the manifest `max-size` hint wins; otherwise the size can only be measured
+/// when the remote is a local path.
+fn estimate_clone_size_mb(full_path: &Path, toml_repo: &TomlRepo) -> Option<u64> {
+    if let Some(max_size) = toml_repo.max_size {
+        return Some(max_size);
+    }
+
+    let remote = toml_repo.fetch_url()?;
+    let remote_path = full_path.join(remote.trim_start_matches("file://"));
+    if !remote_path.is_dir() {
+        return None;
+    }
+
+    let bytes: u64 = WalkDir::new(remote_path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum();
+    Some(bytes / (1024 * 1024))
+}
+
+/// whether a new repository should be created, a broken one is never overwritten
+fn is_repo_none(full_path: &Path) -> anyhow::Result<bool> {
+    match git::is_repository(full_path) {
+        Ok(_) => Ok(false),
+        Err(RepositoryError::NotFound) => Ok(true),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn is_empty_dir(path: &Path) -> bool {
+    std::fs::read_dir(path).is_ok_and(|mut entries| entries.next().is_none())
+}
+
+/// record the wall time of a phase when `--timings` is set
+fn timed<T>(
+    exec_options: &InnerExecOptions,
+    repo_info: &RepoInfo,
+    phase: Phase,
+    f: impl FnOnce() -> T,
+) -> T {
+    let start = Instant::now();
+    let res = f();
+    if let Some(timings) = exec_options.timings {
+        timings.record(repo_info.rel_path().display_path(), phase, start.elapsed());
+    }
+    res
+}
+
+/// take a job slot of the remote host when `--jobs-per-host` is set
+fn acquire_host<'a>(
+    exec_options: &InnerExecOptions<'a>,
+    repo_info: &RepoInfo,
+    progress: &impl Progress,
+) -> Option<HostPermit<'a>> {
+    let host_limiter = exec_options.host_limiter?;
+    let host = repo_info.toml_repo.get_remote_host()?;
+
+    progress.repo_info(repo_info, "waiting for host...".into());
+    let permit = host_limiter.acquire(&host);
+
+    // logged once the repo is about to contact the host
+    if cmd::is_trace_git() {
+        logger::info(StyleMessage::cmd_trace_host(repo_info.rel_path(), &host));
+    }
+    Some(permit)
+}
+
+/// with `--mirror` or `mirror = true`, the repo is a bare mirror clone
+fn is_mirror(exec_options: &InnerExecOptions, toml_repo: &TomlRepo) -> bool {
+    exec_options.mirror || toml_repo.mirror.unwrap_or(false)
+}
+
+fn mirror_clone_args(url: &str, toml_repo: &TomlRepo) -> Vec<String> {
+    let mut args = toml_repo.config_args();
+    args.extend(to_args(&["clone", "--mirror", url, ".", "--progress"]));
+    args
+}
+
+fn mirror_update_args(toml_repo: &TomlRepo) -> Vec<String> {
+    let mut args = toml_repo.config_args();
+    args.extend(to_args(&["remote", "update", "--prune"]));
+    args
+}
+
+/// clone a bare mirror with all refs, or update all refs of an existing one
+fn exec_mirror(
+    input_path: &Path,
+    repo_info: &RepoInfo,
+    exec_options: &InnerExecOptions,
+    progress: &impl Progress,
+) -> anyhow::Result<StyleMessage> {
+    let full_path = input_path.join(repo_info.rel_path());
+    let url = &repo_info
+        .toml_repo
+        .remote_url()
+        .with_context(|| "remote url is null.")?;
+
+    let (args, phase) = match git::is_bare_repository(&full_path) {
+        true => {
+            // a mirror clone always has origin
+            git::update_remote_url(&full_path, "origin", url)?;
+            progress.repo_info(repo_info, "update mirror...".into());
+            (mirror_update_args(repo_info.toml_repo), Phase::Fetch)
+        }
+        false => {
+            // never turn a work tree or other files into a mirror
+            if full_path.exists() && !is_empty_dir(&full_path) {
+                return Err(anyhow!("directory is not empty, can't clone mirror."));
+            }
+            std::fs::create_dir_all(&full_path)
+                .with_context(|| format!("create dir {} failed.", full_path.display()))?;
+            progress.repo_info(repo_info, "clone mirror...".into());
+            (mirror_clone_args(url, repo_info.toml_repo), Phase::Clone)
+        }
+    };
+
+    let _permit = acquire_host(exec_options, repo_info, progress);
+    let (times, delay) = repo_info.toml_repo.retry_policy();
+    timed(exec_options, repo_info, phase, || {
+        retry(times, delay, || {
+            let mut command = Command::new("git");
+            let full_command = command.args(&args).current_dir(&full_path);
+            cmd::exec_cmd_with_progress(repo_info, full_command, progress)
+        })
+    })?;
+
+    let msg = match phase {
+        Phase::Clone => "mirror cloned",
+        _ => "mirror updated",
+    };
+    Ok(msg.into())
+}
+
+fn clone_args(url: &str, toml_repo: &TomlRepo, depth: Option<&usize>) -> Vec<String> {
+    let mut args = toml_repo.config_args();
+    args.extend(to_args(&["clone", url, ".", "--progress"]));
+
+    // a fork fetches from upstream, origin is added after the clone
+    let remote_name = toml_repo.fetch_remote_name();
+    if remote_name != "origin" {
+        args.push("--origin".to_string());
+        args.push(remote_name.to_string());
+    }
+
+    // priority: tag/branch(default-branch), a pinned commit is fetched later
+    if let Some(remote_branch) = toml_repo.tag.as_ref().or(toml_repo.branch.as_ref()) {
+        args.push("--branch".to_string());
+        args.push(remote_branch.to_string());
+    }
+
+    if let Some(depth) = toml_repo.fetch_depth(depth) {
+        args.push("--depth".to_string());
+        args.push(depth.to_string());
+    }
+    args
+}
+
+fn exec_clone(
+    input_path: &Path,
+    repo_info: &RepoInfo,
+    depth: Option<&usize>,
+    progress: &impl Progress,
+) -> anyhow::Result<()> {
+    progress.repo_info(repo_info, "clone...".into());
+
+    let full_path = input_path.join(repo_info.rel_path());
+    let toml_repo = repo_info.toml_repo;
+    let url = toml_repo.fetch_url().unwrap();
+
+    let args = clone_args(&url, toml_repo, depth);
+
+    let (times, delay) = repo_info.toml_repo.retry_policy();
+    retry(times, delay, || {
+        let mut command = Command::new("git");
+        let full_command = command.args(&args).current_dir(&full_path);
+        cmd::exec_cmd_with_progress(repo_info, full_command, progress)
+    })
+}
+
+fn exec_unshallow(
+    input_path: &Path,
+    repo_info: &RepoInfo,
+    exec_options: &InnerExecOptions,
+    progress: &impl Progress,
+) -> anyhow::Result<()> {
+    progress.repo_info(repo_info, "unshallow...".into());
+
+    let full_path = input_path.join(repo_info.rel_path());
+    let remote_name = repo_info.toml_repo.get_remote_name(&full_path)?;
+    let mut args = fetch_args(
+        &remote_name,
+        repo_info.toml_repo,
+        None,
+        exec_options.fast_fetch,
+        exec_options.prune,
+    )?;
+    if git::is_shallow(&full_path)? {
+        args.push("--unshallow".to_string());
+    }
+
+    let (times, delay) = repo_info.toml_repo.retry_policy();
+    retry(times, delay, || {
+        let mut command = Command::new("git");
+        let full_command = command.args(&args).current_dir(&full_path);
+        cmd::exec_cmd_with_progress(repo_info, full_command, progress)
+    })
+}
+
+/// `git submodule update --init --recursive`, cloning submodules shows their transfer progress
+fn exec_submodule_update(
+    full_path: &Path,
+    repo_info: &RepoInfo,
+    progress: &impl Progress,
+) -> anyhow::Result<()> {
+    progress.repo_info(repo_info, "update submodules...".into());
+
+    let mut args = repo_info.toml_repo.config_args();
+    args.extend(git::submodule_update_args(true, true));
+    args.push("--progress".to_string());
+
+    let mut command = Command::new("git");
+    let full_command = command.args(&args).current_dir(full_path);
+    cmd::exec_cmd_with_progress(repo_info, full_command, progress)
 }
 
 fn exec_init(
@@ -432,124 +2220,277 @@ fn exec_add_remote(
     progress.repo_info(repo_info, "add remote...".into());
 
     let full_path = input_path.join(repo_info.rel_path());
-    let url = repo_info.toml_repo.remote.as_ref().unwrap();
-    git::add_remote_url(full_path, url)
+    repo_info.toml_repo.update_remotes(full_path)
 }
 
 fn exec_clean(
     input_path: &Path,
     repo_info: &RepoInfo,
+    clean_excludes: &[String],
     progress: &impl Progress,
 ) -> anyhow::Result<()> {
     progress.repo_info(repo_info, "clean...".into());
 
     let full_path = input_path.join(repo_info.rel_path());
-    git::clean(full_path)
+    git::clean(full_path, clean_excludes)
+}
+
+fn reset_arg(reset_type: ResetType) -> &'static str {
+    match reset_type {
+        ResetType::Soft => "--soft",
+        ResetType::Mixed => "--mixed",
+        ResetType::Hard => "--hard",
+    }
 }
 
 fn exec_reset(
     input_path: &Path,
     repo_info: &RepoInfo,
+    refs: &RepoRefs,
     progress: &impl Progress,
     reset_type: ResetType,
 ) -> anyhow::Result<()> {
     progress.repo_info(repo_info, "reset...".into());
 
     let full_path = input_path.join(repo_info.rel_path());
-    // priority: commit/tag/branch(default-branch)
-    let remote_ref = repo_info.toml_repo.get_remote_ref(full_path.as_path())?;
-    let remote_ref_str = match remote_ref {
-        RemoteRef::Commit(r) | RemoteRef::Tag(r) | RemoteRef::Branch(r) => r,
-    };
+    let remote_ref_str = &refs.remote_ref_str;
+
+    let reset_type = reset_arg(reset_type);
+
+    git::reset(&full_path, reset_type, remote_ref_str)?;
+
+    // git may only warn and still exit 0 when the reset does not take effect
+    let current = git::rev_parse(&full_path, "HEAD")?;
+    match refs.target.as_ref() {
+        Some(target) if current != *target => Err(anyhow!(
+            "reset to {} ({}) left HEAD at {}",
+            remote_ref_str,
+            &target[..7],
+            &current[..7]
+        )),
+        _ => Ok(()),
+    }
+}
 
-    let reset_type = match reset_type {
-        ResetType::Soft => "--soft",
-        ResetType::Mixed => "--mixed",
-        ResetType::Hard => "--hard",
-    };
+/// untracked, modified and staged files a stash would take, with `--report-untracked`
+fn report_local_changes(
+    full_path: &Path,
+    exec_options: &InnerExecOptions,
+) -> anyhow::Result<Vec<String>> {
+    let mut files = Vec::new();
+    if !exec_options.report_untracked {
+        return Ok(files);
+    }
+
+    let mut changes = vec![
+        ("modified", git::get_changed_files(full_path)?),
+        ("staged", git::get_staged_files(full_path)?),
+    ];
+    if !exec_options.keep_untracked {
+        changes.insert(0, ("untracked", git::get_untrack_files(full_path)?));
+    }
+    for (kind, output) in changes {
+        for file in output.trim().lines() {
+            files.push(format!("{} {}", kind, file));
+        }
+    }
+    Ok(files)
+}
 
-    git::reset(&full_path, reset_type, remote_ref_str)
+/// untracked files count too, unless `--keep-untracked` leaves them in place
+fn has_changes_to_stash(full_path: &Path, exec_options: &InnerExecOptions) -> anyhow::Result<bool> {
+    match exec_options.keep_untracked {
+        true => git::has_tracked_changes(full_path),
+        false => git::has_local_changes(full_path),
+    }
 }
 
 fn exec_stash(
     input_path: &Path,
     repo_info: &RepoInfo,
+    exec_options: &InnerExecOptions,
     progress: &impl Progress,
 ) -> Result<InnerStashResponse, anyhow::Error> {
-    progress.repo_info(repo_info, "stash...".into());
-
     let full_path = input_path.join(repo_info.rel_path());
-    let msg = git::stash(full_path)?;
+    let include_untracked = !exec_options.keep_untracked;
 
-    let response = match msg.find("WIP") {
-        Some(idx) => InnerStashResponse::Stash(msg.trim()[idx..].to_string()),
-        None => InnerStashResponse::None,
-    };
-    Ok(response)
+    // a clean work tree has nothing to stash, and no commit to stash on in a new repo
+    if !has_changes_to_stash(&full_path, exec_options)? {
+        progress.repo_info(repo_info, "nothing to stash".into());
+        return Ok(InnerStashResponse::None);
+    }
+
+    progress.repo_info(repo_info, "stash...".into());
+    let msg = git::stash(&full_path, include_untracked)?;
+
+    // `--abort` pops this entry, even when others are pushed on top of it
+    let stash = git::rev_parse(&full_path, "stash@{0}")?;
+    let rel_path = repo_info.rel_path().display_path();
+    exec_options.sync_state.set_stash(&rel_path, stash)?;
+
+    let msg = msg.trim();
+    let msg = msg
+        .strip_prefix("Saved working directory and index state ")
+        .unwrap_or(msg);
+    Ok(InnerStashResponse::Stash(msg.to_string()))
 }
 
+/// pop the stash of this sync, a conflicting pop is handled by `--on-stash-conflict`
+/// and described in the result
 fn exec_stash_pop(
     input_path: &Path,
     repo_info: &RepoInfo,
+    exec_options: &InnerExecOptions,
     progress: &impl Progress,
-) -> Result<String, anyhow::Error> {
+) -> Result<Option<String>, anyhow::Error> {
     progress.repo_info(repo_info, "pop stash...".into());
 
     let full_path = input_path.join(repo_info.rel_path());
-    git::stash_pop(full_path)
+    let pop_err = match git::stash_pop(&full_path) {
+        Ok(_) => return Ok(None),
+        Err(e) => e,
+    };
+
+    // other failures leave the stash entry untouched
+    let conflicted_files = git::get_unmerged_files(&full_path)?;
+    let conflicted_files = conflicted_files.trim().lines().collect::<Vec<_>>();
+    if conflicted_files.is_empty() {
+        return Err(pop_err);
+    }
+
+    let conflict = format!("stash pop conflicted in {}", conflicted_files.join(", "));
+    let desc = match exec_options.on_stash_conflict {
+        OnStashConflict::Keep => {
+            format!("{}, resolve them and run `mgit sync --continue`", conflict)
+        }
+        OnStashConflict::Restash => {
+            git::abort_stash_pop(&full_path)?;
+            format!("{}, changes are kept in stash@{{0}}", conflict)
+        }
+        OnStashConflict::Branch => {
+            git::abort_stash_pop(&full_path)?;
+            let secs = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            let branch = format!("mgit-stash/{}", secs);
+            git::stash_to_branch(&full_path, &branch)?;
+            format!(
+                "{}, changes are moved to branch {}, restore them with `git stash apply {}`",
+                conflict, branch, branch
+            )
+        }
+    };
+    Ok(Some(desc))
 }
 
 fn exec_checkout(
     input_path: &Path,
     repo_info: &RepoInfo,
+    refs: &RepoRefs,
     progress: &impl Progress,
     force: bool,
 ) -> anyhow::Result<()> {
     progress.repo_info(repo_info, "checkout...".into());
 
     let full_path = input_path.join(repo_info.rel_path());
-    // priority: commit/tag/branch(default-branch)
-    let remote_ref = repo_info.toml_repo.get_remote_ref(full_path.as_path())?;
-    let remote_ref_str = match remote_ref.clone() {
-        RemoteRef::Commit(r) | RemoteRef::Tag(r) | RemoteRef::Branch(r) => r,
-    };
-    let branch = match remote_ref {
-        RemoteRef::Commit(commit) => format!("commits/{}", &commit[..7]),
-        RemoteRef::Tag(tag) => format!("tags/{}", tag),
-        RemoteRef::Branch(_) => repo_info
-            .toml_repo
-            .branch
-            .clone()
-            .unwrap_or("invalid-branch".to_string()),
-    };
+    let branch = &refs.branch;
 
-    // don't need to checkout if current branch is the branch
-    if let Ok(current_branch) = git::get_current_branch(full_path.as_path()) {
-        if branch == current_branch {
-            return Ok(());
-        }
+    if refs.is_checked_out(git::get_current_branch(&full_path).ok().as_deref()) {
+        return Ok(());
     }
 
-    let suffix = StyleMessage::git_checking_out(&branch);
+    let suffix = StyleMessage::git_checking_out(branch);
     progress.repo_info(repo_info, suffix);
 
-    // check if local branch already exists
-    let branch_exist = git::local_branch_already_exist(&full_path, &branch)?;
+    let args = checkout_args(branch, &refs.remote_ref_str, refs.branch_exists, force);
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    git::checkout(full_path, &args)
+}
+
+/// refs of a repo resolved once per sync, instead of asking git for the remote name
+/// and the branch again in every step of the checkout
+struct RepoRefs {
+    remote_name: String,
+    /// priority: commit/tag/branch(default-branch)
+    remote_ref: RemoteRef,
+    remote_ref_str: String,
+    /// commit of the remote ref, none until the remote is fetched
+    target: Option<String>,
+    /// local branch checked out for the remote ref
+    branch: String,
+    branch_exists: bool,
+}
 
+impl RepoRefs {
+    /// refs of a fetched repository, its remote ref has to exist
+    fn resolve(full_path: &Path, toml_repo: &TomlRepo) -> anyhow::Result<Self> {
+        let remote_name = toml_repo.resolve_remote_name(full_path)?;
+        let refs = Self::with_remote(full_path, toml_repo, remote_name, false)?;
+        match refs.target {
+            Some(_) => Ok(refs),
+            None => Err(anyhow!(StyleMessage::git_remote_not_found(
+                &refs.remote_ref_str
+            ))),
+        }
+    }
+
+    /// don't need to checkout if current branch is the branch, an unborn one still needs it
+    fn is_checked_out(&self, current_branch: Option<&str>) -> bool {
+        self.branch_exists && current_branch == Some(self.branch.as_str())
+    }
+
+    /// refs on a known remote, a repository not created yet has no target or branches
+    fn with_remote(
+        full_path: &Path,
+        toml_repo: &TomlRepo,
+        remote_name: String,
+        is_repo_none: bool,
+    ) -> anyhow::Result<Self> {
+        let remote_ref = toml_repo.get_remote_ref_by_name(&remote_name)?;
+        let remote_ref_str = remote_ref.to_string();
+        let branch = checkout_branch_name(toml_repo, &remote_ref);
+        let (target, branch_exists) = match is_repo_none {
+            true => (None, false),
+            false => (
+                git::rev_parse(full_path, &remote_ref_str).ok(),
+                git::local_branch_already_exist(full_path, &branch)?,
+            ),
+        };
+        Ok(Self {
+            remote_name,
+            remote_ref,
+            remote_ref_str,
+            target,
+            branch,
+            branch_exists,
+        })
+    }
+}
+
+/// local branch checked out for the remote ref
+pub(crate) fn checkout_branch_name(toml_repo: &TomlRepo, remote_ref: &RemoteRef) -> String {
+    match remote_ref {
+        RemoteRef::Commit(_) => format!("commits/{}", remote_ref.remote_desc()),
+        RemoteRef::Tag(tag) => format!("tags/{}", tag),
+        RemoteRef::Branch(_) => toml_repo
+            .branch
+            .clone()
+            .unwrap_or("invalid-branch".to_string()),
+    }
+}
+
+pub(crate) fn checkout_args(
+    branch: &str,
+    remote_ref_str: &str,
+    branch_exist: bool,
+    force: bool,
+) -> Vec<String> {
     // create/checkout/reset branch
     let args = match (branch_exist, force) {
-        (false, false) => vec!["checkout", "-B", &branch, &remote_ref_str, "--no-track"],
-        (false, true) => vec![
-            "checkout",
-            "-B",
-            &branch,
-            &remote_ref_str,
-            "--no-track",
-            "-f",
-        ],
-        (true, false) => vec!["checkout", &branch],
-        (true, true) => vec!["checkout", "-B", &branch, "-f"],
+        (false, false) => vec!["checkout", "-B", branch, remote_ref_str, "--no-track"],
+        (false, true) => vec!["checkout", "-B", branch, remote_ref_str, "--no-track", "-f"],
+        (true, false) => vec!["checkout", branch],
+        (true, true) => vec!["checkout", "-B", branch, "-f"],
     };
-
-    git::checkout(full_path, &args)
+    args.into_iter().map(String::from).collect()
 }
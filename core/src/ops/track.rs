@@ -1,12 +1,10 @@
 use anyhow::anyhow;
-use std::env;
 use std::path::{Path, PathBuf};
 
 use crate::core::git;
-use crate::core::git::RemoteRef;
 use crate::core::repo::repos_to_map_with_ignore;
 use crate::core::repo::TomlRepo;
-use crate::core::repos::TomlConfig;
+use crate::core::repos::{TomlConfig, WorkspacePaths};
 use crate::utils::error::{MgitError, MgitResult};
 
 use crate::utils::logger;
@@ -25,8 +23,7 @@ impl TrackOptions {
         config_path: Option<impl AsRef<Path>>,
         ignore: Option<Vec<String>>,
     ) -> Self {
-        let path = path.map_or(env::current_dir().unwrap(), |p| p.as_ref().to_path_buf());
-        let config_path = config_path.map_or(path.join(".gitrepos"), |p| p.as_ref().to_path_buf());
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
         Self {
             path,
             config_path,
@@ -108,19 +105,23 @@ pub fn set_tracking_remote_branch(
 
     // priority: commit/tag/branch(default-branch)
     let remote_ref = toml_repo.get_remote_ref(full_path.as_path())?;
-    let remote_ref_str = match remote_ref.clone() {
-        RemoteRef::Commit(r) | RemoteRef::Tag(r) | RemoteRef::Branch(r) => r,
-    };
-    let remote_desc = match remote_ref {
-        RemoteRef::Commit(commit) => commit[..7].to_string(),
-        RemoteRef::Tag(r) | RemoteRef::Branch(r) => r,
-    };
+    let remote_ref_str = remote_ref.to_string();
+    let remote_desc = remote_ref.remote_desc();
 
     if toml_repo.commit.is_some() || toml_repo.tag.is_some() {
         let res = StyleMessage::git_untracked(rel_path, &remote_desc);
         return Ok(res);
     }
 
+    if local_branch.is_empty() {
+        return Err(anyhow!("current branch not found, HEAD is detached."));
+    }
+
+    // a fork pulls from upstream and pushes to origin
+    if toml_repo.is_fork() {
+        git::set_push_remote(&full_path, &local_branch, toml_repo.origin_name())?;
+    }
+
     git::set_tracking_remote_branch(
         full_path,
         rel_path,
@@ -0,0 +1,159 @@
+use anyhow::anyhow;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use crate::core::repos::{TomlConfig, WorkspacePaths};
+use crate::utils::error::{MgitError, MgitResult, OpsErrors};
+use crate::utils::logger;
+use crate::utils::StyleMessage;
+
+pub struct ValidateOptions {
+    pub path: PathBuf,
+    pub config_path: PathBuf,
+    /// fail on warnings too, not only on errors
+    pub strict: bool,
+}
+
+impl ValidateOptions {
+    pub fn new(path: Option<impl AsRef<Path>>, config_path: Option<impl AsRef<Path>>) -> Self {
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
+        Self {
+            path,
+            config_path,
+            strict: false,
+        }
+    }
+}
+
+/// check the config file for repos that would make sync fail or behave unexpectedly,
+/// without touching the repositories
+pub fn validate(options: ValidateOptions) -> MgitResult {
+    let config_path = &options.config_path;
+
+    logger::info(StyleMessage::ops_start("validate", &options.path));
+
+    // check if .gitrepos exists
+    if !config_path.is_file() {
+        return Err(anyhow!(MgitError::ConfigFileNotFound(
+            StyleMessage::config_file_not_found()
+        )));
+    }
+
+    // load config file(like .gitrepos)
+    let Some(toml_config) = TomlConfig::load(config_path) else {
+        return Err(anyhow!(MgitError::LoadConfigFailed));
+    };
+
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+    let toml_repos = toml_config.repos.unwrap_or_default();
+
+    // normalized local paths, `.` and `a/..` become empty
+    let mut locals: Vec<(String, Vec<String>)> = Vec::new();
+    for (idx, toml_repo) in toml_repos.iter().enumerate() {
+        let Some(local) = toml_repo.local.as_ref() else {
+            errors.push(StyleMessage::config_error(
+                format!("repo #{}", idx + 1),
+                "missing local",
+            ));
+            continue;
+        };
+
+        if toml_repo.remote.is_none() {
+            errors.push(StyleMessage::config_error(local, "missing remote"));
+        }
+
+        match normalize_local(local) {
+            Some(components) => locals.push((local.to_string(), components)),
+            None => errors.push(StyleMessage::config_error(
+                local,
+                "local escapes the work directory",
+            )),
+        }
+
+        if toml_repo.commit.is_some() && toml_repo.tag.is_some() {
+            warnings.push(StyleMessage::config_warning(
+                local,
+                "both commit and tag are set, the commit wins",
+            ));
+        }
+
+        let has_ref = toml_repo.commit.is_some()
+            || toml_repo.tag.is_some()
+            || toml_repo.branch.is_some()
+            || toml_config.default_branch.is_some();
+        if !has_ref {
+            warnings.push(StyleMessage::config_warning(
+                local,
+                "no branch, tag or commit, and no default-branch",
+            ));
+        }
+    }
+
+    // the same directory listed twice
+    let mut seen: HashMap<&[String], &str> = HashMap::new();
+    for (local, components) in &locals {
+        match seen.get(components.as_slice()) {
+            Some(first) => errors.push(StyleMessage::config_error(
+                local,
+                format!("duplicate local, same directory as {}", first),
+            )),
+            None => {
+                seen.insert(components, local);
+            }
+        }
+    }
+
+    // repos inside other repos, the root repo `.` is expected to hold the others
+    for (local, components) in &locals {
+        let parent = locals.iter().find(|(_, other)| {
+            !other.is_empty() && other.len() < components.len() && components.starts_with(other)
+        });
+        if let Some((parent, _)) = parent {
+            warnings.push(StyleMessage::config_warning(
+                local,
+                format!("nested in repo {}", parent),
+            ));
+        }
+    }
+
+    if options.strict {
+        errors.append(&mut warnings);
+    }
+
+    // warnings are reported but not counted as failures without --strict
+    let mut warning_msg = StyleMessage::new();
+    if !warnings.is_empty() {
+        warning_msg = StyleMessage::from("Warnings:\n");
+        for warning in warnings {
+            warning_msg = warning_msg.plain_text("  ").join(warning).plain_text("\n");
+        }
+    }
+
+    match errors.len() {
+        0 => Ok(StyleMessage::ops_success("validate").join(warning_msg)),
+        _ => {
+            let msg = StyleMessage::ops_failed("validate", errors.len()).join(warning_msg);
+            Err(anyhow!(MgitError::OpsError {
+                prefix: msg,
+                errors: OpsErrors(errors),
+            }))
+        }
+    }
+}
+
+/// components of the local path with `.` and `..` resolved, none if it leaves the work directory
+pub(crate) fn normalize_local(local: &str) -> Option<Vec<String>> {
+    let mut components = Vec::new();
+    for component in Path::new(&local.replace('\\', "/")).components() {
+        match component {
+            Component::Normal(name) => components.push(name.to_string_lossy().to_string()),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                components.pop()?;
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(components)
+}
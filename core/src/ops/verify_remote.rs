@@ -0,0 +1,155 @@
+use anyhow::anyhow;
+use std::path::{Path, PathBuf};
+
+use crate::core::git;
+use crate::core::git::FetchOrPush;
+use crate::core::repo::{repos_to_map_with_ignore, TomlRepo};
+use crate::core::repos::{TomlConfig, WorkspacePaths};
+use crate::utils::error::{MgitError, MgitResult, OpsErrors};
+use crate::utils::logger;
+use crate::utils::path::PathExtension;
+use crate::utils::StyleMessage;
+
+pub struct VerifyRemoteOptions {
+    pub path: PathBuf,
+    pub config_path: PathBuf,
+    pub fix: bool,
+    pub ignore: Option<Vec<String>>,
+}
+
+impl VerifyRemoteOptions {
+    pub fn new(
+        path: Option<impl AsRef<Path>>,
+        config_path: Option<impl AsRef<Path>>,
+        fix: Option<bool>,
+        ignore: Option<Vec<String>>,
+    ) -> Self {
+        let WorkspacePaths { path, config_path } = WorkspacePaths::resolve(path, config_path);
+        Self {
+            path,
+            config_path,
+            fix: fix.unwrap_or(false),
+            ignore,
+        }
+    }
+}
+
+/// a remote whose url differs from the manifest
+struct RemoteDrift {
+    remote_name: String,
+    actual: String,
+    expected: String,
+}
+
+/// compare remote urls of every repo to the manifest, and set them back with `--fix`
+pub fn verify_remote(options: VerifyRemoteOptions) -> MgitResult {
+    let path = &options.path;
+    let config_path = &options.config_path;
+    let fix = options.fix;
+    let ignore = options.ignore.as_ref();
+
+    logger::info(StyleMessage::ops_start("verify remote", path));
+
+    // if directory doesn't exist, return
+    if !path.is_dir() {
+        return Err(anyhow!(MgitError::DirNotFound(
+            StyleMessage::dir_not_found(path)
+        )));
+    }
+
+    // check if .gitrepos exists
+    if !config_path.is_file() {
+        return Err(anyhow!(MgitError::ConfigFileNotFound(
+            StyleMessage::config_file_not_found()
+        )));
+    }
+
+    // load config file(like .gitrepos)
+    let Some(toml_config) = TomlConfig::load(config_path) else {
+        return Err(anyhow!(MgitError::LoadConfigFailed));
+    };
+
+    let Some(toml_repos) = toml_config.repos else {
+        return Ok("No repos to verify".into());
+    };
+
+    let mut repos = repos_to_map_with_ignore(toml_repos, ignore)
+        .into_iter()
+        .collect::<Vec<_>>();
+    repos.sort_by_key(|(id, _)| *id);
+
+    let mut errors = Vec::new();
+    for (_, toml_repo) in repos {
+        let rel_path = toml_repo.local.as_ref().unwrap().display_path();
+        let full_path = path.join(&rel_path);
+
+        let drifts = match find_drifts(&full_path, &toml_repo) {
+            Ok(drifts) => drifts,
+            Err(e) => {
+                errors.push(StyleMessage::git_error(&rel_path, &e));
+                continue;
+            }
+        };
+
+        for drift in drifts {
+            if !fix {
+                errors.push(StyleMessage::git_remote_mismatch(
+                    &rel_path,
+                    &drift.remote_name,
+                    &drift.actual,
+                    &drift.expected,
+                ));
+                continue;
+            }
+
+            match git::set_remote_url(&full_path, &drift.remote_name, &drift.expected) {
+                Ok(_) => logger::info(StyleMessage::git_repair(
+                    &rel_path,
+                    format!("{} {}", drift.remote_name, drift.actual),
+                    &drift.expected,
+                )),
+                Err(e) => errors.push(StyleMessage::git_error(&rel_path, &e)),
+            }
+        }
+    }
+
+    match errors.len() {
+        0 => Ok(StyleMessage::ops_success("verify remote")),
+        _ => {
+            let msg = StyleMessage::ops_failed("verify remote", errors.len());
+            Err(anyhow!(MgitError::OpsError {
+                prefix: msg,
+                errors: OpsErrors(errors),
+            }))
+        }
+    }
+}
+
+/// remotes of the repo not pointing to the manifest urls, after `insteadOf` rewrites
+fn find_drifts(full_path: &Path, toml_repo: &TomlRepo) -> anyhow::Result<Vec<RemoteDrift>> {
+    git::is_repository(full_path)?;
+
+    let mut expected_remotes = Vec::new();
+    if let Some(remote) = toml_repo.remote_url() {
+        expected_remotes.push((toml_repo.origin_name(), remote));
+    }
+    if toml_repo.is_fork() {
+        expected_remotes.push(("upstream", toml_repo.fetch_url().unwrap()));
+    }
+
+    let mut drifts = Vec::new();
+    for (remote_name, url) in expected_remotes {
+        let expected = git::rewrite_url(full_path, &url)?;
+        let actual = git::find_remote_url_by_name(full_path, remote_name, FetchOrPush::Fetch)
+            .unwrap_or("none".to_string());
+
+        if actual.trim_end_matches('/') != expected.trim_end_matches('/') {
+            drifts.push(RemoteDrift {
+                remote_name: remote_name.to_string(),
+                actual,
+                expected: url.to_string(),
+            });
+        }
+    }
+    Ok(drifts)
+}
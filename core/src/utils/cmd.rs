@@ -1,15 +1,154 @@
 use anyhow::{Context, Error};
 use console::strip_ansi_codes;
-use std::io::{BufRead, BufReader};
+use std::cell::Cell;
+use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
-use std::process::{Command, Stdio};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
 
+use crate::utils::logger;
+use crate::utils::path::PathExtension;
 use crate::utils::progress::{Progress, RepoInfo};
 use crate::utils::StyleMessage;
 
+static TRACE_GIT: AtomicBool = AtomicBool::new(false);
+
+/// log every executed command with its working directory, exit code and duration
+pub fn set_trace_git(enable: bool) {
+    TRACE_GIT.store(enable, Ordering::Relaxed);
+}
+
+pub fn is_trace_git() -> bool {
+    TRACE_GIT.load(Ordering::Relaxed)
+}
+
+static VERBOSE_GIT: AtomicBool = AtomicBool::new(false);
+
+/// pass the output of commands through to the logger, prefixed with their repo: all of it for
+/// streamed commands like clone and fetch, only failures for the others
+pub fn set_verbose_git(enable: bool) {
+    VERBOSE_GIT.store(enable, Ordering::Relaxed);
+}
+
+/// log the lines of `output` prefixed with `prefix`, in `--verbose-git` mode
+fn log_output(prefix: &str, output: &str) {
+    if !VERBOSE_GIT.load(Ordering::Relaxed) {
+        return;
+    }
+    for line in output.split(['\r', '\n']) {
+        let line = strip_ansi_codes(line);
+        if !line.trim().is_empty() {
+            logger::info(StyleMessage::cmd_output(prefix, line.trim_end()));
+        }
+    }
+}
+
+static SKIP_LFS_SMUDGE: AtomicBool = AtomicBool::new(false);
+
+/// run commands with `GIT_LFS_SKIP_SMUDGE=1`, leaving LFS files as pointers
+pub fn set_skip_lfs_smudge(enable: bool) {
+    SKIP_LFS_SMUDGE.store(enable, Ordering::Relaxed);
+}
+
+thread_local! {
+    static GIT_TIMEOUT: Cell<Option<Duration>> = const { Cell::new(None) };
+}
+
+/// time limit of the commands run on this thread, until the guard is dropped
+pub struct GitTimeout {
+    previous: Option<Duration>,
+}
+
+impl GitTimeout {
+    /// kill every command this thread runs for longer than `timeout` while the guard lives,
+    /// none lets them run as long as they take
+    pub fn scoped(timeout: Option<Duration>) -> Self {
+        let timeout = timeout.map(|timeout| timeout.max(Duration::from_secs(1)));
+        Self {
+            previous: GIT_TIMEOUT.with(|cell| cell.replace(timeout)),
+        }
+    }
+}
+
+impl Drop for GitTimeout {
+    fn drop(&mut self) {
+        GIT_TIMEOUT.with(|cell| cell.set(self.previous));
+    }
+}
+
+fn git_timeout() -> Option<Duration> {
+    GIT_TIMEOUT.with(Cell::get)
+}
+
+/// config overrides whose values may hold credentials, masked wherever a command is shown
+const SECRET_CONFIGS: [&str; 2] = ["http.proxy=", "https.proxy="];
+
+/// values of the secret `-c` overrides of the command
+fn secrets(command: &Command) -> Vec<String> {
+    command
+        .get_args()
+        .filter_map(|arg| {
+            let arg = arg.to_string_lossy();
+            SECRET_CONFIGS
+                .iter()
+                .find_map(|key| arg.strip_prefix(key).map(String::from))
+        })
+        .filter(|secret| !secret.is_empty())
+        .collect()
+}
+
+/// `text` with every secret replaced by `***`
+fn redact(text: &str, secrets: &[String]) -> String {
+    secrets
+        .iter()
+        .fold(text.to_string(), |text, secret| text.replace(secret, "***"))
+}
+
+/// args of a planned command, with the secrets of its `-c` overrides masked
+pub(crate) fn redact_args(program: &str, args: Vec<String>) -> Vec<String> {
+    let mut command = Command::new(program);
+    command.args(&args);
+    let secrets = secrets(&command);
+    args.iter().map(|arg| redact(arg, &secrets)).collect()
+}
+
+/// command line of a planned command for display, args with spaces are quoted
+pub(crate) fn display_args(program: &str, args: &[String]) -> String {
+    std::iter::once(program)
+        .chain(args.iter().map(String::as_str))
+        .map(
+            |arg| match arg.is_empty() || arg.contains(char::is_whitespace) {
+                true => format!("'{}'", arg),
+                false => arg.to_string(),
+            },
+        )
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// environment shared by every executed command
+fn apply_env(command: &mut Command) {
+    if SKIP_LFS_SMUDGE.load(Ordering::Relaxed) {
+        command.env("GIT_LFS_SKIP_SMUDGE", "1");
+    }
+}
+
 pub fn exec_cmd(path: impl AsRef<Path>, cmd: &str, args: &[&str]) -> Result<String, anyhow::Error> {
+    exec_cmd_with_timeout(path, cmd, args, git_timeout())
+}
+
+/// `exec_cmd` killing the command once it runs longer than `timeout`
+pub fn exec_cmd_with_timeout(
+    path: impl AsRef<Path>,
+    cmd: &str,
+    args: &[&str],
+    timeout: Option<Duration>,
+) -> Result<String, anyhow::Error> {
     let mut command = std::process::Command::new(cmd);
-    let full_command = command.current_dir(path).args(args);
+    let full_command = command.current_dir(&path).args(args);
+    apply_env(full_command);
 
     #[cfg(target_os = "windows")]
     {
@@ -18,14 +157,26 @@ pub fn exec_cmd(path: impl AsRef<Path>, cmd: &str, args: &[&str]) -> Result<Stri
         full_command.creation_flags(CREATE_NO_WINDOW);
     }
 
-    let output = full_command
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped())
-        .output()
-        .with_context(|| format!("Error starting command: {:?}", full_command))?;
+    let start = trace_start(full_command);
+    let output = match timeout {
+        Some(timeout) => output_with_timeout(full_command, timeout, start)?,
+        None => full_command
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .output()
+            .with_context(|| format!("Error starting command: {}", command_line(full_command)))?,
+    };
+    trace_end(full_command, &output.status, start);
 
-    let stdout = String::from_utf8(output.stdout)?;
-    let stderr = String::from_utf8(output.stderr)?;
+    let secrets = secrets(full_command);
+    let stdout = redact(&String::from_utf8(output.stdout)?, &secrets);
+    let stderr = redact(&String::from_utf8(output.stderr)?, &secrets);
+    // queries would drown the output, only failed commands are passed through
+    if !output.status.success() {
+        let prefix = path.as_ref().display_path();
+        log_output(&prefix, &stdout);
+        log_output(&prefix, &stderr);
+    }
 
     match output.status.success() {
         true => Ok(stdout),
@@ -33,10 +184,172 @@ pub fn exec_cmd(path: impl AsRef<Path>, cmd: &str, args: &[&str]) -> Result<Stri
     }
 }
 
+/// `Command::output` with a time limit, the output is read on threads meanwhile
+fn output_with_timeout(
+    command: &mut Command,
+    timeout: Duration,
+    start: Instant,
+) -> anyhow::Result<Output> {
+    own_process_group(command);
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Error starting command: {}", command_line(command)))?;
+
+    let read_all = |pipe: Option<Box<dyn Read + Send>>| {
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buf);
+            }
+            buf
+        })
+    };
+    let stdout = read_all(
+        child
+            .stdout
+            .take()
+            .map(|p| Box::new(p) as Box<dyn Read + Send>),
+    );
+    let stderr = read_all(
+        child
+            .stderr
+            .take()
+            .map(|p| Box::new(p) as Box<dyn Read + Send>),
+    );
+
+    let status = wait_until(&mut child, command, start + timeout, timeout)?;
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+/// wait for the child, killing it at the deadline
+fn wait_until(
+    child: &mut Child,
+    command: &Command,
+    deadline: Instant,
+    timeout: Duration,
+) -> anyhow::Result<ExitStatus> {
+    loop {
+        if let Some(status) = child
+            .try_wait()
+            .context("Error waiting for process to finish")?
+        {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            return Err(kill_timed_out(child, command, timeout));
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+/// start the command in a process group of its own, so a timeout can kill the processes
+/// it starts too, like ssh or `git-remote-https`
+fn own_process_group(command: &mut Command) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    #[cfg(not(unix))]
+    let _ = command;
+}
+
+/// kill the child which ran out of time with its process group, so no process is left
+/// holding the pipes the reading threads wait on
+fn kill_timed_out(child: &mut Child, command: &Command, timeout: Duration) -> anyhow::Error {
+    #[cfg(unix)]
+    if let Ok(pid) = libc::pid_t::try_from(child.id()) {
+        // SAFETY: the child leads its own process group, see `own_process_group`
+        unsafe {
+            libc::kill(-pid, libc::SIGKILL);
+        }
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+    anyhow::anyhow!(
+        "timed out after {}s: {}",
+        timeout.as_secs(),
+        command_line(command)
+    )
+}
+
+/// what a shell command left behind, whatever its exit code
+pub struct ShellOutput {
+    /// none when the command was killed by a signal
+    pub code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// run `command` with the shell of the platform in `path`, with extra environment variables
+pub fn exec_shell(
+    path: impl AsRef<Path>,
+    command: &str,
+    envs: &[(&str, &str)],
+) -> Result<ShellOutput, anyhow::Error> {
+    #[cfg(target_os = "windows")]
+    let mut shell = {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").creation_flags(CREATE_NO_WINDOW);
+        shell
+    };
+    #[cfg(not(target_os = "windows"))]
+    let mut shell = {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+
+    let full_command = shell
+        .arg(command)
+        .current_dir(&path)
+        .envs(envs.iter().copied())
+        .stdin(Stdio::null());
+    apply_env(full_command);
+
+    let start = trace_start(full_command);
+    let output = full_command
+        .output()
+        .with_context(|| format!("Error starting command: {}", command_line(full_command)))?;
+    trace_end(full_command, &output.status, start);
+
+    Ok(ShellOutput {
+        code: output.status.code(),
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+    })
+}
+
 pub fn exec_cmd_with_progress(
     repo_info: &RepoInfo,
     command: &mut Command,
     progress: &impl Progress,
+) -> anyhow::Result<()> {
+    progress.repo_info(repo_info, "running...".into());
+
+    let prefix = repo_info.rel_path().display_path();
+    exec_cmd_with_callback(&prefix, command, |line| {
+        progress.repo_info(repo_info, line.trim().into());
+    })
+}
+
+/// run the command and pass every progress line of its stderr to `on_line` as it arrives,
+/// instead of buffering the output. git only writes progress there with `--progress`.
+///
+/// lines are split on `\r` too, so each "Receiving objects: 42%" update is a line of its own.
+pub fn exec_cmd_with_callback(
+    prefix: &str,
+    command: &mut Command,
+    mut on_line: impl FnMut(&str),
 ) -> anyhow::Result<()> {
     #[cfg(target_os = "windows")]
     {
@@ -44,38 +357,93 @@ pub fn exec_cmd_with_progress(
         const CREATE_NO_WINDOW: u32 = 0x08000000;
         command.creation_flags(CREATE_NO_WINDOW);
     }
+    apply_env(command);
+    let timeout = git_timeout();
+    if timeout.is_some() {
+        own_process_group(command);
+    }
+    let start = trace_start(command);
     let mut spawned = command
         .stdin(Stdio::null())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()
         .with_context(|| {
-            StyleMessage::new().plain_text(format!("Error starting command {:?}", command))
+            StyleMessage::new()
+                .plain_text(format!("Error starting command {}", command_line(command)))
         })?;
 
-    progress.repo_info(repo_info, "running...".into());
+    // stdout is only passed through with verbose git, while stderr feeds the callback
+    // below. it's drained either way, a full pipe would block the command
+    let secrets = secrets(command);
+    let verbose = VERBOSE_GIT.load(Ordering::Relaxed);
+    let stdout_thread = spawned.stdout.take().map(|stdout| {
+        let prefix = prefix.to_string();
+        let secrets = secrets.clone();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).split(b'\n').map_while(Result::ok) {
+                if verbose {
+                    let line = String::from_utf8_lossy(&line);
+                    log_output(&prefix, &redact(&line, &secrets));
+                }
+            }
+        })
+    });
 
-    // get message from stderr with "--progress" option
+    // get message from stderr with "--progress" option, read on a thread so a
+    // command which stops writing can still be given up on at the timeout
+    let deadline = timeout.map(|timeout| start + timeout);
     let mut last_line = StyleMessage::new();
-    if let Some(ref mut stderr) = spawned.stderr {
-        let lines = BufReader::new(stderr).split(b'\r');
-        for line in lines {
-            let output = line.unwrap();
+    if let Some(stderr) = spawned.stderr.take() {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stderr).split(b'\r') {
+                let Ok(line) = line else {
+                    break;
+                };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        loop {
+            let received = match deadline {
+                Some(deadline) => {
+                    receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            let output = match received {
+                Ok(output) => output,
+                Err(RecvTimeoutError::Disconnected) => break,
+                Err(RecvTimeoutError::Timeout) => {
+                    let timeout = timeout.unwrap_or_default();
+                    return Err(kill_timed_out(&mut spawned, command, timeout));
+                }
+            };
             if output.is_empty() {
                 continue;
             }
-            let line = std::str::from_utf8(&output).unwrap();
-            let plain_line = strip_ansi_codes(line).replace('\n', " ");
-            let full_line = plain_line.trim().into();
+            let line = redact(&String::from_utf8_lossy(&output), &secrets);
+            log_output(prefix, &line);
+            let plain_line = strip_ansi_codes(&line).replace('\n', " ");
 
-            progress.repo_info(repo_info, full_line);
+            on_line(&plain_line);
             last_line = last_line.plain_text(plain_line);
         }
     }
 
-    let exit_code = spawned
-        .wait()
-        .context("Error waiting for process to finish")?;
+    let exit_code = match (deadline, timeout) {
+        (Some(deadline), Some(timeout)) => wait_until(&mut spawned, command, deadline, timeout)?,
+        _ => spawned
+            .wait()
+            .context("Error waiting for process to finish")?,
+    };
+    if let Some(stdout_thread) = stdout_thread {
+        let _ = stdout_thread.join();
+    }
+    trace_end(command, &exit_code, start);
 
     if !exit_code.success() {
         return Err(Error::msg("").context(
@@ -85,16 +453,62 @@ pub fn exec_cmd_with_progress(
                     exit_code.code().unwrap()
                 ))
                 .join(last_line)
-                .plain_text(format!(". With command : {:?}", command)),
+                .plain_text(format!(". With command : {}", command_line(command))),
         ));
     }
     Ok(())
 }
 
+fn trace_start(command: &Command) -> Instant {
+    if TRACE_GIT.load(Ordering::Relaxed) {
+        let dir = command.get_current_dir().unwrap_or(Path::new("."));
+        logger::info(StyleMessage::cmd_trace_start(command_line(command), dir));
+    }
+    Instant::now()
+}
+
+fn trace_end(command: &Command, status: &ExitStatus, start: Instant) {
+    if TRACE_GIT.load(Ordering::Relaxed) {
+        let code = status
+            .code()
+            .map_or("none".to_string(), |code| code.to_string());
+        logger::info(StyleMessage::cmd_trace_end(
+            command_line(command),
+            code,
+            start.elapsed(),
+        ));
+    }
+}
+
+/// full argv of the command, e.g. `git fetch origin --progress`, with secrets masked
+fn command_line(command: &Command) -> String {
+    let line = std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ");
+    redact(&line, &secrets(command))
+}
+
+/// attempts of network commands, unless the manifest sets `retries` for the repo
+pub const RETRY_TIMES: usize = 10;
+/// wait between attempts, unless the manifest sets `retry-delay-ms` for the repo
+pub const RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(400);
+
 pub fn retry<T>(
     times: usize,
     sleep: std::time::Duration,
     f: impl Fn() -> Result<T, anyhow::Error>,
+) -> Result<T, anyhow::Error> {
+    retry_unless(times, sleep, |_| false, f)
+}
+
+/// like `retry`, but gives up at once on an error trying again can't fix
+pub fn retry_unless<T>(
+    times: usize,
+    sleep: std::time::Duration,
+    is_final: impl Fn(&anyhow::Error) -> bool,
+    f: impl Fn() -> Result<T, anyhow::Error>,
 ) -> Result<T, anyhow::Error> {
     let mut result = None::<Result<T, anyhow::Error>>;
     for _ in 0..times {
@@ -103,6 +517,10 @@ pub fn retry<T>(
                 result = Some(Ok(r));
                 break;
             }
+            Err(e) if is_final(&e) => {
+                result = Some(Err(e));
+                break;
+            }
             Err(e) => {
                 result = Some(Err(e));
                 std::thread::sleep(sleep);
@@ -0,0 +1,57 @@
+use std::sync::OnceLock;
+
+use crate::utils::logger;
+use crate::utils::style_message::StyleMessage;
+
+/// cap `thread_count` so the git processes of all threads stay within the open file limit.
+///
+/// the soft limit is raised towards the hard one first, only once per process.
+pub fn cap_thread_count(thread_count: usize) -> usize {
+    static MAX_THREADS: OnceLock<Option<usize>> = OnceLock::new();
+    match *MAX_THREADS.get_or_init(raise_fd_limit) {
+        Some(max) if thread_count > max => {
+            logger::info(StyleMessage::thread_count_capped(thread_count, max));
+            max
+        }
+        _ => thread_count,
+    }
+}
+
+/// the most threads the open file limit allows after raising it, none when unknown
+#[cfg(unix)]
+fn raise_fd_limit() -> Option<usize> {
+    // fds kept aside for config and log files and the terminal
+    const RESERVED_FDS: libc::rlim_t = 64;
+    // pipes of a running git command and the files its thread opens meanwhile
+    const FDS_PER_THREAD: libc::rlim_t = 8;
+    // more than any sensible `--thread`
+    const WANTED_THREADS: libc::rlim_t = 128;
+
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } != 0 {
+        return None;
+    }
+
+    // best-effort, e.g. the default soft limit of macOS is only 256
+    let wanted = RESERVED_FDS + WANTED_THREADS * FDS_PER_THREAD;
+    if limit.rlim_cur < wanted {
+        let raised = libc::rlimit {
+            rlim_cur: wanted.min(limit.rlim_max),
+            rlim_max: limit.rlim_max,
+        };
+        if unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &raised) } == 0 {
+            limit = raised;
+        }
+    }
+
+    let threads = limit.rlim_cur.saturating_sub(RESERVED_FDS) / FDS_PER_THREAD;
+    Some(usize::try_from(threads).unwrap_or(usize::MAX).max(1))
+}
+
+#[cfg(not(unix))]
+fn raise_fd_limit() -> Option<usize> {
+    None
+}
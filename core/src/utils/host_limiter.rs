@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+use std::sync::{Condvar, Mutex};
+
+/// limit the number of concurrent jobs against the same remote host
+pub struct HostLimiter {
+    jobs_per_host: usize,
+    running: Mutex<HashMap<String, usize>>,
+    released: Condvar,
+}
+
+/// a running job slot of a host, released on drop
+pub struct HostPermit<'a> {
+    limiter: &'a HostLimiter,
+    host: String,
+}
+
+impl HostLimiter {
+    pub fn new(jobs_per_host: usize) -> Self {
+        Self {
+            jobs_per_host: jobs_per_host.max(1),
+            running: Mutex::new(HashMap::new()),
+            released: Condvar::new(),
+        }
+    }
+
+    /// block until the host has a free slot
+    pub fn acquire(&self, host: impl AsRef<str>) -> HostPermit<'_> {
+        let host = host.as_ref().to_string();
+        let mut running = self.running.lock().unwrap();
+        while running.get(&host).copied().unwrap_or(0) >= self.jobs_per_host {
+            running = self.released.wait(running).unwrap();
+        }
+        *running.entry(host.clone()).or_insert(0) += 1;
+
+        HostPermit {
+            limiter: self,
+            host,
+        }
+    }
+}
+
+impl Drop for HostPermit<'_> {
+    fn drop(&mut self) {
+        let mut running = self.limiter.running.lock().unwrap();
+        if let Some(count) = running.get_mut(&self.host) {
+            *count -= 1;
+        }
+        self.limiter.released.notify_all();
+    }
+}
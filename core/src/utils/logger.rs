@@ -3,15 +3,11 @@ use crate::utils::style_message::StyleMessage;
 static mut LOGGER: &dyn Log = &NopLogger;
 
 pub(crate) fn info(message: impl Into<StyleMessage>) {
-    unsafe {
-        LOGGER.info(message.into());
-    }
+    get_logger().info(message.into());
 }
 
 pub(crate) fn error(message: impl Into<StyleMessage>) {
-    unsafe {
-        LOGGER.error(message.into());
-    }
+    get_logger().error(message.into());
 }
 
 pub fn set_logger(logger: &'static dyn Log) {
@@ -1,8 +1,13 @@
 pub mod cmd;
 pub mod error;
+pub mod fd_limit;
+pub mod host_limiter;
 pub mod logger;
 pub mod path;
 pub mod progress;
+pub mod repo_list;
 pub mod style_message;
+pub mod sync_state;
+pub mod timings;
 
 pub use style_message::StyleMessage;
@@ -0,0 +1,51 @@
+use anyhow::Context;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::utils::path::PathExtension;
+
+/// read a newline-delimited list of repo paths, blank lines and `#` comments are skipped
+pub fn load_repo_list(list_path: &Path) -> anyhow::Result<HashSet<String>> {
+    let content = std::fs::read_to_string(list_path)
+        .with_context(|| format!("Failed to read {}", list_path.display()))?;
+    let repos = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.norm_path().display_path())
+        .collect();
+    Ok(repos)
+}
+
+/// repos that failed the last run of a bulk operation, e.g. `.mgit/failed-new-tag`
+pub fn failed_record_path(path: &Path, ops_name: &str) -> PathBuf {
+    path.join(".mgit").join(format!("failed-{}", ops_name))
+}
+
+/// repos recorded as failed by the last run of the operation
+pub fn load_failed_repos(path: &Path, ops_name: &str) -> anyhow::Result<HashSet<String>> {
+    let record_path = failed_record_path(path, ops_name);
+    if !record_path.is_file() {
+        return Err(anyhow::anyhow!("no failed repos recorded for {}", ops_name));
+    }
+    load_repo_list(&record_path)
+}
+
+/// record failed repos for `--retry-failed`, the record is removed when nothing failed
+pub fn save_failed_repos(path: &Path, ops_name: &str, repos: &[String]) -> anyhow::Result<()> {
+    let record_path = failed_record_path(path, ops_name);
+    if repos.is_empty() {
+        if record_path.is_file() {
+            std::fs::remove_file(&record_path)?;
+        }
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(record_path.parent().unwrap())?;
+    let mut content = format!("# repos failed by the last {}\n", ops_name);
+    for repo in repos {
+        content += &format!("{}\n", repo);
+    }
+    std::fs::write(&record_path, content)
+        .with_context(|| format!("Failed to write {}", record_path.display()))
+}
@@ -1,6 +1,8 @@
 use crate::utils::path::PathExtension;
 use ansi_term::{Colour, Style};
 use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 use std::path::Path;
 
@@ -14,6 +16,7 @@ lazy_static! {
     pub static ref GREEN_BOLD: Style = Style::new().fg(Colour::Green).bold();
     pub static ref BLUE_BOLD: Style = Style::new().fg(Colour::Blue).bold();
     pub static ref PURPLE_BOLD: Style = Style::new().fg(Colour::Purple).bold();
+    static ref ANSI_ESCAPE: Regex = Regex::new(r"\x1b\[[0-9;]*m").unwrap();
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -93,11 +96,21 @@ impl StyleMessage {
     }
 
     pub fn to_plain_text(&self) -> String {
-        self.0
+        self.plain()
+    }
+
+    /// render without ANSI styles, for log files and JSON output.
+    ///
+    /// escape codes embedded in the content, e.g. a styled message formatted
+    /// into an error string, are stripped as well.
+    pub fn plain(&self) -> String {
+        let text = self
+            .0
             .iter()
             .map(|st| st.to_plain_text())
             .collect::<Vec<_>>()
-            .join("")
+            .join("");
+        ANSI_ESCAPE.replace_all(&text, "").to_string()
     }
 
     pub fn is_empty(&self) -> bool {
@@ -110,13 +123,13 @@ impl StyleMessage {
     pub(crate) fn dir_not_found(path: impl AsRef<Path>) -> Self {
         StyleMessage::new()
             .plain_text("Directory ")
-            .styled_text(path.as_ref().to_str().unwrap(), &PURPLE_BOLD)
+            .styled_text(path.as_ref().display().to_string(), &PURPLE_BOLD)
             .plain_text(" not found!")
     }
 
     pub(crate) fn dir_already_inited(path: impl AsRef<Path>) -> Self {
         StyleMessage::new()
-            .styled_text(path.as_ref().to_str().unwrap(), &PURPLE_BOLD)
+            .styled_text(path.as_ref().display().to_string(), &PURPLE_BOLD)
             .plain_text(" already inited, try ")
             .styled_text("--force", &PURPLE_BOLD)
             .plain_text(" instead!")
@@ -171,6 +184,28 @@ impl StyleMessage {
             .styled_text(path.as_ref().display().to_string(), &PURPLE_BOLD)
     }
 
+    pub(crate) fn manifest_updated(path: impl AsRef<Path>, git_ref: impl AsRef<str>) -> Self {
+        StyleMessage::new()
+            .plain_text("manifest repo ")
+            .styled_text(path.as_ref().display().to_string(), &PURPLE_BOLD)
+            .plain_text(" updated to ")
+            .styled_text(git_ref.as_ref(), &BLUE)
+    }
+
+    pub(crate) fn lock_written(path: impl AsRef<Path>, unlocked: &[String]) -> Self {
+        let mut msg = StyleMessage::new()
+            .plain_text("\nLockfile: ")
+            .styled_text(path.as_ref().display().to_string(), &PURPLE_BOLD)
+            .plain_text(" written\n");
+        if !unlocked.is_empty() {
+            msg = msg.styled_text("warning", &YELLOW).plain_text(format!(
+                ": no commit to lock in {}, their refs are kept\n",
+                unlocked.join(", ")
+            ));
+        }
+        msg
+    }
+
     pub(crate) fn ops_success(prefix: impl AsRef<str>) -> Self {
         StyleMessage::new().plain_text(format!("{} finished! 0 error(s).\n", prefix.as_ref()))
     }
@@ -182,6 +217,60 @@ impl StyleMessage {
             .plain_text(" error(s).\n")
     }
 
+    pub(crate) fn cmd_trace_start(cmd: impl AsRef<str>, path: impl AsRef<Path>) -> Self {
+        StyleMessage::new()
+            .styled_text("[trace] ", &GREY)
+            .styled_text(cmd.as_ref(), &BLUE)
+            .plain_text(" in ")
+            .styled_text(path.as_ref().display().to_string(), &PURPLE_BOLD)
+    }
+
+    pub(crate) fn cmd_output(prefix: impl AsRef<str>, line: impl AsRef<str>) -> Self {
+        StyleMessage::new()
+            .styled_text(prefix.as_ref(), &PURPLE_BOLD)
+            .plain_text(": ")
+            .plain_text(line.as_ref())
+    }
+
+    /// repos by exit code of `foreach`, the ones which succeeded are only counted
+    pub(crate) fn foreach_exit_codes(exit_codes: &BTreeMap<String, Vec<String>>) -> Self {
+        let mut msg = StyleMessage::new().plain_text("Exit codes:\n");
+        for (code, rel_paths) in exit_codes {
+            msg = match code.as_str() {
+                "0" => msg
+                    .styled_text("  0", &GREEN)
+                    .plain_text(format!(": {} repo(s)\n", rel_paths.len())),
+                _ => msg
+                    .styled_text(format!("  {}", code), &RED)
+                    .plain_text(format!(": {}\n", rel_paths.join(", "))),
+            };
+        }
+        msg
+    }
+
+    pub(crate) fn cmd_trace_host(rel_path: impl AsRef<str>, host: impl AsRef<str>) -> Self {
+        StyleMessage::new()
+            .styled_text("[trace] ", &GREY)
+            .styled_text(rel_path.as_ref().display_path(), &PURPLE_BOLD)
+            .plain_text(" -> host ")
+            .styled_text(host.as_ref(), &BLUE)
+    }
+
+    pub(crate) fn cmd_trace_end(
+        cmd: impl AsRef<str>,
+        exit_code: impl AsRef<str>,
+        duration: std::time::Duration,
+    ) -> Self {
+        StyleMessage::new()
+            .styled_text("[trace] ", &GREY)
+            .styled_text(cmd.as_ref(), &BLUE)
+            .plain_text(format!(
+                " exited with code {} after {}ms",
+                exit_code.as_ref(),
+                duration.as_millis()
+            ))
+    }
+
     pub fn repo_end(is_success: bool) -> Self {
         let (sign, style): (&str, &Style) = match is_success {
             true => ("√", &GREEN_BOLD),
@@ -191,11 +280,29 @@ impl StyleMessage {
     }
 
     pub(crate) fn git_error(rel_path: impl AsRef<str>, error: &anyhow::Error) -> Self {
-        let err_msg = error
+        // keep styles of a message error, so it can still be rendered plain
+        if let Some(msg) = error.downcast_ref::<StyleMessage>() {
+            return StyleMessage::new()
+                .styled_text(rel_path.as_ref().display_path(), &PURPLE_BOLD)
+                .plain_text(" ")
+                .join(msg.clone());
+        }
+
+        // like `{:#}` of anyhow, markers such as `CheckoutFailed` have no message to show
+        let messages = error
             .chain()
             .map(|c| c.to_string())
-            .collect::<Vec<_>>()
-            .join("");
+            .filter(|msg| !msg.trim().is_empty())
+            .collect::<Vec<_>>();
+        let err_msg = match messages.split_last() {
+            Some((last, contexts)) => contexts
+                .iter()
+                .map(|msg| msg.trim_end_matches(['.', ':', ';', ' ']))
+                .chain([last.as_str()])
+                .collect::<Vec<_>>()
+                .join(": "),
+            None => String::new(),
+        };
 
         StyleMessage::new()
             .styled_text(rel_path.as_ref().display_path(), &PURPLE_BOLD)
@@ -203,6 +310,34 @@ impl StyleMessage {
             .styled_text(err_msg.trim(), &RED)
     }
 
+    pub(crate) fn git_recloned(rel_path: impl AsRef<str>, backup: impl AsRef<str>) -> Self {
+        StyleMessage::new()
+            .plain_text("  ")
+            .styled_text(rel_path.as_ref().display_path(), &PURPLE_BOLD)
+            .plain_text(format!(
+                ": checkout kept failing, moved to {} and cloned again\n",
+                backup.as_ref()
+            ))
+    }
+
+    pub(crate) fn git_work_branch(
+        rel_path: impl AsRef<str>,
+        branch: impl AsRef<str>,
+        created: bool,
+    ) -> Self {
+        let msg = StyleMessage::new()
+            .styled_text(rel_path.as_ref().display_path(), &PURPLE_BOLD)
+            .plain_text(": ");
+        match created {
+            true => msg
+                .plain_text("created ")
+                .styled_text(branch.as_ref(), &BLUE_BOLD),
+            false => msg
+                .styled_text(branch.as_ref(), &BLUE_BOLD)
+                .plain_text(" already exists, skipped, use --force to move it"),
+        }
+    }
+
     pub(crate) fn git_stash(rel_path: impl AsRef<str>, desc: impl AsRef<str>) -> Self {
         StyleMessage::new()
             .styled_text(rel_path.as_ref().display_path(), &PURPLE_BOLD)
@@ -210,6 +345,202 @@ impl StyleMessage {
             .plain_text(desc.as_ref())
     }
 
+    pub(crate) fn git_stashed_files(rel_path: impl AsRef<str>, files: &[String]) -> Self {
+        let mut msg = StyleMessage::new()
+            .plain_text("  ")
+            .styled_text(rel_path.as_ref().display_path(), &PURPLE_BOLD)
+            .plain_text(":\n");
+        for file in files {
+            msg = msg.plain_text(format!("    {}\n", file));
+        }
+        msg
+    }
+
+    pub(crate) fn git_stale_submodules(rel_path: impl AsRef<str>, dirs: &[String]) -> Self {
+        let mut msg = StyleMessage::new()
+            .plain_text("  ")
+            .styled_text(rel_path.as_ref().display_path(), &PURPLE_BOLD)
+            .plain_text(": submodules no longer in the ref\n");
+        for dir in dirs {
+            msg = msg.plain_text(format!("    {}\n", dir));
+        }
+        msg
+    }
+
+    pub(crate) fn git_skip(rel_path: impl AsRef<str>, desc: StyleMessage) -> Self {
+        StyleMessage::new()
+            .styled_text(rel_path.as_ref().display_path(), &PURPLE_BOLD)
+            .plain_text(": ")
+            .join(desc)
+    }
+
+    pub(crate) fn config_error(local: impl AsRef<str>, desc: impl AsRef<str>) -> Self {
+        StyleMessage::new()
+            .styled_text(local.as_ref().display_path(), &PURPLE_BOLD)
+            .plain_text(": ")
+            .styled_text("error", &RED)
+            .plain_text(format!(", {}", desc.as_ref()))
+    }
+
+    pub(crate) fn config_warning(local: impl AsRef<str>, desc: impl AsRef<str>) -> Self {
+        StyleMessage::new()
+            .styled_text(local.as_ref().display_path(), &PURPLE_BOLD)
+            .plain_text(": ")
+            .styled_text("warning", &YELLOW)
+            .plain_text(format!(", {}", desc.as_ref()))
+    }
+
+    pub(crate) fn git_repair(
+        rel_path: impl AsRef<str>,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+    ) -> Self {
+        StyleMessage::new()
+            .styled_text(rel_path.as_ref().display_path(), &PURPLE_BOLD)
+            .plain_text(": ")
+            .styled_text(from.as_ref(), &BLUE)
+            .plain_text(" -> ")
+            .styled_text(to.as_ref(), &BLUE)
+    }
+
+    pub(crate) fn git_remote_mismatch(
+        rel_path: impl AsRef<str>,
+        remote_name: impl AsRef<str>,
+        actual: impl AsRef<str>,
+        expected: impl AsRef<str>,
+    ) -> Self {
+        StyleMessage::new()
+            .styled_text(rel_path.as_ref().display_path(), &PURPLE_BOLD)
+            .plain_text(format!(" {} is ", remote_name.as_ref()))
+            .styled_text(actual.as_ref(), &RED)
+            .plain_text(", manifest expects ")
+            .styled_text(expected.as_ref(), &BLUE)
+    }
+
+    pub(crate) fn git_hooks(
+        rel_path: impl AsRef<str>,
+        installed: &[String],
+        skipped: &[String],
+        dry_run: bool,
+    ) -> Self {
+        let mut msg = StyleMessage::new()
+            .styled_text(rel_path.as_ref().display_path(), &PURPLE_BOLD)
+            .plain_text(": ");
+        if !installed.is_empty() {
+            let verb = if dry_run {
+                "would install "
+            } else {
+                "installed "
+            };
+            msg = msg
+                .plain_text(verb)
+                .styled_text(installed.join(", "), &GREEN);
+        }
+        if !skipped.is_empty() {
+            if !installed.is_empty() {
+                msg = msg.plain_text("; ");
+            }
+            msg = msg
+                .plain_text("skipped existing ")
+                .styled_text(skipped.join(", "), &YELLOW);
+        }
+        msg
+    }
+
+    pub(crate) fn git_continue(rel_path: impl AsRef<str>, desc: impl AsRef<str>) -> Self {
+        StyleMessage::new()
+            .styled_text(rel_path.as_ref().display_path(), &PURPLE_BOLD)
+            .plain_text(": ")
+            .styled_text(format!("{} continued", desc.as_ref()), &GREEN)
+    }
+
+    pub(crate) fn git_abort(rel_path: impl AsRef<str>, desc: impl AsRef<str>) -> Self {
+        StyleMessage::new()
+            .styled_text(rel_path.as_ref().display_path(), &PURPLE_BOLD)
+            .plain_text(": ")
+            .styled_text(format!("restored {}", desc.as_ref()), &GREEN)
+    }
+
+    pub(crate) fn sync_interrupted(repos: &[String]) -> Self {
+        StyleMessage::new()
+            .styled_text("error", &RED)
+            .plain_text(format!(
+                ": a previous sync was interrupted in {}, run `mgit sync --abort` to restore them first",
+                repos.join(", ")
+            ))
+    }
+
+    pub(crate) fn git_repo_present() -> Self {
+        StyleMessage::new()
+            .styled_text("skipped", &YELLOW)
+            .plain_text(", already a git repository")
+    }
+
+    pub(crate) fn git_dir_not_empty() -> Self {
+        StyleMessage::new()
+            .styled_text("skipped", &YELLOW)
+            .plain_text(", directory is not empty and not a git repository")
+    }
+
+    pub(crate) fn git_cloned() -> Self {
+        StyleMessage::new().styled_text("cloned", &GREEN)
+    }
+
+    pub(crate) fn git_repo_missing() -> Self {
+        StyleMessage::new()
+            .styled_text("missing", &YELLOW)
+            .plain_text(", repository directory not found")
+    }
+
+    pub(crate) fn git_cancelled() -> Self {
+        StyleMessage::new()
+            .styled_text("cancelled", &YELLOW)
+            .plain_text(", an earlier repo failed")
+    }
+
+    pub(crate) fn depth_unshallow_fallback(repos: &[String]) -> Self {
+        StyleMessage::new()
+            .styled_text("warning", &YELLOW)
+            .plain_text(format!(
+                ": the shallow fetch may not reach the pinned commit of {}, their full history is fetched if needed\n",
+                repos.join(", ")
+            ))
+    }
+
+    pub(crate) fn unchanged_since_snapshot(repos: &[String]) -> Self {
+        StyleMessage::new()
+            .styled_text("skipped", &YELLOW)
+            .plain_text(format!(
+                ": {} repo(s) unchanged since snapshot: {}\n",
+                repos.len(),
+                repos.join(", ")
+            ))
+    }
+
+    pub(crate) fn depth_with_commit_pins(repos: &[String]) -> Self {
+        StyleMessage::new()
+            .styled_text("error", &RED)
+            .plain_text(format!(
+                ": the shallow fetch can't reach the pinned commit of {} with --strict",
+                repos.join(", ")
+            ))
+    }
+
+    pub(crate) fn thread_count_capped(thread_count: usize, max: usize) -> Self {
+        StyleMessage::new()
+            .styled_text("warning", &YELLOW)
+            .plain_text(format!(
+                ": the open file limit only allows {} threads, lowered from {}\n",
+                max, thread_count
+            ))
+    }
+
+    pub(crate) fn git_clone_too_large(size_mb: u64, max_mb: u64) -> Self {
+        StyleMessage::new()
+            .styled_text("skipped", &YELLOW)
+            .plain_text(format!(", clone size {}MB exceeds {}MB", size_mb, max_mb))
+    }
+
     pub(crate) fn git_untracked(path: impl AsRef<Path>, desc: impl AsRef<str>) -> Self {
         StyleMessage::new()
             .styled_text(path.display_path(), &PURPLE_BOLD)
@@ -252,6 +583,12 @@ impl StyleMessage {
             .plain_text(" not found")
     }
 
+    pub(crate) fn git_ref_not_found(name: impl AsRef<str>) -> Self {
+        StyleMessage::new()
+            .plain_text("no branch or tag ")
+            .styled_text(name.as_ref(), &BLUE)
+    }
+
     pub(crate) fn git_checking_out(branch: impl AsRef<str>) -> Self {
         StyleMessage::new()
             .plain_text("checkout ")
@@ -283,6 +620,24 @@ impl StyleMessage {
         }
     }
 
+    pub(crate) fn git_divergence(
+        ahead: Option<usize>,
+        behind: Option<usize>,
+        target: impl AsRef<str>,
+    ) -> Self {
+        let (Some(ahead), Some(behind)) = (ahead, behind) else {
+            return StyleMessage::git_unknown_revision()
+                .plain_text(format!(" vs {}", target.as_ref()));
+        };
+
+        let counts = format!("↑{}↓{}", ahead, behind);
+        let msg = match (ahead, behind) {
+            (0, 0) => StyleMessage::new().styled_text(counts, &GREEN),
+            _ => StyleMessage::new().styled_text(counts, &YELLOW),
+        };
+        msg.plain_text(format!(" vs {}", target.as_ref()))
+    }
+
     pub(crate) fn git_unknown_revision() -> Self {
         StyleMessage::new().styled_text("unknown revision", &YELLOW)
     }
@@ -329,12 +684,53 @@ impl StyleMessage {
             .styled_text(branch.as_ref(), &BLUE)
     }
 
+    /// repos about to lose `origin/<branch>`, listed before asking for confirmation
+    pub fn remote_branch_found(branch: impl AsRef<str>, rel_paths: &[String]) -> Self {
+        let mut msg = StyleMessage::new()
+            .styled_text(format!("origin/{}", branch.as_ref()), &BLUE)
+            .plain_text(format!(" exists in {} repo(s):\n", rel_paths.len()));
+        for rel_path in rel_paths {
+            msg = msg
+                .plain_text("  ")
+                .styled_text(rel_path.display_path(), &PURPLE_BOLD)
+                .plain_text("\n");
+        }
+        msg
+    }
+
     pub(crate) fn git_new_tag(path: impl AsRef<Path>, tag: impl AsRef<str>) -> Self {
         StyleMessage::new()
             .styled_text(path.display_path(), &PURPLE_BOLD)
             .plain_text(": ")
             .styled_text(tag.as_ref(), &BLUE)
     }
+
+    pub(crate) fn git_pushed(
+        target: impl AsRef<str>,
+        summary: impl AsRef<str>,
+        dry_run: bool,
+    ) -> Self {
+        let action = match dry_run {
+            true => "would push to ",
+            false => "pushed to ",
+        };
+        StyleMessage::new()
+            .plain_text(action)
+            .styled_text(target.as_ref(), &BLUE)
+            .plain_text(format!(" {}", summary.as_ref()))
+    }
+
+    pub(crate) fn git_no_upstream() -> Self {
+        StyleMessage::new()
+            .styled_text("skipped", &YELLOW)
+            .plain_text(", no upstream branch")
+    }
+
+    pub(crate) fn git_detached_head() -> Self {
+        StyleMessage::new()
+            .styled_text("skipped", &YELLOW)
+            .plain_text(", HEAD is detached")
+    }
 }
 
 impl Display for StyleMessage {
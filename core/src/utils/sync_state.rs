@@ -0,0 +1,95 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// where a repo was before sync started changing its work tree
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepoSyncState {
+    /// branch checked out before sync, empty for a detached HEAD
+    pub branch: String,
+    pub head: String,
+    /// commit of the stash entry sync created, none until it stashes something
+    pub stash: Option<String>,
+}
+
+/// repos a sync has started but not finished changing, by local path.
+///
+/// it is written to `.mgit/sync-state` as repos come and go, so the repos left
+/// behind by an interrupted sync can be restored with `mgit sync --abort`.
+pub struct SyncState {
+    record_path: PathBuf,
+    repos: Mutex<BTreeMap<String, RepoSyncState>>,
+}
+
+impl SyncState {
+    pub fn record_path(path: &Path) -> PathBuf {
+        path.join(".mgit").join("sync-state")
+    }
+
+    /// load the state left in the work directory, empty when there is none
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let record_path = Self::record_path(path);
+        let repos = match record_path.is_file() {
+            true => {
+                let content = std::fs::read_to_string(&record_path)
+                    .with_context(|| format!("Failed to read {}", record_path.display()))?;
+                serde_json::from_str(&content)
+                    .with_context(|| format!("Failed to parse {}", record_path.display()))?
+            }
+            false => BTreeMap::new(),
+        };
+        Ok(Self {
+            record_path,
+            repos: Mutex::new(repos),
+        })
+    }
+
+    pub fn repos(&self) -> BTreeMap<String, RepoSyncState> {
+        self.repos.lock().unwrap().clone()
+    }
+
+    pub fn begin(&self, rel_path: &str, state: RepoSyncState) -> anyhow::Result<()> {
+        self.update(|repos| {
+            repos.insert(rel_path.to_string(), state);
+        })
+    }
+
+    pub fn set_stash(&self, rel_path: &str, stash: String) -> anyhow::Result<()> {
+        self.update(|repos| {
+            if let Some(state) = repos.get_mut(rel_path) {
+                state.stash = Some(stash);
+            }
+        })
+    }
+
+    pub fn finish(&self, rel_path: &str) -> anyhow::Result<()> {
+        self.update(|repos| {
+            repos.remove(rel_path);
+        })
+    }
+
+    /// change the repos and write them, the record is removed once no repo is left
+    fn update(&self, f: impl FnOnce(&mut BTreeMap<String, RepoSyncState>)) -> anyhow::Result<()> {
+        let mut repos = self.repos.lock().unwrap();
+        f(&mut repos);
+
+        if repos.is_empty() {
+            if self.record_path.is_file() {
+                std::fs::remove_file(&self.record_path)?;
+            }
+            return Ok(());
+        }
+
+        std::fs::create_dir_all(self.record_path.parent().unwrap())?;
+        let content = serde_json::to_string_pretty(&*repos)?;
+
+        // written aside and renamed over, a killed sync never leaves a truncated record
+        let mut tmp_path = self.record_path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        std::fs::write(&tmp_path, content)
+            .and_then(|_| std::fs::rename(&tmp_path, &self.record_path))
+            .with_context(|| format!("Failed to write {}", self.record_path.display()))
+    }
+}
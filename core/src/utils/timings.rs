@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::utils::StyleMessage;
+
+/// timed phases of syncing a repo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Clone,
+    Fetch,
+    Checkout,
+    Reset,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 4] = [Phase::Clone, Phase::Fetch, Phase::Checkout, Phase::Reset];
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Phase::Clone => "clone",
+            Phase::Fetch => "fetch",
+            Phase::Checkout => "checkout",
+            Phase::Reset => "reset",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// collect wall time of each phase per repo, shared by sync threads
+#[derive(Debug, Default)]
+pub struct Timings {
+    repos: Mutex<HashMap<String, [Duration; Phase::ALL.len()]>>,
+}
+
+impl Timings {
+    pub fn record(&self, repo: impl AsRef<str>, phase: Phase, elapsed: Duration) {
+        let mut repos = self.repos.lock().unwrap();
+        let durations = repos.entry(repo.as_ref().to_string()).or_default();
+        durations[phase as usize] += elapsed;
+    }
+
+    /// table of repos sorted by total time, followed by the total of each phase
+    pub fn summary(&self) -> StyleMessage {
+        let repos = self.repos.lock().unwrap();
+        let mut rows = repos
+            .iter()
+            .map(|(repo, durations)| (repo.as_str(), *durations))
+            .collect::<Vec<_>>();
+        rows.sort_by(|a, b| {
+            let total = |durations: &[Duration]| durations.iter().sum::<Duration>();
+            total(&b.1).cmp(&total(&a.1)).then(a.0.cmp(b.0))
+        });
+
+        let mut all = [Duration::ZERO; Phase::ALL.len()];
+        for (_, durations) in &rows {
+            for (sum, duration) in all.iter_mut().zip(durations) {
+                *sum += *duration;
+            }
+        }
+
+        let all_name = "all repos";
+        let width = rows
+            .iter()
+            .map(|(repo, _)| repo.len())
+            .chain([all_name.len()])
+            .max()
+            .unwrap();
+
+        let mut table = format!("  {:<width$}", "repo");
+        for phase in Phase::ALL {
+            table += &format!("  {:>9}", phase.to_string());
+        }
+        table += &format!("  {:>9}\n", "total");
+        for (repo, durations) in rows.iter().copied().chain([(all_name, all)]) {
+            table += &format!("  {:<width$}", repo);
+            for duration in durations {
+                table += &format!("  {:>9}", format_secs(duration));
+            }
+            let total = durations.iter().sum::<Duration>();
+            table += &format!("  {:>9}\n", format_secs(total));
+        }
+
+        StyleMessage::new()
+            .plain_text("Timings:\n")
+            .plain_text(table)
+    }
+}
+
+fn format_secs(duration: Duration) -> String {
+    format!("{:.2}s", duration.as_secs_f64())
+}
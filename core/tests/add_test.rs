@@ -0,0 +1,107 @@
+use mgit::core::git::RemoteRef;
+use mgit::core::repos::TomlConfig;
+use mgit::ops;
+use mgit::ops::AddOptions;
+use mgit::utils::error::MgitResult;
+use std::{env, path::PathBuf};
+
+use crate::common::{create_local_remote, failed_message, TestProgress, DEFAULT_BRANCH};
+
+mod common;
+
+/// 测试内容：
+///     1、运行命令 mgit add --local ./foobar-2/ --remote <remote> --tag v1，
+///        仓库追加到配置文件末尾，注释和已有仓库保持不变
+///     2、再次添加 foobar-2 报错，配置文件不变
+///     3、添加工作目录外的 ../foobar 报错
+///     4、运行命令 mgit add --local foobar-3 --remote <remote> --branch <branch> --sync，
+///        只 clone foobar-3，没有拉取的 foobar-1 不受影响
+///
+/// 测试目录结构:
+///   test_add
+///     ├─foobar-3 (.git)
+///     └─remote.git
+#[test]
+fn cli_add() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_add");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+
+    let toml_string = format!(
+        r#"# the workspace of the team
+default-branch = "{branch}"
+
+[[repos]]
+local = "foobar-1"
+remote = "{remote}"
+"#,
+        branch = DEFAULT_BRANCH,
+        remote = remote.replace('\\', "/"),
+    );
+    let config_file = path.join(".gitrepos");
+    std::fs::write(&config_file, &toml_string).expect(failed_message::WRITE_FILE);
+
+    let add_options = |local: &str, remote_ref: RemoteRef| {
+        AddOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            local.to_string(),
+            remote.clone(),
+            remote_ref,
+        )
+    };
+
+    ops::add_repo(
+        add_options("./foobar-2/", RemoteRef::Tag("v1".to_string())),
+        TestProgress,
+    )?;
+    let content = std::fs::read_to_string(&config_file)?;
+    assert!(content.starts_with(&toml_string));
+    let expected = format!(
+        "\n[[repos]]\nlocal = \"foobar-2\"\nremote = \"{}\"\ntag = \"v1\"\n",
+        remote
+    );
+    assert_eq!(&content[toml_string.len()..], expected);
+
+    let err = ops::add_repo(
+        add_options("foobar-2", RemoteRef::Branch(DEFAULT_BRANCH.to_string())),
+        TestProgress,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("already in"));
+    assert_eq!(std::fs::read_to_string(&config_file)?, content);
+
+    let err = ops::add_repo(
+        add_options("../foobar", RemoteRef::Branch(DEFAULT_BRANCH.to_string())),
+        TestProgress,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("outside the work directory"));
+
+    let mut options = add_options("foobar-3", RemoteRef::Branch(DEFAULT_BRANCH.to_string()));
+    options.sync = true;
+    ops::add_repo(options, TestProgress)?;
+    assert!(path.join("foobar-3").join(".git").is_dir());
+    assert!(!path.join("foobar-1").exists());
+    assert!(!path.join("foobar-2").exists());
+
+    let toml_config = TomlConfig::load(&config_file).unwrap();
+    let foobar_3 = toml_config
+        .repos
+        .unwrap()
+        .into_iter()
+        .find(|repo| repo.local.as_deref() == Some("foobar-3"))
+        .unwrap();
+    assert_eq!(foobar_3.branch.as_deref(), Some(DEFAULT_BRANCH));
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use mgit::ops;
+use mgit::ops::{CheckoutOptions, SyncOptions};
+use mgit::utils::error::MgitResult;
+use std::{env, path::PathBuf};
+
+use crate::common::{
+    create_local_remote, exec_cmd, failed_message, TestProgress, TomlBuilder, DEFAULT_BRANCH,
+};
+
+mod common;
+
+/// 测试内容：
+///     1、远端有分支 feature/x 和标签 v1，同步后 foobar-3 删除了远端分支和标签
+///     2、运行命令 mgit checkout feature/x <path>，foobar-1、foobar-2 从远端分支切换到 feature/x，
+///        foobar-3 被跳过且不算错误
+///     3、运行命令 mgit checkout v1 <path>，切换到 tags/v1 分支
+///     4、运行命令 mgit checkout review <path> --create，所有仓库从当前 HEAD 创建并切换到 review
+///
+/// 测试目录结构:
+///   test_checkout
+///     ├─foobar-1 (.git)
+///     ├─foobar-2 (.git)
+///     ├─foobar-3 (.git)
+///     └─remote.git
+#[test]
+fn cli_checkout() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_checkout");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+    let remote_path = path.join("remote.git");
+    exec_cmd(
+        &remote_path,
+        "git",
+        &["branch", "feature/x", DEFAULT_BRANCH],
+    )
+    .expect(failed_message::GIT_BRANCH);
+    exec_cmd(&remote_path, "git", &["tag", "v1", DEFAULT_BRANCH]).expect("git tag failed");
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar-1", &remote, None, None, None)
+        .join_repo("foobar-2", &remote, None, None, None)
+        .join_repo("foobar-3", &remote, None, None, None)
+        .build();
+    let config_file = path.join(".gitrepos");
+    std::fs::write(config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    ops::sync_repo(
+        SyncOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        TestProgress,
+    )?;
+
+    let foobar_3 = path.join("foobar-3");
+    exec_cmd(
+        &foobar_3,
+        "git",
+        &["update-ref", "-d", "refs/remotes/origin/feature/x"],
+    )
+    .expect("git update-ref failed");
+    exec_cmd(&foobar_3, "git", &["tag", "-d", "v1"]).expect("git tag failed");
+
+    let checkout_options = |name: &str, create: bool| {
+        CheckoutOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            name.to_string(),
+            create,
+            None,
+        )
+    };
+    let current_branch = |local: &str| {
+        let output = exec_cmd(path.join(local), "git", &["branch", "--show-current"]).unwrap();
+        output.trim().to_string()
+    };
+
+    let msg = ops::checkout_repos(checkout_options("feature/x", false))?;
+    assert_eq!(current_branch("foobar-1"), "feature/x");
+    assert_eq!(current_branch("foobar-2"), "feature/x");
+    assert_eq!(current_branch("foobar-3"), DEFAULT_BRANCH);
+    assert!(msg.plain().contains("foobar-3: no branch or tag feature/x"));
+
+    ops::checkout_repos(checkout_options("v1", false))?;
+    assert_eq!(current_branch("foobar-1"), "tags/v1");
+    assert_eq!(current_branch("foobar-3"), DEFAULT_BRANCH);
+
+    ops::checkout_repos(checkout_options("review", true))?;
+    for local in ["foobar-1", "foobar-2", "foobar-3"] {
+        assert_eq!(current_branch(local), "review");
+    }
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
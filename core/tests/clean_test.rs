@@ -4,7 +4,9 @@ use mgit::utils::error::MgitResult;
 use std::env;
 use std::path::PathBuf;
 
-use crate::common::{exec_cmd, failed_message, TomlBuilder, IMGUI_REPO};
+use crate::common::{
+    create_local_remote, exec_cmd, failed_message, TomlBuilder, DEFAULT_BRANCH, IMGUI_REPO,
+};
 
 mod common;
 
@@ -332,3 +334,58 @@ pub fn create_repos_tree(path: &PathBuf, rel_paths: &[&str]) {
         std::fs::File::create(dir.join("bar/2.txt")).ok();
     }
 }
+
+/// 测试内容：
+///     1、配置文件中的仓库 foo 带有子模块 libs/sub，以及不在配置文件中的仓库 other
+///     2、运行命令 mgit clean <path>
+///     3、清除 other，子模块 libs/sub 属于 foo，被保留
+///
+/// 测试目录结构:
+///   test_clean_submodule(.gitrepos)
+///     ├─foo (.git)
+///     │  ├─libs/sub (.git 文件)
+///     │  └─other (.git)
+///     ├─sub.git
+///     └─remote.git
+#[test]
+fn cli_clean_submodule() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_clean_submodule");
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+    let sub_remote = create_local_remote(path.join("sub.git"));
+
+    exec_cmd(&path, "git", &["clone", &remote, "foo"]).expect("git clone failed");
+    let foo = path.join("foo");
+    let args = [
+        "-c",
+        "protocol.file.allow=always",
+        "submodule",
+        "add",
+        &sub_remote,
+        "libs/sub",
+    ];
+    exec_cmd(&foo, "git", &args).expect("git submodule add failed");
+    std::fs::create_dir_all(foo.join("other")).unwrap();
+    exec_cmd(foo.join("other"), "git", &["init"]).expect(failed_message::GIT_INIT);
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foo", &remote, None, None, None)
+        .build();
+    std::fs::write(path.join(".gitrepos"), toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    ops::clean_repo(CleanOptions::new(Some(path.clone()), None::<PathBuf>))?;
+
+    assert!(foo.join("libs/sub/1.txt").is_file());
+    assert!(!foo.join("other").exists());
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use mgit::ops;
+use mgit::ops::CloneOptions;
+use mgit::utils::error::MgitResult;
+use std::{env, path::PathBuf};
+
+use crate::common::{
+    create_local_remote, exec_cmd, failed_message, TestProgress, TomlBuilder, DEFAULT_BRANCH,
+};
+
+mod common;
+
+/// 测试内容：
+///     1、foobar-2 已是 git 仓库且有本地修改，foobar-3 是非空的普通目录，foobar-4 固定在 commit 上
+///     2、运行命令 mgit clone <path>，foobar-1、foobar-4 被拉取，foobar-2、foobar-3 被跳过且内容不变
+///     3、再次运行命令 mgit clone <path>，所有仓库都被跳过
+///
+/// 测试目录结构:
+///   test_clone_simple
+///     ├─foobar-1 (.git)
+///     ├─foobar-2 (.git)
+///     ├─foobar-3
+///     ├─foobar-4 (.git)
+///     └─remote.git
+#[test]
+fn cli_clone_simple() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_clone_simple");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote_path = path.join("remote.git");
+    let remote = create_local_remote(&remote_path);
+    let commit = exec_cmd(&remote_path, "git", &["rev-parse", DEFAULT_BRANCH])?;
+    let commit = commit.trim();
+
+    // a repo with local work, and a plain directory with files of its own
+    exec_cmd(&path, "git", &["clone", &remote, "foobar-2"]).expect("git clone failed");
+    std::fs::write(path.join("foobar-2/1.txt"), "local").expect(failed_message::WRITE_FILE);
+    std::fs::create_dir_all(path.join("foobar-3")).unwrap();
+    std::fs::write(path.join("foobar-3/notes.txt"), "notes").expect(failed_message::WRITE_FILE);
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar-1", &remote, None, None, None)
+        .join_repo("foobar-2", &remote, None, None, None)
+        .join_repo("foobar-3", &remote, None, None, None)
+        .join_repo("foobar-4", &remote, None, Some(commit), None)
+        .build();
+    let config_file = path.join(".gitrepos");
+    std::fs::write(config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    let clone_options = || CloneOptions::new(Some(input_path), None::<PathBuf>, None, None, None);
+
+    let msg = ops::clone_repos(clone_options(), TestProgress)?.plain();
+    assert!(msg.contains("foobar-2: skipped, already a git repository\n"));
+    assert!(msg.contains("foobar-3: skipped, directory is not empty and not a git repository\n"));
+
+    let foobar_1 = path.join("foobar-1");
+    assert!(foobar_1.join("foo/1.txt").is_file());
+    let branch = exec_cmd(&foobar_1, "git", &["rev-parse", "--abbrev-ref", "@{u}"])?;
+    assert_eq!(branch.trim(), format!("origin/{}", DEFAULT_BRANCH));
+    let head = exec_cmd(path.join("foobar-4"), "git", &["rev-parse", "HEAD"])?;
+    assert_eq!(head.trim(), commit);
+
+    let content = std::fs::read_to_string(path.join("foobar-2/1.txt")).unwrap();
+    assert_eq!(content, "local");
+    assert!(!path.join("foobar-3/.git").exists());
+
+    let msg = ops::clone_repos(clone_options(), TestProgress)?.plain();
+    assert!(msg.contains("foobar-1: skipped, already a git repository\n"));
+    assert!(msg.contains("foobar-4: skipped, already a git repository\n"));
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
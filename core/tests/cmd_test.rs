@@ -0,0 +1,172 @@
+use mgit::core::repo::TomlRepo;
+use mgit::utils::cmd::{
+    exec_cmd, exec_cmd_with_progress, exec_cmd_with_timeout, set_skip_lfs_smudge, set_verbose_git,
+    GitTimeout,
+};
+use mgit::utils::error::MgitResult;
+use mgit::utils::logger::{set_logger, Log};
+use mgit::utils::progress::RepoInfo;
+use mgit::utils::style_message::StyleMessage;
+use std::env;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::common::TestProgress;
+
+mod common;
+
+/// keeps the logged lines to check them
+struct CollectLogger(Mutex<Vec<String>>);
+
+impl Log for CollectLogger {
+    fn info(&self, message: StyleMessage) {
+        self.0.lock().unwrap().push(message.plain());
+    }
+
+    fn error(&self, message: StyleMessage) {
+        self.0.lock().unwrap().push(message.plain());
+    }
+}
+
+static LOGGER: CollectLogger = CollectLogger(Mutex::new(Vec::new()));
+
+/// 测试内容：
+///     1、开启 skip lfs smudge 后，执行的命令带有环境变量 GIT_LFS_SKIP_SMUDGE=1
+///     2、关闭后不再设置该环境变量
+#[test]
+fn cli_cmd_skip_lfs_smudge() -> MgitResult<()> {
+    let path = env::current_dir().unwrap();
+    let args = [
+        "-c",
+        "alias.lfs-env=!echo ${GIT_LFS_SKIP_SMUDGE:-unset}",
+        "lfs-env",
+    ];
+
+    set_skip_lfs_smudge(true);
+    assert_eq!(exec_cmd(&path, "git", &args)?.trim(), "1");
+
+    // the environment of the test itself is passed through unchanged
+    set_skip_lfs_smudge(false);
+    let inherited = env::var("GIT_LFS_SKIP_SMUDGE").unwrap_or("unset".to_string());
+    assert_eq!(exec_cmd(&path, "git", &args)?.trim(), inherited);
+    Ok(())
+}
+
+/// 测试内容：
+///     1、开启 verbose git 后，流式执行的命令的 stdout/stderr 带上仓库路径前缀输出
+///     2、其他命令只在失败时输出
+///     3、关闭后不再输出
+///     4、关闭后 stdout 输出超过管道容量的命令也能执行完成
+#[test]
+fn cli_cmd_verbose_git() -> MgitResult<()> {
+    let path = env::current_dir().unwrap();
+    set_logger(&LOGGER);
+    let toml_repo = TomlRepo {
+        local: Some("foo".to_string()),
+        ..TomlRepo::default()
+    };
+    let repo_info = RepoInfo::new(0, 1, &toml_repo);
+    let mut command = Command::new("git");
+    command
+        .args(["-c", "alias.both=!echo out; echo err >&2", "both"])
+        .current_dir(&path);
+
+    set_verbose_git(true);
+    exec_cmd_with_progress(&repo_info, &mut command, &TestProgress)?;
+    exec_cmd(&path, "git", &["rev-parse", "HEAD"])?;
+    let _ = exec_cmd(&path, "git", &["rev-parse", "--verify", "not-a-ref"]);
+
+    set_verbose_git(false);
+    exec_cmd_with_progress(&repo_info, &mut command, &TestProgress)?;
+
+    let mut command = Command::new("git");
+    command
+        .args(["-c", "alias.loud=!head -c 1000000 /dev/zero", "loud"])
+        .current_dir(&path);
+    exec_cmd_with_progress(&repo_info, &mut command, &TestProgress)?;
+
+    let lines = LOGGER.0.lock().unwrap().clone();
+    let prefix = path.display().to_string();
+    assert_eq!(lines.len(), 3);
+    assert!(lines.contains(&"foo: out".to_string()));
+    assert!(lines.contains(&"foo: err".to_string()));
+    assert!(lines[2].starts_with(&format!("{}: fatal:", prefix)));
+    Ok(())
+}
+
+/// 测试内容：
+///     1、命令超时后被结束，返回 "timed out after 1s" 错误
+///     2、没有超时的命令正常返回输出
+///     3、流式执行的命令同样受超时限制
+///     4、超时后命令启动的子进程也被结束
+#[test]
+fn cli_cmd_timeout() -> MgitResult<()> {
+    let path = env::current_dir().unwrap();
+    let args = ["-c", "alias.slow=!sleep 5", "slow"];
+
+    let start = Instant::now();
+    let err = exec_cmd_with_timeout(&path, "git", &args, Some(Duration::from_secs(1)))
+        .unwrap_err()
+        .to_string();
+    assert!(err.starts_with("timed out after 1s"), "{}", err);
+    assert!(start.elapsed() < Duration::from_secs(4));
+
+    let output = exec_cmd_with_timeout(
+        &path,
+        "git",
+        &["rev-parse", "--is-inside-work-tree"],
+        Some(Duration::from_secs(10)),
+    )?;
+    assert_eq!(output.trim(), "true");
+
+    let toml_repo = TomlRepo {
+        local: Some("foo".to_string()),
+        ..TomlRepo::default()
+    };
+    let repo_info = RepoInfo::new(0, 1, &toml_repo);
+    let mut command = Command::new("git");
+    command.args(args).current_dir(&path);
+
+    let start = Instant::now();
+    let timeout = GitTimeout::scoped(Some(Duration::from_secs(1)));
+    let result = exec_cmd_with_progress(&repo_info, &mut command, &TestProgress);
+    drop(timeout);
+    let err = result.unwrap_err().to_string();
+    assert!(err.starts_with("timed out after 1s"), "{}", err);
+    assert!(start.elapsed() < Duration::from_secs(4));
+
+    // the background sleep keeps the pipes open unless its group is killed
+    let input_path = path.join("target/tmp/test_cmd_timeout");
+    let _ = std::fs::remove_dir_all(&input_path);
+    std::fs::create_dir_all(&input_path).unwrap();
+    let pid_file = input_path.join("pid");
+    let alias = format!(
+        "alias.slow=!sh -c 'sleep 30 & echo $! > {}; wait'",
+        pid_file.display()
+    );
+    let start = Instant::now();
+    let err = exec_cmd_with_timeout(
+        &path,
+        "git",
+        &["-c", &alias, "slow"],
+        Some(Duration::from_secs(1)),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.starts_with("timed out after 1s"), "{}", err);
+    assert!(start.elapsed() < Duration::from_secs(4));
+
+    let pid = std::fs::read_to_string(&pid_file).unwrap();
+    let stat_path = format!("/proc/{}/stat", pid.trim());
+    let killed = (0..20).any(|_| {
+        // a killed process may be left as a zombie until it is reaped
+        let alive = std::fs::read_to_string(&stat_path).is_ok_and(|stat| !stat.contains(") Z"));
+        if alive {
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        !alive
+    });
+    assert!(killed, "sleep {} is still running", pid.trim());
+    Ok(())
+}
@@ -1,4 +1,8 @@
+// shared by every test crate, each one uses only part of it
+#![allow(dead_code)]
+
 use lazy_static::lazy_static;
+use mgit::utils::path::PathExtension;
 use mgit::utils::progress::{Progress, RepoInfo};
 use mgit::utils::style_message::StyleMessage;
 use std::path::Path;
@@ -117,6 +121,42 @@ impl TomlBuilder {
     }
 }
 
+/// create a bare repository with a single commit on `master`, usable as a remote without network
+///
+/// the commit contains `1.txt`, `foo/1.txt` and `foo/2.txt`
+#[allow(unused)]
+pub fn create_local_remote(path: impl AsRef<Path>) -> String {
+    let path = path.as_ref();
+    let src_path = path.with_extension("src");
+
+    let _ = std::fs::remove_dir_all(path);
+    let _ = std::fs::remove_dir_all(&src_path);
+    std::fs::create_dir_all(&src_path).unwrap();
+
+    exec_cmd(&src_path, "git", &["init", "-b", DEFAULT_BRANCH]).expect(failed_message::GIT_INIT);
+    exec_cmd(&src_path, "git", &["config", "user.name", "mgit"]).expect(failed_message::GIT_CONFIG);
+    exec_cmd(
+        &src_path,
+        "git",
+        &["config", "user.email", "mgit@example.com"],
+    )
+    .expect(failed_message::GIT_CONFIG);
+    std::fs::create_dir_all(src_path.join("foo")).unwrap();
+    for file in ["1.txt", "foo/1.txt", "foo/2.txt"] {
+        std::fs::write(src_path.join(file), file).expect(failed_message::WRITE_FILE);
+    }
+    exec_cmd(&src_path, "git", &["add", "."]).expect(failed_message::GIT_STAGE);
+    exec_cmd(&src_path, "git", &["commit", "-m", "init"]).expect(failed_message::GIT_COMMIT);
+
+    let parent = path.parent().unwrap();
+    let src = src_path.to_str().unwrap();
+    let dst = path.to_str().unwrap();
+    exec_cmd(parent, "git", &["clone", "--bare", src, dst]).expect("git clone failed");
+    std::fs::remove_dir_all(&src_path).unwrap();
+
+    path.norm_path()
+}
+
 fn use_gitea() -> bool {
     cfg!(feature = "use_gitea")
 }
@@ -0,0 +1,295 @@
+use mgit::core::repos::{ConfigFormat, TomlConfig, WorkspacePaths};
+use std::env;
+use std::path::PathBuf;
+
+/// 测试内容：
+///     1、只指定 --path
+///     2、配置文件默认为 <path>/.gitrepos
+#[test]
+fn cli_workspace_paths_only_path() {
+    let path = PathBuf::from("foo").join("bar");
+    let paths = WorkspacePaths::resolve(Some(&path), None::<PathBuf>);
+
+    assert_eq!(paths.path, path);
+    assert_eq!(paths.config_path, path.join(".gitrepos"));
+}
+
+/// 测试内容：
+///     1、只指定 --config
+///     2、工作目录仍为当前目录，不受配置文件位置影响
+#[test]
+fn cli_workspace_paths_only_config() {
+    let config = PathBuf::from("foo").join("custom.gitrepos");
+    let paths = WorkspacePaths::resolve(None::<PathBuf>, Some(&config));
+
+    assert_eq!(paths.path, env::current_dir().unwrap());
+    assert_eq!(paths.config_path, config);
+}
+
+/// 测试内容：
+///     1、同时指定 --path 和 --config
+///     2、两者互不影响
+#[test]
+fn cli_workspace_paths_both() {
+    let path = PathBuf::from("foo");
+    let config = PathBuf::from("bar").join("custom.gitrepos");
+    let paths = WorkspacePaths::resolve(Some(&path), Some(&config));
+
+    assert_eq!(paths.path, path);
+    assert_eq!(paths.config_path, config);
+}
+
+/// 测试内容：
+///     1、--path 和 --config 都不指定
+///     2、使用当前目录及当前目录下的 .gitrepos
+#[test]
+fn cli_workspace_paths_neither() {
+    let cwd = env::current_dir().unwrap();
+    let paths = WorkspacePaths::resolve(None::<PathBuf>, None::<PathBuf>);
+
+    assert_eq!(paths.path, cwd);
+    assert_eq!(paths.config_path, cwd.join(".gitrepos"));
+}
+
+/// 测试内容：
+///     1、.gitrepos 文件中写入 JSON，自动按内容识别为 JSON
+///     2、config.yaml 按扩展名识别为 YAML
+///     3、没有扩展名的 YAML 内容，显式指定 yaml 格式
+///     4、显式指定 toml 格式时，JSON 内容解析失败
+///
+/// 测试目录结构:
+///   test_config_format
+///     ├─.gitrepos
+///     ├─config.yaml
+///     └─manifest
+#[test]
+fn cli_config_format() {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_config_format");
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+
+    let json = r#"{
+  "default-branch": "develop",
+  "repos": [{ "local": "foo", "remote": "https://example.com/foo.git" }]
+}"#;
+    let yaml = r#"default-branch: develop
+repos:
+  - local: foo
+    remote: https://example.com/foo.git
+"#;
+    std::fs::write(path.join(".gitrepos"), json).unwrap();
+    std::fs::write(path.join("config.yaml"), yaml).unwrap();
+    std::fs::write(path.join("manifest"), yaml).unwrap();
+
+    for (file, format) in [
+        (".gitrepos", ConfigFormat::Auto),
+        ("config.yaml", ConfigFormat::Auto),
+        ("manifest", ConfigFormat::Yaml),
+    ] {
+        let toml_config = TomlConfig::load_with_format(path.join(file), format).unwrap();
+        assert_eq!(toml_config.default_branch.as_deref(), Some("develop"));
+
+        let repos = toml_config.repos.unwrap();
+        assert_eq!(repos.len(), 1);
+        assert_eq!(repos[0].local.as_deref(), Some("foo"));
+        assert_eq!(
+            repos[0].remote.as_deref(),
+            Some("https://example.com/foo.git")
+        );
+    }
+
+    assert!(TomlConfig::load_with_format(path.join(".gitrepos"), ConfigFormat::Toml).is_none());
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+}
+
+/// 测试内容：
+///     1、工作目录下只有 .gitrepos.yaml 时，默认使用该文件
+///     2、添加仓库后保存，文件仍为 YAML 格式
+///     3、保存到新的 .yml 文件时使用 YAML 格式
+///     4、同时存在 .gitrepos 时，默认使用 .gitrepos
+///
+/// 测试目录结构:
+///   test_config_yaml_default
+///     ├─.gitrepos
+///     ├─.gitrepos.yaml
+///     └─new.yml
+#[test]
+fn cli_config_yaml_default() {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_config_yaml_default");
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+
+    let yaml = r#"default-branch: develop
+repos:
+  - local: foo
+    remote: https://example.com/foo.git
+"#;
+    let yaml_path = path.join(".gitrepos.yaml");
+    std::fs::write(&yaml_path, yaml).unwrap();
+
+    let paths = WorkspacePaths::resolve(Some(&path), None::<PathBuf>);
+    assert_eq!(paths.config_path, yaml_path);
+
+    let mut toml_config = TomlConfig::load(&paths.config_path).unwrap();
+    let mut bar = toml_config.repos.as_ref().unwrap()[0].clone();
+    bar.local = Some("bar".to_string());
+    toml_config.repos.as_mut().unwrap().push(bar);
+
+    let new_path = path.join("new.yml");
+    for config_path in [&yaml_path, &new_path] {
+        toml_config.save(config_path).unwrap();
+        let content = std::fs::read_to_string(config_path).unwrap();
+        assert!(content.contains("- local: bar"), "{}", content);
+
+        let toml_config = TomlConfig::load_with_format(config_path, ConfigFormat::Yaml).unwrap();
+        assert_eq!(toml_config.repos.unwrap().len(), 2);
+    }
+
+    std::fs::write(path.join(".gitrepos"), "").unwrap();
+    let paths = WorkspacePaths::resolve(Some(&path), None::<PathBuf>);
+    assert_eq!(paths.config_path, path.join(".gitrepos"));
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+}
+
+/// 测试内容：
+///     1、解析配置内容后序列化，字段按固定顺序输出，并带有文件头
+#[test]
+fn cli_config_serialize() {
+    let toml_string = r#"
+[[repos]]
+branch = "master"
+remote = "https://example.com/foo.git"
+local = "foo"
+
+[[repos]]
+local = "bar"
+remote = "https://example.com/bar.git"
+commit = "0123456789abcdef0123456789abcdef01234567"
+depth = 1
+"#;
+    let mut toml_config = TomlConfig::parse(toml_string, ConfigFormat::Toml).unwrap();
+    toml_config.default_branch = Some("develop".to_string());
+
+    let expected = r#"# This file is automatically @generated by mgit.
+# Editing it as you wish.
+default-branch = "develop"
+
+[[repos]]
+local = "bar"
+remote = "https://example.com/bar.git"
+commit = "0123456789abcdef0123456789abcdef01234567"
+depth = 1
+
+[[repos]]
+local = "foo"
+remote = "https://example.com/foo.git"
+branch = "master"
+
+"#;
+    assert_eq!(toml_config.serialize().unwrap(), expected);
+}
+
+/// 测试内容：
+///     1、depth 超出 toml 整数范围时，序列化返回错误而不是 panic
+#[test]
+fn cli_config_serialize_error() {
+    let toml_string = r#"
+[[repos]]
+local = "foo"
+remote = "https://example.com/foo.git"
+"#;
+    let mut toml_config = TomlConfig::parse(toml_string, ConfigFormat::Toml).unwrap();
+    toml_config.repos.as_mut().unwrap()[0].depth = Some(usize::MAX);
+
+    let err = toml_config.serialize().unwrap_err();
+    assert!(err.to_string().starts_with("Failed to serialize config"));
+}
+
+/// 测试内容：
+///     1、repo 的 remote 保留 ${VAR}，调用 git 时使用的地址按环境变量展开，local 不展开
+///     2、保存配置文件（toml 和 yaml）时保留 ${VAR}，不写入变量的值
+///     3、remote 引用的环境变量未设置时，加载配置失败
+///
+/// 测试目录结构:
+///   test_config_remote_env
+///     ├─.gitrepos
+///     └─.gitrepos.yaml
+#[test]
+fn cli_config_remote_env() {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_config_remote_env");
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+
+    env::set_var("MGIT_TEST_REMOTE_HOST", "example.com");
+    env::set_var("MGIT_TEST_REMOTE_LOCAL", "expanded");
+    let toml_string = r#"
+[[repos]]
+local = "foo/${MGIT_TEST_REMOTE_LOCAL}"
+remote = "https://${MGIT_TEST_REMOTE_HOST}/foo.git"
+branch = "master"
+"#;
+    let toml_config = TomlConfig::parse(toml_string, ConfigFormat::Toml).unwrap();
+    let repo = &toml_config.repos.as_ref().unwrap()[0];
+    assert_eq!(
+        repo.remote.as_deref(),
+        Some("https://${MGIT_TEST_REMOTE_HOST}/foo.git")
+    );
+    assert_eq!(
+        repo.remote_url().as_deref(),
+        Some("https://example.com/foo.git")
+    );
+    assert_eq!(repo.local.as_deref(), Some("foo/${MGIT_TEST_REMOTE_LOCAL}"));
+
+    let yaml_string = r#"repos:
+  - local: foo/${MGIT_TEST_REMOTE_LOCAL}
+    remote: https://${MGIT_TEST_REMOTE_HOST}/foo.git
+    branch: master
+"#;
+    for (name, content) in [(".gitrepos", toml_string), (".gitrepos.yaml", yaml_string)] {
+        let config_path = path.join(name);
+        std::fs::write(&config_path, content).unwrap();
+
+        let mut toml_config = TomlConfig::load(&config_path).unwrap();
+        toml_config.repos.as_mut().unwrap()[0].branch = Some("develop".to_string());
+        toml_config.save(&config_path).unwrap();
+
+        let content = std::fs::read_to_string(&config_path).unwrap();
+        assert!(
+            content.contains("https://${MGIT_TEST_REMOTE_HOST}/foo.git"),
+            "{}",
+            content
+        );
+        assert!(!content.contains("example.com"), "{}", content);
+        assert!(content.contains("develop"), "{}", content);
+    }
+
+    let unset = r#"
+[[repos]]
+local = "foo"
+remote = "https://${MGIT_TEST_REMOTE_UNSET}/foo.git"
+"#;
+    env::remove_var("MGIT_TEST_REMOTE_UNSET");
+    assert!(TomlConfig::parse(unset, ConfigFormat::Toml).is_none());
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+}
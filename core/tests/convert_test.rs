@@ -0,0 +1,89 @@
+use mgit::core::repos::{ConfigFormat, TomlConfig};
+use mgit::ops;
+use mgit::ops::ConvertOptions;
+use mgit::utils::error::MgitResult;
+use std::env;
+
+use crate::common::failed_message;
+
+mod common;
+
+/// 测试内容：
+///     1、包含所有字段的 toml 配置文件依次转换为 yaml、json、toml，每次读取结果都与原配置一致
+///     2、json 中超出 toml 整数范围的 max-size 无法转换为 toml，报错且不写文件
+///
+/// 测试目录结构:
+///   test_convert(.gitrepos)
+#[test]
+fn cli_convert_round_trip() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_convert");
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+
+    let toml_string = r#"
+version = "1.0"
+name = "client"
+description = "client and its dependencies"
+default-branch = "develop"
+default-remote = "https://example.com"
+sync-strategy = "stash"
+clean-exclude = ["node_modules"]
+
+[[repos]]
+local = "foo"
+remote = "https://example.com/foo.git"
+fetch-remote = "https://example.com/upstream/foo.git"
+push-remote = "https://example.com/fork/foo.git"
+branch = "main"
+sparse = ["doc"]
+subdir = "lib"
+max-size = 100
+groups = ["client"]
+retries = 3
+retry-delay-ms = 1000
+
+[[repos]]
+local = "bar"
+remote = "https://example.com/bar.git"
+tag = "v1.0"
+commit = "0123456789abcdef0123456789abcdef01234567"
+mirror = true
+"#;
+    let config_file = path.join(".gitrepos");
+    std::fs::write(&config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+    let toml_config = TomlConfig::load(&config_file).unwrap();
+
+    let mut input = config_file.clone();
+    for (format, name) in [
+        (ConfigFormat::Yaml, "repos.yaml"),
+        (ConfigFormat::Json, "repos.json"),
+        (ConfigFormat::Toml, "repos.toml"),
+    ] {
+        let out = path.join(name);
+        let mut options = ConvertOptions::new(Some(&input), format);
+        options.out = Some(out.clone());
+        let content = ops::convert_config(options)?;
+
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), content);
+        assert_eq!(TomlConfig::load(&out).unwrap(), toml_config);
+        input = out;
+    }
+
+    // toml integers are signed 64-bit
+    let json_string = r#"{"repos": [{"local": "foo", "max-size": 18446744073709551615}]}"#;
+    let json_file = path.join("large.json");
+    std::fs::write(&json_file, json_string).expect(failed_message::WRITE_FILE);
+    let mut options = ConvertOptions::new(Some(&json_file), ConfigFormat::Toml);
+    options.out = Some(path.join("large.toml"));
+    let err = ops::convert_config(options).unwrap_err();
+    assert!(err.to_string().contains("can't write the config as Toml"));
+    assert!(!path.join("large.toml").exists());
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use mgit::ops;
+use mgit::ops::{DelBranchOptions, SyncOptions};
+use mgit::utils::error::{MgitError, MgitResult};
+use std::{env, path::PathBuf};
+
+use crate::common::{create_local_remote, exec_cmd, TestProgress, TomlBuilder, DEFAULT_BRANCH};
+
+mod common;
+
+/// 测试内容：
+///     1、只有 foo 的远端有 release 分支
+///     2、find_remote_branch 只找到 foo，不删除任何分支
+///     3、确认后删除，foo 的远端 release 分支被删除
+///     4、确认列表中的 bar 删除失败，与删除成功的 foo 分开报告
+///
+/// 测试目录结构:
+///   test_del_remote_branch(.gitrepos)
+///     ├─foo
+///     ├─bar
+///     ├─foo.git
+///     └─bar.git
+#[test]
+fn cli_del_remote_branch_confirmed() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_del_remote_branch");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let foo_remote = create_local_remote(path.join("foo.git"));
+    let bar_remote = create_local_remote(path.join("bar.git"));
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foo", &foo_remote, Some(DEFAULT_BRANCH), None, None)
+        .join_repo("bar", &bar_remote, Some(DEFAULT_BRANCH), None, None)
+        .build();
+    std::fs::write(path.join(".gitrepos"), toml_string.trim()).unwrap();
+    let sync_options = SyncOptions::new(
+        Some(input_path),
+        None::<PathBuf>,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    );
+    ops::sync_repo(sync_options, TestProgress)?;
+    exec_cmd(path.join("foo"), "git", &["push", "origin", "HEAD:release"]).unwrap();
+
+    let options = || {
+        DelBranchOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            "release".to_string(),
+            None,
+        )
+    };
+    let has_release = |remote: &str| {
+        let output = exec_cmd(path.join(remote), "git", &["branch", "--list", "release"]);
+        !output.unwrap().trim().is_empty()
+    };
+
+    let scan = ops::find_remote_branch(&options())?;
+    assert_eq!(scan.found, vec!["foo".to_string()]);
+    assert!(scan.errors.is_empty());
+    assert!(has_release("foo.git"));
+
+    let mut del_options = options();
+    del_options.confirmed = Some(scan);
+    let res = ops::del_remote_branch(del_options)?;
+    assert!(res
+        .plain()
+        .contains("Deleted:\n  foo: deleted origin/release"));
+    assert!(!has_release("foo.git"));
+
+    // a confirmed repo losing the branch in the meantime fails on its own
+    exec_cmd(path.join("foo"), "git", &["push", "origin", "HEAD:release"]).unwrap();
+    let mut del_options = options();
+    del_options.confirmed = Some(ops::RemoteBranchScan {
+        found: vec!["foo".to_string(), "bar".to_string()],
+        errors: Vec::new(),
+    });
+    let err = ops::del_remote_branch(del_options).unwrap_err();
+    let Some(MgitError::OpsError { prefix, errors }) = err.downcast_ref::<MgitError>() else {
+        panic!("unexpected error: {}", err);
+    };
+    assert!(prefix
+        .plain()
+        .contains("Deleted:\n  foo: deleted origin/release"));
+    assert_eq!(errors.0.len(), 1);
+    assert!(errors.0[0].plain().starts_with("bar "));
+    assert!(!has_release("foo.git"));
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
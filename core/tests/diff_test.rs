@@ -0,0 +1,107 @@
+use mgit::ops;
+use mgit::ops::{DiffOptions, SyncOptions};
+use mgit::utils::error::MgitResult;
+use std::{env, path::PathBuf};
+
+use crate::common::{
+    create_local_remote, exec_cmd, failed_message, TestProgress, TomlBuilder, DEFAULT_BRANCH,
+};
+
+mod common;
+
+/// 测试内容：
+///     1、同步 foobar-1、foobar-2，foobar-3 没有拉取
+///     2、foobar-1 修改 1.txt，foobar-2 新增并暂存 2.txt
+///     3、运行命令 mgit diff <path>，只列出 foobar-1，没有拉取的 foobar-3 被跳过
+///     4、运行命令 mgit diff <path> --staged，只列出 foobar-2
+///
+/// 测试目录结构:
+///   test_diff
+///     ├─foobar-1 (.git)
+///     ├─foobar-2 (.git)
+///     └─remote.git
+#[test]
+fn cli_diff() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_diff");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar-1", &remote, None, None, None)
+        .join_repo("foobar-2", &remote, None, None, None)
+        .build();
+    let config_file = path.join(".gitrepos");
+    std::fs::write(&config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    ops::sync_repo(
+        SyncOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        TestProgress,
+    )?;
+
+    // foobar-3 is only added after the sync
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar-1", &remote, None, None, None)
+        .join_repo("foobar-2", &remote, None, None, None)
+        .join_repo("foobar-3", &remote, None, None, None)
+        .build();
+    std::fs::write(&config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    std::fs::write(path.join("foobar-1").join("1.txt"), "changed\nlines\n")
+        .expect(failed_message::WRITE_FILE);
+    let repo_path = path.join("foobar-2");
+    std::fs::write(repo_path.join("2.txt"), "2.txt\n").expect(failed_message::WRITE_FILE);
+    exec_cmd(&repo_path, "git", &["add", "2.txt"]).expect(failed_message::GIT_STAGE);
+
+    let diff_options = |staged: bool| {
+        let mut options = DiffOptions::new(Some(input_path), None::<PathBuf>);
+        options.staged = staged;
+        options
+    };
+
+    let diff_stat = ops::diff_repos(diff_options(false))?;
+    assert_eq!(diff_stat.repos.len(), 1);
+    assert_eq!(diff_stat.repos[0].local, "foobar-1");
+    assert!(diff_stat.repos[0].stat.contains("1.txt"));
+    assert_eq!(diff_stat.files_changed, 1);
+    assert_eq!(diff_stat.insertions, 2);
+    assert_eq!(diff_stat.deletions, 1);
+    assert!(diff_stat
+        .to_string()
+        .ends_with("Total: 1 files changed, 2 insertions(+), 1 deletions(-) in 1 repos\n"));
+
+    let diff_stat = ops::diff_repos(diff_options(true))?;
+    assert_eq!(diff_stat.repos.len(), 1);
+    assert_eq!(diff_stat.repos[0].local, "foobar-2");
+    assert_eq!(
+        (
+            diff_stat.files_changed,
+            diff_stat.insertions,
+            diff_stat.deletions
+        ),
+        (1, 1, 0)
+    );
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
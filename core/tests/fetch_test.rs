@@ -4,7 +4,10 @@ use mgit::utils::error::MgitResult;
 use std::env;
 use std::path::PathBuf;
 
-use crate::common::{exec_cmd, failed_message, TestProgress, TomlBuilder, CSBOOKS_REPO, MGIT_REPO};
+use crate::common::{
+    create_local_remote, exec_cmd, failed_message, TestProgress, TomlBuilder, CSBOOKS_REPO,
+    DEFAULT_BRANCH, MGIT_REPO,
+};
 
 mod common;
 
@@ -48,7 +51,7 @@ fn cli_fetch_simple() -> MgitResult<()> {
     }
 
     // init command
-    ops::init_repo(InitOptions::new(Some(path.clone()), None))?;
+    ops::init_repo(InitOptions::new(Some(path.clone()), None), TestProgress)?;
     // fetch command
     ops::fetch_repos(
         FetchOptions::new(Some(path.clone()), None::<PathBuf>, None, None, None, None),
@@ -120,3 +123,134 @@ fn cli_fetch_new_remote_url() -> MgitResult<()> {
     std::fs::remove_dir_all(&path).unwrap();
     Ok(())
 }
+
+/// 测试内容：
+///     1、远端有新的提交
+///     2、运行命令: mgit fetch <path> --fast-fetch
+///     3、使用 skipping 协商算法和 protocol v2 拉取到新的提交
+///
+/// 测试目录结构:
+///   test_fetch_fast_fetch(.gitrepos)
+///     ├─foobar (.git)
+///     └─remote.git
+#[test]
+fn cli_fetch_fast_fetch() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_fetch_fast_fetch");
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+    exec_cmd(&path, "git", &["clone", &remote, "foobar"]).expect("git clone failed");
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar", &remote, None, None, None)
+        .build();
+    let config_file = path.join(".gitrepos");
+    std::fs::write(&config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    // push a new commit from another clone
+    exec_cmd(&path, "git", &["clone", &remote, "upstream"]).expect("git clone failed");
+    let upstream = path.join("upstream");
+    exec_cmd(
+        &upstream,
+        "git",
+        &[
+            "-c",
+            "user.name=mgit",
+            "-c",
+            "user.email=mgit@example.com",
+            "commit",
+            "--allow-empty",
+            "-m",
+            "upstream",
+        ],
+    )
+    .expect(failed_message::GIT_COMMIT);
+    exec_cmd(&upstream, "git", &["push", "origin", DEFAULT_BRANCH]).expect("git push failed");
+    let upstream_head = exec_cmd(&upstream, "git", &["rev-parse", "HEAD"])?;
+
+    let mut options =
+        FetchOptions::new(Some(path.clone()), None::<PathBuf>, None, None, None, None);
+    options.fast_fetch = true;
+    ops::fetch_repos(options, TestProgress)?;
+
+    let origin_head = exec_cmd(path.join("foobar"), "git", &["rev-parse", "origin/master"])?;
+    assert_eq!(origin_head, upstream_head);
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
+
+/// 测试内容：
+///     1、远端新建分支 stale，fetch 后存在远程跟踪分支 origin/stale
+///     2、远端删除分支 stale
+///     3、运行命令: mgit fetch <path> --no-prune，origin/stale 仍然存在
+///     4、运行命令: mgit fetch <path>，默认 prune，origin/stale 被删除
+///
+/// 测试目录结构:
+///   test_fetch_prune(.gitrepos)
+///     ├─foobar (.git)
+///     ├─upstream (.git)
+///     └─remote.git
+#[test]
+fn cli_fetch_prune() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_fetch_prune");
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+    exec_cmd(&path, "git", &["clone", &remote, "foobar"]).expect("git clone failed");
+    exec_cmd(&path, "git", &["clone", &remote, "upstream"]).expect("git clone failed");
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar", &remote, None, None, None)
+        .build();
+    let config_file = path.join(".gitrepos");
+    std::fs::write(&config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    let upstream = path.join("upstream");
+    let foobar = path.join("foobar");
+    let fetch = |no_prune: bool| {
+        let mut options =
+            FetchOptions::new(Some(path.clone()), None::<PathBuf>, None, None, None, None);
+        if no_prune {
+            options.prune = false;
+        }
+        ops::fetch_repos(options, TestProgress)
+    };
+
+    exec_cmd(
+        &upstream,
+        "git",
+        &["push", "origin", "HEAD:refs/heads/stale"],
+    )
+    .expect("git push failed");
+    fetch(false)?;
+    let branches = exec_cmd(&foobar, "git", &["branch", "-r"])?;
+    assert!(branches.contains("origin/stale"), "{}", branches);
+
+    exec_cmd(&upstream, "git", &["push", "origin", "--delete", "stale"]).expect("git push failed");
+    fetch(true)?;
+    let branches = exec_cmd(&foobar, "git", &["branch", "-r"])?;
+    assert!(branches.contains("origin/stale"), "{}", branches);
+
+    fetch(false)?;
+    let branches = exec_cmd(&foobar, "git", &["branch", "-r"])?;
+    assert!(!branches.contains("origin/stale"), "{}", branches);
+    assert!(branches.contains("origin/master"), "{}", branches);
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
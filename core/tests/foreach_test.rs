@@ -0,0 +1,96 @@
+use mgit::ops;
+use mgit::ops::{ForeachOptions, SyncOptions};
+use mgit::utils::error::{MgitError, MgitResult};
+use std::{env, path::PathBuf};
+
+use crate::common::{
+    create_local_remote, failed_message, TestProgress, TomlBuilder, DEFAULT_BRANCH,
+};
+
+mod common;
+
+/// 测试内容：
+///     1、运行命令 mgit foreach 'echo "$MGIT_REPO_LOCAL $MGIT_REPO_REMOTE" > env.txt' <path>，
+///        每个仓库写入自己的 local 和 remote
+///     2、运行命令 mgit foreach 'test "$MGIT_REPO_LOCAL" != foobar-1' <path> --continue-on-error，
+///        foobar-1 失败，foobar-2 仍然执行，汇总中包含退出码 1
+///
+/// 测试目录结构:
+///   test_foreach_simple
+///     ├─foobar-1 (.git)
+///     ├─foobar-2 (.git)
+///     └─remote.git
+#[test]
+fn cli_foreach_simple() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_foreach_simple");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar-1", &remote, None, None, None)
+        .join_repo("foobar-2", &remote, None, None, None)
+        .build();
+    let config_file = path.join(".gitrepos");
+    std::fs::write(config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    ops::sync_repo(
+        SyncOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        TestProgress,
+    )?;
+
+    let foreach_options = |command: &str, continue_on_error: bool| {
+        let mut options = ForeachOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            command.to_string(),
+            None,
+            None,
+        );
+        options.continue_on_error = continue_on_error;
+        options
+    };
+
+    ops::foreach_repo(foreach_options(
+        "echo \"$MGIT_REPO_LOCAL $MGIT_REPO_REMOTE\" > env.txt",
+        false,
+    ))?;
+    for local in ["foobar-1", "foobar-2"] {
+        let env = std::fs::read_to_string(path.join(local).join("env.txt")).unwrap();
+        assert_eq!(env.trim(), format!("{} {}", local, remote));
+    }
+
+    let e = ops::foreach_repo(foreach_options(
+        "test \"$MGIT_REPO_LOCAL\" != foobar-1 && touch ran.txt",
+        true,
+    ))
+    .unwrap_err();
+    let Some(MgitError::OpsError { prefix, errors }) = e.downcast_ref::<MgitError>() else {
+        panic!("unexpected error: {}", e);
+    };
+    assert!(prefix.plain().contains("  0: 1 repo(s)\n  1: foobar-1\n"));
+    assert_eq!(errors.0.len(), 1);
+    assert!(path.join("foobar-2").join("ran.txt").is_file());
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
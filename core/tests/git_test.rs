@@ -0,0 +1,414 @@
+use mgit::core::git;
+use mgit::core::git::{CheckoutError, FetchOrPush, GitVersion, RemoteRef};
+use mgit::utils::cmd::exec_cmd_with_callback;
+use mgit::utils::error::MgitResult;
+use std::env;
+use std::process::Command;
+
+use crate::common::{create_local_remote, exec_cmd, failed_message, DEFAULT_BRANCH};
+
+mod common;
+
+/// 测试内容：
+///     1、远端配置了不同的 fetch url 和 push url
+///     2、按 fetch/push 分别获取到正确的 url
+///
+/// 测试目录结构:
+///   test_git_remote_fetch_push_url(.git)
+#[test]
+fn cli_git_remote_fetch_push_url() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_git_remote_fetch_push_url");
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+
+    let fetch_url = "https://example.com/upstream/foobar.git";
+    let push_url = "https://example.com/fork/foobar.git";
+    exec_cmd(&path, "git", &["init", "-b", DEFAULT_BRANCH]).expect(failed_message::GIT_INIT);
+    exec_cmd(&path, "git", &["remote", "add", "origin", fetch_url])
+        .expect(failed_message::GIT_ADD_REMOTE);
+    exec_cmd(
+        &path,
+        "git",
+        &["remote", "set-url", "--push", "origin", push_url],
+    )
+    .expect(failed_message::GIT_CONFIG);
+
+    assert_eq!(
+        git::find_remote_url_by_name(&path, "origin", FetchOrPush::Fetch)?,
+        fetch_url
+    );
+    assert_eq!(
+        git::find_remote_url_by_name(&path, "origin", FetchOrPush::Push)?,
+        push_url
+    );
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
+
+/// 测试内容：
+///     1、RemoteRef 的 Display 输出完整的 commit/tag/branch
+///     2、remote_desc 输出短 commit/tag/branch
+#[test]
+fn cli_git_remote_ref_display() -> MgitResult<()> {
+    let commit = RemoteRef::Commit("0e1fd4cd5d6cd5c3d4d7b11cfb7a1cae1bd8a8a9".to_string());
+    assert_eq!(
+        commit.to_string(),
+        "0e1fd4cd5d6cd5c3d4d7b11cfb7a1cae1bd8a8a9"
+    );
+    assert_eq!(commit.remote_desc(), "0e1fd4c");
+
+    let tag = RemoteRef::Tag("v1.0.0".to_string());
+    assert_eq!(tag.to_string(), "v1.0.0");
+    assert_eq!(tag.remote_desc(), "v1.0.0");
+
+    let branch = RemoteRef::Branch("origin/master".to_string());
+    assert_eq!(branch.to_string(), "origin/master");
+    assert_eq!(branch.remote_desc(), "origin/master");
+    Ok(())
+}
+
+/// 测试内容：
+///     1、rev_parse 将 branch、轻量 tag、附注 tag 解析为完整的 commit
+///     2、不存在的 ref 解析失败
+///
+/// 测试目录结构:
+///   test_git_rev_parse
+///     ├─foobar (.git)
+///     └─remote.git
+#[test]
+fn cli_git_rev_parse() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_git_rev_parse");
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+    exec_cmd(&path, "git", &["clone", &remote, "foobar"]).expect("git clone failed");
+
+    let repo_path = path.join("foobar");
+    let head = exec_cmd(&repo_path, "git", &["rev-parse", "HEAD"])?;
+    let head = head.trim();
+    exec_cmd(&repo_path, "git", &["tag", "v1"]).expect("git tag failed");
+    exec_cmd(
+        &repo_path,
+        "git",
+        &[
+            "-c",
+            "user.name=mgit",
+            "-c",
+            "user.email=mgit@example.com",
+            "tag",
+            "-a",
+            "v2",
+            "-m",
+            "v2",
+        ],
+    )
+    .expect("git tag failed");
+
+    assert_eq!(git::rev_parse(&repo_path, "HEAD")?, head);
+    assert_eq!(git::rev_parse(&repo_path, "origin/master")?, head);
+    assert_eq!(git::rev_parse(&repo_path, "v1")?, head);
+    assert_eq!(git::rev_parse(&repo_path, "v2")?, head);
+    assert_eq!(git::rev_parse(&repo_path, &head[..7])?, head);
+    assert!(git::rev_parse(&repo_path, "origin/not-found").is_err());
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
+
+/// 测试内容：
+///     1、本地 master 比 origin/master 多两个提交，side 分支从 origin/master 多一个提交
+///     2、rev_list_ahead_behind 返回两边各自独有的提交数
+///     3、不存在的 ref 返回错误
+///
+/// 测试目录结构:
+///   test_git_rev_list_ahead_behind
+///     ├─foobar (.git)
+///     └─remote.git
+#[test]
+fn cli_git_rev_list_ahead_behind() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_git_rev_list_ahead_behind");
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+    exec_cmd(&path, "git", &["clone", &remote, "foobar"]).expect("git clone failed");
+
+    let repo_path = path.join("foobar");
+    let commit = |message: &str| {
+        let args = [
+            "-c",
+            "user.name=mgit",
+            "-c",
+            "user.email=mgit@example.com",
+            "commit",
+            "--allow-empty",
+            "-m",
+            message,
+        ];
+        exec_cmd(&repo_path, "git", &args).expect(failed_message::GIT_COMMIT);
+    };
+    commit("one");
+    commit("two");
+    exec_cmd(
+        &repo_path,
+        "git",
+        &["checkout", "-b", "side", "origin/master"],
+    )
+    .expect(failed_message::GIT_CHECKOUT);
+    commit("three");
+
+    assert_eq!(
+        git::rev_list_ahead_behind(&repo_path, "master...origin/master")?,
+        (2, 0)
+    );
+    assert_eq!(
+        git::rev_list_ahead_behind(&repo_path, "origin/master...master")?,
+        (0, 2)
+    );
+    assert_eq!(
+        git::rev_list_ahead_behind(&repo_path, "master...side")?,
+        (2, 1)
+    );
+    assert!(git::rev_list_ahead_behind(&repo_path, "master...not-found").is_err());
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
+
+/// 测试内容：
+///     1、提交信息的标题跨多行，且包含引号
+///     2、head_commit 返回正确的 hash、作者、邮箱和日期
+///     3、多行标题被 git 合并为一行，正文不包含在 subject 中
+///
+/// 测试目录结构:
+///   test_git_head_commit(.git)
+#[test]
+fn cli_git_head_commit() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_git_head_commit");
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+
+    exec_cmd(&path, "git", &["init", "-b", DEFAULT_BRANCH]).expect(failed_message::GIT_INIT);
+    exec_cmd(
+        &path,
+        "git",
+        &[
+            "-c",
+            "user.name=mgit dev",
+            "-c",
+            "user.email=mgit@example.com",
+            "commit",
+            "--allow-empty",
+            "-m",
+            "fix \"quoted\" title\nspanning two lines\n\nbody text",
+        ],
+    )
+    .expect(failed_message::GIT_COMMIT);
+
+    let head = exec_cmd(&path, "git", &["rev-parse", "HEAD"])?;
+    let commit = git::head_commit(&path)?;
+    assert_eq!(commit.hash, head.trim());
+    assert_eq!(commit.author, "mgit dev");
+    assert_eq!(commit.email, "mgit@example.com");
+    assert_eq!(commit.date.len(), "2000-01-01 00:00:00".len());
+    assert_eq!(commit.subject, "fix \"quoted\" title spanning two lines");
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
+
+/// 测试内容：
+///     1、仓库在 master 分支上，get_current_branch 返回 master
+///     2、切换到游离 HEAD 后，get_current_branch 返回空字符串
+///
+/// 测试目录结构:
+///   test_git_current_branch(.git)
+#[test]
+fn cli_git_current_branch() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_git_current_branch");
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+
+    exec_cmd(&path, "git", &["init", "-b", DEFAULT_BRANCH]).expect(failed_message::GIT_INIT);
+    exec_cmd(
+        &path,
+        "git",
+        &[
+            "-c",
+            "user.name=mgit",
+            "-c",
+            "user.email=mgit@example.com",
+            "commit",
+            "--allow-empty",
+            "-m",
+            "init",
+        ],
+    )
+    .expect(failed_message::GIT_COMMIT);
+    assert_eq!(git::get_current_branch(&path)?, DEFAULT_BRANCH);
+
+    exec_cmd(&path, "git", &["checkout", "--detach"]).expect(failed_message::GIT_CHECKOUT);
+    assert_eq!(git::get_current_branch(&path)?, "");
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
+
+/// 测试内容：
+///     1、使用 file:// 远端执行 git clone --progress
+///     2、exec_cmd_with_callback 逐行收到 git 的传输进度，每次百分比更新都是单独的一行
+///
+/// 测试目录结构:
+///   test_git_clone_progress_lines
+///     ├─foobar
+///     └─remote.git
+#[test]
+fn cli_git_clone_progress_lines() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_git_clone_progress_lines");
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+
+    let url = format!("file://{}", remote);
+    let mut command = Command::new("git");
+    command
+        .args(["clone", "--progress", &url, "foobar"])
+        .current_dir(&path);
+    let mut lines = Vec::new();
+    exec_cmd_with_callback("foobar", &mut command, |line| lines.push(line.to_string()))?;
+
+    assert!(lines.iter().any(|line| line.starts_with("Cloning into")));
+    assert!(lines
+        .iter()
+        .any(|line| line.starts_with("Receiving objects: 100%")));
+    assert!(path.join("foobar/1.txt").is_file());
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
+
+/// 测试内容：
+///     1、目标分支有一个文件，当前分支中同名文件未被跟踪，checkout 失败
+///     2、错误信息中列出冲突的文件，并提示大小写不敏感文件系统的处理方法
+///     3、git 报告的大小写冲突路径被解析为 CaseCollision
+///
+/// 测试目录结构:
+///   test_git_checkout_conflict(.git)
+#[test]
+fn cli_git_checkout_conflict() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_git_checkout_conflict");
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+
+    let commit = |args: &[&str]| {
+        let mut full_args = vec!["-c", "user.name=mgit", "-c", "user.email=mgit@example.com"];
+        full_args.extend_from_slice(args);
+        exec_cmd(&path, "git", &full_args).expect(failed_message::GIT_COMMIT);
+    };
+    exec_cmd(&path, "git", &["init", "-b", DEFAULT_BRANCH]).expect(failed_message::GIT_INIT);
+    commit(&["commit", "--allow-empty", "-m", "init"]);
+    exec_cmd(&path, "git", &["switch", "-c", "feature"]).expect(failed_message::GIT_CHECKOUT);
+    std::fs::write(path.join("README.md"), "feature").expect(failed_message::WRITE_FILE);
+    exec_cmd(&path, "git", &["add", "README.md"]).expect(failed_message::GIT_STAGE);
+    commit(&["commit", "-m", "add readme"]);
+    exec_cmd(&path, "git", &["switch", DEFAULT_BRANCH]).expect(failed_message::GIT_CHECKOUT);
+    std::fs::write(path.join("README.md"), "untracked").expect(failed_message::WRITE_FILE);
+
+    let err = git::checkout(&path, &["checkout", "feature"]).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<CheckoutError>(),
+        Some(&CheckoutError::UntrackedOverwritten(vec![
+            "README.md".to_string()
+        ]))
+    );
+    assert!(err.to_string().contains("core.ignorecase"));
+
+    let output = "warning: the following paths have collided (e.g. case-sensitive paths\n\
+                  on a case-insensitive filesystem) and only one from the same\n\
+                  colliding group is in the working tree:\n\n  'readme.md'\n  'README.md'\n";
+    assert_eq!(
+        git::parse_checkout_error(output),
+        Some(CheckoutError::CaseCollision(vec![
+            "readme.md".to_string(),
+            "README.md".to_string()
+        ]))
+    );
+    assert_eq!(
+        git::parse_checkout_error("fatal: invalid reference: x"),
+        None
+    );
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
+
+/// 测试内容：
+///     1、解析不同平台的 git --version 输出
+///     2、版本号按 major/minor/patch 比较
+#[test]
+fn cli_git_version_parse() -> MgitResult<()> {
+    assert_eq!(
+        GitVersion::parse("git version 2.39.5\n"),
+        Some(GitVersion::new(2, 39, 5))
+    );
+    assert_eq!(
+        GitVersion::parse("git version 2.39.3 (Apple Git-145)"),
+        Some(GitVersion::new(2, 39, 3))
+    );
+    assert_eq!(
+        GitVersion::parse("git version 2.41.0.windows.1"),
+        Some(GitVersion::new(2, 41, 0))
+    );
+    assert_eq!(
+        GitVersion::parse("git version 3.0"),
+        Some(GitVersion::new(3, 0, 0))
+    );
+    assert_eq!(GitVersion::parse("not git"), None);
+
+    assert!(GitVersion::new(2, 34, 9) < GitVersion::SPARSE_CONE);
+    assert!(GitVersion::new(2, 100, 0) > GitVersion::SPARSE_CONE);
+    assert!(git::git_version().is_some());
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use mgit::ops;
+use mgit::ops::InstallHooksOptions;
+use mgit::utils::error::MgitResult;
+use std::env;
+use std::path::PathBuf;
+
+use crate::common::{create_local_remote, exec_cmd, failed_message, TomlBuilder, DEFAULT_BRANCH};
+
+mod common;
+
+/// 测试内容：
+///     1、hooks 目录中有 pre-commit 和 commit-msg 两个脚本，foo 中已有 commit-msg
+///     2、dry-run 不修改仓库
+///     3、安装时跳过已有的 hook，--force 时覆盖
+///     4、不存在的仓库报错，其他仓库照常安装
+///
+/// 测试目录结构:
+///   test_hooks_install(.gitrepos)
+///     ├─hooks
+///     ├─foo (.git)
+///     ├─bar (.git)
+///     └─remote.git
+#[test]
+fn cli_hooks_install() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_hooks_install");
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(path.join("hooks")).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+    exec_cmd(&path, "git", &["clone", &remote, "foo"]).expect("git clone failed");
+    exec_cmd(&path, "git", &["clone", &remote, "bar"]).expect("git clone failed");
+
+    let source = path.join("hooks");
+    std::fs::write(source.join("pre-commit"), "#!/bin/sh\nexit 0\n")
+        .expect(failed_message::WRITE_FILE);
+    std::fs::write(source.join("commit-msg"), "#!/bin/sh\nexit 0\n")
+        .expect(failed_message::WRITE_FILE);
+    let foo_hooks = path.join("foo").join(".git").join("hooks");
+    let bar_hooks = path.join("bar").join(".git").join("hooks");
+    std::fs::write(foo_hooks.join("commit-msg"), "custom").expect(failed_message::WRITE_FILE);
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foo", &remote, None, None, None)
+        .join_repo("bar", &remote, None, None, None)
+        .build();
+    std::fs::write(path.join(".gitrepos"), toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    let options = || InstallHooksOptions::new(Some(&path), None::<PathBuf>, &source);
+
+    // preview only
+    let mut dry_run = options();
+    dry_run.dry_run = true;
+    ops::install_hooks(dry_run)?;
+    assert!(!bar_hooks.join("pre-commit").exists());
+
+    // existing hooks are kept
+    ops::install_hooks(options())?;
+    assert!(foo_hooks.join("pre-commit").is_file());
+    assert!(bar_hooks.join("pre-commit").is_file());
+    assert!(bar_hooks.join("commit-msg").is_file());
+    let content = std::fs::read_to_string(foo_hooks.join("commit-msg")).unwrap();
+    assert_eq!(content, "custom");
+
+    // and overwritten on demand
+    let mut force = options();
+    force.force = true;
+    ops::install_hooks(force)?;
+    let content = std::fs::read_to_string(foo_hooks.join("commit-msg")).unwrap();
+    assert_eq!(content, "#!/bin/sh\nexit 0\n");
+
+    // missing repos fail without stopping the others
+    std::fs::remove_dir_all(path.join("foo")).unwrap();
+    std::fs::remove_file(bar_hooks.join("pre-commit")).unwrap();
+    assert!(ops::install_hooks(options()).is_err());
+    assert!(bar_hooks.join("pre-commit").is_file());
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use mgit::core::repos::TomlConfig;
+use mgit::ops;
+use mgit::ops::{InfoOptions, WorkspaceInfo};
+use mgit::utils::error::MgitResult;
+use std::{env, path::PathBuf};
+
+use crate::common::{exec_cmd, failed_message};
+
+mod common;
+
+/// 测试内容：
+///     1、配置文件中有 name、description 和三个仓库，其中两个使用同一个远端，只有 foo 已拉取
+///     2、运行命令 mgit info <path>，输出描述、仓库数量、远端数量和已拉取/缺失的数量
+///     3、name、description 序列化后重新加载保持不变
+///
+/// 测试目录结构:
+///   test_info(.gitrepos)
+///     └─foo
+#[test]
+fn cli_info() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_info");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(path.join("foo")).unwrap();
+    exec_cmd(path.join("foo"), "git", &["init"]).expect(failed_message::GIT_INIT);
+
+    let toml_string = r#"
+name = "client"
+description = "client and its dependencies"
+default-branch = "develop"
+
+[[repos]]
+local = "bar"
+remote = "https://example.com/bar.git"
+
+[[repos]]
+local = "foo"
+remote = "https://example.com/foo.git"
+
+[[repos]]
+local = "foo/baz"
+remote = "https://example.com/foo.git"
+"#;
+    let config_file = path.join(".gitrepos");
+    std::fs::write(&config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    let info = ops::workspace_info(InfoOptions::new(Some(input_path), None::<PathBuf>))?;
+    assert_eq!(
+        info,
+        WorkspaceInfo {
+            name: Some("client".to_string()),
+            description: Some("client and its dependencies".to_string()),
+            config_path: config_file.clone(),
+            default_branch: Some("develop".to_string()),
+            repo_count: 3,
+            remote_count: 2,
+            present: 1,
+            missing: 2,
+        }
+    );
+
+    let toml_config = TomlConfig::load(&config_file).unwrap();
+    std::fs::write(&config_file, toml_config.serialize()?).expect(failed_message::WRITE_FILE);
+    let toml_config = TomlConfig::load(&config_file).unwrap();
+    assert_eq!(toml_config.name.as_deref(), Some("client"));
+    assert_eq!(
+        toml_config.description.as_deref(),
+        Some("client and its dependencies")
+    );
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use mgit::ops;
+use mgit::ops::{ListFilesOptions, SyncOptions};
+use mgit::utils::error::MgitResult;
+use std::{env, path::PathBuf};
+
+use crate::common::{
+    create_local_remote, failed_message, TestProgress, TomlBuilder, DEFAULT_BRANCH,
+};
+
+mod common;
+
+/// 测试内容：
+///     1、运行命令 mgit ls-files <path>，输出写入 writer 与返回的列表一致
+///     2、运行命令 mgit ls-files <path> --format json，每个文件包含仓库、mode、object、stage 和路径
+///     3、运行命令 mgit ls-files <path> --glob <pattern>，只输出匹配的文件
+///
+/// 测试目录结构:
+///   test_list_files_writer
+///     ├─foobar (.git)
+///     └─remote.git
+#[test]
+fn cli_list_files_writer() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_list_files_writer");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar", &remote, None, None, None)
+        .build();
+    let config_file = path.join(".gitrepos");
+    std::fs::write(config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    ops::sync_repo(
+        SyncOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        TestProgress,
+    )?;
+
+    let files = ops::list_files(ListFilesOptions::new(Some(input_path), None::<PathBuf>))?;
+    let paths = files
+        .iter()
+        .map(|file| file.path.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        vec!["foobar/1.txt", "foobar/foo/1.txt", "foobar/foo/2.txt"]
+    );
+
+    // stream to a writer
+    let mut output = Vec::new();
+    let count = ops::list_files_to_writer(
+        ListFilesOptions::new(Some(input_path), None::<PathBuf>),
+        &mut output,
+    )?;
+    assert_eq!(count, files.len());
+    let lines = files
+        .iter()
+        .map(|file| format!("{}\n", file))
+        .collect::<String>();
+    assert_eq!(String::from_utf8(output).unwrap(), lines);
+    assert!(lines.starts_with("100644 "));
+    assert!(lines.contains(" 0\tfoobar/1.txt\n"));
+
+    // records of the json output
+    let json = serde_json::to_value(&files)?;
+    assert_eq!(json[0]["local"], "foobar");
+    assert_eq!(json[0]["mode"], "100644");
+    assert_eq!(json[0]["object"].as_str().unwrap().len(), 40);
+    assert_eq!(json[0]["stage"], 0);
+    assert_eq!(json[0]["path"], "foobar/1.txt");
+
+    // filter by glob
+    let mut options = ListFilesOptions::new(Some(input_path), None::<PathBuf>);
+    options.glob = Some("foobar/foo/*.txt".to_string());
+    let files = ops::list_files(options)?;
+    assert_eq!(files.len(), 2);
+    assert!(files
+        .iter()
+        .all(|file| file.path.starts_with("foobar/foo/")));
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use mgit::ops;
+use mgit::ops::{ListReposOptions, RepoEntry};
+use mgit::utils::error::MgitResult;
+use std::{env, path::PathBuf};
+
+use crate::common::failed_message;
+
+mod common;
+
+/// 测试内容：
+///     1、配置文件中有三个仓库，分别指定 commit、tag 和默认分支，其中两个有分组
+///     2、运行命令 mgit list-repos <path>，按配置顺序列出仓库和解析后的 ref
+///     3、运行命令 mgit list-repos <path> --group tools，只列出 tools 分组的仓库
+///     4、运行命令 mgit list-repos <path> --only foo --ignore bar，只列出 foo
+///
+/// 测试目录结构:
+///   test_list_repos
+///     └─.gitrepos
+#[test]
+fn cli_list_repos() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_list_repos");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+
+    let toml_string = r#"
+default-branch = "develop"
+
+[[repos]]
+local = "bar"
+remote = "https://example.com/bar.git"
+commit = "0123456789abcdef0123456789abcdef01234567"
+groups = ["tools"]
+
+[[repos]]
+local = "foo"
+remote = "https://example.com/foo.git"
+tag = "v1.0"
+groups = ["client", "tools"]
+
+[[repos]]
+local = "foo/baz"
+remote = "https://example.com/baz.git"
+"#;
+    let config_file = path.join(".gitrepos");
+    std::fs::write(config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    let repos = ops::list_repos(ListReposOptions::new(Some(input_path), None::<PathBuf>))?;
+    assert_eq!(
+        repos,
+        vec![
+            RepoEntry {
+                local: "bar".to_string(),
+                remote: Some("https://example.com/bar.git".to_string()),
+                remote_ref: Some("0123456789abcdef0123456789abcdef01234567".to_string()),
+                groups: vec!["tools".to_string()],
+            },
+            RepoEntry {
+                local: "foo".to_string(),
+                remote: Some("https://example.com/foo.git".to_string()),
+                remote_ref: Some("v1.0".to_string()),
+                groups: vec!["client".to_string(), "tools".to_string()],
+            },
+            RepoEntry {
+                local: "foo/baz".to_string(),
+                remote: Some("https://example.com/baz.git".to_string()),
+                remote_ref: Some("origin/develop".to_string()),
+                groups: vec![],
+            },
+        ]
+    );
+
+    let mut options = ListReposOptions::new(Some(input_path), None::<PathBuf>);
+    options.groups = Some(vec!["tools".to_string()]);
+    let locals = ops::list_repos(options)?
+        .into_iter()
+        .map(|repo| repo.local)
+        .collect::<Vec<_>>();
+    assert_eq!(locals, vec!["bar", "foo"]);
+
+    let mut options = ListReposOptions::new(Some(input_path), None::<PathBuf>);
+    options.only = Some(vec!["foo".to_string(), "bar".to_string()]);
+    options.ignore = Some(vec!["bar".to_string()]);
+    let locals = ops::list_repos(options)?
+        .into_iter()
+        .map(|repo| repo.local)
+        .collect::<Vec<_>>();
+    assert_eq!(locals, vec!["foo"]);
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
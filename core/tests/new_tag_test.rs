@@ -0,0 +1,334 @@
+use mgit::core::repo::TomlRepo;
+use mgit::ops;
+use mgit::ops::{NewTagOptions, SyncOptions};
+use mgit::utils::error::{MgitError, MgitResult};
+use std::time::{Duration, Instant};
+use std::{env, path::PathBuf};
+
+use crate::common::{
+    create_local_remote, exec_cmd, failed_message, TestProgress, TomlBuilder, DEFAULT_BRANCH,
+};
+
+mod common;
+
+/// 测试内容：
+///     1、同步后，foobar-2 的 origin 改为不存在的地址
+///     2、运行命令 mgit new-tag <path> --tag v1.0 --push --no-force，foobar-2 推送失败并记录
+///     3、修复 foobar-2 的 origin 后，运行命令 mgit new-tag <path> --tag v1.0 --push --no-force --retry-failed
+///     4、只有 foobar-2 重新推送已有的 tag，失败记录被删除
+///     5、远端已有指向其他提交的同名 tag 时推送被拒绝，不再重试
+///
+/// 测试目录结构:
+///   test_new_tag_retry_failed
+///     ├─foobar-1 (.git)
+///     ├─foobar-2 (.git)
+///     └─remote.git
+#[test]
+fn cli_new_tag_retry_failed() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_new_tag_retry_failed");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar-1", &remote, None, None, None)
+        .join_repo("foobar-2", &remote, None, None, None)
+        .build();
+    let config_file = path.join(".gitrepos");
+    std::fs::write(config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    ops::sync_repo(
+        SyncOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        TestProgress,
+    )?;
+
+    let repo_path = path.join("foobar-2");
+    let missing = path
+        .join("missing.git")
+        .to_str()
+        .unwrap()
+        .replace('\\', "/");
+    exec_cmd(
+        &repo_path,
+        "git",
+        &["remote", "set-url", "origin", &missing],
+    )
+    .expect(failed_message::GIT_CONFIG);
+
+    let new_tag_options = |retry_failed: bool| {
+        let mut options = NewTagOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            "v1.0".to_string(),
+            true,
+            None,
+        );
+        options.retry_failed = retry_failed;
+        options.force = false;
+        options
+    };
+    assert!(ops::new_tag(new_tag_options(false)).is_err());
+
+    let record_path = path.join(".mgit").join("failed-new-tag");
+    let record = std::fs::read_to_string(&record_path).unwrap();
+    assert!(record.lines().any(|line| line == "foobar-2"));
+    assert!(!record.lines().any(|line| line == "foobar-1"));
+
+    // foobar-1 is not tagged again by the retry
+    exec_cmd(path.join("foobar-1"), "git", &["tag", "-d", "v1.0"])?;
+    exec_cmd(&repo_path, "git", &["remote", "set-url", "origin", &remote])
+        .expect(failed_message::GIT_CONFIG);
+    ops::new_tag(new_tag_options(true))?;
+
+    let tags = exec_cmd(path.join("foobar-1"), "git", &["tag", "-l", "v1.0"])?;
+    assert!(tags.trim().is_empty());
+    let remote_tags = exec_cmd(&repo_path, "git", &["ls-remote", "--tags", "origin"])?;
+    assert!(remote_tags.contains("refs/tags/v1.0"));
+    assert!(!record_path.exists());
+
+    // nothing recorded to retry
+    assert!(ops::new_tag(new_tag_options(true)).is_err());
+
+    // the remote keeps v1.0 on the old commit
+    exec_cmd(
+        path.join("foobar-1"),
+        "git",
+        &[
+            "-c",
+            "user.name=mgit",
+            "-c",
+            "user.email=mgit@example.com",
+            "commit",
+            "--allow-empty",
+            "-m",
+            "second",
+        ],
+    )
+    .expect(failed_message::GIT_COMMIT);
+    let mut options = new_tag_options(false);
+    options.ignore = Some(vec!["foobar-2".to_string()]);
+    let start = Instant::now();
+    let err = ops::new_tag(options).unwrap_err();
+    assert!(start.elapsed() < Duration::from_secs(2));
+    let Some(MgitError::OpsError { errors, .. }) = err.downcast_ref::<MgitError>() else {
+        panic!("unexpected error: {}", err);
+    };
+    assert_eq!(errors.0.len(), 1);
+    assert!(
+        errors.0[0].plain().contains("rejected"),
+        "{}",
+        errors.0[0].plain()
+    );
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
+
+/// 测试内容：
+///     1、配置文件 default-remote = "upstream"，foobar-2 设置 remote-name = "mirror"
+///     2、运行命令 mgit sync <path>，foobar-1 的 remote 名为 upstream，foobar-2 的为 mirror
+///     3、get_remote_ref 使用对应的 remote 名
+///     4、运行命令 mgit new-tag <path> --tag v1.0 --push，tag 推送到对应的 remote
+///
+/// 测试目录结构:
+///   test_new_tag_remote_name
+///     ├─foobar-1 (.git)
+///     ├─foobar-2 (.git)
+///     └─remote.git
+#[test]
+fn cli_new_tag_remote_name() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_new_tag_remote_name");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar-1", &remote, None, None, None)
+        .join_repo("foobar-2", &remote, None, None, None)
+        .build();
+    let toml_string = format!(
+        "default-remote = \"upstream\"\n{}remote-name = \"mirror\"\n",
+        toml_string.trim_start()
+    );
+    let config_file = path.join(".gitrepos");
+    std::fs::write(config_file, toml_string).expect(failed_message::WRITE_FILE);
+
+    ops::sync_repo(
+        SyncOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        TestProgress,
+    )?;
+
+    for (local, remote_name) in [("foobar-1", "upstream"), ("foobar-2", "mirror")] {
+        let repo_path = path.join(local);
+        let remotes = exec_cmd(&repo_path, "git", &["remote"])?;
+        assert_eq!(remotes.trim(), remote_name);
+
+        let toml_repo = TomlRepo {
+            local: Some(local.to_string()),
+            remote: Some(remote.clone()),
+            branch: Some(DEFAULT_BRANCH.to_string()),
+            remote_name: Some(remote_name.to_string()),
+            ..Default::default()
+        };
+        let remote_ref = toml_repo.get_remote_ref(&repo_path)?;
+        assert_eq!(
+            remote_ref.to_string(),
+            format!("{}/{}", remote_name, DEFAULT_BRANCH)
+        );
+    }
+
+    ops::new_tag(NewTagOptions::new(
+        Some(input_path),
+        None::<PathBuf>,
+        "v1.0".to_string(),
+        true,
+        None,
+    ))?;
+    let remote_tags = exec_cmd(path.join("remote.git"), "git", &["tag", "-l", "v1.0"])?;
+    assert_eq!(remote_tags.trim(), "v1.0");
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
+
+/// 测试内容：
+///     1、运行命令 mgit new-tag <path> --tag v1.0 --message "release 1.0" --push，创建并推送附注 tag
+///     2、提交新的 commit 后运行命令 mgit new-tag <path> --tag v1.0 --no-force --push，已有的 tag 不会被覆盖
+///     3、gpg 不可用时运行命令 mgit new-tag <path> --tag v2.0 --sign，报告签名失败
+///
+/// 测试目录结构:
+///   test_new_tag_annotated
+///     ├─foobar (.git)
+///     └─remote.git
+#[test]
+fn cli_new_tag_annotated() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_new_tag_annotated");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar", &remote, None, None, None)
+        .build();
+    let config_file = path.join(".gitrepos");
+    std::fs::write(config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    ops::sync_repo(
+        SyncOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        TestProgress,
+    )?;
+
+    let repo_path = path.join("foobar");
+    for (key, value) in [("user.name", "mgit"), ("user.email", "mgit@example.com")] {
+        exec_cmd(&repo_path, "git", &["config", key, value]).expect(failed_message::GIT_CONFIG);
+    }
+
+    let new_tag_options = |tag: &str, force: bool, sign: bool| {
+        let mut options = NewTagOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            tag.to_string(),
+            !sign,
+            None,
+        );
+        options.message = Some("release 1.0".to_string());
+        options.force = force;
+        options.sign = sign;
+        options
+    };
+    ops::new_tag(new_tag_options("v1.0", true, false))?;
+
+    let tag_type = exec_cmd(&repo_path, "git", &["cat-file", "-t", "v1.0"])?;
+    assert_eq!(tag_type.trim(), "tag");
+    let tag_message = exec_cmd(&repo_path, "git", &["tag", "-l", "-n1", "v1.0"])?;
+    assert!(tag_message.contains("release 1.0"));
+    let remote_tags = exec_cmd(&repo_path, "git", &["ls-remote", "--tags", "origin"])?;
+    assert!(remote_tags.contains("refs/tags/v1.0"));
+
+    // an existing release tag is kept
+    exec_cmd(
+        &repo_path,
+        "git",
+        &["commit", "--allow-empty", "-m", "after release"],
+    )
+    .expect(failed_message::GIT_COMMIT);
+    let tag_commit = exec_cmd(&repo_path, "git", &["rev-parse", "v1.0^{commit}"])?;
+    assert!(ops::new_tag(new_tag_options("v1.0", false, false)).is_err());
+    let kept_commit = exec_cmd(&repo_path, "git", &["rev-parse", "v1.0^{commit}"])?;
+    assert_eq!(kept_commit, tag_commit);
+
+    // signing fails without a working gpg
+    exec_cmd(
+        &repo_path,
+        "git",
+        &["config", "gpg.program", "mgit-missing-gpg"],
+    )
+    .expect(failed_message::GIT_CONFIG);
+    let err = ops::new_tag(new_tag_options("v2.0", true, true)).unwrap_err();
+    let Some(MgitError::OpsError { errors, .. }) = err.downcast_ref::<MgitError>() else {
+        panic!("unexpected error: {}", err);
+    };
+    assert!(errors.0[0].plain().contains("signing the tag failed"));
+    let tags = exec_cmd(&repo_path, "git", &["tag", "-l", "v2.0"])?;
+    assert!(tags.trim().is_empty());
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use mgit::ops;
+use mgit::ops::{OpenOptions, RepoUrl, SyncOptions};
+use mgit::utils::error::MgitResult;
+use std::{env, path::PathBuf};
+
+use crate::common::{
+    create_local_remote, exec_cmd, failed_message, TestProgress, TomlBuilder, DEFAULT_BRANCH,
+};
+
+mod common;
+
+/// 测试内容：
+///     1、同步后，将 foobar-1 的 origin 改为 github 的 ssh 地址
+///     2、运行命令 mgit open foobar-1 <path>，输出 origin 对应的网页地址
+///     3、运行命令 mgit open --all <path>，本地远端没有网页地址，原样输出
+///     4、未在配置文件中的仓库报错
+///
+/// 测试目录结构:
+///   test_open
+///     ├─foobar-1 (.git)
+///     ├─foobar-2 (.git)
+///     └─remote.git
+#[test]
+fn cli_open() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_open");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar-1", &remote, None, None, None)
+        .join_repo("foobar-2", &remote, None, None, None)
+        .build();
+    let config_file = path.join(".gitrepos");
+    std::fs::write(config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    ops::sync_repo(
+        SyncOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        TestProgress,
+    )?;
+
+    exec_cmd(
+        path.join("foobar-1"),
+        "git",
+        &[
+            "remote",
+            "set-url",
+            "origin",
+            "git@github.com:funny/mgit.git",
+        ],
+    )
+    .expect(failed_message::GIT_CONFIG);
+
+    let urls = ops::open_repo(OpenOptions::new(
+        Some(input_path),
+        None::<PathBuf>,
+        Some("foobar-1".to_string()),
+    ))?;
+    assert_eq!(
+        urls,
+        vec![RepoUrl {
+            local: "foobar-1".to_string(),
+            url: "https://github.com/funny/mgit".to_string(),
+        }]
+    );
+
+    let urls = ops::open_repo(OpenOptions::new(Some(input_path), None::<PathBuf>, None))?;
+    assert_eq!(urls.len(), 2);
+    assert_eq!(urls[1].local, "foobar-2");
+    assert_eq!(urls[1].url, remote);
+
+    let result = ops::open_repo(OpenOptions::new(
+        Some(input_path),
+        None::<PathBuf>,
+        Some("foobar-3".to_string()),
+    ));
+    assert!(result.is_err());
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
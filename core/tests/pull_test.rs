@@ -0,0 +1,120 @@
+use mgit::ops;
+use mgit::ops::{PullOptions, SyncOptions};
+use mgit::utils::error::MgitResult;
+use std::path::Path;
+use std::{env, path::PathBuf};
+
+use crate::common::{
+    create_local_remote, exec_cmd, failed_message, TestProgress, TomlBuilder, DEFAULT_BRANCH,
+};
+
+mod common;
+
+/// 测试内容：
+///     1、同步后，foobar-3 固定在 commit 上，remote.git 新增一次提交
+///     2、运行命令 mgit pull <path>，foobar-1 快进到新提交，foobar-3 被跳过
+///     3、foobar-1 和 remote.git 修改同一文件，运行命令 mgit pull <path> --rebase，
+///        foobar-1 冲突报错，foobar-2 仍然拉取成功
+///
+/// 测试目录结构:
+///   test_pull_simple
+///     ├─foobar-1 (.git)
+///     ├─foobar-2 (.git)
+///     ├─foobar-3 (.git)
+///     └─remote.git
+#[test]
+fn cli_pull_simple() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_pull_simple");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote_path = path.join("remote.git");
+    let remote = create_local_remote(&remote_path);
+    let commit = exec_cmd(&remote_path, "git", &["rev-parse", DEFAULT_BRANCH])?;
+    let commit = commit.trim();
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar-1", &remote, None, None, None)
+        .join_repo("foobar-2", &remote, None, None, None)
+        .join_repo("foobar-3", &remote, None, Some(commit), None)
+        .build();
+    let config_file = path.join(".gitrepos");
+    std::fs::write(config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    ops::sync_repo(
+        SyncOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        TestProgress,
+    )?;
+
+    // a new commit upstream, made through a clone of remote.git
+    let upstream_path = path.join("upstream");
+    exec_cmd(&path, "git", &["clone", &remote, "upstream"])?;
+    commit_file(&upstream_path, "upstream")?;
+    exec_cmd(&upstream_path, "git", &["push", "origin", DEFAULT_BRANCH])?;
+    let upstream_head = exec_cmd(&upstream_path, "git", &["rev-parse", "HEAD"])?;
+
+    let pull_options = |rebase: bool| {
+        let mut options = PullOptions::new(Some(input_path), None::<PathBuf>, None, None);
+        options.rebase = rebase;
+        options
+    };
+
+    let msg = ops::pull_repos(pull_options(false), TestProgress)?;
+    assert!(msg.to_plain_text().contains("foobar-3"));
+    let head = exec_cmd(path.join("foobar-1"), "git", &["rev-parse", "HEAD"])?;
+    assert_eq!(head, upstream_head);
+    let head = exec_cmd(path.join("foobar-3"), "git", &["rev-parse", "HEAD"])?;
+    assert_eq!(head.trim(), commit);
+
+    // conflicting changes on both sides, only foobar-1 fails
+    commit_file(&upstream_path, "upstream again")?;
+    exec_cmd(&upstream_path, "git", &["push", "origin", DEFAULT_BRANCH])?;
+    let upstream_head = exec_cmd(&upstream_path, "git", &["rev-parse", "HEAD"])?;
+    commit_file(&path.join("foobar-1"), "local")?;
+
+    let e = ops::pull_repos(pull_options(true), TestProgress).unwrap_err();
+    assert!(e.to_string().contains("foobar-1"));
+    assert!(!e.to_string().contains("foobar-2"));
+    let head = exec_cmd(path.join("foobar-2"), "git", &["rev-parse", "HEAD"])?;
+    assert_eq!(head, upstream_head);
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
+
+fn commit_file(path: &Path, content: &str) -> MgitResult<()> {
+    std::fs::write(path.join("1.txt"), content).expect(failed_message::WRITE_FILE);
+    exec_cmd(path, "git", &["add", "-A"])?;
+    exec_cmd(
+        path,
+        "git",
+        &[
+            "-c",
+            "user.name=mgit",
+            "-c",
+            "user.email=mgit@example.com",
+            "commit",
+            "-m",
+            content,
+        ],
+    )?;
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use mgit::ops;
+use mgit::ops::{PushOptions, SyncOptions};
+use mgit::utils::error::MgitResult;
+use std::{env, path::PathBuf};
+
+use crate::common::{
+    create_local_remote, exec_cmd, failed_message, TestProgress, TomlBuilder, DEFAULT_BRANCH,
+};
+
+mod common;
+
+/// 测试内容：
+///     1、同步后，foobar-1 本地提交一次，foobar-3 固定在 commit 上（没有上游分支）
+///     2、运行命令 mgit push <path> --dry-run，remote.git 不变
+///     3、运行命令 mgit push <path>，foobar-1 的提交推送到 remote.git，foobar-3 被跳过
+///     4、foobar-1 回退一次提交，运行命令 mgit push <path> --force-with-lease，remote.git 随之回退
+///
+/// 测试目录结构:
+///   test_push_simple
+///     ├─foobar-1 (.git)
+///     ├─foobar-2 (.git)
+///     ├─foobar-3 (.git)
+///     ├─remote.git
+///     └─remote-2.git
+#[test]
+fn cli_push_simple() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_push_simple");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote_path = path.join("remote.git");
+    let remote = create_local_remote(&remote_path);
+    let remote_2 = create_local_remote(path.join("remote-2.git"));
+    let commit = exec_cmd(&remote_path, "git", &["rev-parse", DEFAULT_BRANCH])?;
+    let commit = commit.trim();
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar-1", &remote, None, None, None)
+        .join_repo("foobar-2", &remote_2, None, None, None)
+        .join_repo("foobar-3", &remote, None, Some(commit), None)
+        .build();
+    let config_file = path.join(".gitrepos");
+    std::fs::write(config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    ops::sync_repo(
+        SyncOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        TestProgress,
+    )?;
+
+    let repo_path = path.join("foobar-1");
+    exec_cmd(
+        &repo_path,
+        "git",
+        &[
+            "-c",
+            "user.name=mgit",
+            "-c",
+            "user.email=mgit@example.com",
+            "commit",
+            "--allow-empty",
+            "-m",
+            "local",
+        ],
+    )?;
+    let local_head = exec_cmd(&repo_path, "git", &["rev-parse", "HEAD"])?;
+
+    let push_options = |force_with_lease: bool, dry_run: bool| {
+        let mut options = PushOptions::new(Some(input_path), None::<PathBuf>, None, None);
+        options.force_with_lease = force_with_lease;
+        options.dry_run = dry_run;
+        options
+    };
+    let remote_head = || exec_cmd(&remote_path, "git", &["rev-parse", DEFAULT_BRANCH]);
+
+    ops::push_repos(push_options(false, true), TestProgress)?;
+    assert_eq!(remote_head()?.trim(), commit);
+
+    let msg = ops::push_repos(push_options(false, false), TestProgress)?;
+    assert_eq!(remote_head()?, local_head);
+    assert!(msg.to_plain_text().contains("foobar-3"));
+    assert!(!msg.to_plain_text().contains("foobar-2"));
+
+    // rewriting the pushed commit needs the lease
+    exec_cmd(&repo_path, "git", &["reset", "--hard", "HEAD~1"])?;
+    assert!(ops::push_repos(push_options(false, false), TestProgress).is_err());
+    ops::push_repos(push_options(true, false), TestProgress)?;
+    assert_eq!(remote_head()?.trim(), commit);
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
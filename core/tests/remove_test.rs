@@ -0,0 +1,138 @@
+use mgit::ops;
+use mgit::ops::{RemoveOptions, SyncOptions};
+use mgit::utils::error::{MgitError, MgitResult};
+use std::{env, path::PathBuf};
+
+use crate::common::{create_local_remote, exec_cmd, failed_message, TestProgress, DEFAULT_BRANCH};
+
+mod common;
+
+/// 测试内容：
+///     1、运行命令 mgit remove foobar-4，仓库不在配置文件中，报错且不做修改
+///     2、foobar-2 有未提交的改动，运行命令 mgit remove foobar-1 foobar-2 --delete，
+///        报错且配置文件和目录都不变
+///     3、运行命令 mgit remove foobar-2，只修改配置文件，注释保留，目录不删除
+///     4、运行命令 mgit remove ./foobar-1 --delete，配置和目录都被删除
+///     5、foobar-3 有未推送的提交或 stash 时，运行命令 mgit remove foobar-3 --delete，报错且不做修改
+///
+/// 测试目录结构:
+///   test_remove
+///     ├─foobar-1 (.git)
+///     ├─foobar-2 (.git)
+///     ├─foobar-3 (.git)
+///     └─remote.git
+#[test]
+fn cli_remove() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_remove");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+
+    let toml_string = format!(
+        r#"# the workspace of the team
+default-branch = "{branch}"
+
+[[repos]]
+local = "foobar-1"
+remote = "{remote}"
+
+[[repos]]
+local = "foobar-2"
+remote = "{remote}"
+
+# keep this one
+[[repos]]
+local = "foobar-3"
+remote = "{remote}"
+"#,
+        branch = DEFAULT_BRANCH,
+        remote = remote.replace('\\', "/"),
+    );
+    let config_file = path.join(".gitrepos");
+    std::fs::write(&config_file, &toml_string).expect(failed_message::WRITE_FILE);
+
+    ops::sync_repo(
+        SyncOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        TestProgress,
+    )?;
+
+    let remove_options = |locals: &[&str], delete: bool| {
+        let locals = locals.iter().map(|local| local.to_string()).collect();
+        RemoveOptions::new(Some(input_path), None::<PathBuf>, locals, delete)
+    };
+
+    let err = ops::remove_repos(remove_options(&["foobar-4"], false)).unwrap_err();
+    assert!(err.to_string().contains("not found"));
+    assert!(err.to_string().contains("foobar-4"));
+    assert_eq!(std::fs::read_to_string(&config_file)?, toml_string);
+
+    std::fs::write(path.join("foobar-2").join("1.txt"), "changed")
+        .expect(failed_message::WRITE_FILE);
+    let err = ops::remove_repos(remove_options(&["foobar-1", "foobar-2"], true)).unwrap_err();
+    let Some(MgitError::OpsError { errors, .. }) = err.downcast_ref::<MgitError>() else {
+        panic!("unexpected error: {}", err);
+    };
+    assert_eq!(errors.0.len(), 1);
+    let error = errors.0[0].plain();
+    assert!(error.starts_with("foobar-2"));
+    assert!(error.contains("uncommitted changes"));
+    assert_eq!(std::fs::read_to_string(&config_file)?, toml_string);
+    assert!(path.join("foobar-1").is_dir());
+
+    ops::remove_repos(remove_options(&["foobar-2"], false))?;
+    let content = std::fs::read_to_string(&config_file)?;
+    assert!(!content.contains("foobar-2"));
+    assert!(content.contains("# the workspace of the team"));
+    assert!(content.contains("# keep this one"));
+    assert!(path.join("foobar-2").is_dir());
+
+    ops::remove_repos(remove_options(&["./foobar-1"], true))?;
+    let content = std::fs::read_to_string(&config_file)?;
+    assert!(!content.contains("foobar-1"));
+    assert!(content.contains("foobar-3"));
+    assert!(!path.join("foobar-1").exists());
+    assert!(path.join("foobar-3").is_dir());
+
+    let foobar_3 = path.join("foobar-3");
+    let git_args = ["-c", "user.name=mgit", "-c", "user.email=mgit@example.com"];
+    let commit_args = [&git_args[..], &["commit", "--allow-empty", "-m", "local"]].concat();
+    exec_cmd(&foobar_3, "git", &commit_args).expect(failed_message::GIT_COMMIT);
+    let err = ops::remove_repos(remove_options(&["foobar-3"], true)).unwrap_err();
+    let Some(MgitError::OpsError { errors, .. }) = err.downcast_ref::<MgitError>() else {
+        panic!("unexpected error: {}", err);
+    };
+    assert!(errors.0[0].plain().contains("on no remote"));
+
+    exec_cmd(&foobar_3, "git", &["reset", "--hard", "HEAD~1"]).expect(failed_message::GIT_RESET);
+    std::fs::write(foobar_3.join("1.txt"), "changed").expect(failed_message::WRITE_FILE);
+    let stash_args = [&git_args[..], &["stash"]].concat();
+    exec_cmd(&foobar_3, "git", &stash_args).expect("git stash failed");
+    let err = ops::remove_repos(remove_options(&["foobar-3"], true)).unwrap_err();
+    let Some(MgitError::OpsError { errors, .. }) = err.downcast_ref::<MgitError>() else {
+        panic!("unexpected error: {}", err);
+    };
+    assert!(errors.0[0].plain().contains("stash"));
+    assert!(foobar_3.is_dir());
+    assert!(std::fs::read_to_string(&config_file)?.contains("foobar-3"));
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use mgit::ops;
+use mgit::ops::{RepairOptions, SyncOptions};
+use mgit::utils::error::MgitResult;
+use std::{env, path::PathBuf};
+
+use crate::common::{
+    create_local_remote, exec_cmd, failed_message, TestProgress, TomlBuilder, DEFAULT_BRANCH,
+};
+
+mod common;
+
+/// 测试内容：
+///     1、同步后，foobar-1 切换到其他分支，foobar-2 处于游离状态，foobar-3 删除了本地分支
+///     2、运行命令 mgit repair <path>，所有仓库切换回配置文件指定的分支
+///     3、其他分支上的提交被保留
+///
+/// 测试目录结构:
+///   test_repair
+///     ├─foobar-1 (.git)
+///     ├─foobar-2 (.git)
+///     └─foobar-3 (.git)
+#[test]
+fn cli_repair() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_repair");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar-1", &remote, None, None, None)
+        .join_repo("foobar-2", &remote, None, None, None)
+        .join_repo("foobar-3", &remote, None, None, None)
+        .build();
+    let config_file = path.join(".gitrepos");
+    std::fs::write(config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    ops::sync_repo(
+        SyncOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        TestProgress,
+    )?;
+
+    // commit on another branch
+    let repo_path = path.join("foobar-1");
+    exec_cmd(&repo_path, "git", &["checkout", "-b", "feature"])
+        .expect(failed_message::GIT_CHECKOUT);
+    std::fs::write(repo_path.join("2.txt"), "2.txt").expect(failed_message::WRITE_FILE);
+    exec_cmd(&repo_path, "git", &["add", "."]).expect(failed_message::GIT_STAGE);
+    exec_cmd(
+        &repo_path,
+        "git",
+        &[
+            "-c",
+            "user.name=mgit",
+            "-c",
+            "user.email=mgit@example.com",
+            "commit",
+            "-m",
+            "feature",
+        ],
+    )
+    .expect(failed_message::GIT_COMMIT);
+
+    // detached HEAD
+    exec_cmd(path.join("foobar-2"), "git", &["checkout", "--detach"])
+        .expect(failed_message::GIT_CHECKOUT);
+
+    // local branch deleted
+    let repo_path = path.join("foobar-3");
+    exec_cmd(&repo_path, "git", &["checkout", "-b", "feature"])
+        .expect(failed_message::GIT_CHECKOUT);
+    exec_cmd(&repo_path, "git", &["branch", "-D", DEFAULT_BRANCH])
+        .expect(failed_message::GIT_BRANCH);
+
+    ops::repair_repos(RepairOptions::new(Some(input_path), None::<PathBuf>, None))?;
+
+    for repo in ["foobar-1", "foobar-2", "foobar-3"] {
+        let branch = exec_cmd(path.join(repo), "git", &["branch", "--show-current"])?;
+        assert_eq!(branch.trim(), DEFAULT_BRANCH);
+    }
+    let feature_log = exec_cmd(
+        path.join("foobar-1"),
+        "git",
+        &["log", "-1", "--format=%s", "feature"],
+    )?;
+    assert_eq!(feature_log.trim(), "feature");
+    assert!(!path.join("foobar-1/2.txt").exists());
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use mgit::core::repos::TomlConfig;
 use mgit::ops;
 use mgit::ops::{InitOptions, SnapshotOptions, SnapshotType};
 use mgit::utils::cmd::retry;
@@ -6,7 +7,9 @@ use std::env;
 use std::path::PathBuf;
 use std::time::Duration;
 
-use crate::common::{exec_cmd, failed_message, TomlBuilder, CSBOOKS_REPO};
+use crate::common::{
+    create_local_remote, exec_cmd, failed_message, TestProgress, TomlBuilder, CSBOOKS_REPO,
+};
 
 mod common;
 
@@ -33,7 +36,7 @@ fn cli_init_simple() -> MgitResult<()> {
 
     let input_path = path.clone().into_os_string().into_string().unwrap();
     // execute cli init function with path
-    ops::init_repo(InitOptions::new(Some(path.clone()), None))?;
+    ops::init_repo(InitOptions::new(Some(path.clone()), None), TestProgress)?;
 
     // get content from .gitrepos
     let real_result = std::fs::read_to_string(input_path + "/.gitrepos").unwrap();
@@ -73,7 +76,10 @@ fn cli_init_force1() -> MgitResult<()> {
 
     let input_path = path.clone().into_os_string().into_string().unwrap();
     // execute cli init function with path
-    ops::init_repo(InitOptions::new(Some(path.clone()), Some(true)))?;
+    ops::init_repo(
+        InitOptions::new(Some(path.clone()), Some(true)),
+        TestProgress,
+    )?;
 
     // get content from .gitrepos
     let real_result = std::fs::read_to_string(input_path + "/.gitrepos").unwrap();
@@ -119,7 +125,7 @@ fn cli_init_force2() -> MgitResult<()> {
 
     let input_path = path.clone().into_os_string().into_string().unwrap();
     // execute cli init function with path
-    ops::init_repo(InitOptions::new(Some(path.clone()), None))?;
+    ops::init_repo(InitOptions::new(Some(path.clone()), None), TestProgress)?;
 
     // get content from .gitrepos
     let real_result = std::fs::read_to_string(input_path + "/.gitrepos").unwrap();
@@ -182,13 +188,10 @@ fn cli_snapshot_simple() -> MgitResult<()> {
 
     let input_path = path.clone().into_os_string().into_string().unwrap();
     // execute cli init function with path
-    ops::snapshot_repo(SnapshotOptions::new(
-        Some(path.clone()),
-        None::<PathBuf>,
-        None,
-        None,
-        None,
-    ))?;
+    ops::snapshot_repo(
+        SnapshotOptions::new(Some(path.clone()), None::<PathBuf>, None, None, None),
+        TestProgress,
+    )?;
 
     // get content from .gitrepos
     let real_result = std::fs::read_to_string(input_path + "/.gitrepos").unwrap();
@@ -234,13 +237,16 @@ fn cli_snapshot_branch() -> MgitResult<()> {
 
     let input_path = path.clone().into_os_string().into_string().unwrap();
     // execute cli init function with path
-    ops::snapshot_repo(SnapshotOptions::new(
-        Some(path.clone()),
-        None::<PathBuf>,
-        None,
-        Some(SnapshotType::Branch),
-        None,
-    ))?;
+    ops::snapshot_repo(
+        SnapshotOptions::new(
+            Some(path.clone()),
+            None::<PathBuf>,
+            None,
+            Some(SnapshotType::Branch),
+            None,
+        ),
+        TestProgress,
+    )?;
 
     // get content from .gitrepos
     let real_result = std::fs::read_to_string(input_path + "/.gitrepos").unwrap();
@@ -276,13 +282,16 @@ fn cli_snapshot_force() -> MgitResult<()> {
     let input_path = path.clone().into_os_string().into_string().unwrap();
     let config_file = input_path.clone() + "/.gitrepos";
     // execute cli init function with path
-    ops::snapshot_repo(SnapshotOptions::new(
-        Some(path.clone()),
-        Some(config_file.clone()),
-        Some(true),
-        None,
-        None,
-    ))?;
+    ops::snapshot_repo(
+        SnapshotOptions::new(
+            Some(path.clone()),
+            Some(config_file.clone()),
+            Some(true),
+            None,
+            None,
+        ),
+        TestProgress,
+    )?;
 
     // get content from .gitrepos
     let real_result = std::fs::read_to_string(config_file).unwrap();
@@ -365,18 +374,21 @@ fn cli_snapshot_ignore() -> MgitResult<()> {
     let input_path = path.clone().into_os_string().into_string().unwrap();
     let config_file = input_path.clone() + "/.gitrepos";
     // execute cli init function with path
-    ops::snapshot_repo(SnapshotOptions::new(
-        Some(path.clone()),
-        Some(config_file.clone()),
-        Some(true),
-        None,
-        Some(vec![
-            ".".to_string(),
-            "foobar-1/foobar-1-2".to_string(),
-            "foobar-2".to_string(),
-            "foobar-2/foobar-2-2".to_string(),
-        ]),
-    ))?;
+    ops::snapshot_repo(
+        SnapshotOptions::new(
+            Some(path.clone()),
+            Some(config_file.clone()),
+            Some(true),
+            None,
+            Some(vec![
+                ".".to_string(),
+                "foobar-1/foobar-1-2".to_string(),
+                "foobar-2".to_string(),
+                "foobar-2/foobar-2-2".to_string(),
+            ]),
+        ),
+        TestProgress,
+    )?;
 
     // get content from .gitrepos
     let real_result = std::fs::read_to_string(config_file).unwrap();
@@ -412,6 +424,229 @@ fn cli_snapshot_ignore() -> MgitResult<()> {
     Ok(())
 }
 
+/// 测试内容：
+///     1、运行命令 mgit init <path>，默认跳过隐藏目录下的仓库
+///     2、运行命令 mgit init <path> --scan-hidden，抓取隐藏目录下的仓库
+///     3、隐藏仓库的 local 路径经过序列化后可以被正确加载
+///
+/// 测试目录结构:
+///   test_snapshot_init_scan_hidden
+///     ├─.config
+///     │  └─foo (.git)
+///     ├─foobar (.git)
+///     └─remote.git
+#[test]
+fn cli_init_scan_hidden() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_snapshot_init_scan_hidden");
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(path.join(".config")).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+    exec_cmd(&path, "git", &["clone", &remote, "foobar"]).expect("git clone failed");
+    exec_cmd(path.join(".config"), "git", &["clone", &remote, "foo"]).expect("git clone failed");
+    let config_file = path.join(".gitrepos");
+
+    // hidden directories are skipped by default
+    ops::init_repo(InitOptions::new(Some(path.clone()), None), TestProgress)?;
+    let toml_config = TomlConfig::load(&config_file).unwrap();
+    let locals = toml_config
+        .repos
+        .unwrap()
+        .into_iter()
+        .filter_map(|repo| repo.local)
+        .collect::<Vec<_>>();
+    assert_eq!(locals, vec!["foobar"]);
+
+    // scan hidden directories on demand
+    let mut options = InitOptions::new(Some(path.clone()), None);
+    options.scan_hidden = true;
+    ops::init_repo(options, TestProgress)?;
+    let toml_config = TomlConfig::load(&config_file).unwrap();
+    let locals = toml_config
+        .repos
+        .unwrap()
+        .into_iter()
+        .filter_map(|repo| repo.local)
+        .collect::<Vec<_>>();
+    assert_eq!(locals, vec![".config/foo", "foobar"]);
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
+
+/// 测试内容：
+///     1、配置文件中有注释和自定义字段，本地仓库提交了新的 commit
+///     2、运行命令 mgit snapshot --amend，只更新已有仓库的 commit，注释和其他字段保持不变
+///     3、磁盘上有但配置文件中没有的仓库不会被添加
+///     4、运行命令 mgit snapshot --amend --add-new，新仓库被追加到配置文件
+///
+/// 测试目录结构:
+///   test_snapshot_amend(.gitrepos)
+///     ├─foo (.git)
+///     ├─bar (.git)
+///     └─remote.git
+#[test]
+fn cli_snapshot_amend() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_snapshot_amend");
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+    exec_cmd(&path, "git", &["clone", &remote, "foo"]).expect("git clone failed");
+    exec_cmd(&path, "git", &["clone", &remote, "bar"]).expect("git clone failed");
+
+    let foo_path = path.join("foo");
+    let old_commit = exec_cmd(&foo_path, "git", &["rev-parse", "HEAD"])?;
+    let config_file = path.join(".gitrepos");
+    let toml_string = format!(
+        r#"# keep this comment
+default-branch = "master"
+
+[[repos]]
+local = "foo"
+remote = "{remote}"
+branch = "master"
+commit = "{commit}"
+groups = ["core"]
+"#,
+        remote = remote,
+        commit = old_commit.trim()
+    );
+    std::fs::write(&config_file, &toml_string).expect(failed_message::WRITE_FILE);
+
+    exec_cmd(
+        &foo_path,
+        "git",
+        &[
+            "-c",
+            "user.name=mgit",
+            "-c",
+            "user.email=mgit@example.com",
+            "commit",
+            "--allow-empty",
+            "-m",
+            "new commit",
+        ],
+    )
+    .expect(failed_message::GIT_COMMIT);
+    let new_commit = exec_cmd(&foo_path, "git", &["rev-parse", "HEAD"])?;
+
+    let amend_options = |add_new: bool| {
+        let mut options =
+            SnapshotOptions::new(Some(path.clone()), None::<PathBuf>, None, None, None);
+        options.amend = true;
+        options.add_new = add_new;
+        options
+    };
+    let msg = ops::snapshot_repo(amend_options(false), TestProgress)?;
+    assert!(msg.plain().contains("bar"));
+
+    let expected = toml_string.replace(old_commit.trim(), new_commit.trim());
+    assert_eq!(std::fs::read_to_string(&config_file).unwrap(), expected);
+
+    ops::snapshot_repo(amend_options(true), TestProgress)?;
+    let content = std::fs::read_to_string(&config_file).unwrap();
+    assert!(content.starts_with(&expected));
+    let toml_config = TomlConfig::load(&config_file).unwrap();
+    let repos = toml_config.repos.unwrap();
+    assert_eq!(repos.len(), 2);
+    let bar = repos
+        .iter()
+        .find(|repo| repo.local.as_deref() == Some("bar"))
+        .unwrap();
+    assert_eq!(bar.remote.as_deref(), Some(remote.as_str()));
+    assert_eq!(bar.commit.as_deref(), Some(old_commit.trim()));
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
+
+/// 测试内容：
+///     1、已有的配置文件中有注释，本地仓库提交了新的 commit
+///     2、运行命令 mgit snapshot <path> --force，只更新变化的 commit，注释和字段顺序保持不变
+///     3、新仓库 bar 被追加到末尾，不会重新生成文件头
+///
+/// 测试目录结构:
+///   test_snapshot_keep_comments(.gitrepos)
+///     ├─foo (.git)
+///     ├─bar (.git)
+///     └─remote.git
+#[test]
+fn cli_snapshot_keep_comments() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_snapshot_keep_comments");
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+    exec_cmd(&path, "git", &["clone", &remote, "foo"]).expect("git clone failed");
+
+    let foo_path = path.join("foo");
+    let old_commit = exec_cmd(&foo_path, "git", &["rev-parse", "HEAD"])?;
+    let config_file = path.join(".gitrepos");
+    let toml_string = format!(
+        r#"# our workspace
+default-branch = "develop"
+
+# the core library
+[[repos]]
+local = "foo"
+remote = "{remote}"
+commit = "{commit}" # pinned for the release
+"#,
+        remote = remote,
+        commit = old_commit.trim()
+    );
+    std::fs::write(&config_file, &toml_string).expect(failed_message::WRITE_FILE);
+
+    exec_cmd(
+        &foo_path,
+        "git",
+        &[
+            "-c",
+            "user.name=mgit",
+            "-c",
+            "user.email=mgit@example.com",
+            "commit",
+            "--allow-empty",
+            "-m",
+            "new commit",
+        ],
+    )
+    .expect(failed_message::GIT_COMMIT);
+    let new_commit = exec_cmd(&foo_path, "git", &["rev-parse", "HEAD"])?;
+    exec_cmd(&path, "git", &["clone", &remote, "bar"]).expect("git clone failed");
+
+    ops::snapshot_repo(
+        SnapshotOptions::new(Some(path.clone()), None::<PathBuf>, Some(true), None, None),
+        TestProgress,
+    )?;
+
+    let content = std::fs::read_to_string(&config_file).unwrap();
+    let expected = toml_string.replace(old_commit.trim(), new_commit.trim());
+    assert!(content.starts_with(&expected), "{}", content);
+    // the new repo is appended after the existing ones
+    assert!(content[expected.len()..].contains("local = \"bar\""));
+    assert!(!content.contains("@generated"));
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
+
 pub fn create_repos_tree1(path: &PathBuf) {
     if path.exists() {
         std::fs::remove_dir_all(path).unwrap();
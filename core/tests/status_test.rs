@@ -0,0 +1,490 @@
+use mgit::core::repo::cmp_upstream_manifest;
+use mgit::core::repos::TomlConfig;
+use mgit::ops;
+use mgit::ops::{StatusOptions, SyncOptions};
+use mgit::utils::error::MgitResult;
+use std::{env, path::PathBuf};
+
+use crate::common::{
+    create_local_remote, exec_cmd, failed_message, TestProgress, TomlBuilder, DEFAULT_BRANCH,
+};
+
+mod common;
+
+/// 测试内容：
+///     1、同步仓库后，远端新增一个提交
+///     2、运行命令 mgit status <path>，不会更新远端引用
+///     3、运行命令 mgit status <path> --fetch，远端引用更新到最新提交
+///     4、工作区保持不变
+///
+/// 测试目录结构:
+///   test_status_fetch
+///     ├─foobar (.git)
+///     ├─remote.git
+///     └─upstream (.git)
+#[test]
+fn cli_status_fetch() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_status_fetch");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar", &remote, None, None, None)
+        .build();
+    let config_file = path.join(".gitrepos");
+    std::fs::write(config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    ops::sync_repo(
+        SyncOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        TestProgress,
+    )?;
+
+    // push a new commit to remote
+    let upstream_path = path.join("upstream");
+    exec_cmd(&path, "git", &["clone", &remote, "upstream"]).expect("git clone failed");
+    std::fs::write(upstream_path.join("2.txt"), "2.txt").expect(failed_message::WRITE_FILE);
+    exec_cmd(&upstream_path, "git", &["add", "."]).expect(failed_message::GIT_STAGE);
+    exec_cmd(
+        &upstream_path,
+        "git",
+        &[
+            "-c",
+            "user.name=mgit",
+            "-c",
+            "user.email=mgit@example.com",
+            "commit",
+            "-m",
+            "second",
+        ],
+    )
+    .expect(failed_message::GIT_COMMIT);
+    exec_cmd(&upstream_path, "git", &["push"]).expect("git push failed");
+    let upstream_head = exec_cmd(&upstream_path, "git", &["rev-parse", "HEAD"])?;
+
+    let repo_path = path.join("foobar");
+    let local_head = exec_cmd(&repo_path, "git", &["rev-parse", "HEAD"])?;
+    let remote_ref = format!("origin/{}", DEFAULT_BRANCH);
+
+    // status without fetch keeps stale remote refs
+    ops::status_repos(
+        StatusOptions::new(Some(input_path), None::<PathBuf>, None, None, None),
+        TestProgress,
+    )?;
+    let origin_head = exec_cmd(&repo_path, "git", &["rev-parse", &remote_ref])?;
+    assert_eq!(origin_head, local_head);
+
+    // status with fetch updates remote refs only
+    ops::status_repos(
+        StatusOptions::new(Some(input_path), None::<PathBuf>, None, Some(true), None),
+        TestProgress,
+    )?;
+    let origin_head = exec_cmd(&repo_path, "git", &["rev-parse", &remote_ref])?;
+    assert_eq!(origin_head, upstream_head);
+    assert_eq!(
+        exec_cmd(&repo_path, "git", &["rev-parse", "HEAD"])?,
+        local_head
+    );
+    assert!(!repo_path.join("2.txt").exists());
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
+
+/// 测试内容：
+///     1、同步 foobar-1 和 foobar-2 后，远端推送新的提交，foobar-2 有一个本地改动
+///     2、调用 ops::status，foobar-1 与配置的 ref 一致
+///     3、调用 ops::status 并 fetch，foobar-1 落后 1 个提交，foobar-2 落后 1 个提交且有 1 个改动
+///
+/// 测试目录结构:
+///   test_status_structured
+///     ├─foobar-1 (.git)
+///     ├─foobar-2 (.git)
+///     ├─remote.git
+///     └─upstream (.git)
+#[test]
+fn cli_status_structured() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_status_structured");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar-1", &remote, None, None, None)
+        .join_repo("foobar-2", &remote, None, None, None)
+        .build();
+    let config_file = path.join(".gitrepos");
+    std::fs::write(config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    ops::sync_repo(
+        SyncOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        TestProgress,
+    )?;
+
+    let statuses = ops::status(StatusOptions::new(
+        Some(input_path),
+        None::<PathBuf>,
+        None,
+        None,
+        None,
+    ))?;
+    assert_eq!(statuses.len(), 2);
+    assert_eq!(statuses[0].local, "foobar-1");
+    assert_eq!(statuses[0].branch, DEFAULT_BRANCH);
+    assert_eq!((statuses[0].ahead, statuses[0].behind), (Some(0), Some(0)));
+    assert_eq!(statuses[0].changes, 0);
+    assert!(statuses[0].matches_ref);
+
+    // push a new commit to remote
+    let upstream_path = path.join("upstream");
+    exec_cmd(&path, "git", &["clone", &remote, "upstream"]).expect("git clone failed");
+    std::fs::write(upstream_path.join("2.txt"), "2.txt").expect(failed_message::WRITE_FILE);
+    exec_cmd(&upstream_path, "git", &["add", "."]).expect(failed_message::GIT_STAGE);
+    exec_cmd(
+        &upstream_path,
+        "git",
+        &[
+            "-c",
+            "user.name=mgit",
+            "-c",
+            "user.email=mgit@example.com",
+            "commit",
+            "-m",
+            "second",
+        ],
+    )
+    .expect(failed_message::GIT_COMMIT);
+    exec_cmd(&upstream_path, "git", &["push"]).expect("git push failed");
+
+    std::fs::write(path.join("foobar-2").join("1.txt"), "changed")
+        .expect(failed_message::WRITE_FILE);
+
+    let statuses = ops::status(StatusOptions::new(
+        Some(input_path),
+        None::<PathBuf>,
+        None,
+        Some(true),
+        None,
+    ))?;
+    assert_eq!((statuses[0].ahead, statuses[0].behind), (Some(0), Some(1)));
+    assert!(!statuses[0].matches_ref);
+    assert_eq!(statuses[1].local, "foobar-2");
+    assert_eq!((statuses[1].ahead, statuses[1].behind), (Some(0), Some(1)));
+    assert_eq!(statuses[1].changes, 1);
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
+
+/// 测试内容：
+///     1、同步仓库后本地新增一个提交，远端新增 dev 分支，比 master 多两个提交
+///     2、配置文件改为 dev 分支，运行命令 mgit status <path> --fetch
+///     3、上游分支仍为 origin/master：领先 1 落后 0；配置文件的 dev：领先 1 落后 2
+///
+/// 测试目录结构:
+///   test_status_upstream
+///     ├─foobar (.git)
+///     ├─remote.git
+///     └─upstream (.git)
+#[test]
+fn cli_status_upstream() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_status_upstream");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+
+    let config_file = path.join(".gitrepos");
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar", &remote, None, None, None)
+        .build();
+    std::fs::write(&config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    ops::sync_repo(
+        SyncOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        TestProgress,
+    )?;
+
+    let commit = |repo_path: &PathBuf, file: &str| {
+        std::fs::write(repo_path.join(file), file).expect(failed_message::WRITE_FILE);
+        exec_cmd(repo_path, "git", &["add", "."]).expect(failed_message::GIT_STAGE);
+        exec_cmd(
+            repo_path,
+            "git",
+            &[
+                "-c",
+                "user.name=mgit",
+                "-c",
+                "user.email=mgit@example.com",
+                "commit",
+                "-m",
+                file,
+            ],
+        )
+        .expect(failed_message::GIT_COMMIT);
+    };
+    commit(&path.join("foobar"), "local.txt");
+
+    let upstream_path = path.join("upstream");
+    exec_cmd(&path, "git", &["clone", &remote, "upstream"]).expect("git clone failed");
+    commit(&upstream_path, "2.txt");
+    commit(&upstream_path, "3.txt");
+    exec_cmd(&upstream_path, "git", &["push", "origin", "HEAD:dev"]).expect("git push failed");
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar", &remote, Some("dev"), None, None)
+        .build();
+    std::fs::write(&config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    let statuses = ops::status(StatusOptions::new(
+        Some(input_path),
+        None::<PathBuf>,
+        None,
+        Some(true),
+        None,
+    ))?;
+    let status = &statuses[0];
+    assert_eq!(status.upstream.as_deref(), Some("origin/master"));
+    assert_eq!(
+        (status.upstream_ahead, status.upstream_behind),
+        (Some(1), Some(0))
+    );
+    assert_eq!((status.ahead, status.behind), (Some(1), Some(2)));
+
+    let toml_repo = TomlConfig::load(&config_file).unwrap().repos.unwrap()[0].clone();
+    let desc = cmp_upstream_manifest(input_path, &toml_repo, &None)?;
+    assert_eq!(desc.plain(), "↑1↓0 vs upstream, ↑1↓2 vs manifest");
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
+
+/// 测试内容：
+///     1、同步仓库后，暂存一个新文件，修改一个已跟踪文件，新增一个未跟踪文件
+///     2、调用 ops::status_files，分别列出 staged、changed、untracked 文件
+///     3、调用 ops::status_files_to_writer，输出可解析的 JSON 数组
+///
+/// 测试目录结构:
+///   test_status_files
+///     ├─foobar (.git)
+///     └─remote.git
+#[test]
+fn cli_status_files() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_status_files");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar", &remote, None, None, None)
+        .build();
+    std::fs::write(path.join(".gitrepos"), toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    ops::sync_repo(
+        SyncOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        TestProgress,
+    )?;
+
+    let repo_path = path.join("foobar");
+    std::fs::write(repo_path.join("staged.txt"), "staged").expect(failed_message::WRITE_FILE);
+    exec_cmd(&repo_path, "git", &["add", "staged.txt"]).expect(failed_message::GIT_STAGE);
+    std::fs::write(repo_path.join("foo").join("1.txt"), "changed")
+        .expect(failed_message::WRITE_FILE);
+    std::fs::write(repo_path.join("new.txt"), "new").expect(failed_message::WRITE_FILE);
+
+    let options = || StatusOptions::new(Some(input_path), None::<PathBuf>, None, None, None);
+    let statuses = ops::status_files(options())?;
+    assert_eq!(statuses.len(), 1);
+    let status = &statuses[0];
+    assert_eq!(status.local, "foobar");
+    assert_eq!(status.branch, DEFAULT_BRANCH);
+    assert_eq!((status.ahead, status.behind), (Some(0), Some(0)));
+    assert_eq!(status.staged, vec!["staged.txt"]);
+    assert_eq!(status.changed, vec!["foo/1.txt"]);
+    assert_eq!(status.untracked, vec!["new.txt"]);
+    assert!(status.matches_manifest);
+
+    let mut output = Vec::new();
+    assert_eq!(ops::status_files_to_writer(options(), &mut output)?, 1);
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json[0]["local"], "foobar");
+    assert_eq!(json[0]["staged"][0], "staged.txt");
+    assert_eq!(json[0]["matches_manifest"], true);
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
+
+/// 测试内容：
+///     1、配置文件中 foobar 固定到 master，missing 尚未 clone
+///     2、调用 ops::status，missing 标记为未 clone，不报错
+///     3、foobar 上游分支改为 origin/dev（比 master 多一个提交），开启 tracking 后落后 1 个提交
+///
+/// 测试目录结构:
+///   test_status_missing_tracking
+///     ├─foobar (.git)
+///     ├─remote.git
+///     └─upstream (.git)
+#[test]
+fn cli_status_missing_tracking() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_status_missing_tracking");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar", &remote, None, None, None)
+        .build();
+    let config_file = path.join(".gitrepos");
+    std::fs::write(&config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    ops::sync_repo(
+        SyncOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        TestProgress,
+    )?;
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar", &remote, None, None, None)
+        .join_repo("missing", &remote, None, None, None)
+        .build();
+    std::fs::write(&config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    let options = || StatusOptions::new(Some(input_path), None::<PathBuf>, None, None, None);
+    let statuses = ops::status(options())?;
+    assert_eq!(statuses.len(), 2);
+    assert!(!statuses[0].missing);
+    assert_eq!(statuses[1].local, "missing");
+    assert!(statuses[1].missing);
+
+    // push a dev branch one commit ahead of master, and track it
+    let upstream_path = path.join("upstream");
+    exec_cmd(&path, "git", &["clone", &remote, "upstream"]).expect("git clone failed");
+    std::fs::write(upstream_path.join("2.txt"), "2.txt").expect(failed_message::WRITE_FILE);
+    exec_cmd(&upstream_path, "git", &["add", "."]).expect(failed_message::GIT_STAGE);
+    exec_cmd(
+        &upstream_path,
+        "git",
+        &[
+            "-c",
+            "user.name=mgit",
+            "-c",
+            "user.email=mgit@example.com",
+            "commit",
+            "-m",
+            "second",
+        ],
+    )
+    .expect(failed_message::GIT_COMMIT);
+    exec_cmd(&upstream_path, "git", &["push", "origin", "HEAD:dev"]).expect("git push failed");
+
+    let repo_path = path.join("foobar");
+    exec_cmd(&repo_path, "git", &["fetch", "origin"]).expect("git fetch failed");
+    exec_cmd(&repo_path, "git", &["branch", "-u", "origin/dev"]).expect("git branch failed");
+
+    let statuses = ops::status(options())?;
+    assert_eq!((statuses[0].ahead, statuses[0].behind), (Some(0), Some(0)));
+
+    let mut tracking_options = options();
+    tracking_options.tracking = true;
+    let statuses = ops::status(tracking_options)?;
+    assert_eq!((statuses[0].ahead, statuses[0].behind), (Some(0), Some(1)));
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
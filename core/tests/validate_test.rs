@@ -0,0 +1,83 @@
+use mgit::ops;
+use mgit::ops::ValidateOptions;
+use mgit::utils::error::{MgitError, MgitResult};
+use std::{env, path::PathBuf};
+
+use crate::common::{failed_message, TomlBuilder, DEFAULT_BRANCH};
+
+mod common;
+
+/// 测试内容：
+///     1、配置文件没有问题，运行命令 mgit validate <path> 成功
+///     2、foobar-1 同时指定 commit 和 tag，foobar-1/sub 嵌套在 foobar-1 中，只有警告
+///     3、运行命令 mgit validate <path> --strict，警告也会报错
+///     4、local 重复、缺少 remote、local 超出工作目录时报错
+///
+/// 测试目录结构:
+///   test_validate(.gitrepos)
+#[test]
+fn cli_validate() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_validate");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+
+    let remote = "https://example.com/foobar.git";
+    let config_file = path.join(".gitrepos");
+    let validate = |toml_string: &str, strict: bool| {
+        std::fs::write(&config_file, toml_string).expect(failed_message::WRITE_FILE);
+        let mut options = ValidateOptions::new(Some(input_path), None::<PathBuf>);
+        options.strict = strict;
+        ops::validate(options)
+    };
+    let plain_errors = |err: anyhow::Error| {
+        let Some(MgitError::OpsError { errors, .. }) = err.downcast_ref::<MgitError>() else {
+            panic!("unexpected error: {}", err);
+        };
+        errors.0.iter().map(|e| e.plain()).collect::<Vec<_>>()
+    };
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo(".", remote, None, None, None)
+        .join_repo("foobar-1", remote, None, None, None)
+        .join_repo("foobar-2", remote, Some("develop"), None, None)
+        .build();
+    let msg = validate(&toml_string, false)?;
+    assert!(!msg.plain().contains("Warnings"));
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar-1", remote, None, Some("1234567"), Some("v1.0"))
+        .join_repo("foobar-1/sub", remote, None, None, None)
+        .build();
+    let msg = validate(&toml_string, false)?.plain();
+    assert!(msg.contains("foobar-1: warning, both commit and tag are set, the commit wins\n"));
+    assert!(msg.contains("foobar-1/sub: warning, nested in repo foobar-1\n"));
+
+    let errors = plain_errors(validate(&toml_string, true).unwrap_err());
+    assert_eq!(errors.len(), 2);
+
+    let toml_string = format!(
+        "{}\n[[repos]]\nlocal = \"./foobar-1/\"\nremote = \"{}\"\n\n[[repos]]\nlocal = \"foobar-3\"\n\n[[repos]]\nlocal = \"foobar-4/../../foobar\"\nremote = \"{}\"\n",
+        toml_string, remote, remote
+    );
+    let errors = plain_errors(validate(&toml_string, false).unwrap_err());
+    assert_eq!(errors.len(), 3);
+    assert!(errors.iter().any(|e| e.contains("duplicate local")));
+    assert!(errors
+        .iter()
+        .any(|e| e == "foobar-3: error, missing remote"));
+    assert!(errors
+        .iter()
+        .any(|e| e == "foobar-4/../../foobar: error, local escapes the work directory"));
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use mgit::ops;
+use mgit::ops::{SyncOptions, VerifyRemoteOptions};
+use mgit::utils::error::{MgitError, MgitResult};
+use std::{env, path::PathBuf};
+
+use crate::common::{
+    create_local_remote, exec_cmd, failed_message, TestProgress, TomlBuilder, DEFAULT_BRANCH,
+};
+
+mod common;
+
+/// 测试内容：
+///     1、同步后，foobar-1 的 origin 改为经 insteadOf 改写后与配置一致的地址
+///     2、foobar-2 的 origin 被改为其他地址
+///     3、运行命令 mgit verify-remote <path>，只报告 foobar-2 不一致
+///     4、运行命令 mgit verify-remote <path> --fix，foobar-2 的 origin 改回配置地址
+///
+/// 测试目录结构:
+///   test_verify_remote
+///     ├─foobar-1 (.git)
+///     ├─foobar-2 (.git)
+///     └─remote.git
+#[test]
+fn cli_verify_remote() -> MgitResult<()> {
+    let path = env::current_dir()
+        .unwrap()
+        .join("target")
+        .join("tmp")
+        .join("test_verify_remote");
+    let input_path = path.to_str().unwrap();
+
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    let remote = create_local_remote(path.join("remote.git"));
+
+    let toml_string = TomlBuilder::default()
+        .default_branch(DEFAULT_BRANCH)
+        .join_repo("foobar-1", &remote, None, None, None)
+        .join_repo("foobar-2", &remote, None, None, None)
+        .build();
+    let config_file = path.join(".gitrepos");
+    std::fs::write(config_file, toml_string.trim()).expect(failed_message::WRITE_FILE);
+
+    ops::sync_repo(
+        SyncOptions::new(
+            Some(input_path),
+            None::<PathBuf>,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        TestProgress,
+    )?;
+
+    // an alias rewritten to the manifest url is not a drift
+    let repo_path = path.join("foobar-1");
+    let instead_of = format!("url.{}.insteadOf", remote);
+    exec_cmd(&repo_path, "git", &["config", &instead_of, "mirror:"])
+        .expect(failed_message::GIT_CONFIG);
+    exec_cmd(
+        &repo_path,
+        "git",
+        &["remote", "set-url", "origin", "mirror:"],
+    )
+    .expect(failed_message::GIT_CONFIG);
+
+    let repo_path = path.join("foobar-2");
+    let elsewhere = path
+        .join("elsewhere.git")
+        .to_str()
+        .unwrap()
+        .replace('\\', "/");
+    exec_cmd(
+        &repo_path,
+        "git",
+        &["remote", "set-url", "origin", &elsewhere],
+    )
+    .expect(failed_message::GIT_CONFIG);
+
+    let err = ops::verify_remote(VerifyRemoteOptions::new(
+        Some(input_path),
+        None::<PathBuf>,
+        None,
+        None,
+    ))
+    .unwrap_err();
+    let Some(MgitError::OpsError { errors, .. }) = err.downcast_ref::<MgitError>() else {
+        panic!("unexpected error: {}", err);
+    };
+    assert_eq!(errors.0.len(), 1);
+    assert_eq!(
+        errors.0[0].plain(),
+        format!(
+            "foobar-2 origin is {}, manifest expects {}",
+            elsewhere, remote
+        )
+    );
+
+    // fix drifted remotes
+    ops::verify_remote(VerifyRemoteOptions::new(
+        Some(input_path),
+        None::<PathBuf>,
+        Some(true),
+        None,
+    ))?;
+    let origin_url = exec_cmd(&repo_path, "git", &["remote", "get-url", "origin"])?;
+    assert_eq!(origin_url.trim(), remote);
+
+    ops::verify_remote(VerifyRemoteOptions::new(
+        Some(input_path),
+        None::<PathBuf>,
+        None,
+        None,
+    ))?;
+
+    // clean-up
+    std::fs::remove_dir_all(&path).unwrap();
+    Ok(())
+}
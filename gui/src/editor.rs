@@ -78,6 +78,8 @@ pub struct Editor {
     show_sync_hard_dialog: bool,
 
     progress: Arc<AtomicUsize>,
+    /// repos the running ops process, as reported by `repos_start`
+    progress_total: Arc<AtomicUsize>,
     ops_message_collector: OpsMessageCollector,
 }
 
@@ -86,6 +88,7 @@ impl Default for Editor {
         //let cur_dir = std::env::current_dir().unwrap_or(std::path::PathBuf::from(""));
         let (send, recv) = channel();
         let progress = Arc::new(AtomicUsize::new(0));
+        let progress_total = Arc::new(AtomicUsize::new(0));
         Self {
             context: egui::Context::default(),
 
@@ -135,7 +138,8 @@ impl Default for Editor {
             show_sync_hard_dialog: false,
 
             progress: progress.clone(),
-            ops_message_collector: OpsMessageCollector::new(send, progress),
+            progress_total: progress_total.clone(),
+            ops_message_collector: OpsMessageCollector::new(send, progress, progress_total),
         }
     }
 }
@@ -2,6 +2,7 @@ use std::path::{Path, PathBuf};
 
 use eframe::egui;
 
+use mgit::core::repo::ignore_set;
 use mgit::core::repos::TomlConfig;
 use mgit::utils::path::PathExtension;
 
@@ -20,19 +21,12 @@ impl Editor {
                 self.toml_config = toml_config;
                 // init repo states and sync ignore
                 if let Some(toml_repos) = &self.toml_config.repos {
-                    let ignores = self.get_ignores().unwrap_or(vec![]);
+                    // same filter as ops, so the checked repos are the ones processed
+                    let ignore_paths = ignore_set(self.get_ignores().as_ref());
                     toml_repos.iter().for_each(|toml_repo| {
-                        let rel_path = toml_repo
-                            .local
-                            .as_ref()
-                            .map_or(String::from("invalid"), |p| p.clone());
-
-                        // get ignore state
-                        let do_ignore = ignores.contains(&rel_path.display_path());
-
                         // init repo state
                         self.repo_states.push(RepoState {
-                            no_ignore: !do_ignore,
+                            no_ignore: toml_repo.no_ignore(&ignore_paths),
                             ..RepoState::default()
                         });
                     });
@@ -39,7 +39,7 @@ impl Editor {
 
                 ui.add(ProgressBar::new(
                     self.progress.clone(),
-                    &self.repo_states,
+                    self.progress_total.clone(),
                     self.context.clone(),
                 ));
             });
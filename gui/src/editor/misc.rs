@@ -114,12 +114,12 @@ pub fn open_in_file_explorer(path: &str) {
         let path = path.replace('/', "\\");
         std::process::Command::new("explorer")
             .arg(path)
-            .spawn()
+            .status()
             .expect("open in file explorer failed");
     } else {
         std::process::Command::new("open")
             .arg(path)
-            .spawn()
+            .status()
             .expect("open in file explorer failed");
     }
 }
@@ -20,6 +20,7 @@ use crate::editor::Editor;
 use crate::toml_settings::SyncType;
 use crate::utils::command::CommandType;
 use crate::utils::logger::GUI_LOGGER;
+use crate::utils::progress::CountProgress;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum StateType {
@@ -86,6 +87,7 @@ impl Editor {
         }
 
         self.progress = Arc::new(AtomicUsize::new(0));
+        self.progress_total = Arc::new(AtomicUsize::new(0));
         match command_type {
             CommandType::Init => {
                 self.config_file = format!("{}/.gitrepos", &self.project_path);
@@ -95,9 +97,10 @@ impl Editor {
 
                 let options = InitOptions::new(path, force);
                 let send = self.send.clone();
+                let progress = self.count_progress();
                 self.clear_status();
                 std::thread::spawn(move || {
-                    let _ = ops::init_repo(options);
+                    let _ = ops::init_repo(options, progress);
                     send.send(RepoMessage::new(command_type, RepoState::default(), None))
                         .unwrap();
                 });
@@ -120,12 +123,13 @@ impl Editor {
 
                 let options = SnapshotOptions::new(path, config_path, force, snapshot_type, ignore);
                 let send = self.send.clone();
+                let progress = self.count_progress();
 
                 self.push_recent_config();
                 self.clear_status();
 
                 std::thread::spawn(move || {
-                    let _ = ops::snapshot_repo(options);
+                    let _ = ops::snapshot_repo(options, progress);
                     send.send(RepoMessage::new(command_type, RepoState::default(), None))
                         .unwrap();
                 });
@@ -299,12 +303,20 @@ impl Editor {
 
     fn progress(&mut self, command_type: CommandType) -> impl Progress {
         self.ops_message_collector.progress = self.progress.clone();
+        self.ops_message_collector.progress_total = self.progress_total.clone();
         self.ops_message_collector.command_type = command_type;
         self.ops_message_collector.project_path = self.project_path.clone();
         self.ops_message_collector.default_branch = self.toml_config.default_remote.clone();
         self.ops_message_collector.clone()
     }
 
+    fn count_progress(&self) -> CountProgress {
+        CountProgress {
+            progress: self.progress.clone(),
+            progress_total: self.progress_total.clone(),
+        }
+    }
+
     fn clear_status(&mut self) {
         self.clear_repo_state();
         self.clear_toml_config();
@@ -416,6 +428,10 @@ pub(crate) fn get_repo_state(
     if is_ok {
         // get current branch
         match git::get_current_branch(&full_path) {
+            Ok(res) if res.is_empty() => {
+                repo_state.err_msg = "current branch not found.".to_string();
+                is_ok = false;
+            }
             Ok(res) => {
                 repo_state.track_state = StateType::Normal;
                 repo_state.current_branch = res;
@@ -42,7 +42,7 @@ impl Editor {
                                         {
                                             if let Some(rel_path) = &toml_repo.local {
                                                 self.save_ignore(
-                                                    rel_path.display_path(),
+                                                    rel_path.clone(),
                                                     !self.repo_states[idx].no_ignore,
                                                 );
                                             }
@@ -84,8 +84,8 @@ impl Editor {
 
                     if is_modified {
                         // serialize .gitrepos
-                        let toml_string = self.toml_config.serialize();
-                        std::fs::write(Path::new(&self.config_file), toml_string)
+                        self.toml_config
+                            .save(Path::new(&self.config_file))
                             .expect("Failed to write file .gitrepos!");
                     }
                 }
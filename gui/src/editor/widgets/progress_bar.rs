@@ -5,8 +5,6 @@ use std::time::Duration;
 
 use eframe::egui::{vec2, NumExt, Rect, Response, Sense, Stroke, Ui, Widget};
 
-use crate::editor::ops::RepoState;
-
 pub(crate) struct ProgressBar {
     pub(crate) progress: f32,
     current: Arc<AtomicUsize>,
@@ -14,11 +12,8 @@ pub(crate) struct ProgressBar {
 }
 
 impl ProgressBar {
-    pub fn new(current: Arc<AtomicUsize>, repos: &[RepoState], context: egui::Context) -> Self {
-        let total = repos.iter().fold(
-            0,
-            |total, repo| if repo.no_ignore { total + 1 } else { total },
-        );
+    pub fn new(current: Arc<AtomicUsize>, total: Arc<AtomicUsize>, context: egui::Context) -> Self {
+        let total = total.load(Ordering::Relaxed);
         let current_rate = current.load(Ordering::Relaxed);
         let progress = if total > 0 {
             current_rate as f32 / total as f32
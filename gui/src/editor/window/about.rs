@@ -5,9 +5,7 @@ use egui_extras::RetainedImage;
 use crate::utils::defines::{hex_code, resource};
 
 #[derive(Default)]
-pub struct AboutWindow {
-    pub mgit_version: String,
-}
+pub struct AboutWindow;
 
 impl super::WindowBase for AboutWindow {
     fn name(&self) -> String {
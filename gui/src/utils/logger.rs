@@ -21,11 +21,11 @@ pub struct GuiLogger;
 
 impl logger::Log for GuiLogger {
     fn info(&self, message: StyleMessage) {
-        info!("{}", message)
+        info!("{}", message.plain())
     }
 
     fn error(&self, message: StyleMessage) {
-        error!("{}", message)
+        error!("{}", message.plain())
     }
 }
 
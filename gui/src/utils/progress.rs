@@ -23,13 +23,18 @@ pub(crate) struct OpsMessageCollector {
     repo_names: Vec<String>,
     sender: Arc<Mutex<Sender<RepoMessage>>>,
     pub progress: Arc<AtomicUsize>,
+    pub progress_total: Arc<AtomicUsize>,
     pub command_type: CommandType,
     pub project_path: String,
     pub default_branch: Option<String>,
 }
 
 impl OpsMessageCollector {
-    pub(crate) fn new(sender: Sender<RepoMessage>, progress: Arc<AtomicUsize>) -> Self {
+    pub(crate) fn new(
+        sender: Sender<RepoMessage>,
+        progress: Arc<AtomicUsize>,
+        progress_total: Arc<AtomicUsize>,
+    ) -> Self {
         Self {
             repo_state_buffers: vec![],
             file_loggers: vec![],
@@ -39,6 +44,7 @@ impl OpsMessageCollector {
             project_path: String::new(),
             default_branch: None,
             progress,
+            progress_total,
         }
     }
 
@@ -94,8 +100,9 @@ impl OpsMessageCollector {
 }
 
 impl Progress for OpsMessageCollector {
-    fn repos_start(&self, _total: usize) {
+    fn repos_start(&self, total: usize) {
         self.progress.store(0, Ordering::Relaxed);
+        self.progress_total.store(total, Ordering::Relaxed);
     }
 
     fn repos_end(&self) {}
@@ -119,7 +126,7 @@ impl Progress for OpsMessageCollector {
         ptr.lock().unwrap().replace(message.clone());
 
         let mut file = self.file_loggers[repo_info.id].lock().unwrap();
-        writeln!(file, "{}", message.to_plain_text()).unwrap();
+        writeln!(file, "{}", message.plain()).unwrap();
     }
 
     #[allow(unused_variables)]
@@ -152,3 +159,32 @@ impl Progress for OpsMessageCollector {
         self.repo_end(repo_info, message)
     }
 }
+
+/// progress of ops that find their repos on disk, like init and snapshot:
+/// there are no repo states to update, only the counts
+#[derive(Debug, Clone)]
+pub(crate) struct CountProgress {
+    pub progress: Arc<AtomicUsize>,
+    pub progress_total: Arc<AtomicUsize>,
+}
+
+impl Progress for CountProgress {
+    fn repos_start(&self, total: usize) {
+        self.progress.store(0, Ordering::Relaxed);
+        self.progress_total.store(total, Ordering::Relaxed);
+    }
+
+    fn repos_end(&self) {}
+
+    fn repo_start(&self, _repo_info: &RepoInfo, _message: StyleMessage) {}
+
+    fn repo_info(&self, _repo_info: &RepoInfo, _message: StyleMessage) {}
+
+    fn repo_end(&self, _repo_info: &RepoInfo, _message: StyleMessage) {
+        self.progress.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn repo_error(&self, repo_info: &RepoInfo, message: StyleMessage) {
+        self.repo_end(repo_info, message)
+    }
+}